@@ -0,0 +1,45 @@
+//! A click counter embedded in the event loop of a host application, such as a GUI: the host
+//! feeds its events to the program and executes one instant per frame, then draws the state
+//! held by the program.
+
+extern crate reactive_rs;
+
+use reactive_rs::reactive::process::*;
+use reactive_rs::reactive::runtime::*;
+use reactive_rs::reactive::runtime::sequential_runtime::*;
+use reactive_rs::reactive::signal::latch_signal::*;
+use reactive_rs::reactive::signal::value_signal::*;
+
+/// Simulates `frames` frames of the host, with a click during each frame of `clicks`, and
+/// returns what is drawn at each frame.
+pub fn run(frames: usize, clicks: &[usize]) -> Vec<String> {
+    let clicked: ValueSignal<u32, u32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let count = LatchSignal::new(0);
+    let (displayed, mut total) = (count.clone(), 0);
+    let add = move|n: u32| {
+        total += n;
+        total
+    };
+    let continue_loop: LoopStatus<()> = LoopStatus::Continue;
+    let counter = displayed.emit(clicked.await().map(add)).then(value(continue_loop)).while_loop();
+
+    let mut runtime = SequentialRuntime::new();
+    runtime.register(counter);
+    let inbox = runtime.external_inbox();
+    let mut drawn = Vec::new();
+    for frame in 0..frames {
+        if clicks.contains(&frame) {
+            inbox.emit(&clicked, 1);
+        }
+        runtime.instant();
+        drawn.push(format!("clicks: {}", count.current()));
+    }
+    drawn
+}
+
+#[allow(dead_code)]
+fn main() {
+    for frame in run(6, &[0, 1, 3]) {
+        println!("{}", frame);
+    }
+}
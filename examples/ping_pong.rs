@@ -0,0 +1,64 @@
+//! Two programs executed by separate runtimes on their own threads, exchanging a ball through
+//! bounded inboxes until it was hit a given number of times.
+
+extern crate reactive_rs;
+
+use reactive_rs::reactive::boxed::*;
+use reactive_rs::reactive::process::*;
+use reactive_rs::reactive::runtime::ExternalSource;
+use reactive_rs::reactive::runtime::parallel_runtime::*;
+use reactive_rs::reactive::signal::value_signal::*;
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// The hits of both players, as the player and the number of hits before.
+type Log = Arc<Mutex<Vec<(&'static str, u32)>>>;
+
+/// Hits every ball received on `ball` back to `other_ball`, through the inbox of the runtime of
+/// the other player, until `hits` hits, then hits it back once more so that the other player
+/// stops too, and drops `other` to let its runtime terminate.
+fn player(name: &'static str, ball: ValueSignal<u32, u32>, other: ExternalSource, other_ball: ValueSignal<u32, u32>,
+          hits: u32, log: Log) -> ProcessBox<()> {
+    let mut other = Some(other);
+    let hit = move|n: u32| {
+        log.lock().unwrap().push((name, n));
+        if let Some(ref other) = other {
+            other.emit(&other_ball, n + 1);
+        }
+        if n >= hits {
+            other = None;
+            LoopStatus::Exit(())
+        } else {
+            LoopStatus::Continue
+        }
+    };
+    ball.await().map(hit).while_loop().boxed()
+}
+
+/// Plays until the ball was hit `hits` times, and returns the hits in order.
+pub fn run(hits: u32) -> Vec<(&'static str, u32)> {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let ping_ball = ValueSignal::new(0, Box::new(|_, n| n));
+    let pong_ball = ValueSignal::new(0, Box::new(|_, n| n));
+    let ping_runtime = RuntimeBuilder::new(1).inbox_capacity(1).build();
+    let pong_runtime = RuntimeBuilder::new(1).inbox_capacity(1).build();
+    let to_ping = ping_runtime.external_inbox().source();
+    let to_pong = pong_runtime.external_inbox().source();
+    to_ping.emit(&ping_ball, 0);
+    let ping = player("ping", ping_ball.clone(), to_pong, pong_ball.clone(), hits, log.clone());
+    let pong = player("pong", pong_ball, to_ping, ping_ball, hits, log.clone());
+    let ping = thread::spawn(move|| execute_process_par_with(ping_runtime, ping));
+    let pong = thread::spawn(move|| execute_process_par_with(pong_runtime, pong));
+    ping.join().unwrap();
+    pong.join().unwrap();
+    let log = log.lock().unwrap().clone();
+    log
+}
+
+#[allow(dead_code)]
+fn main() {
+    for (player, n) in run(10) {
+        println!("{} hits the ball for the {}-th time", player, n + 1);
+    }
+}
@@ -0,0 +1,41 @@
+//! A producer thread emitting items to a reactive consumer through a bounded inbox: the producer
+//! blocks whenever the program is `capacity` items behind.
+
+extern crate reactive_rs;
+
+use reactive_rs::reactive::process::*;
+use reactive_rs::reactive::runtime::parallel_runtime::*;
+use reactive_rs::reactive::signal::value_signal::*;
+
+use std::thread;
+
+/// Emits the items `1..items + 1` from another thread, and returns their sum as received by the
+/// program with the largest number of items received during one instant.
+pub fn run(items: i32, capacity: usize) -> (i32, usize) {
+    let received: ValueSignal<Vec<i32>, i32> = ValueSignal::new(Vec::new(), Box::new(|mut batch, item| {
+        batch.push(item);
+        batch
+    }));
+    let runtime = RuntimeBuilder::new(1).inbox_capacity(capacity).build();
+    let source = runtime.external_inbox().source();
+    let emitted = received.clone();
+    let producer = thread::spawn(move|| for item in 1..items + 1 {
+        source.emit(&emitted, item);
+    });
+    let (mut count, mut sum, mut largest_batch) = (0, 0, 0);
+    let consume = move|batch: Vec<i32>| {
+        count += batch.len() as i32;
+        sum += batch.iter().sum::<i32>();
+        largest_batch = largest_batch.max(batch.len());
+        if count == items { LoopStatus::Exit((sum, largest_batch)) } else { LoopStatus::Continue }
+    };
+    let result = execute_process_par_with(runtime, received.await().map(consume).while_loop());
+    producer.join().unwrap();
+    result
+}
+
+#[allow(dead_code)]
+fn main() {
+    let (sum, largest_batch) = run(100, 4);
+    println!("received a sum of {}, at most {} items per instant", sum, largest_batch);
+}
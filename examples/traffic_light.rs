@@ -0,0 +1,49 @@
+//! A traffic light cycling through its colors, whose green phase is cut short when a pedestrian
+//! presses the button.
+
+extern crate reactive_rs;
+
+use reactive_rs::reactive::boxed::*;
+use reactive_rs::reactive::process::*;
+use reactive_rs::reactive::race::*;
+use reactive_rs::reactive::signal::pure_signal::*;
+
+use std::sync::{Arc, Mutex};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Light { Green, Yellow, Red }
+
+/// The color of the light at each instant.
+type Timeline = Arc<Mutex<Vec<Light>>>;
+
+/// Shows `light` during `instants` instants.
+fn show(timeline: &Timeline, light: Light, instants: usize) -> ProcessBox<()> {
+    let timeline = timeline.clone();
+    let record = move|()| timeline.lock().unwrap().push(light);
+    value(()).map(record).pause().repeat(instants).map(|_| ()).boxed()
+}
+
+/// Runs the light for `cycles` cycles, the button being pressed at the instant `press`, and
+/// returns the color shown at each instant.
+pub fn run(cycles: usize, press: usize) -> Vec<Light> {
+    let timeline = Arc::new(Mutex::new(Vec::new()));
+    let button = PureSignal::new();
+    let (shown, pressed) = (timeline.clone(), button.clone());
+    let cycle = move|| {
+        // The light turns yellow at the instant of a press.
+        let green = until(show(&shown, Light::Green, 5), pressed.await_immediate());
+        green.then(show(&shown, Light::Yellow, 2)).then(show(&shown, Light::Red, 3))
+    };
+    let light = from_fn(cycle).repeat(cycles);
+    let pedestrian = value(()).pause().repeat(press).then(button.emit());
+    execute_process(light.join(pedestrian));
+    let timeline = timeline.lock().unwrap().clone();
+    timeline
+}
+
+#[allow(dead_code)]
+fn main() {
+    for (instant, light) in run(2, 2).into_iter().enumerate() {
+        println!("{}: {:?}", instant, light);
+    }
+}
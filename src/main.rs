@@ -1,17 +1,18 @@
 #![feature(conservative_impl_trait)]
-#![type_length_limit="33554432"]
 
 extern crate reactive_rs;
 
 mod redstone;
 
+use reactive_rs::reactive::lockstep::*;
 use reactive_rs::reactive::process::*;
 use reactive_rs::reactive::signal::pure_signal::*;
 use reactive_rs::reactive::signal::value_signal::*;
 
 use redstone::*;
 
-use std::{thread, time};
+use std::{env, thread, time};
+use std::net::TcpListener;
 
 fn _main() {
     let s = PureSignal::new();
@@ -39,12 +40,9 @@ fn __main() {
     let s = ValueSignal::new(0, Box::new(|x, y| x+y));
 
     let conti: LoopStatus<()> = LoopStatus::Continue;
-    let mut ps = Vec::new();
-    for _ in 0..1000 {
-        let sleep = |_| thread::sleep(time::Duration::from_millis(1));
-        ps.push(s.emit(value(1)).map(sleep).then(value(conti).pause()).while_loop());
-    }
-    let p = multi_join(ps);
+    let sleep = |_| thread::sleep(time::Duration::from_millis(1));
+    let worker = s.emit(value(1)).map(sleep).then(value(conti).pause()).while_loop();
+    let p = multi_join(replicate(worker, 1000));
     let print = |x| {
         println!("x = {}", x);
         x
@@ -53,6 +51,59 @@ fn __main() {
     execute_process(join(p, q));
 }
 
+/// `ppr host <addr>` and `ppr join <addr>` share the simulation between two players.
+///
+/// `--map <file>` simulates another map than `map.txt`, which can be a Minecraft schematic when
+/// built with the `schematic` feature.
+///
+/// `--workers <n>` executes the simulation with a parallel runtime of `n` workers instead of the
+/// sequential runtime, the number of instants per second being printed every second. During the
+/// run, `P` switches between the sequential and the parallel runtime, and `=` and `-` add and
+/// remove a worker.
+///
+/// `--stimuli <file>` injects the powers listed in the file during the run, and
+/// `--record <file> <x>,<y>...` writes the power of the given cells to the file at every instant.
+///
+/// `--input <x>,<y>` and `--output <x>,<y>`, repeated for each cell, print the truth table of the
+/// circuit between these cells instead of opening the simulation.
 fn main() {
-    redstone_sim();
-}
\ No newline at end of file
+    let mut options = SimOptions::default();
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "host" => {
+                let listener = TcpListener::bind(args.next().expect("host <addr>").as_str()).unwrap();
+                println!("waiting for a player on {}", listener.local_addr().unwrap());
+                options.session = Some(lockstep_host(&listener).unwrap());
+            },
+            "join" => options.session = Some(lockstep_join(args.next().expect("join <addr>").as_str()).unwrap()),
+            "--map" => options.map_file = Some(args.next().expect("--map <file>")),
+            "--workers" => options.workers = args.next().and_then(|n| n.parse().ok()).expect("--workers <n>"),
+            "--stimuli" => options.stimuli_file = Some(args.next().expect("--stimuli <file>")),
+            "--record" => options.record_file = Some(args.next().expect("--record <file> <x>,<y>...")),
+            "--input" => inputs.push(args.next().as_ref().and_then(|cell| parse_cell(cell)).expect("--input <x>,<y>")),
+            "--output" => outputs.push(args.next().as_ref().and_then(|cell| parse_cell(cell)).expect("--output <x>,<y>")),
+            probe => {
+                match parse_cell(&probe) {
+                    Some(cell) if options.record_file.is_some() => options.probes.push(cell),
+                    _ => panic!("Unexpected argument: {}", probe),
+                }
+            },
+        }
+    }
+    if inputs.is_empty() && outputs.is_empty() {
+        redstone_sim_with(options);
+    } else {
+        redstone_truth_table(options.map_file, inputs, outputs);
+    }
+}
+
+fn parse_cell(cell: &str) -> Option<(usize, usize)> {
+    let coordinates: Option<Vec<usize>> = cell.split(',').map(|coordinate| coordinate.parse().ok()).collect();
+    match coordinates {
+        Some(ref coordinates) if coordinates.len() == 2 => Some((coordinates[0], coordinates[1])),
+        _ => None,
+    }
+}
@@ -0,0 +1,157 @@
+use super::*;
+
+//     _        _
+//    / \   ___| |_ ___  _ __
+//   / _ \ / __| __/ _ \| '__|
+//  / ___ \ (__| || (_) | |
+// /_/   \_\___|\__\___/|_|
+
+/// Sends messages to an actor, see `actor`.
+pub struct Address<M> where M: Clone + Send + Sync + 'static {
+    mailbox: ValueSignal<Vec<M>, M>,
+}
+
+impl<M> Clone for Address<M> where M: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        Address { mailbox: self.mailbox.clone() }
+    }
+}
+
+impl<M> Address<M> where M: Clone + Send + Sync + 'static {
+    /// Sends the value of `message` to the actor, which receives it at the next instant.
+    pub fn send<P>(&self, message: P) -> VEmit<Vec<M>, M, P> where P: Process<Value = M> {
+        self.mailbox.emit(message)
+    }
+
+    /// Sends `message` from another thread, see `ExternalInbox`.
+    pub fn send_external(&self, inbox: &ExternalInbox, message: M) {
+        inbox.emit(&self.mailbox, message);
+    }
+}
+
+/// A process owning a state, which handles the messages sent to its address one after the other,
+/// see `actor`.
+pub struct Actor<S, M, F> where M: Clone + Send + Sync + 'static {
+    state: S,
+    handler: F,
+    mailbox: ValueSignal<Vec<M>, M>,
+    room: Arc<Mutex<Mailroom<M>>>,
+}
+
+/// The messages of an actor, gathered from its mailbox at each instant while it executes.
+struct Mailroom<M> {
+    /// Messages received but not handled yet, in the order they were gathered.
+    backlog: VecDeque<M>,
+    /// The actor waiting for a message, if the backlog is empty.
+    idle: Option<Box<Continuation<()>>>,
+    /// Set once the actor terminated or was dropped, after which the mailbox is not drained.
+    stopped: bool,
+}
+
+impl<M> Mailroom<M> {
+    fn new() -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Mailroom { backlog: VecDeque::new(), idle: None, stopped: false }))
+    }
+}
+
+/// An actor with the state `state`, and the address to send it messages.
+///
+/// The actor handles one message per instant, in the order they were received, by executing the
+/// process returned by `handler`, which can send messages to other actors. A handler taking more
+/// than an instant delays the next messages, which are kept until it completes. The actor
+/// terminates with `v` once a handler returns `LoopStatus::Exit(v)`, and waits for messages
+/// otherwise.
+///
+/// The messages received in the same instant are ordered like the values gathered by a signal,
+/// deterministically with a deterministic runtime only. A restarted supervised actor starts again
+/// from `state` with the same address, the messages it had not handled yet being lost.
+pub fn actor<S, M, F, P, V>(state: S, handler: F) -> (Address<M>, Actor<S, M, F>)
+    where S: Send + Sync + 'static, M: Clone + Send + Sync + 'static, F: FnMut(&mut S, M) -> P + Send + Sync + 'static,
+          P: Process<Value = LoopStatus<V>>, V: Send + Sync {
+    let mailbox = ValueSignal::new(Vec::new(), Box::new(|mut messages: Vec<M>, message| {
+        messages.push(message);
+        messages
+    }));
+    (Address { mailbox: mailbox.clone() }, Actor { state, handler, mailbox, room: Mailroom::new() })
+}
+
+/// Adds the messages of each instant to the backlog of `room`, waking the actor if it is idle,
+/// until the actor stops.
+fn drain<M>(room: Arc<Mutex<Mailroom<M>>>, mailbox: ValueSignal<Vec<M>, M>, runtime: &mut Runtime)
+    where M: Clone + Send + Sync + 'static {
+    mailbox.await().call(runtime, move|runtime: &mut Runtime, messages: Vec<M>| {
+        let idle = {
+            let mut room = room.lock().unwrap();
+            if room.stopped {
+                return;
+            }
+            room.backlog.extend(messages);
+            room.idle.take()
+        };
+        drain(room, mailbox, runtime);
+        if let Some(idle) = idle {
+            idle.call_box(runtime, ());
+        }
+    });
+}
+
+impl<S, M, F, P, V> Actor<S, M, F>
+    where S: Send + Sync + 'static, M: Clone + Send + Sync + 'static, F: FnMut(&mut S, M) -> P + Send + Sync + 'static,
+          P: Process<Value = LoopStatus<V>>, V: Send + Sync {
+    /// Handles the next message of the backlog, or waits for one.
+    fn step<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<V> {
+        let room = self.room.clone();
+        let message = {
+            let mut room = room.lock().unwrap();
+            match room.backlog.pop_front() {
+                Some(message) => message,
+                None => {
+                    room.idle = Some(Box::new(move|runtime: &mut Runtime, ()| self.step(runtime, next)));
+                    return;
+                },
+            }
+        };
+        let handled = (self.handler)(&mut self.state, message);
+        handled.call(runtime, move|runtime: &mut Runtime, status| match status {
+            LoopStatus::Exit(v) => {
+                drop(self);
+                next.call(runtime, v);
+            },
+            LoopStatus::Continue => {
+                runtime.on_next_instant(Box::new(move|runtime: &mut Runtime, ()| self.step(runtime, next)));
+            },
+        });
+    }
+}
+
+impl<S, M, F> Drop for Actor<S, M, F> where M: Clone + Send + Sync + 'static {
+    fn drop(&mut self) {
+        let mut room = self.room.lock().unwrap();
+        room.stopped = true;
+        room.backlog.clear();
+    }
+}
+
+impl<S, M, F, P, V> Process for Actor<S, M, F>
+    where S: Send + Sync + 'static, M: Clone + Send + Sync + 'static, F: FnMut(&mut S, M) -> P + Send + Sync + 'static,
+          P: Process<Value = LoopStatus<V>>, V: Send + Sync {
+    type Value = V;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<V> {
+        drain(self.room.clone(), self.mailbox.clone(), runtime);
+        self.step(runtime, next);
+    }
+}
+
+impl<S, M, F, P, V> CloneProcess for Actor<S, M, F>
+    where S: Clone + Send + Sync + 'static, M: Clone + Send + Sync + 'static, F: FnMut(&mut S, M) -> P + Clone + Send + Sync + 'static,
+          P: Process<Value = LoopStatus<V>>, V: Send + Sync {
+    fn clone_process(&self) -> Self {
+        Actor {
+            state: self.state.clone(),
+            handler: self.handler.clone(),
+            mailbox: self.mailbox.clone(),
+            room: Mailroom::new(),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use super::*;
+use std::fmt;
+
+//     _             _ _ _
+//    / \  _   _  __| (_) |_
+//   / _ \| | | |/ _` | | __|
+//  / ___ \ |_| | (_| | | |_
+// /_/   \_\__,_|\__,_|_|\__|
+
+thread_local! {
+    /// The audited signals read by the continuation executed by the current thread, by address.
+    static READS: RefCell<Vec<usize>> = RefCell::new(Vec::new());
+}
+
+/// Forgets the read of a signal once the continuation which read it returns, or panics.
+struct ReadGuard;
+
+impl Drop for ReadGuard {
+    fn drop(&mut self) {
+        READS.with(|reads| reads.borrow_mut().pop());
+    }
+}
+
+/// Executes `f`, which continues a process which just read the audited signal at `signal`.
+pub(crate) fn reading<F, T>(signal: usize, f: F) -> T where F: FnOnce() -> T {
+    READS.with(|reads| reads.borrow_mut().push(signal));
+    let _guard = ReadGuard;
+    f()
+}
+
+/// Whether the process being executed by the current thread read the audited signal at `signal`
+/// since it last resumed.
+pub(crate) fn has_read(signal: usize) -> bool {
+    READS.with(|reads| reads.borrow().contains(&signal))
+}
+
+/// An emission on a signal by a process which read the same signal earlier in the instant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditWarning {
+    pub instant: usize,
+    /// The name given to the signal with `ValueSignal::audited`.
+    pub signal: String,
+    /// The name given to the emitting process with `emit_named`, or its emitter index.
+    pub process: String,
+}
+
+impl fmt::Display for AuditWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "instant {}: {} emitted on {} after reading it during the same instant", self.instant, self.process, self.signal)
+    }
+}
+
+/// Warns about processes emitting on a signal after reading it during the same instant.
+///
+/// A process resuming from `await` with the value of the previous instant, then emitting on the
+/// same signal, contributes to the value of the current instant rather than to the one it read,
+/// which is rarely intended. Likewise, a process emitting after `await_immediate` or `present`
+/// changes a value other readers of the instant may already have seen.
+///
+/// Only the signals given to `ValueSignal::audited` are checked. A read is attributed to the
+/// continuation resumed by the signal until it returns, that is until the process pauses, waits
+/// for another signal or is scheduled again with the runtime, as `join` does. Each warning is
+/// printed to the standard error and recorded.
+#[derive(Clone)]
+pub struct EmitAudit {
+    warnings: Arc<Mutex<Vec<AuditWarning>>>,
+}
+
+impl EmitAudit {
+    pub fn new() -> Self {
+        EmitAudit { warnings: Arc::new(Mutex::new(Vec::new())) }
+    }
+
+    /// The warnings recorded so far, in the order they were raised.
+    pub fn warnings(&self) -> Vec<AuditWarning> {
+        self.warnings.lock().unwrap().clone()
+    }
+
+    pub(crate) fn warn(&self, warning: AuditWarning) {
+        eprintln!("warning: {}", warning);
+        self.warnings.lock().unwrap().push(warning);
+    }
+}
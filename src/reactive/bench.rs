@@ -3,4 +3,58 @@
 //#[bench]
 //fn bench_emit(b: &mut Bencher) {
 //    b.iter(|| add_two(2));
-//}
\ No newline at end of file
+//}
+use super::*;
+use std::time::Instant;
+
+/// Number of instants of the signal benchmarks.
+const INSTANTS: i32 = 100_000;
+
+/// Executes `program` and prints how long it took under `name`.
+fn measure<P>(name: &str, program: P) where P: Process<Value = ()> {
+    let start = Instant::now();
+    execute_process(program);
+    let elapsed = start.elapsed();
+    println!("{}: {:?} for {} instants", name, elapsed, INSTANTS);
+}
+
+/// A producer emitting once per instant and a consumer waiting for each emission, with the unique
+/// producer signal locking its runtime because a consumer handle is kept, with the same signal
+/// switched to the lock-free runtime once the handle is dropped, and with the SPSC signal. Run
+/// with `cargo test --release bench_ -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn bench_spsc_signal() {
+    let (producer, consumer) = UniqueProducerSignalProducer::new(0);
+    let emit = producer.emit(value(1)).then(value(()).pause()).repeat(INSTANTS as usize).map(|_| ());
+    let mut received = 0;
+    let count = move|v: i32| {
+        received += v;
+        if received == INSTANTS { LoopStatus::Exit(()) } else { LoopStatus::Continue }
+    };
+    let status = |(status, ()): (LoopStatus<()>, ())| status;
+    let wait = consumer.await_immediate().map(count).join(value(()).pause()).map(status).while_loop();
+    measure("unique producer signal", emit.join(wait).map(|_| ()));
+    drop(consumer);
+
+    let (producer, consumer) = UniqueProducerSignalProducer::new(0);
+    let emit = producer.emit(value(1)).then(value(()).pause()).repeat(INSTANTS as usize).map(|_| ());
+    let mut received = 0;
+    let count = move|v: i32| {
+        received += v;
+        if received == INSTANTS { LoopStatus::Exit(()) } else { LoopStatus::Continue }
+    };
+    let wait = consumer.await_immediate().map(count).join(value(()).pause()).map(status).while_loop();
+    drop(consumer);
+    measure("specialized unique producer signal", emit.join(wait).map(|_| ()));
+
+    let (producer, consumer) = SPSCSignalProducer::new();
+    let emit = producer.emit(value(1)).then(value(()).pause()).repeat(INSTANTS as usize).map(|_| ());
+    let mut received = 0;
+    let count = move|v: i32| {
+        received += v;
+        if received == INSTANTS { LoopStatus::Exit(()) } else { LoopStatus::Continue }
+    };
+    let wait = consumer.await_immediate().map(count).join(value(()).pause()).map(status).while_loop();
+    measure("SPSC signal", emit.join(wait).map(|_| ()));
+}
@@ -0,0 +1,241 @@
+use super::*;
+use std::fmt::Display;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender, SyncSender};
+
+//  ____  _            _    _               ___ ___
+// | __ )| | ___   ___| | _(_)_ __   __ _  |_ _/ _ \
+// |  _ \| |/ _ \ / __| |/ / | '_ \ / _` |  | | | | |
+// | |_) | | (_) | (__|   <| | | | | (_| |  | | |_| |
+// |____/|_|\___/ \___|_|\_\_|_| |_|\__, | |___\___/
+//                                  |___/
+
+/// Values produced by a thread outside of the runtime, collected by a process polling it once
+/// per instant.
+pub(crate) struct Inbox<T> {
+    values: Arc<Mutex<VecDeque<T>>>,
+}
+
+impl<T> Clone for Inbox<T> {
+    fn clone(&self) -> Self {
+        Inbox { values: self.values.clone() }
+    }
+}
+
+impl<T> Inbox<T> {
+    pub(crate) fn new() -> Self {
+        Inbox { values: Arc::new(Mutex::new(VecDeque::new())) }
+    }
+
+    pub(crate) fn push(&self, value: T) {
+        self.values.lock().unwrap().push_back(value);
+    }
+
+    pub(crate) fn drain(&self) -> Vec<T> {
+        self.values.lock().unwrap().drain(..).collect()
+    }
+}
+
+/// The value returned by a blocking function, or the payload of its panic.
+type BlockingResult<T> = Result<T, Mutex<Box<Any + Send>>>;
+
+/// The result of a blocking function, or the continuation waiting for it.
+struct BlockingState<T> where T: Send + Sync + 'static {
+    result: Option<BlockingResult<T>>,
+    waiter: Option<(ExternalSource, Box<Continuation<T>>)>,
+}
+
+/// A process executing a blocking function on its own thread. It resumes at the first instant
+/// starting after the function returned, instants keep going in the meantime.
+pub struct Blocking<T> where T: Send + Sync + 'static {
+    state: Arc<Mutex<BlockingState<T>>>,
+}
+
+/// Resumes `next` with the value of a blocking function, or resumes its panic in the process.
+fn resume<T>(runtime: &mut Runtime, next: Box<Continuation<T>>, result: BlockingResult<T>) where T: Send + Sync + 'static {
+    match result {
+        Ok(v) => next.call_box(runtime, v),
+        Err(payload) => panic::resume_unwind(payload.into_inner().unwrap()),
+    }
+}
+
+impl<T> Process for Blocking<T> where T: Send + Sync + 'static {
+    type Value = T;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<T> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(result) = state.result.take() {
+            drop(state);
+            return resume(runtime, Box::new(next), result);
+        }
+        let waiter = Arc::downgrade(&self.state);
+        let next = waiting(next, &*self.state as *const _ as usize, move|| {
+            if let Some(state) = waiter.upgrade() {
+                state.lock().unwrap().waiter = None;
+            }
+        });
+        state.waiter = Some((runtime.external_inbox().source(), Box::new(next)));
+    }
+}
+
+/// Executes `f` on a new thread, the returned process waiting for its value. A panic of `f` is
+/// resumed in the process, as if it panicked itself.
+pub fn blocking<F, T>(f: F) -> Blocking<T> where F: FnOnce() -> T + Send + 'static, T: Send + Sync + 'static {
+    let state = Arc::new(Mutex::new(BlockingState { result: None, waiter: None }));
+    let state_ref = state.clone();
+    thread::spawn(move|| {
+        let result = panic::catch_unwind(AssertUnwindSafe(f)).map_err(Mutex::new);
+        let mut state = state_ref.lock().unwrap();
+        match state.waiter.take() {
+            Some((source, next)) => source.push(move|runtime: &mut Runtime, ()| resume(runtime, next, result)),
+            None => state.result = Some(result),
+        }
+    });
+    Blocking { state }
+}
+
+/// Reads the whole content of a file without blocking the runtime.
+pub fn read_file_process<P>(path: P) -> Blocking<io::Result<String>> where P: Into<PathBuf> {
+    let path = path.into();
+    blocking(move|| {
+        let mut content = String::new();
+        File::open(path)?.read_to_string(&mut content)?;
+        Ok(content)
+    })
+}
+
+/// The process sending the values of a signal to a `bounded_subscriber`.
+pub struct Subscription<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    await: VAwait<V, G>,
+    sender: SyncSender<V>,
+}
+
+impl<V, G> Process for Subscription<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        let sender = self.sender;
+        self.await.call_mut(runtime, move|runtime: &mut Runtime, (await, v): (VAwait<V, G>, V)| {
+            match sender.send(v) {
+                Ok(()) => Subscription { await, sender }.call(runtime, next),
+                Err(_) => next.call(runtime, ()),
+            }
+        });
+    }
+}
+
+/// A receiver through which another thread gets the gathered value of `signal` for every instant
+/// it is emitted, with at most `capacity` values waiting for the thread: the program then waits
+/// for it to receive one before going on, so that it cannot get ahead of the thread by more than
+/// `capacity` values. See `ExternalInbox::emit` for the other direction.
+///
+/// The returned process forwards the values, and terminates at the first emission after the
+/// receiver is dropped.
+pub fn bounded_subscriber<S, V, G>(signal: &S, capacity: usize) -> (Receiver<V>, Subscription<V, G>)
+    where S: VSignal<V, G>, V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    assert!(capacity > 0, "a bounded subscriber needs room for at least one value");
+    let (sender, receiver) = sync_channel(capacity);
+    (receiver, Subscription { await: signal.await(), sender })
+}
+
+/// The process emitting the connections accepted by a `tcp_listener_signal`.
+pub struct TcpAccept {
+    listener: TcpListener,
+    signal: UniqueConsumerSignalProducer<Vec<TcpStream>, TcpStream>,
+    local_addr: SocketAddr,
+}
+
+impl TcpAccept {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Process for TcpAccept {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (stop, local_addr) = (stopped.clone(), self.local_addr);
+        // Never called, but kept by the thread for the token to stop it.
+        let next = waiting(next, &*stopped as *const _ as usize, move|| {
+            stop.store(true, Ordering::SeqCst);
+            // Wakes the listener up from `accept`.
+            let _ = TcpStream::connect(local_addr);
+        });
+        let source = runtime.external_inbox().source();
+        let signal = Arc::new(self.signal);
+        let listener = self.listener;
+        thread::spawn(move|| {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let signal = signal.clone();
+                    source.push(move|runtime: &mut Runtime, ()| signal.emit(value(stream)).call(runtime, |_: &mut Runtime, ()| ()));
+                }
+            }
+            drop(next);
+        });
+    }
+}
+
+/// Listens on `addr`, and emits the connections accepted during an instant on the returned signal.
+///
+/// Connections are accepted on a dedicated thread once the returned process is executed, and
+/// emitted at the start of the next instant. The process never terminates, the thread stopping
+/// when it is cancelled, such as the loser of a `race` or a `timeout`.
+pub fn tcp_listener_signal<A>(addr: A) -> io::Result<(UniqueConsumerSignalConsumer<Vec<TcpStream>, TcpStream>, TcpAccept)>
+    where A: ToSocketAddrs {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    let (producer, consumer) = UniqueConsumerSignalProducer::new(
+        Box::new(|| Vec::new()),
+        Box::new(|mut streams: Vec<TcpStream>, stream| {
+            streams.push(stream);
+            streams
+        }));
+    Ok((consumer, TcpAccept { listener, signal: producer, local_addr }))
+}
+
+/// The process writing the values of a signal, see `write_lines`.
+pub struct WriteLines<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    await: VAwait<V, G>,
+    sender: Arc<Mutex<Sender<String>>>,
+}
+
+impl<V, G> Process for WriteLines<V, G> where V: Clone + Display + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        let sender = self.sender;
+        self.await.call_mut(runtime, move|runtime: &mut Runtime, (await, v): (VAwait<V, G>, V)| {
+            let _ = sender.lock().unwrap().send(v.to_string());
+            WriteLines { await, sender }.call(runtime, next);
+        });
+    }
+}
+
+/// Writes one line per instant in which `signal` is emitted, with the gathered value. Writing
+/// happens on a dedicated thread, and the returned process never terminates.
+pub fn write_lines<S, V, G, W>(signal: &S, writer: W) -> WriteLines<V, G>
+    where S: VSignal<V, G>, V: Clone + Display + Send + Sync + 'static, G: Clone + Send + Sync + 'static,
+          W: Write + Send + 'static {
+    let (sender, receiver) = channel::<String>();
+    thread::spawn(move|| {
+        let mut writer = writer;
+        for line in receiver {
+            if writeln!(writer, "{}", line).and_then(|_| writer.flush()).is_err() {
+                break;
+            }
+        }
+    });
+    WriteLines { await: signal.await(), sender: Arc::new(Mutex::new(sender)) }
+}
@@ -0,0 +1,79 @@
+use super::*;
+
+//  ____                      _
+// | __ )  _____  _____  __| |
+// |  _ \ / _ \ \/ / _ \/ _` |
+// | |_) | (_) >  <  __/ (_| |
+// |____/ \___/_/\_\___|\__,_|
+
+/// The object-safe side of a process, called with a boxed continuation.
+trait BoxedProcess<V>: Send + Sync where V: Send + Sync + 'static {
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, next: Box<Continuation<V>>);
+}
+
+impl<P> BoxedProcess<P::Value> for P where P: Process, P::Value: 'static {
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, next: Box<Continuation<P::Value>>) {
+        (*self).call(runtime, move|runtime: &mut Runtime, v: P::Value| next.call_box(runtime, v));
+    }
+}
+
+/// A process whose type only depends on its value, see `Process::boxed`.
+pub struct ProcessBox<V> where V: Send + Sync + 'static {
+    process: Box<BoxedProcess<V>>,
+}
+
+impl<V> Process for ProcessBox<V> where V: Send + Sync + 'static {
+    type Value = V;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<V> {
+        self.process.call_box(runtime, Box::new(next));
+    }
+}
+
+/// Erases the type of `process`, see `Process::boxed`.
+pub fn process_box<P>(process: P) -> ProcessBox<P::Value> where P: Process, P::Value: 'static {
+    ProcessBox { process: Box::new(process) }
+}
+
+/// The object-safe side of a mutable process, called with a boxed continuation.
+trait BoxedProcessMut<V>: Send + Sync where V: Send + Sync + 'static {
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, next: Box<Continuation<V>>);
+
+    fn call_mut_box(self: Box<Self>, runtime: &mut Runtime, next: Box<Continuation<(ProcessMutBox<V>, V)>>);
+}
+
+impl<P> BoxedProcessMut<P::Value> for P where P: ProcessMut, P::Value: 'static {
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, next: Box<Continuation<P::Value>>) {
+        (*self).call(runtime, move|runtime: &mut Runtime, v: P::Value| next.call_box(runtime, v));
+    }
+
+    fn call_mut_box(self: Box<Self>, runtime: &mut Runtime, next: Box<Continuation<(ProcessMutBox<P::Value>, P::Value)>>) {
+        (*self).call_mut(runtime, move|runtime: &mut Runtime, (process, v): (P, P::Value)| {
+            next.call_box(runtime, (process_mut_box(process), v));
+        });
+    }
+}
+
+/// A mutable process whose type only depends on its value, see `ProcessMut::boxed_mut`.
+pub struct ProcessMutBox<V> where V: Send + Sync + 'static {
+    process: Box<BoxedProcessMut<V>>,
+}
+
+impl<V> Process for ProcessMutBox<V> where V: Send + Sync + 'static {
+    type Value = V;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<V> {
+        self.process.call_box(runtime, Box::new(next));
+    }
+}
+
+impl<V> ProcessMut for ProcessMutBox<V> where V: Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, V)> {
+        self.process.call_mut_box(runtime, Box::new(next));
+    }
+}
+
+/// Erases the type of `process`, see `ProcessMut::boxed_mut`.
+pub fn process_mut_box<P>(process: P) -> ProcessMutBox<P::Value> where P: ProcessMut, P::Value: 'static {
+    ProcessMutBox { process: Box::new(process) }
+}
@@ -0,0 +1,196 @@
+use super::*;
+use std::fmt::Debug;
+use std::io::prelude::*;
+use std::str::FromStr;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+
+//   ____                      _
+//  / ___|___  _ __  ___  ___ | | ___
+// | |   / _ \| '_ \/ __|/ _ \| |/ _ \
+// | |__| (_) | | | \__ \ (_) | |  __/
+//  \____\___/|_| |_|___/\___/|_|\___|
+
+const HELP: &'static str = "commands: signals, get <signal>, emit <signal> <value>, pause, resume, instant, quit";
+
+/// Emits a value parsed from a command on a registered signal.
+type Emitter = Box<Fn(&mut Runtime, &str) -> Result<(), String> + Send + Sync>;
+
+struct ConsoleSignal {
+    name: String,
+    /// The last value gathered by the signal, as its `Debug` representation.
+    last: Arc<Mutex<Option<String>>>,
+    emit: Emitter,
+    /// Starts recording the values of the signal in `last`.
+    watch: Option<Box<Continuation<()>>>,
+}
+
+/// An interactive console to inspect a running program, reading one command per line.
+///
+/// The signals given to `register` can be listed, their last gathered value printed, and values
+/// parsed from the commands emitted on them. `pause` blocks the instant in which it is read until
+/// `resume`, which holds back the whole program with a sequential runtime. The console is an
+/// ordinary process started with `process`, executing once per instant the commands received
+/// since its previous execution.
+pub struct Console {
+    signals: Vec<ConsoleSignal>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console { signals: Vec::new() }
+    }
+
+    /// Makes `signal` available to the commands under `name`. The values emitted from the console
+    /// are parsed with `FromStr`.
+    pub fn register<S, V, G>(&mut self, name: &str, signal: &S)
+        where S: VSignal<V, G> + Clone + Send + Sync, V: Clone + Debug + Send + Sync + 'static,
+              G: Clone + FromStr + Send + Sync + 'static, G::Err: Debug {
+        let last = Arc::new(Mutex::new(None));
+        let emitted = signal.clone();
+        let emit = move|runtime: &mut Runtime, text: &str| {
+            let v = G::from_str(text).map_err(|e| format!("invalid value {:?}: {:?}", text, e))?;
+            emitted.emit(value(v)).call(runtime, |_: &mut Runtime, _| ());
+            Ok(())
+        };
+        let (await, recorded) = (signal.await(), last.clone());
+        self.signals.push(ConsoleSignal {
+            name: name.to_string(),
+            last,
+            emit: Box::new(emit),
+            watch: Some(Box::new(move|runtime: &mut Runtime, ()| watch_next(await, recorded, runtime))),
+        });
+    }
+
+    /// The process executing the commands read from `reader` on a dedicated thread, and writing
+    /// the answers to `writer`. It terminates with `quit`, or once `reader` is exhausted.
+    pub fn process<R, W>(self, reader: R, writer: W) -> ConsoleProcess
+        where R: BufRead + Send + 'static, W: Write + Send + 'static {
+        let (sender, receiver) = channel();
+        thread::spawn(move|| {
+            for line in reader.lines() {
+                match line {
+                    Ok(line) => if sender.send(line).is_err() {
+                        break;
+                    },
+                    Err(_) => break,
+                }
+            }
+        });
+        self.commands(receiver, writer)
+    }
+
+    /// Like `process`, with the commands sent on a channel, one per line. It terminates with
+    /// `quit`, or once every sender is dropped.
+    pub fn commands<W>(self, commands: Receiver<String>, writer: W) -> ConsoleProcess where W: Write + Send + 'static {
+        ConsoleProcess {
+            signals: self.signals,
+            commands: Mutex::new(commands),
+            writer: Mutex::new(Box::new(writer)),
+            started: false,
+        }
+    }
+
+    /// Like `process`, with the standard input and output.
+    pub fn stdio(self) -> ConsoleProcess {
+        let stdin = std::io::BufReader::new(std::io::stdin());
+        self.process(stdin, std::io::stdout())
+    }
+}
+
+/// Records the next value gathered by a registered signal in `last`, then waits for the following
+/// one.
+fn watch_next<V, G>(await: VAwait<V, G>, last: Arc<Mutex<Option<String>>>, runtime: &mut Runtime)
+    where V: Clone + Debug + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    await.clone_process().call(runtime, move|runtime: &mut Runtime, v: V| {
+        *last.lock().unwrap() = Some(format!("{:?}", v));
+        watch_next(await, last, runtime);
+    });
+}
+
+/// What the console does after a command.
+enum Command { Continue, Pause, Resume, Quit }
+
+/// The process executing the commands of a `Console`.
+pub struct ConsoleProcess {
+    signals: Vec<ConsoleSignal>,
+    commands: Mutex<Receiver<String>>,
+    writer: Mutex<Box<Write + Send>>,
+    /// Whether the registered signals are being recorded.
+    started: bool,
+}
+
+impl ConsoleProcess {
+    fn execute(&self, runtime: &mut Runtime, line: &str) -> Command {
+        let mut words = line.trim().splitn(3, ' ');
+        let (command, name, argument) = (words.next().unwrap_or(""), words.next(), words.next());
+        let signal = |name: Option<&str>| self.signals.iter().find(|s| Some(&s.name[..]) == name);
+        let (answer, command) = match (command, signal(name)) {
+            ("", _) => return Command::Continue,
+            ("signals", _) => {
+                let names: Vec<&str> = self.signals.iter().map(|s| &s.name[..]).collect();
+                (names.join(" "), Command::Continue)
+            },
+            ("get", Some(signal)) => {
+                let last = signal.last.lock().unwrap().clone();
+                (last.unwrap_or_else(|| String::from("never emitted")), Command::Continue)
+            },
+            ("emit", Some(signal)) => match (signal.emit)(runtime, argument.unwrap_or("").trim()) {
+                Ok(()) => (format!("emitted on {} at instant {}", signal.name, runtime.instant_index()), Command::Continue),
+                Err(e) => (e, Command::Continue),
+            },
+            ("get", None) | ("emit", None) => (format!("unknown signal {:?}", name.unwrap_or("")), Command::Continue),
+            ("pause", _) => (format!("paused at instant {}", runtime.instant_index()), Command::Pause),
+            ("resume", _) => (String::from("resumed"), Command::Resume),
+            ("instant", _) => (runtime.instant_index().to_string(), Command::Continue),
+            ("quit", _) => (String::from("bye"), Command::Quit),
+            _ => (HELP.to_string(), Command::Continue),
+        };
+        let mut writer = self.writer.lock().unwrap();
+        let _ = writeln!(writer, "{}", answer).and_then(|_| writer.flush());
+        command
+    }
+
+    /// Executes the commands received so far, and waits for `resume` after a `pause`. Returns
+    /// whether the console carries on.
+    fn execute_received(&self, runtime: &mut Runtime) -> bool {
+        let mut paused = false;
+        loop {
+            let received = if paused {
+                self.commands.lock().unwrap().recv().map_err(|_| TryRecvError::Disconnected)
+            } else {
+                self.commands.lock().unwrap().try_recv()
+            };
+            let line = match received {
+                Ok(line) => line,
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            };
+            match self.execute(runtime, &line) {
+                Command::Pause => paused = true,
+                Command::Resume => paused = false,
+                Command::Quit => return false,
+                Command::Continue => (),
+            }
+        }
+    }
+}
+
+impl Process for ConsoleProcess {
+    type Value = ();
+
+    fn call<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        if !self.started {
+            self.started = true;
+            for signal in self.signals.iter_mut() {
+                if let Some(watch) = signal.watch.take() {
+                    watch.call_box(runtime, ());
+                }
+            }
+        }
+        if self.execute_received(runtime) {
+            runtime.on_next_instant(Box::new(move|runtime: &mut Runtime, ()| self.call(runtime, next)));
+        } else {
+            next.call(runtime, ());
+        }
+    }
+}
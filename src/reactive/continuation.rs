@@ -1,4 +1,6 @@
 use super::*;
+use std::any::{Any, TypeId};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 //   ____            _   _                   _   _
 //  / ___|___  _ __ | |_(_)_ __  _   _  __ _| |_(_) ___  _ __
@@ -29,6 +31,29 @@ pub trait Continuation<V>: 'static + Sync + Send where V: Sync + Send {
     fn pause(self) -> Pause<Self> where Self: Sized + 'static {
         Pause { continuation: self }
     }
+
+    /// Creates a new continuation that does nothing once `token` is cancelled.
+    fn guarded(self, token: &WaiterToken) -> Guarded<Self> where Self: Sized {
+        Guarded { continuation: self, token: token.clone() }
+    }
+
+    /// The token of the `Guarded` continuation this continuation ends with, if any. Signals use
+    /// it to forget their cancelled waiters.
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        None
+    }
+
+    /// The key ordering this continuation in a deterministic runtime, if it was given one, see
+    /// `OrderKey`.
+    fn order_key(&self) -> Option<&OrderKey> {
+        None
+    }
+
+    /// The priority with which a runtime executes this continuation once it is queued, see
+    /// `Priority`.
+    fn priority(&self) -> Priority {
+        Priority::NORMAL
+    }
 }
 
 impl<V, F> Continuation<V> for F where F: FnOnce(&mut Runtime, V) + Sync + Send + 'static, V: Sync + Send {
@@ -54,6 +79,10 @@ impl<C, F, V1, V2> Continuation<V1> for Map<C, F>
     fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V1) {
         (*self).call(runtime, value);
     }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        self.continuation.waiter_token()
+    }
 }
 
 pub struct Pause<C> { continuation: C }
@@ -62,10 +91,314 @@ impl<C, V> Continuation<V> for Pause<C>
     where C: Continuation<V> + 'static, V: 'static, V: Sync + Send {
     fn call(self, runtime: &mut Runtime, value: V) {
         let c = self.continuation;
-        runtime.on_next_instant(Box::new(|run: &mut Runtime, _| c.call(run, value)));
+        runtime.on_next_instant(recycled_box(|run: &mut Runtime, _| c.call(run, value)));
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V) {
+        (*self).call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        self.continuation.waiter_token()
+    }
+}
+
+/// The first continuation of a pair created by `join_continuations`.
+pub struct JoinLeft<V1, V2> where V1: Send + Sync + 'static, V2: Send + Sync + 'static {
+    cell: PairJoinCell<V1, V2>,
+    /// The token of the joined continuation.
+    token: Option<WaiterToken>,
+}
+
+impl<V1, V2> Continuation<V1> for JoinLeft<V1, V2> where V1: Send + Sync + 'static, V2: Send + Sync + 'static {
+    fn call(self, runtime: &mut Runtime, value: V1) {
+        self.cell.fill_left(runtime, value);
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V1) {
+        (*self).call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        self.token.as_ref()
+    }
+}
+
+/// The second continuation of a pair created by `join_continuations`.
+pub struct JoinRight<V1, V2> where V1: Send + Sync + 'static, V2: Send + Sync + 'static {
+    cell: PairJoinCell<V1, V2>,
+    /// The token of the joined continuation.
+    token: Option<WaiterToken>,
+}
+
+impl<V1, V2> Continuation<V2> for JoinRight<V1, V2> where V1: Send + Sync + 'static, V2: Send + Sync + 'static {
+    fn call(self, runtime: &mut Runtime, value: V2) {
+        self.cell.fill_right(runtime, value);
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V2) {
+        (*self).call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        self.token.as_ref()
+    }
+}
+
+/// Splits `next` in two continuations, `next` being called with both of their values once both
+/// were called, in any order and from any thread.
+pub fn join_continuations<V1, V2, C>(next: C) -> (JoinLeft<V1, V2>, JoinRight<V1, V2>)
+    where V1: Send + Sync + 'static, V2: Send + Sync + 'static, C: Continuation<(V1, V2)> {
+    let token = next.waiter_token().cloned();
+    let cell = PairJoinCell::completing(next);
+    (JoinLeft { cell: cell.clone(), token: token.clone() }, JoinRight { cell, token })
+}
+
+/// A continuation created by `multi_join_continuations`.
+pub struct JoinSlot<V> where V: Send + Sync + 'static {
+    cell: JoinCell<V>,
+    slot: usize,
+    /// The token of the joined continuation.
+    token: Option<WaiterToken>,
+}
+
+impl<V> Continuation<V> for JoinSlot<V> where V: Send + Sync + 'static {
+    fn call(self, runtime: &mut Runtime, value: V) {
+        self.cell.fill(runtime, self.slot, value);
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V) {
+        (*self).call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        self.token.as_ref()
+    }
+}
+
+/// Like `join_continuations`, for `n` continuations of the same type, `next` being called with
+/// their values in order. `n` must not be zero.
+pub fn multi_join_continuations<V, C>(n: usize, next: C) -> Vec<JoinSlot<V>> where V: Send + Sync + 'static, C: Continuation<Vec<V>> {
+    let token = next.waiter_token().cloned();
+    let cell = JoinCell::completing(n, next);
+    (0..n).map(|slot| JoinSlot { cell: cell.clone(), slot, token: token.clone() }).collect()
+}
+
+/// A continuation calling a closure which calls, in the end, a continuation guarded by `token`,
+/// see `carry`.
+pub struct Carry<F> { f: F, token: Option<WaiterToken> }
+
+impl<F, V> Continuation<V> for Carry<F> where F: FnOnce(&mut Runtime, V) + Send + Sync + 'static, V: Send + Sync {
+    fn call(self, runtime: &mut Runtime, value: V) {
+        (self.f)(runtime, value);
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V) {
+        (*self).call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        self.token.as_ref()
+    }
+}
+
+/// Wraps `f`, a closure calling a continuation with the token `token` once it executed other
+/// processes, so that the signals these processes wait for still forget it once the token is
+/// cancelled. Processes pass `next.waiter_token().cloned()`.
+pub fn carry<F, V>(token: Option<WaiterToken>, f: F) -> Carry<F> where F: FnOnce(&mut Runtime, V) + Send + Sync + 'static, V: Send + Sync {
+    Carry { f, token }
+}
+
+/// Cancels the `Guarded` continuations created with it.
+#[derive(Clone)]
+pub struct WaiterToken {
+    cancelled: Arc<AtomicBool>,
+    /// The signals the guarded continuations wait for, by address.
+    waited: Arc<Mutex<HashMap<usize, Waited>>>,
+}
+
+/// A signal some continuations guarded by a token wait for.
+struct Waited {
+    /// The number of these continuations, the signal being forgotten once none is left.
+    waiters: usize,
+    /// Called at the end of the instant of the cancellation to remove the cancelled waiters from
+    /// the signal.
+    purge: Box<Fn() + Send + Sync>,
+}
+
+impl WaiterToken {
+    pub fn new() -> Self {
+        WaiterToken { cancelled: Arc::new(AtomicBool::new(false)), waited: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// The guarded continuations are not called anymore, and are dropped by the signals they
+    /// wait for at the end of the instant.
+    pub fn cancel(&self, runtime: &mut Runtime) {
+        if self.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let purges: Vec<_> = self.waited.lock().unwrap().drain().map(|(_, waited)| waited.purge).collect();
+        runtime.on_end_of_instant(Box::new(move|_: &mut Runtime, ()| {
+            for purge in purges {
+                purge();
+            }
+        }));
+    }
+
+    /// The number of signals the guarded continuations wait for.
+    pub fn waited_signals(&self) -> usize {
+        self.waited.lock().unwrap().len()
+    }
+
+    /// Counts a guarded continuation waiting for the signal at `signal`, `purge` being called
+    /// once the token is cancelled unless the signal already registered one.
+    fn wait_for<F>(&self, signal: usize, purge: F) where F: Fn() + Send + Sync + 'static {
+        if self.is_cancelled() {
+            // Dropped by the signal when it wakes its waiters.
+            return;
+        }
+        let mut waited = self.waited.lock().unwrap();
+        waited.entry(signal).or_insert_with(|| Waited { waiters: 0, purge: Box::new(purge) }).waiters += 1;
+    }
+
+    /// Forgets a guarded continuation which stopped waiting for the signal at `signal`.
+    fn stop_waiting(&self, signal: usize) {
+        let mut waited = self.waited.lock().unwrap();
+        let done = match waited.get_mut(&signal) {
+            Some(entry) => {
+                entry.waiters -= 1;
+                entry.waiters == 0
+            },
+            None => false,
+        };
+        if done {
+            waited.remove(&signal);
+        }
+    }
+}
+
+/// A continuation waiting for a signal, see `waiting`.
+pub(crate) struct Waiting<C> {
+    continuation: Option<C>,
+    token: Option<WaiterToken>,
+    signal: usize,
+}
+
+impl<C, V> Continuation<V> for Waiting<C> where C: Continuation<V>, V: Sync + Send {
+    fn call(mut self, runtime: &mut Runtime, value: V) {
+        let c = self.continuation.take().unwrap();
+        drop(self);
+        c.call(runtime, value);
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V) {
+        (*self).call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        self.token.as_ref()
+    }
+
+    fn priority(&self) -> Priority {
+        self.continuation.as_ref().map_or(Priority::NORMAL, |c| c.priority())
+    }
+}
+
+impl<C> Drop for Waiting<C> {
+    fn drop(&mut self) {
+        if let Some(ref token) = self.token {
+            token.stop_waiting(self.signal);
+        }
+    }
+}
+
+/// Wraps `c` before the signal at address `signal` stores it, so that `purge` is called to drop
+/// it if its token is cancelled while it waits. The token forgets the signal once the signal
+/// called or dropped all of its continuations.
+pub(crate) fn waiting<C, V, F>(c: C, signal: usize, purge: F) -> Waiting<C>
+    where C: Continuation<V>, V: Send + Sync, F: Fn() + Send + Sync + 'static {
+    let token = c.waiter_token().cloned();
+    if let Some(ref token) = token {
+        token.wait_for(signal, purge);
+    }
+    Waiting { continuation: Some(c), token, signal }
+}
+
+/// Whether `c` is guarded by a cancelled token.
+pub(crate) fn is_cancelled<V>(c: &Continuation<V>) -> bool where V: Send + Sync + 'static {
+    c.waiter_token().map_or(false, |token| token.is_cancelled())
+}
+
+/// A continuation that does nothing once its token is cancelled, see `Continuation::guarded`.
+pub struct Guarded<C> { continuation: C, token: WaiterToken }
+
+impl<C, V> Continuation<V> for Guarded<C> where C: Continuation<V>, V: Sync + Send {
+    fn call(self, runtime: &mut Runtime, value: V) {
+        if !self.token.is_cancelled() {
+            self.continuation.call(runtime, value);
+        }
     }
 
     fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V) {
         (*self).call(runtime, value);
     }
-}
\ No newline at end of file
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        Some(&self.token)
+    }
+}
+
+/// Maximum number of allocations kept for each continuation type by `recycled_box`.
+const POOL_CAPACITY: usize = 256;
+
+thread_local! {
+    /// Allocations of the `Recycled` continuations already called, by continuation type.
+    static POOLS: RefCell<HashMap<TypeId, Vec<Box<Any>>>> = RefCell::new(HashMap::new());
+}
+
+/// A boxed continuation giving its allocation back to the pool of the current thread once called.
+struct Recycled<C> { continuation: Option<C> }
+
+impl<C, V> Continuation<V> for Recycled<C> where C: Continuation<V>, V: Sync + Send {
+    fn call(self, runtime: &mut Runtime, value: V) {
+        self.continuation.unwrap().call(runtime, value);
+    }
+
+    fn call_box(mut self: Box<Self>, runtime: &mut Runtime, value: V) {
+        let c = self.continuation.take().unwrap();
+        // The pool may already be destroyed if the thread is exiting.
+        let _ = POOLS.try_with(|pools| {
+            let mut pools = pools.borrow_mut();
+            let pool = pools.entry(TypeId::of::<Recycled<C>>()).or_insert_with(Vec::new);
+            if pool.len() < POOL_CAPACITY {
+                pool.push(self);
+            }
+        });
+        c.call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        self.continuation.as_ref().and_then(|c| c.waiter_token())
+    }
+    fn priority(&self) -> Priority {
+        self.continuation.as_ref().map_or(Priority::NORMAL, |c| c.priority())
+    }
+}
+
+/// Boxes `c` reusing the allocation of a continuation of the same type already called on this
+/// thread if there is one, so that loops reach a steady state without allocation.
+pub fn recycled_box<C, V>(c: C) -> Box<Continuation<V>> where C: Continuation<V>, V: Sync + Send {
+    let recycled = POOLS.try_with(|pools| {
+        pools.borrow_mut().get_mut(&TypeId::of::<Recycled<C>>()).and_then(|pool| pool.pop())
+    }).ok().and_then(|b| b);
+    let mut b: Box<Recycled<C>> = match recycled {
+        Some(b) => b.downcast().unwrap(),
+        None => Box::new(Recycled { continuation: None }),
+    };
+    b.continuation = Some(c);
+    b
+}
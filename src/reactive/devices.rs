@@ -0,0 +1,152 @@
+#[cfg(feature = "gamepad")]
+extern crate gilrs;
+
+use super::*;
+#[cfg(feature = "gamepad")]
+use super::blocking_io::Inbox;
+#[cfg(feature = "gamepad")]
+use self::gilrs::{Axis, Button, EventType, Gilrs};
+#[cfg(feature = "gamepad")]
+use std::sync::mpsc::channel;
+
+//  ____            _
+// |  _ \  _____   _(_) ___ ___  ___
+// | | | |/ _ \ \ / / |/ __/ _ \/ __|
+// | |_| |  __/\ V /| | (_|  __/\__ \
+// |____/ \___| \_/ |_|\___\___||___/
+
+/// Signals fed by an input device, one per button of type `B` and per axis of type `A`.
+///
+/// A button signal is emitted with `true` when the button is pressed and `false` when it is
+/// released, an axis signal with the new position of the axis, in `-1.0..1.0`. If a button or an
+/// axis changes several times during an instant, the signal is emitted with its last state.
+///
+/// Signals are only kept alive by their users, a signal nobody uses anymore is freed and a new
+/// one is created the next time it is asked for.
+pub struct InputSignals<B, A> where B: Eq + Hash, A: Eq + Hash {
+    buttons: Arc<Mutex<HashMap<B, WeakValueSignal<bool, bool>>>>,
+    axes: Arc<Mutex<HashMap<A, WeakValueSignal<f32, f32>>>>,
+}
+
+impl<B, A> Clone for InputSignals<B, A> where B: Eq + Hash, A: Eq + Hash {
+    fn clone(&self) -> Self {
+        InputSignals { buttons: self.buttons.clone(), axes: self.axes.clone() }
+    }
+}
+
+impl<B, A> InputSignals<B, A> where B: Eq + Hash, A: Eq + Hash {
+    pub fn new() -> Self {
+        InputSignals { buttons: Arc::new(Mutex::new(HashMap::new())), axes: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    pub fn button(&self, button: B) -> ValueSignal<bool, bool> {
+        let mut buttons = self.buttons.lock().unwrap();
+        if let Some(signal) = buttons.get(&button).and_then(|signal| signal.upgrade()) {
+            return signal;
+        }
+        let signal = ValueSignal::new(false, Box::new(|_, pressed| pressed));
+        buttons.insert(button, signal.downgrade());
+        signal
+    }
+
+    pub fn axis(&self, axis: A) -> ValueSignal<f32, f32> {
+        let mut axes = self.axes.lock().unwrap();
+        if let Some(signal) = axes.get(&axis).and_then(|signal| signal.upgrade()) {
+            return signal;
+        }
+        let signal = ValueSignal::new(0.0, Box::new(|_, position| position));
+        axes.insert(axis, signal.downgrade());
+        signal
+    }
+}
+
+/// Signals fed by the gamepads, shared by every connected gamepad, see `InputSignals`.
+#[cfg(feature = "gamepad")]
+pub type GamepadSignals = InputSignals<Button, Axis>;
+
+/// A change of a button or of an axis of an input device.
+pub enum InputEvent<B, A> {
+    Button(B, bool),
+    Axis(A, f32),
+}
+
+/// Emits the signal of the button or of the axis changed by `event`, unless nobody uses it, for
+/// the processes feeding `signals` from a device.
+pub fn emit_input<B, A>(runtime: &mut Runtime, signals: &InputSignals<B, A>, event: InputEvent<B, A>)
+    where B: Eq + Hash, A: Eq + Hash {
+    match event {
+        InputEvent::Button(button, pressed) => {
+            let signal = signals.buttons.lock().unwrap().get(&button).and_then(|signal| signal.upgrade());
+            if let Some(signal) = signal {
+                signal.emit(value(pressed)).call(runtime, |_: &mut Runtime, _| ());
+            }
+        },
+        InputEvent::Axis(axis, position) => {
+            let signal = signals.axes.lock().unwrap().get(&axis).and_then(|signal| signal.upgrade());
+            if let Some(signal) = signal {
+                signal.emit(value(position)).call(runtime, |_: &mut Runtime, _| ());
+            }
+        },
+    }
+}
+
+/// The change of a gamepad input reported by a gilrs event, if any.
+#[cfg(feature = "gamepad")]
+fn gamepad_input(event: EventType) -> Option<InputEvent<Button, Axis>> {
+    match event {
+        EventType::ButtonPressed(button, _) => Some(InputEvent::Button(button, true)),
+        EventType::ButtonReleased(button, _) => Some(InputEvent::Button(button, false)),
+        EventType::AxisChanged(axis, position, _) => Some(InputEvent::Axis(axis, position)),
+        _ => None,
+    }
+}
+
+/// The process emitting the gamepad events received since the previous instant, never terminates.
+#[cfg(feature = "gamepad")]
+pub struct GamepadPoller {
+    inbox: Inbox<InputEvent<Button, Axis>>,
+    signals: GamepadSignals,
+}
+
+#[cfg(feature = "gamepad")]
+impl Process for GamepadPoller {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        for event in self.inbox.drain() {
+            emit_input(runtime, &self.signals, event);
+        }
+        runtime.on_next_instant(Box::new(move|run: &mut Runtime, ()| self.call(run, next)));
+    }
+}
+
+/// Starts listening to the gamepads on a dedicated thread. Fails if the platform is not supported.
+#[cfg(feature = "gamepad")]
+pub fn gamepad_signals() -> Result<(GamepadSignals, GamepadPoller), String> {
+    let signals = GamepadSignals::new();
+    let inbox = Inbox::new();
+    let inbox_ref = inbox.clone();
+    let (started, is_started) = channel();
+    thread::spawn(move|| {
+        let mut gilrs = match Gilrs::new() {
+            Ok(gilrs) => {
+                let _ = started.send(Ok(()));
+                gilrs
+            },
+            Err(e) => {
+                let _ = started.send(Err(e.to_string()));
+                return;
+            },
+        };
+        loop {
+            while let Some(event) = gilrs.next_event() {
+                if let Some(input) = gamepad_input(event.event) {
+                    inbox_ref.push(input);
+                }
+            }
+            thread::sleep(time::Duration::from_millis(1));
+        }
+    });
+    is_started.recv().map_err(|e| e.to_string())??;
+    Ok((signals.clone(), GamepadPoller { inbox, signals }))
+}
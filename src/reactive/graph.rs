@@ -0,0 +1,169 @@
+use super::*;
+
+//   ____                 _
+//  / ___|_ __ __ _ _ __ | |__
+// | |  _| '__/ _` | '_ \| '_ \
+// | |_| | | | (_| | |_) | | | |
+//  \____|_|  \__,_| .__/|_| |_|
+//                 |_|
+
+/// A dataflow graph, whose nodes compute output signals from the values of input signals.
+///
+/// The nodes are declared with `node`, then `build` gives the process executing them. A node
+/// waits until each of its inputs was emitted once, then computes its output at the instant
+/// following any emission of its inputs, from the last value gathered by each of them. A value
+/// thus crosses every node of a chain with a latency of one instant.
+///
+/// The output of a node inherits the greatest priority of its inputs, so that a value of a high
+/// priority input is not held back by the other continuations at any node of the chain, see
+/// `Priority`.
+pub struct Graph {
+    nodes: Vec<Box<Continuation<()>>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Graph { nodes: Vec::new() }
+    }
+
+    /// A node computing its output from the values of `inputs` with `f`, and the signal emitted
+    /// with the output, holding the last value emitted in the instant. The values given to `f`
+    /// are in the order of `inputs`, and the output has the greatest priority of `inputs`.
+    pub fn node<V, G, W, F>(&mut self, inputs: &[ValueSignal<V, G>], f: F) -> ValueSignal<W, W>
+        where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static, W: Clone + Default + Send + Sync + 'static,
+              F: FnMut(&[V]) -> W + Send + Sync + 'static {
+        assert!(!inputs.is_empty(), "a node of a graph needs at least one input");
+        let priority = inputs.iter().map(|input| input.priority()).max().unwrap();
+        let output = ValueSignal::new(W::default(), Box::new(|_, w| w)).with_priority(priority);
+        let node = Node {
+            inputs: combine_latest(inputs),
+            output: output.clone(),
+            f,
+        };
+        self.nodes.push(Box::new(move|runtime: &mut Runtime, ()| node.wait(runtime)));
+        output
+    }
+
+    /// The process executing the nodes of the graph, which terminates immediately. The nodes keep
+    /// computing their outputs while their inputs are emitted.
+    pub fn build(self) -> GraphProcess {
+        GraphProcess { nodes: self.nodes }
+    }
+}
+
+pub struct GraphProcess {
+    nodes: Vec<Box<Continuation<()>>>,
+}
+
+impl Process for GraphProcess {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        for node in self.nodes {
+            node.call_box(runtime, ());
+        }
+        next.call(runtime, ());
+    }
+}
+
+struct Node<V, G, W, F> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static, W: Clone + Send + Sync + 'static {
+    inputs: CombineLatest<V, G>,
+    output: ValueSignal<W, W>,
+    f: F,
+}
+
+impl<V, G, W, F> Node<V, G, W, F>
+    where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static, W: Clone + Send + Sync + 'static,
+          F: FnMut(&[V]) -> W + Send + Sync + 'static {
+    /// Computes the output each time the inputs are combined.
+    fn wait(self, runtime: &mut Runtime) {
+        let Node {inputs, output, mut f} = self;
+        inputs.call_mut(runtime, move|runtime: &mut Runtime, (inputs, values): (CombineLatest<V, G>, Vec<V>)| {
+            let w = f(&values);
+            let node = Node {inputs, output: output.clone(), f};
+            output.emit(value(w)).call(runtime, move|runtime: &mut Runtime, _| node.wait(runtime));
+        });
+    }
+}
+
+pub struct CombineLatest<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    inputs: Vec<ValueSignal<V, G>>,
+    /// The last value gathered by each input, once it was emitted.
+    latest: Vec<Option<V>>,
+}
+
+/// Waits for the first instant in which one of `inputs` is emitted, then calls `next` at the next
+/// instant with the values they gathered, `None` for the absent ones. Every input is awaited with
+/// the greatest priority of `inputs`, so that a value of a high priority input is combined as
+/// soon as it is delivered, rather than once the inputs of lower priority are.
+fn next_emission<V, G, C>(inputs: &[ValueSignal<V, G>], runtime: &mut Runtime, next: C)
+    where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static, C: Continuation<Vec<Option<V>>> {
+    let priority = inputs.iter().map(|input| input.priority()).max().unwrap();
+    let next = Arc::new(Mutex::new(Some(next)));
+    let token = WaiterToken::new();
+    for input in inputs {
+        let (next, cancel, inputs) = (next.clone(), token.clone(), inputs.to_vec());
+        let woken = move|runtime: &mut Runtime, ()| {
+            let next = next.lock().unwrap().take();
+            if let Some(next) = next {
+                cancel.cancel(runtime);
+                let slots = multi_join_continuations(inputs.len(), next);
+                for (input, slot) in inputs.iter().zip(slots) {
+                    input.runtime().await_or(runtime, prioritized(priority, Box::new(slot)));
+                }
+            }
+        };
+        input.await_immediate().call(runtime, woken.guarded(&token));
+    }
+}
+
+impl<V, G> CombineLatest<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn combine<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Vec<V>)> {
+        let inputs = self.inputs.clone();
+        let mut combine = self;
+        next_emission(&inputs, runtime, move|runtime: &mut Runtime, values: Vec<Option<V>>| {
+            for (latest, v) in combine.latest.iter_mut().zip(values) {
+                if v.is_some() {
+                    *latest = v;
+                }
+            }
+            let complete: Option<Vec<V>> = combine.latest.iter().cloned().collect();
+            match complete {
+                Some(values) => next.call(runtime, (combine, values)),
+                None => combine.combine(runtime, next),
+            }
+        });
+    }
+}
+
+impl<V, G> Process for CombineLatest<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    type Value = Vec<V>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        self.combine(runtime, next.map(|(_, values): (Self, Vec<V>)| values));
+    }
+}
+
+impl<V, G> ProcessMut for CombineLatest<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        self.combine(runtime, next);
+    }
+}
+
+impl<V, G> CloneProcess for CombineLatest<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        CombineLatest { inputs: self.inputs.clone(), latest: self.latest.clone() }
+    }
+}
+
+/// Waits until one of `inputs` is emitted, and resumes at the next instant with the last value
+/// gathered by each of them, in the order of `inputs`. The values are only delivered once each
+/// input was emitted, until then the process keeps waiting. Executed as a `ProcessMut`, the
+/// process remembers the last values from one execution to the next, to merge streams such as
+/// the readings of several sensors. The process resumes with the greatest priority of `inputs`,
+/// see `Priority`.
+pub fn combine_latest<V, G>(inputs: &[ValueSignal<V, G>]) -> CombineLatest<V, G>
+    where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    assert!(!inputs.is_empty(), "combine_latest needs at least one signal");
+    CombineLatest { inputs: inputs.to_vec(), latest: vec![None; inputs.len()] }
+}
@@ -0,0 +1,182 @@
+use super::*;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+//  _   _ _   _
+// | | | | |_| |_ _ __
+// | |_| | __| __| '_ \
+// |  _  | |_| |_| |_) |
+// |_| |_|\__|\__| .__/
+//               |_|
+
+/// A request received by an `http_signal` endpoint. The body is left as is, typically JSON.
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    /// Identifies the request when responding to it with `HttpResponder::respond`.
+    pub id: u64,
+    pub method: String,
+    pub path: String,
+    pub body: String,
+}
+
+/// Sends the responses of the requests received by an `http_signal` endpoint.
+#[derive(Clone)]
+pub struct HttpResponder {
+    pending: Arc<Mutex<HashMap<u64, TcpStream>>>,
+}
+
+impl HttpResponder {
+    /// Responds to request `id`, returns `false` if it was already answered.
+    pub fn respond(&self, id: u64, status: u16, body: &str) -> bool {
+        let stream = self.pending.lock().unwrap().remove(&id);
+        match stream {
+            Some(mut stream) => {
+                write_response(&mut stream, status, body);
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+/// Largest body of a request an `http_signal` endpoint accepts, larger ones being answered with
+/// 413 without being emitted.
+pub const MAX_BODY_LENGTH: usize = 1 << 20;
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        500 => "Internal Server Error",
+        _ => "",
+    }
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &str) {
+    let _ = write!(stream, "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                   status, reason(status), body.len(), body);
+    let _ = stream.flush();
+}
+
+/// Reads the method, the path and the body of a request, `None` if its body is larger than
+/// `MAX_BODY_LENGTH`.
+fn read_request(stream: &TcpStream) -> io::Result<Option<(String, String, String)>> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("").to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        let mut split = header.splitn(2, ':');
+        let name = split.next().unwrap_or("").trim().to_lowercase();
+        if name == "content-length" {
+            let length = split.next().unwrap_or("").trim();
+            // Digits which do not fit a `usize` are too large anyway.
+            let too_large = !length.is_empty() && length.bytes().all(|b| b.is_ascii_digit());
+            content_length = length.parse().unwrap_or(if too_large { usize::max_value() } else { 0 });
+        }
+    }
+    if content_length > MAX_BODY_LENGTH {
+        return Ok(None);
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(Some((method, path, body)))
+}
+
+/// The process serving the requests of an `http_signal` endpoint.
+pub struct HttpEndpoint {
+    listener: TcpListener,
+    signal: ValueSignal<Vec<HttpRequest>, HttpRequest>,
+    pending: Arc<Mutex<HashMap<u64, TcpStream>>>,
+    local_addr: SocketAddr,
+}
+
+impl HttpEndpoint {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Process for HttpEndpoint {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (stop, local_addr) = (stopped.clone(), self.local_addr);
+        // Never called, but kept by the thread for the token to stop it, like `TcpAccept`.
+        let next = waiting(next, &*stopped as *const _ as usize, move|| {
+            stop.store(true, Ordering::SeqCst);
+            // Wakes the listener up from `accept`.
+            let _ = TcpStream::connect(local_addr);
+        });
+        let source = Arc::new(runtime.external_inbox().source());
+        let (listener, signal, pending) = (self.listener, self.signal, self.pending);
+        thread::spawn(move|| {
+            let next_id = Arc::new(Mutex::new(0));
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let (source, signal, pending, next_id) = (source.clone(), signal.clone(), pending.clone(), next_id.clone());
+                // A slow client must not delay the others.
+                thread::spawn(move|| {
+                    match read_request(&stream) {
+                        Ok(Some((method, path, body))) => {
+                            let id = {
+                                let mut next_id = next_id.lock().unwrap();
+                                *next_id += 1;
+                                *next_id - 1
+                            };
+                            pending.lock().unwrap().insert(id, stream);
+                            source.emit(&signal, HttpRequest { id, method, path, body });
+                        },
+                        Ok(None) => write_response(&mut stream, 413, ""),
+                        Err(_) => (),
+                    }
+                });
+            }
+            drop(next);
+        });
+    }
+}
+
+/// Serves HTTP on `addr`, emitting the requests received during an instant on the returned signal
+/// at the start of the next one. Each request stays open until it is answered with the returned
+/// `HttpResponder`, requests whose body is larger than `MAX_BODY_LENGTH` being answered with 413.
+///
+/// Requests are accepted on a dedicated thread once the returned process is executed. The process
+/// never terminates, the runtime waiting for requests while it has nothing else to do, and the
+/// thread stops when it is cancelled, such as the loser of a `race`.
+pub fn http_signal<A>(addr: A) -> io::Result<(ValueSignal<Vec<HttpRequest>, HttpRequest>, HttpResponder, HttpEndpoint)>
+    where A: ToSocketAddrs {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    let signal = ValueSignal::new(Vec::new(), Box::new(|mut requests: Vec<HttpRequest>, request| {
+        requests.push(request);
+        requests
+    }));
+    let responder = HttpResponder { pending: Arc::new(Mutex::new(HashMap::new())) };
+    let pending = responder.pending.clone();
+    Ok((signal.clone(), responder, HttpEndpoint { listener, signal, pending, local_addr }))
+}
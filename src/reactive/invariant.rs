@@ -0,0 +1,99 @@
+use super::*;
+use std::fmt::{self, Debug};
+
+//  ___                        _             _
+// |_ _|_ ____   ____ _ _ __(_) __ _ _ __ | |_
+//  | || '_ \ \ / / _` | '__| |/ _` | '_ \| __|
+//  | || | | \ V / (_| | |  | | (_| | | | | |_
+// |___|_| |_|\_/ \__,_|_|  |_|\__,_|_| |_|\__|
+
+/// A failed check of an invariant asserted with `assert_process`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvariantViolation {
+    /// The name given to `assert_process`.
+    pub name: String,
+    /// The instant during which the invariant failed.
+    pub instant: usize,
+    /// The values the invariant failed with, formatted with `Debug`.
+    pub values: String,
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invariant {} broken during instant {} with {}", self.name, self.instant, self.values)
+    }
+}
+
+/// What `assert_process` does with a violation of its invariant.
+#[derive(Clone)]
+pub enum AssertPolicy {
+    /// Panics with the violation, the panic being handled like any other, see `PanicPolicy`.
+    Abort,
+    /// Reports the violation and carries on checking, see `AssertPolicy::emit_on`.
+    Report(Arc<Fn(&mut Runtime, InvariantViolation) + Send + Sync>),
+}
+
+impl AssertPolicy {
+    /// Emits the violations on `errors`.
+    pub fn emit_on<V>(errors: &ValueSignal<V, InvariantViolation>) -> Self where V: Clone + Send + Sync + 'static {
+        let errors = errors.clone();
+        AssertPolicy::Report(Arc::new(move|runtime: &mut Runtime, violation: InvariantViolation| {
+            errors.emit(value(violation)).call(runtime, |_: &mut Runtime, _| ());
+        }))
+    }
+}
+
+/// One evaluation of the invariant of `assert_process`.
+pub struct Check<P> {
+    invariant: P,
+    name: Arc<String>,
+    policy: AssertPolicy,
+}
+
+impl<P> Check<P> {
+    fn check<E>(&self, runtime: &mut Runtime, result: Result<(), E>) where E: Debug {
+        let values = match result {
+            Ok(()) => return,
+            Err(values) => values,
+        };
+        let violation = InvariantViolation {
+            name: self.name.to_string(),
+            instant: runtime.instant_index(),
+            values: format!("{:?}", values),
+        };
+        match self.policy {
+            AssertPolicy::Abort => panic!("{}", violation),
+            AssertPolicy::Report(ref report) => report(runtime, violation),
+        }
+    }
+}
+
+impl<P, E> Process for Check<P> where P: ProcessMut<Value = Result<(), E>>, E: Debug + Send + Sync + 'static {
+    type Value = LoopStatus<()>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        self.call_mut(runtime, |runtime: &mut Runtime, (_, status)| next.call(runtime, status));
+    }
+}
+
+impl<P, E> ProcessMut for Check<P> where P: ProcessMut<Value = Result<(), E>>, E: Debug + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let Check {invariant, name, policy} = self;
+        invariant.call_mut(runtime, move|runtime: &mut Runtime, (invariant, result)| {
+            let check = Check {invariant, name, policy};
+            check.check(runtime, result);
+            next.call(runtime, (check, LoopStatus::Continue));
+        });
+    }
+}
+
+/// Evaluates `invariant` over and over, applying `policy` each time it fails with the values
+/// involved.
+///
+/// The invariant is restarted as soon as it completes, so it must wait between two evaluations:
+/// `value(()).pause().map(...)` checks a condition every instant, `signal.await().map(...)` checks
+/// each value of a signal. The process never completes.
+pub fn assert_process<P, E>(name: &str, invariant: P, policy: AssertPolicy) -> While<Check<P>>
+    where P: ProcessMut<Value = Result<(), E>>, E: Debug + Send + Sync + 'static {
+    Check {invariant, name: Arc::new(name.to_string()), policy}.while_loop().named(name)
+}
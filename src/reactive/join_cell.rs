@@ -0,0 +1,172 @@
+use super::*;
+
+//      _       _        ____     _ _
+//     | | ___ (_)_ __  / ___|___| | |
+//  _  | |/ _ \| | '_ \| |   / _ \ | |
+// | |_| | (_) | | | | | |__|  __/ | |
+//  \___/ \___/|_|_| |_|\____\___|_|_|
+
+struct JoinState<S, V> {
+    slots: S,
+    missing: usize,
+    next: Option<Box<Continuation<V>>>,
+    /// The greatest key of the branches which reached the join so far in a deterministic
+    /// runtime, the continuation being called with it whichever branch comes last.
+    key: Option<OrderKey>,
+}
+
+impl<S, V> JoinState<S, V> where V: Send + Sync + 'static {
+    /// Accounts for the key of the branch reaching the join.
+    fn arrive(&mut self) {
+        let key = next_order_key();
+        if key > self.key {
+            self.key = key;
+        }
+    }
+
+    /// Takes the continuation out if every slot is filled and a continuation is registered.
+    fn take_next(&mut self) -> Option<Box<Continuation<V>>> {
+        if self.missing == 0 {
+            let key = self.key.take();
+            self.next.take().map(|next| with_key(key, next))
+        } else {
+            None
+        }
+    }
+}
+
+/// A join point collecting the values of `n` branches, then calling a continuation with all of
+/// them, ordered by slot.
+///
+/// Slots can be filled from any thread, in any order, and the continuation can be registered
+/// before or after the slots are filled. A cell with no slot completes as soon as its continuation
+/// is registered.
+pub struct JoinCell<V> where V: Send + Sync + 'static {
+    state: Arc<Mutex<JoinState<Vec<Option<V>>, Vec<V>>>>,
+}
+
+impl<V> Clone for JoinCell<V> where V: Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        JoinCell { state: self.state.clone() }
+    }
+}
+
+impl<V> JoinCell<V> where V: Send + Sync + 'static {
+    pub fn new(n: usize) -> Self {
+        let mut slots = Vec::with_capacity(n);
+        for _ in 0..n { slots.push(None); }
+        JoinCell { state: Arc::new(Mutex::new(JoinState { slots, missing: n, next: None, key: None })) }
+    }
+
+    /// A cell of `n` slots calling `next` once they are filled, which must not be empty.
+    pub fn completing<C>(n: usize, next: C) -> Self where C: Continuation<Vec<V>> {
+        assert!(n > 0, "a join cell without slot needs a runtime to complete");
+        let cell = JoinCell::new(n);
+        cell.state.lock().unwrap().next = Some(Box::new(next));
+        cell
+    }
+
+    /// Fills `slot` with `value`, calls the continuation if it was the last missing one.
+    ///
+    /// Panics if the slot was already filled.
+    pub fn fill(&self, runtime: &mut Runtime, slot: usize, value: V) {
+        let next = {
+            let mut state = self.state.lock().unwrap();
+            state.arrive();
+            assert!(state.slots[slot].is_none(), "join slot {} filled twice", slot);
+            state.slots[slot] = Some(value);
+            state.missing -= 1;
+            state.take_next()
+        };
+        self.try_complete(runtime, next);
+    }
+
+    /// Registers the continuation called once every slot is filled.
+    pub fn on_complete<C>(&self, runtime: &mut Runtime, next: C) where C: Continuation<Vec<V>> {
+        let next = {
+            let mut state = self.state.lock().unwrap();
+            state.arrive();
+            state.next = Some(Box::new(next));
+            state.take_next()
+        };
+        self.try_complete(runtime, next);
+    }
+
+    fn try_complete(&self, runtime: &mut Runtime, next: Option<Box<Continuation<Vec<V>>>>) {
+        if let Some(next) = next {
+            let values = {
+                let mut state = self.state.lock().unwrap();
+                state.slots.iter_mut().map(|v| v.take().unwrap()).collect()
+            };
+            call_ordered(runtime, next, values);
+        }
+    }
+}
+
+/// A join point between two branches of different value types.
+pub struct PairJoinCell<V1, V2> where V1: Send + Sync + 'static, V2: Send + Sync + 'static {
+    state: Arc<Mutex<JoinState<(Option<V1>, Option<V2>), (V1, V2)>>>,
+}
+
+impl<V1, V2> Clone for PairJoinCell<V1, V2> where V1: Send + Sync + 'static, V2: Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        PairJoinCell { state: self.state.clone() }
+    }
+}
+
+impl<V1, V2> PairJoinCell<V1, V2> where V1: Send + Sync + 'static, V2: Send + Sync + 'static {
+    pub fn new() -> Self {
+        PairJoinCell { state: Arc::new(Mutex::new(JoinState { slots: (None, None), missing: 2, next: None, key: None })) }
+    }
+
+    /// A cell calling `next` once both slots are filled.
+    pub fn completing<C>(next: C) -> Self where C: Continuation<(V1, V2)> {
+        let cell = PairJoinCell::new();
+        cell.state.lock().unwrap().next = Some(Box::new(next));
+        cell
+    }
+
+    pub fn fill_left(&self, runtime: &mut Runtime, value: V1) {
+        let next = {
+            let mut state = self.state.lock().unwrap();
+            state.arrive();
+            assert!(state.slots.0.is_none(), "left join slot filled twice");
+            state.slots.0 = Some(value);
+            state.missing -= 1;
+            state.take_next()
+        };
+        self.try_complete(runtime, next);
+    }
+
+    pub fn fill_right(&self, runtime: &mut Runtime, value: V2) {
+        let next = {
+            let mut state = self.state.lock().unwrap();
+            state.arrive();
+            assert!(state.slots.1.is_none(), "right join slot filled twice");
+            state.slots.1 = Some(value);
+            state.missing -= 1;
+            state.take_next()
+        };
+        self.try_complete(runtime, next);
+    }
+
+    pub fn on_complete<C>(&self, runtime: &mut Runtime, next: C) where C: Continuation<(V1, V2)> {
+        let next = {
+            let mut state = self.state.lock().unwrap();
+            state.arrive();
+            state.next = Some(Box::new(next));
+            state.take_next()
+        };
+        self.try_complete(runtime, next);
+    }
+
+    fn try_complete(&self, runtime: &mut Runtime, next: Option<Box<Continuation<(V1, V2)>>>) {
+        if let Some(next) = next {
+            let values = {
+                let mut state = self.state.lock().unwrap();
+                (state.slots.0.take().unwrap(), state.slots.1.take().unwrap())
+            };
+            call_ordered(runtime, next, values);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use super::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+//  _          _
+// | |    __ _| |_ ___ _ __   ___ _   _
+// | |   / _` | __/ _ \ '_ \ / __| | | |
+// | |__| (_| | ||  __/ | | | (__| |_| |
+// |_____\__,_|\__\___|_| |_|\___|\__, |
+//                                |___/
+
+static NEXT_TAG: AtomicUsize = AtomicUsize::new(0);
+
+/// A value carrying an identifier, kept by the stages of a pipeline so that `measure_latency`
+/// can correlate its emissions on different signals.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tagged<T> {
+    pub id: usize,
+    pub value: T,
+}
+
+impl<T> Tagged<T> {
+    /// Tags `value` with a new identifier.
+    pub fn new(value: T) -> Self {
+        Tagged { id: NEXT_TAG.fetch_add(1, Ordering::SeqCst), value }
+    }
+
+    /// Transforms the value, keeping its identifier.
+    pub fn map<U, F>(self, f: F) -> Tagged<U> where F: FnOnce(T) -> U {
+        Tagged { id: self.id, value: f(self.value) }
+    }
+}
+
+/// Emits `v` on `signal`, tagged with a new identifier at each execution, so that the copies made
+/// by `clone_process` or `repeat` are told apart.
+pub fn emit_tagged<S, V, T>(signal: &S, v: T) -> VEmit<V, Tagged<T>, process::Map<Value<T>, fn(T) -> Tagged<T>>>
+    where S: VSignal<V, Tagged<T>>, V: Clone + Send + Sync + 'static, T: Clone + Send + Sync + 'static {
+    signal.emit(value(v).map(Tagged::new as fn(T) -> Tagged<T>))
+}
+
+/// The gathered value of a signal, listing the identifiers of the tagged values it holds.
+pub trait Correlated {
+    fn ids(&self) -> Vec<usize>;
+}
+
+impl<T> Correlated for Tagged<T> {
+    fn ids(&self) -> Vec<usize> {
+        vec![self.id]
+    }
+}
+
+impl<T> Correlated for Option<Tagged<T>> {
+    fn ids(&self) -> Vec<usize> {
+        self.iter().map(|tagged| tagged.id).collect()
+    }
+}
+
+impl<T> Correlated for Vec<Tagged<T>> {
+    fn ids(&self) -> Vec<usize> {
+        self.iter().map(|tagged| tagged.id).collect()
+    }
+}
+
+/// The identifiers watched by `measure_latency`.
+struct Correlation {
+    /// The instant at which each identifier was emitted on `src`, until it is emitted on `dst`.
+    sent: HashMap<usize, usize>,
+    /// The identifiers emitted on `dst` but not yet seen on `src` during `instant`, in which `src`
+    /// may still be read.
+    received: HashSet<usize>,
+    instant: usize,
+}
+
+impl Correlation {
+    /// Forgets the identifiers received during an earlier instant.
+    fn at(&mut self, instant: usize) -> &mut Self {
+        if self.instant != instant {
+            self.received.clear();
+            self.instant = instant;
+        }
+        self
+    }
+}
+
+/// A signal emitted, during each instant in which `dst` is emitted with values tagged like values
+/// emitted earlier on `src`, or in the same instant, with the number of instants between both
+/// emissions, one per value. Only the first emission of an identifier on `dst` is measured.
+///
+/// The signals are watched while the returned process runs, which never terminates. Identifiers
+/// emitted on `src` and never on `dst` are kept until then.
+pub fn measure_latency<S, V1, G1, D, V2, G2>(src: &S, dst: &D)
+    -> (ValueSignal<Vec<usize>, usize>, Join<Forward<VAwait<V1, G1>>, Forward<VAwait<V2, G2>>>)
+    where S: VSignal<V1, G1>, V1: Correlated + Clone + Send + Sync + 'static, G1: Clone + Send + Sync + 'static,
+          D: VSignal<V2, G2>, V2: Correlated + Clone + Send + Sync + 'static, G2: Clone + Send + Sync + 'static {
+    let latencies = ValueSignal::new(Vec::new(), Box::new(|mut latencies: Vec<usize>, latency| {
+        latencies.push(latency);
+        latencies
+    }));
+    let correlation = Arc::new(Mutex::new(Correlation { sent: HashMap::new(), received: HashSet::new(), instant: 0 }));
+    let correlation_ref = correlation.clone();
+    let output = latencies.clone();
+    // `await` resumes at the instant following the emission, on both signals, in any order.
+    let record = move|runtime: &mut Runtime, v: V1| {
+        let now = runtime.instant_index();
+        for id in v.ids() {
+            let received = {
+                let mut correlation = correlation_ref.lock().unwrap();
+                let correlation = correlation.at(now);
+                let received = correlation.received.remove(&id);
+                if !received {
+                    correlation.sent.entry(id).or_insert(now);
+                }
+                received
+            };
+            if received {
+                output.emit(value(0)).call(runtime, |_: &mut Runtime, _| ());
+            }
+        }
+    };
+    let output = latencies.clone();
+    let measure = move|runtime: &mut Runtime, v: V2| {
+        let now = runtime.instant_index();
+        for id in v.ids() {
+            let emitted = {
+                let mut correlation = correlation.lock().unwrap();
+                let correlation = correlation.at(now);
+                let emitted = correlation.sent.remove(&id);
+                if emitted.is_none() {
+                    correlation.received.insert(id);
+                }
+                emitted
+            };
+            if let Some(emitted) = emitted {
+                output.emit(value(now - emitted)).call(runtime, |_: &mut Runtime, _| ());
+            }
+        }
+    };
+    (latencies, join(forward(src.await(), false, record), forward(dst.await(), false, measure)))
+}
@@ -0,0 +1,219 @@
+use super::*;
+use super::blocking_io::Inbox;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+//  _               _        _
+// | |    ___   ___| | _____| |_ ___ _ __
+// | |   / _ \ / __| |/ / __| __/ _ \ '_ \
+// | |__| (_) | (__|   <\__ \ ||  __/ |_) |
+// |_____\___/ \___|_|\_\___/\__\___| .__/
+//                                  |_|
+
+/// An input exchanged between the two peers of a lockstep session.
+pub trait LockstepInput: Clone + Send + Sync + 'static {
+    /// Encodes the input, without tabulations nor line breaks.
+    fn encode(&self) -> String;
+
+    fn decode(encoded: &str) -> Option<Self>;
+}
+
+/// Strings are encoded with their backslashes, tabulations and line breaks escaped.
+impl LockstepInput for String {
+    fn encode(&self) -> String {
+        let mut encoded = String::with_capacity(self.len());
+        for c in self.chars() {
+            match c {
+                '\\' => encoded.push_str("\\\\"),
+                '\t' => encoded.push_str("\\t"),
+                '\n' => encoded.push_str("\\n"),
+                '\r' => encoded.push_str("\\r"),
+                c => encoded.push(c),
+            }
+        }
+        encoded
+    }
+
+    fn decode(encoded: &str) -> Option<Self> {
+        let mut decoded = String::with_capacity(encoded.len());
+        let mut chars = encoded.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                decoded.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('\\') => decoded.push('\\'),
+                Some('t') => decoded.push('\t'),
+                Some('n') => decoded.push('\n'),
+                Some('r') => decoded.push('\r'),
+                _ => return None,
+            }
+        }
+        Some(decoded)
+    }
+}
+
+impl LockstepInput for bool {
+    fn encode(&self) -> String {
+        self.to_string()
+    }
+
+    fn decode(encoded: &str) -> Option<Self> {
+        encoded.parse().ok()
+    }
+}
+
+impl LockstepInput for i32 {
+    fn encode(&self) -> String {
+        self.to_string()
+    }
+
+    fn decode(encoded: &str) -> Option<Self> {
+        encoded.parse().ok()
+    }
+}
+
+impl LockstepInput for u32 {
+    fn encode(&self) -> String {
+        self.to_string()
+    }
+
+    fn decode(encoded: &str) -> Option<Self> {
+        encoded.parse().ok()
+    }
+}
+
+/// An input of a lockstep session, with the peer it comes from: 0 for the host, 1 for the peer
+/// that joined it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerInput<T> {
+    pub player: usize,
+    pub input: T,
+}
+
+/// Sends the local inputs of a lockstep session, from any thread.
+pub struct LockstepInputs<T> {
+    inbox: Inbox<T>,
+}
+
+impl<T> Clone for LockstepInputs<T> {
+    fn clone(&self) -> Self {
+        LockstepInputs { inbox: self.inbox.clone() }
+    }
+}
+
+impl<T> LockstepInputs<T> where T: LockstepInput {
+    /// The input is emitted at the next instant, on both peers.
+    pub fn push(&self, input: T) {
+        self.inbox.push(input);
+    }
+}
+
+/// The process synchronizing a lockstep session, see `lockstep_host`.
+///
+/// Each instant, it sends the local inputs received since the previous instant to the other peer,
+/// blocks until it received the inputs of the other peer for the same instant, then emits both,
+/// host inputs first. It terminates with an error when the connection is lost.
+pub struct Lockstep<T> where T: LockstepInput {
+    inbox: Inbox<T>,
+    signal: ValueSignal<Vec<PlayerInput<T>>, PlayerInput<T>>,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    player: usize,
+    instant: u64,
+}
+
+impl<T> Lockstep<T> where T: LockstepInput {
+    fn exchange(&mut self) -> io::Result<Vec<PlayerInput<T>>> {
+        let local = self.inbox.drain();
+        let mut line = self.instant.to_string();
+        for input in local.iter() {
+            line.push('\t');
+            line.push_str(&input.encode());
+        }
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "lockstep peer disconnected"));
+        }
+        let mut fields = line.trim_end_matches('\n').split('\t');
+        if fields.next().and_then(|instant| instant.parse().ok()) != Some(self.instant) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "lockstep peers out of sync"));
+        }
+        let mut remote = Vec::new();
+        for field in fields {
+            match T::decode(field) {
+                Some(input) => remote.push(input),
+                None => return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid lockstep input")),
+            }
+        }
+
+        let (host, guest) = if self.player == 0 { (local, remote) } else { (remote, local) };
+        Ok(host.into_iter().map(|input| PlayerInput { player: 0, input })
+            .chain(guest.into_iter().map(|input| PlayerInput { player: 1, input }))
+            .collect())
+    }
+}
+
+impl<T> Process for Lockstep<T> where T: LockstepInput {
+    type Value = io::Error;
+
+    fn call<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<io::Error> {
+        match self.exchange() {
+            Ok(inputs) => {
+                for input in inputs {
+                    self.signal.emit(value(input)).call(runtime, |_: &mut Runtime, _| ());
+                }
+                self.instant += 1;
+                runtime.on_next_instant(Box::new(move|run: &mut Runtime, ()| self.call(run, next)));
+            },
+            Err(e) => next.call(runtime, e),
+        }
+    }
+}
+
+fn lockstep<T>(stream: TcpStream, player: usize)
+    -> io::Result<(ValueSignal<Vec<PlayerInput<T>>, PlayerInput<T>>, LockstepInputs<T>, Lockstep<T>)>
+    where T: LockstepInput {
+    stream.set_nodelay(true)?;
+    let signal = ValueSignal::new(Vec::new(), Box::new(|mut inputs: Vec<PlayerInput<T>>, input| {
+        inputs.push(input);
+        inputs
+    }));
+    let inbox = Inbox::new();
+    let inputs = LockstepInputs { inbox: inbox.clone() };
+    let process = Lockstep {
+        inbox,
+        signal: signal.clone(),
+        reader: BufReader::new(stream.try_clone()?),
+        writer: stream,
+        player,
+        instant: 0,
+    };
+    Ok((signal, inputs, process))
+}
+
+/// Waits for a peer on `listener`, and starts a lockstep session with it as player 0.
+///
+/// Both peers emit the same inputs on the returned signal at the same instant, so two copies of a
+/// program whose only external inputs go through the session stay identical, provided they are
+/// executed by deterministic runtimes.
+pub fn lockstep_host<T>(listener: &TcpListener)
+    -> io::Result<(ValueSignal<Vec<PlayerInput<T>>, PlayerInput<T>>, LockstepInputs<T>, Lockstep<T>)>
+    where T: LockstepInput {
+    let (stream, _) = listener.accept()?;
+    lockstep(stream, 0)
+}
+
+/// Joins the lockstep session hosted at `addr`, as player 1.
+pub fn lockstep_join<A, T>(addr: A)
+    -> io::Result<(ValueSignal<Vec<PlayerInput<T>>, PlayerInput<T>>, LockstepInputs<T>, Lockstep<T>)>
+    where A: ToSocketAddrs, T: LockstepInput {
+    lockstep(TcpStream::connect(addr)?, 1)
+}
@@ -0,0 +1,193 @@
+use super::*;
+use std::mem;
+use std::sync::Weak;
+use std::time::Duration;
+
+//  __  __
+// |  \/  | ___ _ __ ___   ___  _ __ _   _
+// | |\/| |/ _ \ '_ ` _ \ / _ \| '__| | | |
+// | |  | |  __/ | | | | | (_) | |  | |_| |
+// |_|  |_|\___|_| |_| |_|\___/|_|   \__, |
+//                                   |___/
+
+/// An estimate of the memory used by a value, including the memory it owns on the heap.
+pub trait SizeHint {
+    fn size_hint(&self) -> usize;
+}
+
+macro_rules! shallow_size_hint {
+    ($($t:ty),*) => {
+        $(impl SizeHint for $t {
+            fn size_hint(&self) -> usize {
+                mem::size_of::<$t>()
+            }
+        })*
+    }
+}
+
+shallow_size_hint!((), bool, char, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64, Duration);
+
+impl SizeHint for String {
+    fn size_hint(&self) -> usize {
+        mem::size_of::<String>() + self.capacity()
+    }
+}
+
+impl<T> SizeHint for Vec<T> where T: SizeHint {
+    fn size_hint(&self) -> usize {
+        let unused = self.capacity() - self.len();
+        mem::size_of::<Vec<T>>() + unused * mem::size_of::<T>() + self.iter().map(SizeHint::size_hint).sum::<usize>()
+    }
+}
+
+impl<T> SizeHint for VecDeque<T> where T: SizeHint {
+    fn size_hint(&self) -> usize {
+        let unused = self.capacity() - self.len();
+        mem::size_of::<VecDeque<T>>() + unused * mem::size_of::<T>() + self.iter().map(SizeHint::size_hint).sum::<usize>()
+    }
+}
+
+impl<T> SizeHint for Option<T> where T: SizeHint {
+    fn size_hint(&self) -> usize {
+        let inner = self.as_ref().map_or(0, |v| v.size_hint().saturating_sub(mem::size_of::<T>()));
+        mem::size_of::<Option<T>>() + inner
+    }
+}
+
+impl<A, B> SizeHint for (A, B) where A: SizeHint, B: SizeHint {
+    fn size_hint(&self) -> usize {
+        self.0.size_hint() + self.1.size_hint()
+    }
+}
+
+impl<A, B, C> SizeHint for (A, B, C) where A: SizeHint, B: SizeHint, C: SizeHint {
+    fn size_hint(&self) -> usize {
+        self.0.size_hint() + self.1.size_hint() + self.2.size_hint()
+    }
+}
+
+/// A number of items and the bytes they use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub count: usize,
+    pub bytes: usize,
+}
+
+impl MemoryUsage {
+    pub(crate) fn add(&mut self, bytes: usize) {
+        self.count += 1;
+        self.bytes += bytes;
+    }
+
+    pub(crate) fn merge(&mut self, other: MemoryUsage) {
+        self.count += other.count;
+        self.bytes += other.bytes;
+    }
+}
+
+/// The memory used by the boxed continuations of `continuations`, without what they own on the
+/// heap.
+pub(crate) fn continuations_usage<'a, I, V>(continuations: I) -> MemoryUsage
+    where I: Iterator<Item = &'a Box<Continuation<V>>>, V: Send + Sync + 'static {
+    let mut usage = MemoryUsage::default();
+    for c in continuations {
+        usage.add(mem::size_of_val(&**c));
+    }
+    usage
+}
+
+/// Pushes `item` at the back of `queue`, panicking instead of growing it past `capacity`, for
+/// the runtimes and signals with a fixed capacity which must not allocate once created.
+pub(crate) fn push_bounded<T>(queue: &mut VecDeque<T>, capacity: Option<usize>, item: T, what: &str) {
+    if let Some(capacity) = capacity {
+        if queue.len() >= capacity {
+            panic!("{} overflowed its capacity of {}", what, capacity);
+        }
+    }
+    queue.push_back(item);
+}
+
+/// The memory used by a signal registered with `ValueSignal::account_memory`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignalMemory {
+    pub name: String,
+    /// The continuations of the processes waiting for the signal.
+    pub waiters: MemoryUsage,
+    /// The value gathered so far, the emissions waiting to be gathered and the recorded ones.
+    pub values: MemoryUsage,
+}
+
+/// The memory used by a program at the end of an instant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemoryReport {
+    pub instant: usize,
+    /// The continuations queued in the runtime for the next instants.
+    pub continuations: MemoryUsage,
+    pub signals: Vec<SignalMemory>,
+}
+
+impl MemoryReport {
+    pub fn total_bytes(&self) -> usize {
+        self.continuations.bytes + self.signals.iter().map(|s| s.waiters.bytes + s.values.bytes).sum::<usize>()
+    }
+}
+
+/// Measures the memory of a signal, `None` once it is dropped.
+type SignalProbe = Box<Fn() -> Option<SignalMemory> + Send + Sync>;
+
+/// Reports the approximate memory used by the continuations queued in a runtime and by the
+/// buffers of the registered signals at the end of every instant, to tell which of them grows.
+///
+/// Given to a runtime with `SequentialRuntime::account_memory` or
+/// `RuntimeBuilder::account_memory`. The sizes of the continuations do not include what they own
+/// on the heap, and the values of the signals are measured with their `SizeHint`.
+#[derive(Clone)]
+pub struct MemoryAccounting {
+    history: usize,
+    reports: Arc<Mutex<VecDeque<MemoryReport>>>,
+    signals: Arc<Mutex<Vec<SignalProbe>>>,
+}
+
+impl MemoryAccounting {
+    /// Keeps the reports of the last `history` instants.
+    pub fn new(history: usize) -> Self {
+        MemoryAccounting {
+            history,
+            reports: Arc::new(Mutex::new(VecDeque::new())),
+            signals: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub(crate) fn add_signal<T, F>(&self, signal: Weak<T>, probe: F) where T: Send + Sync + 'static, F: Fn(&T) -> SignalMemory + Send + Sync + 'static {
+        self.signals.lock().unwrap().push(Box::new(move|| signal.upgrade().map(|signal| probe(&signal))));
+    }
+
+    /// Records the report of the instant `instant`, with the continuations queued in the runtime.
+    pub(crate) fn record(&self, instant: usize, continuations: MemoryUsage) {
+        let mut signals = Vec::new();
+        self.signals.lock().unwrap().retain(|probe| match probe() {
+            Some(memory) => {
+                signals.push(memory);
+                true
+            },
+            None => false,
+        });
+        let mut reports = self.reports.lock().unwrap();
+        if reports.len() == self.history {
+            reports.pop_front();
+        }
+        if self.history > 0 {
+            reports.push_back(MemoryReport { instant, continuations, signals });
+        }
+    }
+
+    /// The reports of the last instants, oldest first.
+    pub fn reports(&self) -> Vec<MemoryReport> {
+        self.reports.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// The report of the last instant executed, if any.
+    pub fn last_report(&self) -> Option<MemoryReport> {
+        self.reports.lock().unwrap().back().cloned()
+    }
+}
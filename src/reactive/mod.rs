@@ -1,25 +1,84 @@
+use std::any::Any;
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
 use std::option::Option;
 use std::sync::{Arc, Mutex};
 use std;
 use std::{thread, time};
 
-mod continuation;
+pub mod continuation;
+pub mod join_cell;
 pub mod runtime;
+#[macro_use]
 pub mod process;
+pub mod profiler;
+pub mod latency;
 pub mod signal;
+pub mod testing;
+pub mod blocking_io;
+pub mod lockstep;
+pub mod ops;
+pub mod pacing;
+pub mod tracing;
+pub mod supervision;
+pub mod replay;
+pub mod actor;
+pub mod memory;
+pub mod audit;
+pub mod invariant;
+pub mod worker_local;
+pub mod race;
+pub mod graph;
+pub mod console;
+pub mod when;
+pub mod boxed;
+pub mod swap;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod devices;
+#[cfg(test)]
 mod tests;
+#[cfg(test)]
 mod bench;
 
 use self::continuation::*;
+use self::join_cell::*;
 use self::runtime::*;
 use self::runtime::sequential_runtime::*;
 use self::runtime::parallel_runtime::*;
 use self::process::*;
+use self::profiler::*;
+use self::latency::*;
+use self::pacing::*;
 use self::signal::*;
 use self::signal::pure_signal::*;
 use self::signal::value_signal::*;
+use self::signal::copy_value_signal::*;
 use self::signal::unique_consumer_signal::*;
-use self::signal::unique_producer_signal::*;
\ No newline at end of file
+use self::signal::unique_producer_signal::*;
+use self::signal::result_signal::*;
+use self::signal::transaction_signal::*;
+use self::signal::group::*;
+use self::signal::latch_signal::*;
+use self::signal::delta_signal::*;
+use self::signal::direction::*;
+use self::signal::spsc_signal::*;
+use self::signal::adapter::*;
+use self::testing::*;
+use self::tracing::*;
+use self::supervision::*;
+use self::replay::*;
+use self::actor::*;
+use self::memory::*;
+use self::audit::*;
+use self::invariant::*;
+use self::worker_local::*;
+use self::race::*;
+use self::graph::*;
+use self::console::*;
+use self::when::*;
+use self::boxed::*;
+use self::swap::*;
\ No newline at end of file
@@ -0,0 +1,68 @@
+use super::*;
+use std::ops::{BitOr, Shr};
+
+//   ___
+//  / _ \ _ __  ___
+// | | | | '_ \/ __|
+// | |_| | |_) \__ \
+//  \___/| .__/|___/
+//       |_|
+
+/// A process composed with operators: `p >> q` executes `p` then `q`, and `p | q` executes both
+/// jointly. `>>` binds tighter than `|`, so `a >> b | c` is `(a >> b) | c`.
+pub struct Flow<P>(P);
+
+pub fn flow<P>(process: P) -> Flow<P> where P: Process {
+    Flow(process)
+}
+
+/// Brings `flow` as a method into scope, with `use reactive::ops::*`.
+pub trait IntoFlow: Process + Sized {
+    fn flow(self) -> Flow<Self> {
+        Flow(self)
+    }
+}
+
+impl<P> IntoFlow for P where P: Process {}
+
+impl<P> Flow<P> {
+    pub fn into_inner(self) -> P {
+        self.0
+    }
+}
+
+impl<P> Process for Flow<P> where P: Process {
+    type Value = P::Value;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        self.0.call(runtime, next);
+    }
+}
+
+impl<P> ProcessMut for Flow<P> where P: ProcessMut {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        self.0.call_mut(runtime, next.map(|(p, v)| (Flow(p), v)));
+    }
+}
+
+impl<P> CloneProcess for Flow<P> where P: CloneProcess {
+    fn clone_process(&self) -> Self {
+        Flow(self.0.clone_process())
+    }
+}
+
+impl<P, Q> Shr<Q> for Flow<P> where P: Process, Q: Process {
+    type Output = Flow<Then<P, Q>>;
+
+    fn shr(self, q: Q) -> Self::Output {
+        Flow(self.0.then(q))
+    }
+}
+
+impl<P, Q> BitOr<Q> for Flow<P> where P: Process, Q: Process {
+    type Output = Flow<Join<P, Q>>;
+
+    fn bitor(self, q: Q) -> Self::Output {
+        Flow(self.0.join(q))
+    }
+}
@@ -0,0 +1,228 @@
+use super::*;
+use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+//  ____            _
+// |  _ \ __ _  ___(_)_ __   __ _
+// | |_) / _` |/ __| | '_ \ / _` |
+// |  __/ (_| | (__| | | | | (_| |
+// |_|   \__,_|\___|_|_| |_|\__, |
+//                          |___/
+
+/// The source of the wall-clock time read by a runtime and its processes, see `Runtime::clock`.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+
+    /// Whether the time moves forward by itself. A runtime left with only timers to wait for on a
+    /// clock which does not stops instead, see `ManualClock`.
+    fn runs(&self) -> bool {
+        true
+    }
+}
+
+/// The clock of a runtime, shared with the processes it executes.
+pub type SharedClock = Arc<Clock>;
+
+/// The time of the system, the clock of the runtimes by default.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock which only moves forward when advanced, to test time-based processes without
+/// sleeping. Its copies share the same time.
+///
+/// A runtime whose only work left is a `sleep` which did not expire yet does not wait for it:
+/// `execute` returns and `TestScheduler::step` returns `false`, the timers expiring at the first
+/// instant executed once the clock is advanced.
+#[derive(Clone)]
+pub struct ManualClock {
+    origin: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        ManualClock { origin: Instant::now(), elapsed: Arc::new(Mutex::new(Duration::from_secs(0))) }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.origin + *self.elapsed.lock().unwrap()
+    }
+
+    fn runs(&self) -> bool {
+        false
+    }
+}
+
+/// A clock running `factor` times as fast as another one from its creation on, to fast-forward
+/// or slow down paced simulations.
+pub struct ScaledClock {
+    clock: SharedClock,
+    origin: Instant,
+    factor: f64,
+}
+
+impl ScaledClock {
+    pub fn new<C>(clock: C, factor: f64) -> Self where C: Clock + 'static {
+        assert!(factor >= 0., "a clock cannot run backwards");
+        let origin = clock.now();
+        ScaledClock { clock: Arc::new(clock), origin, factor }
+    }
+}
+
+impl Clock for ScaledClock {
+    fn now(&self) -> Instant {
+        let elapsed = self.clock.now().duration_since(self.origin);
+        let seconds = (elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9) * self.factor;
+        self.origin + Duration::new(seconds.trunc() as u64, (seconds.fract() * 1e9) as u32)
+    }
+
+    fn runs(&self) -> bool {
+        self.factor > 0. && self.clock.runs()
+    }
+}
+
+/// How the logical duration of the instants is determined, see `SequentialRuntime::dt_signal`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pacing {
+    /// Every instant lasts the same duration, whatever the time it takes to execute, which keeps
+    /// simulations reproducible.
+    Fixed(Duration),
+    /// An instant lasts the time elapsed on the clock of the runtime since the start of the
+    /// previous one, the first instant lasting zero.
+    WallClock,
+}
+
+/// Emits the logical duration of each instant on the dt signal of a runtime, see
+/// `SequentialRuntime::dt_signal`.
+pub(crate) struct Pacer {
+    signal: ValueSignal<Duration, Duration>,
+    pacing: Pacing,
+    /// The start of the previous instant.
+    last: Option<Instant>,
+}
+
+impl Pacer {
+    pub(crate) fn new(pacing: Pacing) -> Self {
+        Pacer { signal: ValueSignal::new(Duration::from_secs(0), Box::new(|_, dt| dt)), pacing, last: None }
+    }
+
+    pub(crate) fn signal(&self) -> ValueSignal<Duration, Duration> {
+        self.signal.clone()
+    }
+
+    /// The continuation emitting the duration of the instant starting at `now`.
+    pub(crate) fn start_instant(&mut self, now: Instant) -> Box<Continuation<()>> {
+        let dt = match self.pacing {
+            Pacing::Fixed(dt) => dt,
+            Pacing::WallClock => {
+                let dt = self.last.map_or(Duration::from_secs(0), |last| now.duration_since(last));
+                self.last = Some(now);
+                dt
+            },
+        };
+        let signal = self.signal.clone();
+        Box::new(move|runtime: &mut Runtime, ()| signal.emit(value(dt)).call(runtime, |_: &mut Runtime, _| ()))
+    }
+}
+
+/// A process resuming at the first instant starting once `duration` elapsed on the clock of the
+/// runtime, see `sleep`.
+#[derive(Clone, Copy)]
+pub struct Sleep {
+    duration: Duration,
+}
+
+impl Process for Sleep {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        let deadline = runtime.clock().now() + self.duration;
+        runtime.on_deadline(deadline, Box::new(next));
+    }
+}
+
+impl ProcessMut for Sleep {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, ())> {
+        self.call(runtime, move|runtime: &mut Runtime, ()| next.call(runtime, (self, ())));
+    }
+}
+
+/// Waits for `duration` without occupying a worker, unlike a `thread::sleep` in `map`, and resumes
+/// at the first instant starting after the deadline, the next instant at the earliest.
+///
+/// Instants keep going in the meantime. A runtime with nothing else to do blocks until the
+/// earliest deadline instead of executing empty instants, unless its clock does not run, see
+/// `ManualClock`. A cancelled sleep, such as the loser of a `race`, is forgotten.
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep { duration }
+}
+
+/// An instant which lasted longer than the period it had to fit in, see `overrun_signal`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overrun {
+    pub instant: usize,
+    pub duration: Duration,
+    pub period: Duration,
+}
+
+/// The number of instants which overran their period, see `overrun_signal`.
+#[derive(Clone)]
+pub struct DeadlineMisses {
+    count: Arc<AtomicUsize>,
+}
+
+impl DeadlineMisses {
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+}
+
+/// The process checking the duration of each instant against a period, never terminates.
+pub struct OverrunMonitor {
+    signal: ValueSignal<Option<Overrun>, Overrun>,
+    period: Duration,
+    misses: DeadlineMisses,
+    last: Option<Instant>,
+}
+
+impl Process for OverrunMonitor {
+    type Value = ();
+
+    fn call<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        let now = runtime.clock().now();
+        if let Some(last) = self.last {
+            let duration = now.duration_since(last);
+            if duration > self.period {
+                self.misses.count.fetch_add(1, Ordering::SeqCst);
+                let overrun = Overrun { instant: runtime.instant_index() - 1, duration, period: self.period };
+                self.signal.emit(value(overrun)).call(runtime, |_: &mut Runtime, _| ());
+            }
+        }
+        self.last = Some(now);
+        runtime.on_next_instant(Box::new(move|run: &mut Runtime, ()| self.call(run, next)));
+    }
+}
+
+/// A signal emitted with the instants lasting longer than `period` on the clock of the runtime,
+/// for programs paced to run an instant per period which need to know when they fall behind.
+/// The misses are also counted in the returned `DeadlineMisses`.
+///
+/// An overrun is reported during the instant following it, the duration of an instant being the
+/// time elapsed between two steps of the returned process, which must run for the signal to be
+/// emitted.
+pub fn overrun_signal(period: Duration) -> (ValueSignal<Option<Overrun>, Overrun>, DeadlineMisses, OverrunMonitor) {
+    let signal = ValueSignal::new(None, Box::new(|_, overrun| Some(overrun)));
+    let misses = DeadlineMisses { count: Arc::new(AtomicUsize::new(0)) };
+    (signal.clone(), misses.clone(), OverrunMonitor { signal, period, misses, last: None })
+}
@@ -19,6 +19,12 @@ pub trait Process: Send + Sync + 'static {
         Map { process: self, map }
     }
 
+    /// Calls `inspect` with a reference to the value of the process, which is then passed on
+    /// unchanged.
+    fn inspect<F>(self, inspect: F) -> Inspect<Self, F> where Self: Sized, F: FnOnce(&Self::Value) + 'static {
+        Inspect { process: self, inspect }
+    }
+
     fn pause(self) -> Pause<Self> where Self: Sized + 'static {
         Pause { process: self }
     }
@@ -35,12 +41,98 @@ pub trait Process: Send + Sync + 'static {
         Then {p: self, q: process}
     }
 
+    /// Executes the process built by `then` from the value of this one, like `and_then` with a
+    /// closure called only once.
+    fn then_with<F, Q>(self, then: F) -> ThenWith<Self, F> where Self: Sized, F: FnOnce(Self::Value) -> Q + Send + Sync + 'static, Q: Process {
+        ThenWith {p: self, then}
+    }
+
     fn join<P>(self, process: P) -> Join<Self, P> where Self: Sized, P: Process {
         Join {
             p1: self,
             p2: process
         }
     }
+
+    /// Executes the process jointly with `process` until one of them completes, see `race`.
+    fn race<P>(self, process: P) -> Race<Self, P> where Self: Sized, P: Process {
+        race(self, process)
+    }
+
+    /// Returns `None` if the process did not complete within `instants` instants, see `timeout`.
+    fn timeout(self, instants: usize) -> Timeout<Self> where Self: Sized {
+        timeout(self, instants)
+    }
+
+    /// Executes the process until the end of the instant in which `trigger` completes, see
+    /// `until`.
+    fn until<Q>(self, trigger: Q) -> Until<Self, Q> where Self: Sized, Q: Process {
+        until(self, trigger)
+    }
+
+    /// Executes the process until `token` is cancelled, see `CancelToken`.
+    fn cancellable(self, token: &CancelToken) -> Until<Self, CancelAwait> where Self: Sized {
+        until(self, token.await())
+    }
+
+    /// Executes the process only during the instants in which `control` completes immediately,
+    /// see `when`.
+    fn when<Q>(self, control: Q) -> When<Self, Q> where Self: Sized, Q: CloneProcess {
+        when(self, control)
+    }
+
+    /// Maps the value of a process returning an `Either` when it is a `Left`.
+    fn map_left<F, A, B, A2>(self, map: F) -> MapLeft<Self, F>
+        where Self: Sized + Process<Value = Either<A, B>>, F: FnOnce(A) -> A2 + 'static {
+        MapLeft { process: self, map }
+    }
+
+    /// Maps the value of a process returning an `Either` when it is a `Right`.
+    fn map_right<F, A, B, B2>(self, map: F) -> MapRight<Self, F>
+        where Self: Sized + Process<Value = Either<A, B>>, F: FnOnce(B) -> B2 + 'static {
+        MapRight { process: self, map }
+    }
+
+    /// Turns the value of a process returning an `Either` into a single type, with `left` or
+    /// `right` depending on the side.
+    fn fold_either<F, G, A, B, V>(self, left: F, right: G) -> FoldEither<Self, F, G>
+        where Self: Sized + Process<Value = Either<A, B>>, F: FnOnce(A) -> V + 'static, G: FnOnce(B) -> V + 'static {
+        FoldEither { process: self, left, right }
+    }
+
+    /// Accounts the time spent executing the process to `name` in `profiler`.
+    fn profile(self, profiler: &Profiler, name: &str) -> Profiled<Self> where Self: Sized {
+        Profiled::new(self, profiler, name)
+    }
+
+    /// Records the executions of the process as spans named `name` in `tracer`.
+    fn traced(self, tracer: &Tracer, name: &str) -> Traced<Self> where Self: Sized {
+        Traced::new(self, tracer, name)
+    }
+
+    /// Hands the panics of the process to the panic handler of the runtime under the name `name`,
+    /// see `supervised`.
+    fn supervised(self, name: &str) -> Supervised<Self> where Self: CloneProcess {
+        supervised(self, name)
+    }
+
+    /// Returns the panic of the process as an error instead of propagating it, see `catch_panic`.
+    fn catch_panic(self) -> CatchPanic<Self> where Self: Sized {
+        catch_panic(self)
+    }
+
+    /// Executes the process at most once for all the copies of the returned handle, the value
+    /// being given to each of them, see `Shared`.
+    fn shared(self) -> Shared<Self> where Self: Sized, Self::Value: Clone {
+        Shared { process: Arc::new(Mutex::new(Some(self))), result: ResultSignal::new() }
+    }
+
+    /// Erases the type of the process, which only depends on its value from then on. Large
+    /// compositions, such as a process per cell of a grid, then stay cheap to compile, at the cost
+    /// of an allocation per execution.
+    fn boxed(self) -> ProcessBox<Self::Value> where Self: Sized, Self::Value: 'static {
+        process_box(self)
+    }
 }
 
 pub struct Then<P, Q> {
@@ -54,7 +146,7 @@ impl<P, Q> Process for Then<P, Q> where P: Process, Q: Process {
     fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
         let p = self.p;
         let q = self.q;
-        p.call(runtime, move|runtime: &mut Runtime, _| q.call(runtime, next))
+        p.call(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, _| q.call(runtime, next)))
     }
 }
 
@@ -62,11 +154,45 @@ impl<P, Q> ProcessMut for Then<P, Q> where P: ProcessMut, Q: ProcessMut {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
         let p = self.p;
         let q = self.q;
-        p.call_mut(runtime, move|runtime: &mut Runtime, (p, _): (P, P::Value)|
-            q.call_mut(runtime, |runtime: &mut Runtime, (q, value): (Q, Q::Value)|
-                next.call(runtime, (p.then(q), value))
-            )
-        )
+        p.call_mut(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, (p, _): (P, P::Value)|
+            q.call_mut(runtime, next.map(|(q, value): (Q, Q::Value)| (p.then(q), value)))
+        ))
+    }
+}
+
+impl<P, Q> CloneProcess for Then<P, Q> where P: CloneProcess, Q: CloneProcess {
+    fn clone_process(&self) -> Self {
+        self.p.clone_process().then(self.q.clone_process())
+    }
+}
+
+pub struct ThenWith<P, F> {
+    p: P,
+    then: F,
+}
+
+impl<P, F, Q> Process for ThenWith<P, F> where P: Process, F: FnOnce(P::Value) -> Q + Send + Sync + 'static, Q: Process {
+    type Value = Q::Value;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let then = self.then;
+        self.p.call(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, v| then(v).call(runtime, next)))
+    }
+}
+
+impl<P, F, Q> ProcessMut for ThenWith<P, F> where P: ProcessMut, F: FnMut(P::Value) -> Q + Send + Sync + 'static, Q: Process {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let mut then = self.then;
+        self.p.call_mut(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, (p, v): (P, P::Value)| {
+            let q = then(v);
+            q.call(runtime, next.map(move|value| (p.then_with(then), value)))
+        }))
+    }
+}
+
+impl<P, F, Q> CloneProcess for ThenWith<P, F> where P: CloneProcess, F: FnOnce(P::Value) -> Q + Clone + Send + Sync + 'static, Q: Process {
+    fn clone_process(&self) -> Self {
+        self.p.clone_process().then_with(self.then.clone())
     }
 }
 
@@ -77,8 +203,57 @@ pub trait ProcessMut: Process {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where
         Self: Sized, C: Continuation<(Self, Self::Value)>;
 
+    /// Executes the process until it returns `LoopStatus::Exit`. The loop only checks that its body
+    /// pauses when asked to, see `While::spin_guard`.
     fn while_loop<V>(self) -> While<Self> where Self: ProcessMut<Value = LoopStatus<V>>, Self: Sized, V: Send + Sync {
-        While {process: self}
+        While {process: self, spin: SpinGuard::new()}
+    }
+
+    /// Executes the process repeatedly, folding its values into an accumulator starting from
+    /// `init` with `fold`, until `fold` returns `FoldStatus::Exit` with the value of the loop. The
+    /// loop can be guarded against spinning like `while_loop`, see `Fold::spin_guard`.
+    fn fold<A, F>(self, init: A, fold: F) -> Fold<Self, F, A>
+        where Self: Sized, F: FnMut(A, Self::Value) -> FoldStatus<A> + Send + Sync + 'static, A: Send + Sync + 'static {
+        let mut spin = SpinGuard::new();
+        spin.name = Some(Arc::new("fold".to_string()));
+        Fold {process: self, fold, init, spin}
+    }
+
+    /// Folds the values of the successive executions of the process into an accumulator starting
+    /// from `init` with `scan`, each execution returning the new accumulator and emitting it on
+    /// `signal`, to build stateful stream processors looping with `while_loop` or `repeat`.
+    fn scan<A, F, S, V>(self, init: A, scan: F, signal: &S) -> VEmit<V, A, Scan<Self, F, A>>
+        where Self: Sized, F: FnMut(A, Self::Value) -> A + Send + Sync + 'static, S: VSignal<V, A>,
+              V: Clone + Send + Sync + 'static, A: Clone + Send + Sync + 'static {
+        signal.emit(Scan {process: self, scan, acc: init})
+    }
+
+    /// Like `Process::boxed`, keeping the process mutable.
+    fn boxed_mut(self) -> ProcessMutBox<Self::Value> where Self: Sized, Self::Value: 'static {
+        process_mut_box(self)
+    }
+
+    /// Executes the process `times` times one after the other, returns the values in order.
+    fn repeat(self, times: usize) -> Repeat<Self> where Self: Sized {
+        Repeat {process: self, times}
+    }
+
+    /// Executes the process at most `k` times per instant, each further execution waiting for the
+    /// next instant, so that the body of a loop which never pauses can run `k` iterations per
+    /// instant instead of one. A `while_loop` guarded against spinning then has to allow `k + 1`
+    /// iterations, see `While::spin_guard`.
+    fn iterations_per_instant(self, k: usize) -> IterationsPerInstant<Self> where Self: Sized {
+        assert!(k > 0, "a process cannot be executed zero times per instant");
+        let spin = SpinGuard::with_limit(k, SpinPolicy::Pause);
+        IterationsPerInstant {process: self, spin}
+    }
+
+    /// Skips the execution of the process when `key` returns a key already computed less than
+    /// `window` instants ago, returning the cached value instead. `key` is called before each
+    /// execution.
+    fn memoize_by<F, K>(self, key: F, window: usize) -> Memoize<Self, F, K>
+        where Self: Sized, F: FnMut() -> K + Send + Sync + 'static, K: Hash + Eq + Send + Sync + 'static, Self::Value: Clone {
+        Memoize {process: self, key, window, cache: HashMap::new()}
     }
 }
 
@@ -86,6 +261,35 @@ pub trait ProcessMut: Process {
 #[derive(Copy, Clone)]
 pub enum LoopStatus<V> { Continue, Exit(V) }
 
+/// Indicates if a `fold` is finished, with the accumulator of the next iteration or its value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FoldStatus<A> { Continue(A), Exit(A) }
+
+/// Indicates if a `while_loop_with` is finished, with the state of the next iteration or its
+/// value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LoopState<S, V> { Continue(S), Exit(V) }
+
+/// A process that can be duplicated before its execution, the copy being executed independently
+/// of the original.
+///
+/// Combinators are `CloneProcess` when the processes and closures they are made of are, the
+/// closures having to be `Clone`. The copy of an emission emits as another process, with its own
+/// place in the order of the gathers.
+pub trait CloneProcess: Process + Sized {
+    fn clone_process(&self) -> Self;
+}
+
+/// `n` independent copies of `process`, to be executed jointly with `multi_join` for instance.
+pub fn replicate<P>(process: P, n: usize) -> Vec<P> where P: CloneProcess {
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut copies: Vec<P> = (1..n).map(|_| process.clone_process()).collect();
+    copies.insert(0, process);
+    copies
+}
+
 pub fn execute_process<P>(p: P) -> P::Value where P: Process {
     let mut runtime = SequentialRuntime::new();
     let result = Arc::new(Mutex::new(None));
@@ -107,7 +311,11 @@ pub fn execute_process<P>(p: P) -> P::Value where P: Process {
 }
 
 pub fn execute_process_par<P>(p: P) -> P::Value where P: Process {
-    let runtime = ParallelRuntime::new(12);
+    execute_process_par_with(ParallelRuntime::new(12), p)
+}
+
+/// Like `execute_process_par`, on a runtime configured with a `RuntimeBuilder`.
+pub fn execute_process_par_with<P>(runtime: ParallelRuntime, p: P) -> P::Value where P: Process {
     let result = Arc::new(Mutex::new(None));
     let result_ref = result.clone();
     runtime.on_current_instant(Box::new(|run: &mut Runtime, _|
@@ -116,7 +324,9 @@ pub fn execute_process_par<P>(p: P) -> P::Value where P: Process {
             *res = Some(val);
         })
     ));
-    runtime.start().execute();
+    let runtime = runtime.start();
+    runtime.execute();
+    runtime.stop();
     let mut res = None;
     std::mem::swap(&mut res, &mut *result.lock().unwrap());
     if let Some(res) = res {
@@ -126,6 +336,10 @@ pub fn execute_process_par<P>(p: P) -> P::Value where P: Process {
     }
 }
 
+/// The value of a process that can take one of two types.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Either<A, B> { Left(A), Right(B) }
+
 pub struct Value<T> {
     val: T
 }
@@ -148,6 +362,75 @@ pub fn value<T>(val: T) -> Value<T> {
     Value {val}
 }
 
+impl<T: 'static> CloneProcess for Value<T> where T: Clone + Send + Sync {
+    fn clone_process(&self) -> Self {
+        value(self.val.clone())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct InstantIndex;
+
+impl Process for InstantIndex {
+    type Value = usize;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<usize> {
+        let index = runtime.instant_index();
+        next.call(runtime, index)
+    }
+}
+
+impl ProcessMut for InstantIndex {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, usize)> {
+        let index = runtime.instant_index();
+        next.call(runtime, (InstantIndex, index))
+    }
+}
+
+impl CloneProcess for InstantIndex {
+    fn clone_process(&self) -> Self {
+        InstantIndex
+    }
+}
+
+/// Returns the index of the current instant, counted from zero by the runtime, see
+/// `Runtime::instant_index`.
+pub fn instant_index() -> InstantIndex {
+    InstantIndex
+}
+
+pub struct FromFn<F> {
+    factory: F,
+}
+
+impl<F, P> Process for FromFn<F> where F: FnMut() -> P + Send + Sync + 'static, P: Process {
+    type Value = P::Value;
+
+    fn call<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        (self.factory)().call(runtime, next);
+    }
+}
+
+impl<F, P> ProcessMut for FromFn<F> where F: FnMut() -> P + Send + Sync + 'static, P: Process {
+    fn call_mut<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let process = (self.factory)();
+        process.call(runtime, next.map(move|v: P::Value| (self, v)));
+    }
+}
+
+impl<F, P> CloneProcess for FromFn<F> where F: FnMut() -> P + Clone + Send + Sync + 'static, P: Process {
+    fn clone_process(&self) -> Self {
+        from_fn(self.factory.clone())
+    }
+}
+
+/// A process executing the process built by `factory` when it is called. As a `ProcessMut`, a new
+/// process is built for each execution, which lets a loop execute a process that is not a
+/// `ProcessMut` itself.
+pub fn from_fn<F, P>(factory: F) -> FromFn<F> where F: FnMut() -> P + Send + Sync + 'static, P: Process {
+    FromFn { factory }
+}
+
 pub struct Flatten<P> {
     process: P
 }
@@ -158,7 +441,7 @@ impl<P> Process for Flatten<P>
     type Value = <P::Value as Process>::Value;
 
     fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
-        self.process.call(runtime, |runtime: &mut Runtime, p: P::Value| p.call(runtime, next));
+        self.process.call(runtime, carry(next.waiter_token().cloned(), |runtime: &mut Runtime, p: P::Value| p.call(runtime, next)));
     }
 }
 
@@ -166,9 +449,15 @@ impl<P> ProcessMut for Flatten<P>
     where P: ProcessMut + 'static, P::Value: ProcessMut {
 
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
-        self.process.call_mut(runtime, |runtime: &mut Runtime, (process, p): (P, P::Value)|
+        self.process.call_mut(runtime, carry(next.waiter_token().cloned(), |runtime: &mut Runtime, (process, p): (P, P::Value)|
             p.call_mut(runtime, next.map(|(_, v)| (process.flatten(), v)))
-        );
+        ));
+    }
+}
+
+impl<P> CloneProcess for Flatten<P> where P: CloneProcess + 'static, P::Value: Process {
+    fn clone_process(&self) -> Self {
+        self.process.clone_process().flatten()
     }
 }
 
@@ -179,7 +468,7 @@ impl<F, V, P> Process for Map<P, F>
     type Value = V;
     fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
         let f = self.map;
-        (self.process).call(runtime, move|runtime: &mut Runtime, x| (next.call(runtime, f(x))))
+        (self.process).call(runtime, next.map(f))
     }
 }
 
@@ -187,10 +476,156 @@ impl<F, V, P> ProcessMut for Map<P, F>
     where P: ProcessMut, F: FnMut(P::Value) -> V + Send + Sync + 'static, V: Send + Sync  {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
         let mut f: F = self.map;
-        self.process.call_mut(runtime, move|runtime: &mut Runtime, (p, x): (P, P::Value)| {
+        self.process.call_mut(runtime, next.map(move|(p, x): (P, P::Value)| {
             let y = f(x);
-            next.call(runtime, (p.map(f), y))
-        })
+            (p.map(f), y)
+        }))
+    }
+}
+
+impl<F, V, P> CloneProcess for Map<P, F>
+    where P: CloneProcess, F: FnOnce(P::Value) -> V + Clone + Send + Sync + 'static, V: Send + Sync  {
+    fn clone_process(&self) -> Self {
+        self.process.clone_process().map(self.map.clone())
+    }
+}
+
+pub struct Inspect<P, F> { process: P, inspect: F }
+
+impl<P, F> Process for Inspect<P, F> where P: Process, F: FnOnce(&P::Value) + Send + Sync + 'static {
+    type Value = P::Value;
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let f = self.inspect;
+        self.process.call(runtime, next.map(move|x| {
+            f(&x);
+            x
+        }))
+    }
+}
+
+impl<P, F> ProcessMut for Inspect<P, F> where P: ProcessMut, F: FnMut(&P::Value) + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let mut f = self.inspect;
+        self.process.call_mut(runtime, next.map(move|(p, x): (P, P::Value)| {
+            f(&x);
+            (p.inspect(f), x)
+        }))
+    }
+}
+
+impl<P, F> CloneProcess for Inspect<P, F> where P: CloneProcess, F: FnOnce(&P::Value) + Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        self.process.clone_process().inspect(self.inspect.clone())
+    }
+}
+
+pub struct MapLeft<P, F> { process: P, map: F }
+
+impl<P, F, A, B, A2> Process for MapLeft<P, F>
+    where P: Process<Value = Either<A, B>>, F: FnOnce(A) -> A2 + Send + Sync + 'static, A: Send + Sync, A2: Send + Sync, B: Send + Sync {
+    type Value = Either<A2, B>;
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let f = self.map;
+        self.process.call(runtime, next.map(move|x| match x {
+            Either::Left(a) => Either::Left(f(a)),
+            Either::Right(b) => Either::Right(b),
+        }))
+    }
+}
+
+impl<P, F, A, B, A2> ProcessMut for MapLeft<P, F>
+    where P: ProcessMut<Value = Either<A, B>>, F: FnMut(A) -> A2 + Send + Sync + 'static, A: Send + Sync, A2: Send + Sync, B: Send + Sync {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let mut f: F = self.map;
+        self.process.call_mut(runtime, next.map(move|(p, x): (P, P::Value)| {
+            let y = match x {
+                Either::Left(a) => Either::Left(f(a)),
+                Either::Right(b) => Either::Right(b),
+            };
+            (p.map_left(f), y)
+        }))
+    }
+}
+
+impl<P, F, A, B, A2> CloneProcess for MapLeft<P, F>
+    where P: CloneProcess<Value = Either<A, B>>, F: FnOnce(A) -> A2 + Clone + Send + Sync + 'static, A: Send + Sync, A2: Send + Sync, B: Send + Sync {
+    fn clone_process(&self) -> Self {
+        self.process.clone_process().map_left(self.map.clone())
+    }
+}
+
+pub struct MapRight<P, F> { process: P, map: F }
+
+impl<P, F, A, B, B2> Process for MapRight<P, F>
+    where P: Process<Value = Either<A, B>>, F: FnOnce(B) -> B2 + Send + Sync + 'static, A: Send + Sync, B: Send + Sync, B2: Send + Sync {
+    type Value = Either<A, B2>;
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let f = self.map;
+        self.process.call(runtime, next.map(move|x| match x {
+            Either::Left(a) => Either::Left(a),
+            Either::Right(b) => Either::Right(f(b)),
+        }))
+    }
+}
+
+impl<P, F, A, B, B2> ProcessMut for MapRight<P, F>
+    where P: ProcessMut<Value = Either<A, B>>, F: FnMut(B) -> B2 + Send + Sync + 'static, A: Send + Sync, B: Send + Sync, B2: Send + Sync {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let mut f: F = self.map;
+        self.process.call_mut(runtime, next.map(move|(p, x): (P, P::Value)| {
+            let y = match x {
+                Either::Left(a) => Either::Left(a),
+                Either::Right(b) => Either::Right(f(b)),
+            };
+            (p.map_right(f), y)
+        }))
+    }
+}
+
+impl<P, F, A, B, B2> CloneProcess for MapRight<P, F>
+    where P: CloneProcess<Value = Either<A, B>>, F: FnOnce(B) -> B2 + Clone + Send + Sync + 'static, A: Send + Sync, B: Send + Sync, B2: Send + Sync {
+    fn clone_process(&self) -> Self {
+        self.process.clone_process().map_right(self.map.clone())
+    }
+}
+
+pub struct FoldEither<P, F, G> { process: P, left: F, right: G }
+
+impl<P, F, G, A, B, V> Process for FoldEither<P, F, G>
+    where P: Process<Value = Either<A, B>>, F: FnOnce(A) -> V + Send + Sync + 'static,
+          G: FnOnce(B) -> V + Send + Sync + 'static, A: Send + Sync, B: Send + Sync, V: Send + Sync {
+    type Value = V;
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let left = self.left;
+        let right = self.right;
+        self.process.call(runtime, next.map(move|x| match x {
+            Either::Left(a) => left(a),
+            Either::Right(b) => right(b),
+        }))
+    }
+}
+
+impl<P, F, G, A, B, V> ProcessMut for FoldEither<P, F, G>
+    where P: ProcessMut<Value = Either<A, B>>, F: FnMut(A) -> V + Send + Sync + 'static,
+          G: FnMut(B) -> V + Send + Sync + 'static, A: Send + Sync, B: Send + Sync, V: Send + Sync {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let mut left: F = self.left;
+        let mut right: G = self.right;
+        self.process.call_mut(runtime, next.map(move|(p, x): (P, P::Value)| {
+            let y = match x {
+                Either::Left(a) => left(a),
+                Either::Right(b) => right(b),
+            };
+            (p.fold_either(left, right), y)
+        }))
+    }
+}
+
+impl<P, F, G, A, B, V> CloneProcess for FoldEither<P, F, G>
+    where P: CloneProcess<Value = Either<A, B>>, F: FnOnce(A) -> V + Clone + Send + Sync + 'static,
+          G: FnOnce(B) -> V + Clone + Send + Sync + 'static, A: Send + Sync, B: Send + Sync, V: Send + Sync {
+    fn clone_process(&self) -> Self {
+        self.process.clone_process().fold_either(self.left.clone(), self.right.clone())
     }
 }
 
@@ -200,14 +635,14 @@ impl<P> Process for Pause<P> where P: Process {
     type Value = P::Value;
     fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
         let process = self.process;
-        runtime.on_next_instant(Box::new(|run: &mut Runtime, _| process.call(run, next)))
+        runtime.on_next_instant(recycled_box(|run: &mut Runtime, _| process.call(run, next)))
     }
 }
 
 impl<P> ProcessMut for Pause<P> where P: ProcessMut {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
         let process = self.process;
-        runtime.on_next_instant(Box::new(|run: &mut Runtime, _|
+        runtime.on_next_instant(recycled_box(|run: &mut Runtime, _|
             process.call_mut(run, next.map(
                 |(p, x): (P, P::Value)| (p.pause(), x)
             ))
@@ -215,111 +650,64 @@ impl<P> ProcessMut for Pause<P> where P: ProcessMut {
     }
 }
 
-pub struct Join<P1, P2> { p1: P1, p2: P2 }
+impl<P> CloneProcess for Pause<P> where P: CloneProcess {
+    fn clone_process(&self) -> Self {
+        self.process.clone_process().pause()
+    }
+}
 
-impl<P1, P2> Process for Join<P1, P2> where P1: Process, P2: Process {
-    type Value = (P1::Value, P2::Value);
-    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
-        struct JoinPoint<V1, V2, C> where V1: Send + Sync, V2: Send + Sync {
-            v1: Option<V1>,
-            v2: Option<V2>,
-            next: Option<C>
-        }
+#[derive(Clone, Copy)]
+pub struct YieldNow;
 
-        impl<V1, V2, C> JoinPoint<V1, V2, C> where C: Continuation<(V1, V2)>, V1: Send + Sync, V2: Send + Sync {
-            fn try_call_next(&mut self, run: &mut Runtime) {
-                if self.is_filled() {
-                    let next = self.next.take().unwrap();
-                    let v1 = self.v1.take().unwrap();
-                    let v2 = self.v2.take().unwrap();
-                    next.call(run, (v1, v2));
-                }
-            }
+impl Process for YieldNow {
+    type Value = ();
 
-            fn is_filled(&self) -> bool {
-                self.v1.is_some() && self.v2.is_some() && self.next.is_some()
-            }
-        };
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        runtime.on_current_instant(recycled_box(|runtime: &mut Runtime, ()| next.call(runtime, ())));
+    }
+}
 
-        let jp = Arc::new(Mutex::new(JoinPoint{v1: None, v2: None, next: Some(next)}));
-
-        {
-            let jp = jp.clone();
-            let p1 = self.p1;
-            runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
-                p1.call(runtime, move|run: &mut Runtime, v1| {
-                    let mut jp = jp.lock().unwrap();
-                    jp.v1 = Some(v1);
-                    jp.try_call_next(run)
-                });
-            }));
-        }
-        {
-            let jp = jp.clone();
-            let p2 = self.p2;
-            runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
-                p2.call(runtime, move|run: &mut Runtime, v2| {
-                    let mut jp = jp.lock().unwrap();
-                    jp.v2 = Some(v2);
-                    jp.try_call_next(run)
-                });
-            }));
-        }
+impl ProcessMut for YieldNow {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, ())> {
+        self.call(runtime, next.map(|()| (YieldNow, ())));
     }
 }
 
-impl<P1, P2> ProcessMut for Join<P1, P2> where P1: ProcessMut, P2: ProcessMut {
-    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
-        struct JoinPoint<V1, V2, P1, P2, C> where P1: ProcessMut, P2: ProcessMut {
-            v1: Option<V1>,
-            v2: Option<V2>,
-            p1: Option<P1>,
-            p2: Option<P2>,
-            next: Option<C>
-        }
+impl CloneProcess for YieldNow {
+    fn clone_process(&self) -> Self {
+        YieldNow
+    }
+}
 
-        impl<V1, V2, P1, P2, C> JoinPoint<V1, V2, P1, P2, C> where C: Continuation<(Join<P1, P2>, (V1, V2))>, P1: ProcessMut, P2: ProcessMut, V1: Send + Sync, V2: Send + Sync {
-            fn try_call_next(&mut self, run: &mut Runtime) {
-                if self.is_filled() {
-                    let next = self.next.take().unwrap();
-                    let v1 = self.v1.take().unwrap();
-                    let v2 = self.v2.take().unwrap();
-                    let p1 = self.p1.take().unwrap();
-                    let p2 = self.p2.take().unwrap();
-                    next.call(run, (Join {p1, p2}, (v1, v2)));
-                }
-            }
+/// Completes during the current instant, after the continuations already queued for it, which
+/// lets a long computation split in several steps leave room to the other processes of the
+/// instant, or to other workers of a parallel runtime.
+pub fn yield_now() -> YieldNow {
+    YieldNow
+}
 
-            fn is_filled(&self) -> bool {
-                self.v1.is_some() && self.v2.is_some() && self.next.is_some() && self.p1.is_some() && self.p2.is_some()
-            }
-        };
+pub struct Join<P1, P2> { p1: P1, p2: P2 }
 
-        let jp = Arc::new(Mutex::new(JoinPoint{v1: None, v2: None, p1: None, p2: None, next: Some(next)}));
-        {
-            let jp = jp.clone();
-            let p1 = self.p1;
-            runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
-                p1.call_mut(runtime, move|run: &mut Runtime, (p1, v1)| {
-                    let mut jp = jp.lock().unwrap();
-                    jp.v1 = Some(v1);
-                    jp.p1 = Some(p1);
-                    jp.try_call_next(run)
-                });
-            }));
-        }
-        {
-            let jp = jp.clone();
-            let p2 = self.p2;
-            runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
-                p2.call_mut(runtime, move|run: &mut Runtime, (p2, v2)| {
-                    let mut jp = jp.lock().unwrap();
-                    jp.v2 = Some(v2);
-                    jp.p2 = Some(p2);
-                    jp.try_call_next(run)
-                });
-            }));
-        }
+impl<P1, P2> Process for Join<P1, P2> where P1: Process, P2: Process {
+    type Value = (P1::Value, P2::Value);
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let (left, right) = join_continuations(next);
+        let p1 = self.p1;
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| p1.call(runtime, left)));
+        let p2 = self.p2;
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| p2.call(runtime, right)));
+    }
+}
+
+impl<P1, P2> ProcessMut for Join<P1, P2> where P1: ProcessMut, P2: ProcessMut {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let (left, right) = join_continuations(next.map(|((p1, v1), (p2, v2)): ((P1, P1::Value), (P2, P2::Value))|
+            (Join {p1, p2}, (v1, v2))
+        ));
+        let p1 = self.p1;
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| p1.call_mut(runtime, left)));
+        let p2 = self.p2;
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| p2.call_mut(runtime, right)));
     }
 }
 
@@ -327,6 +715,12 @@ pub fn join<P1, P2>(p1: P1, p2: P2) -> Join<P1, P2> where P1: Process, P2: Proce
     Join {p1, p2}
 }
 
+impl<P1, P2> CloneProcess for Join<P1, P2> where P1: CloneProcess, P2: CloneProcess {
+    fn clone_process(&self) -> Self {
+        join(self.p1.clone_process(), self.p2.clone_process())
+    }
+}
+
 pub struct MultiJoin<P> where P: Process {
     processes: Vec<P>
 }
@@ -335,168 +729,889 @@ impl<P> Process for MultiJoin<P> where P: Process {
     type Value = Vec<P::Value>;
 
     fn call<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<Self::Value> {
-        struct JoinPoint<V, C> where C: Continuation<Vec<V>>, V: Send + Sync {
-            results: Vec<Option<V>>,
-            next: Option<C>,
-        }
-
-        impl<V, C> JoinPoint<V, C> where C: Continuation<Vec<V>>, V: Send + Sync {
-            fn try_call_next(&mut self, runtime: &mut Runtime) {
-                if self.is_filled() {
-                    let next = self.next.take().unwrap();
-                    let mut results = Vec::new();
-                    for ref mut res in &mut self.results {
-                        results.push(res.take().unwrap());
-                    }
-                    next.call(runtime, results);
-                }
-            }
-
-            fn is_filled(&self) -> bool {
-                let mut filled = self.next.is_some();
-                for ref res in &self.results {
-                    filled = filled && res.is_some();
-                }
-                return filled
-            }
+        if self.processes.is_empty() {
+            return c.call(runtime, Vec::new());
         }
-
-        let mut results = Vec::with_capacity(self.processes.len());
-        for _ in 0..self.processes.len() { results.push(None); }
-        let jp = Arc::new(Mutex::new(JoinPoint{results, next: Some(c)}));
-
-        let mut ct = 0;
-        for process in self.processes {
-            let jp = jp.clone();
-            let cur = ct;
-            runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
-                process.call(runtime, move|runtime: &mut Runtime, res| {
-                    let mut jp = jp.lock().unwrap();
-                    jp.results[cur] = Some(res);
-                    jp.try_call_next(runtime);
-                });
-            }));
-            ct = ct + 1;
+        let slots = multi_join_continuations(self.processes.len(), c);
+        for (process, slot) in self.processes.into_iter().zip(slots) {
+            runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| process.call(runtime, slot)));
         }
     }
 }
 
 impl<P> ProcessMut for MultiJoin<P> where P: ProcessMut {
     fn call_mut<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<(Self, Self::Value)> {
-        struct JoinPoint<P, V, C> where P: ProcessMut<Value = V>, C: Continuation<(MultiJoin<P>, Vec<V>)>, V: Send + Sync {
-            results: Vec<Option<V>>,
-            processes: Vec<Option<P>>,
-            next: Option<C>,
-        }
-
-        impl<P, V, C> JoinPoint<P, V, C> where P: ProcessMut<Value = V>, C: Continuation<(MultiJoin<P>, Vec<V>)>, V: Send + Sync {
-            fn try_call_next(&mut self, runtime: &mut Runtime) {
-                if self.is_filled() {
-                    let next = self.next.take().unwrap();
-                    let n = self.results.len();
-                    let mut results = Vec::with_capacity(n);
-                    let mut processes = Vec::with_capacity(n);
-                    for ref mut res in &mut self.results {
-                        results.push(res.take().unwrap());
-                    }
-                    for ref mut p in &mut self.processes {
-                        processes.push(p.take().unwrap());
-                    }
-                    next.call(runtime, (multi_join(processes), results));
-                }
-            }
-
-            fn is_filled(&self) -> bool {
-                let mut filled = self.next.is_some();
-                for ref res in &self.results {
-                    filled = filled && res.is_some();
-                }
-                for ref p in &self.processes {
-                    filled = filled && p.is_some();
-                }
-                return filled
-            }
+        if self.processes.is_empty() {
+            return c.call(runtime, (self, Vec::new()));
         }
-
-        let mut results = Vec::with_capacity(self.processes.len());
-        for _ in 0..self.processes.len() { results.push(None); }
-        let mut processes = Vec::with_capacity(self.processes.len());
-        for _ in 0..self.processes.len() { processes.push(None); }
-        let jp = Arc::new(Mutex::new(JoinPoint{results, processes, next: Some(c)}));
-
-        let mut ct = 0;
-        for process in self.processes {
-            let jp = jp.clone();
-            let cur = ct;
-            runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
-                process.call_mut (runtime, move|runtime: &mut Runtime, (process, res)| {
-                    let mut jp = jp.lock().unwrap();
-                    jp.results[cur] = Some(res);
-                    jp.processes[cur] = Some(process);
-                    jp.try_call_next(runtime);
-                });
-            }));
-            ct = ct + 1;
+        let slots = multi_join_continuations(self.processes.len(), c.map(|entries: Vec<(P, P::Value)>| {
+            let (processes, results): (Vec<P>, Vec<P::Value>) = entries.into_iter().unzip();
+            (multi_join(processes), results)
+        }));
+        for (process, slot) in self.processes.into_iter().zip(slots) {
+            runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| process.call_mut(runtime, slot)));
         }
     }
 }
 
+/// Executes all the processes jointly, returns their values in order. Completes immediately with
+/// an empty `Vec` when there is no process.
 pub fn multi_join<P>(processes: Vec<P>) -> MultiJoin<P> where P: Process {
     MultiJoin{processes}
 }
 
-pub struct While<P> {
-    process: P
+impl<P> CloneProcess for MultiJoin<P> where P: CloneProcess {
+    fn clone_process(&self) -> Self {
+        multi_join(self.processes.iter().map(|p| p.clone_process()).collect())
+    }
 }
 
-impl<P, V> Process for While<P> where P: ProcessMut<Value = LoopStatus<V>>, V: Send + Sync + 'static {
-    type Value = V;
+pub struct Sequence<P> where P: Process {
+    processes: Vec<P>
+}
+
+/// Executes the remaining processes one after the other, then calls `next` with all the values.
+fn call_sequence<P, C>(runtime: &mut Runtime, mut remaining: std::vec::IntoIter<P>, mut values: Vec<P::Value>, next: C)
+    where P: Process, C: Continuation<Vec<P::Value>> {
+    match remaining.next() {
+        Some(process) => process.call(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, v: P::Value| {
+            values.push(v);
+            call_sequence(runtime, remaining, values, next);
+        })),
+        None => next.call(runtime, values),
+    }
+}
+
+/// Like `call_sequence`, keeping the processes along with their values.
+fn call_sequence_mut<P, C>(runtime: &mut Runtime, mut remaining: std::vec::IntoIter<P>, mut entries: Vec<(P, P::Value)>, next: C)
+    where P: ProcessMut, C: Continuation<Vec<(P, P::Value)>> {
+    match remaining.next() {
+        Some(process) => process.call_mut(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, entry: (P, P::Value)| {
+            entries.push(entry);
+            call_sequence_mut(runtime, remaining, entries, next);
+        })),
+        None => next.call(runtime, entries),
+    }
+}
+
+impl<P> Process for Sequence<P> where P: Process {
+    type Value = Vec<P::Value>;
 
     fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
-        self.process.call_mut(runtime, |runtime: &mut Runtime, (p, loop_status): (P, LoopStatus<V>)|
-            match loop_status {
-                LoopStatus::Continue => p.while_loop().call(runtime, next),
-                LoopStatus::Exit(value) => return next.call(runtime, value)
-            }
-        );
+        let values = Vec::with_capacity(self.processes.len());
+        call_sequence(runtime, self.processes.into_iter(), values, next);
     }
 }
 
-impl<P, V> ProcessMut for While<P> where P: ProcessMut<Value = LoopStatus<V>>, V: Send + Sync + 'static {
+impl<P> ProcessMut for Sequence<P> where P: ProcessMut {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
-        self.process.call_mut(runtime, |runtime: &mut Runtime, (p, loop_status): (P, LoopStatus<V>)|
-            match loop_status {
-                LoopStatus::Continue => p.while_loop().call_mut(runtime, next),
-                LoopStatus::Exit(value) => return next.call(runtime, (p.while_loop(), value))
-            }
-        );
+        let entries = Vec::with_capacity(self.processes.len());
+        call_sequence_mut(runtime, self.processes.into_iter(), entries, next.map(|entries: Vec<(P, P::Value)>| {
+            let (processes, values): (Vec<P>, Vec<P::Value>) = entries.into_iter().unzip();
+            (sequence(processes), values)
+        }));
     }
 }
 
-pub fn if_else<P, Q, R>(r: R, p: P, q: Q) -> If<P, Q, R> {
-    If {process_if: p, process_else: q, process_cond: r}
+/// Executes the processes one after the other, each starting in the instant the previous one
+/// terminated, returns their values in order. Completes immediately with an empty `Vec` when there
+/// is no process.
+pub fn sequence<P>(processes: Vec<P>) -> Sequence<P> where P: Process {
+    Sequence{processes}
 }
 
-pub struct If<P, Q, R> {
-    process_if: P,
-    process_else: Q,
-    process_cond: R,
+impl<P> CloneProcess for Sequence<P> where P: CloneProcess {
+    fn clone_process(&self) -> Self {
+        sequence(self.processes.iter().map(|p| p.clone_process()).collect())
+    }
 }
 
-impl<P, Q, R, V> Process for If<P, Q, R> where P: Process<Value = V>, Q: Process<Value = V>, R: Process<Value = bool>, V: Send + Sync {
-    type Value = V;
+/// Arrays of processes executed jointly by `join_array`.
+///
+/// Const generics are not available on our toolchain, this is implemented for arrays of 1 to 8
+/// processes.
+pub trait ProcessArray: Send + Sync + 'static {
+    type Values: Send + Sync;
 
-    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<V> {
-        let p = self.process_if;
-        let q = self.process_else;
-        let r = self.process_cond;
-        r.call(runtime, move|runtime: &mut Runtime, cond: bool| {
-            if cond {
+    fn call_all<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Values>;
+}
+
+/// Arrays of mutable processes executed jointly by `join_array`.
+pub trait ProcessMutArray: ProcessArray {
+    fn call_all_mut<C>(self, runtime: &mut Runtime, next: C) where Self: Sized, C: Continuation<(Self, Self::Values)>;
+}
+
+/// The values already produced by the processes of an array or a tuple, with the continuation
+/// once known.
+struct ArrayJoinState<S, C> {
+    slots: S,
+    missing: usize,
+    next: Option<C>,
+}
+
+macro_rules! process_array {
+    ($n:expr; $($p:ident $i:tt),+) => {
+        impl<P> ProcessArray for [P; $n] where P: Process {
+            type Values = [P::Value; $n];
+
+            fn call_all<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Values> {
+                fn take_values<V>(slots: &mut [Option<V>; $n]) -> [V; $n] {
+                    [$(slots[$i].take().unwrap()),+]
+                }
+                let token = next.waiter_token().cloned();
+                let state = Arc::new(Mutex::new(ArrayJoinState {
+                    slots: <[Option<P::Value>; $n]>::default(),
+                    missing: $n,
+                    next: Some(next),
+                }));
+                let [$($p),+] = self;
+                $({
+                    let (state, token) = (state.clone(), token.clone());
+                    runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
+                        $p.call(runtime, carry(token, move|runtime: &mut Runtime, v| {
+                            let completed = {
+                                let mut state = state.lock().unwrap();
+                                state.slots[$i] = Some(v);
+                                state.missing -= 1;
+                                if state.missing == 0 {
+                                    let values = take_values(&mut state.slots);
+                                    state.next.take().map(|next| (next, values))
+                                } else {
+                                    None
+                                }
+                            };
+                            if let Some((next, values)) = completed {
+                                next.call(runtime, values);
+                            }
+                        }));
+                    }));
+                })+
+            }
+        }
+
+        impl<P> ProcessMutArray for [P; $n] where P: ProcessMut {
+            fn call_all_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Values)> {
+                fn take_values<P, V>(slots: &mut [Option<(P, V)>; $n]) -> ([P; $n], [V; $n]) {
+                    $(let $p = slots[$i].take().unwrap();)+
+                    ([$($p.0),+], [$($p.1),+])
+                }
+                let token = next.waiter_token().cloned();
+                let state = Arc::new(Mutex::new(ArrayJoinState {
+                    slots: <[Option<(P, P::Value)>; $n]>::default(),
+                    missing: $n,
+                    next: Some(next),
+                }));
+                let [$($p),+] = self;
+                $({
+                    let (state, token) = (state.clone(), token.clone());
+                    runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
+                        $p.call_mut(runtime, carry(token, move|runtime: &mut Runtime, res: (P, P::Value)| {
+                            let completed = {
+                                let mut state = state.lock().unwrap();
+                                state.slots[$i] = Some(res);
+                                state.missing -= 1;
+                                if state.missing == 0 {
+                                    let values = take_values(&mut state.slots);
+                                    state.next.take().map(|next| (next, values))
+                                } else {
+                                    None
+                                }
+                            };
+                            if let Some((next, values)) = completed {
+                                next.call(runtime, values);
+                            }
+                        }));
+                    }));
+                })+
+            }
+        }
+    }
+}
+
+process_array!(1; p0 0);
+process_array!(2; p0 0, p1 1);
+process_array!(3; p0 0, p1 1, p2 2);
+process_array!(4; p0 0, p1 1, p2 2, p3 3);
+process_array!(5; p0 0, p1 1, p2 2, p3 3, p4 4);
+process_array!(6; p0 0, p1 1, p2 2, p3 3, p4 4, p5 5);
+process_array!(7; p0 0, p1 1, p2 2, p3 3, p4 4, p5 5, p6 6);
+process_array!(8; p0 0, p1 1, p2 2, p3 3, p4 4, p5 5, p6 6, p7 7);
+
+pub struct JoinArray<A> {
+    processes: A,
+}
+
+impl<A> Process for JoinArray<A> where A: ProcessArray {
+    type Value = A::Values;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        self.processes.call_all(runtime, next);
+    }
+}
+
+impl<A> ProcessMut for JoinArray<A> where A: ProcessMutArray {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        self.processes.call_all_mut(runtime, next.map(|(processes, values)| (join_array(processes), values)));
+    }
+}
+
+/// Executes a fixed number of processes jointly, like `multi_join`, but returns their values in
+/// an array instead of a `Vec`.
+pub fn join_array<A>(processes: A) -> JoinArray<A> where A: ProcessArray {
+    JoinArray {processes}
+}
+
+macro_rules! join_tuple {
+    ($join:ident, $f:ident, $n:expr; $($P:ident $V:ident $p:ident $i:tt),+) => {
+        /// Processes executed jointly by `join!`, whose values are returned in a flat tuple.
+        pub struct $join<$($P),+> { $($p: $P),+ }
+
+        impl<$($P),+> Process for $join<$($P),+> where $($P: Process),+ {
+            type Value = ($($P::Value,)+);
+
+            fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+                fn take_values<$($V),+>(slots: &mut ($(Option<$V>,)+)) -> ($($V,)+) {
+                    ($(slots.$i.take().unwrap(),)+)
+                }
+                let token = next.waiter_token().cloned();
+                let state = Arc::new(Mutex::new(ArrayJoinState {
+                    slots: <($(Option<$P::Value>,)+)>::default(),
+                    missing: $n,
+                    next: Some(next),
+                }));
+                $({
+                    let (state, token) = (state.clone(), token.clone());
+                    let $p = self.$p;
+                    runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
+                        $p.call(runtime, carry(token, move|runtime: &mut Runtime, v: $P::Value| {
+                            let completed = {
+                                let mut state = state.lock().unwrap();
+                                state.slots.$i = Some(v);
+                                state.missing -= 1;
+                                if state.missing == 0 {
+                                    let values = take_values(&mut state.slots);
+                                    state.next.take().map(|next| (next, values))
+                                } else {
+                                    None
+                                }
+                            };
+                            if let Some((next, values)) = completed {
+                                next.call(runtime, values);
+                            }
+                        }));
+                    }));
+                })+
+            }
+        }
+
+        impl<$($P),+> ProcessMut for $join<$($P),+> where $($P: ProcessMut),+ {
+            fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+                fn take_values<$($P, $V),+>(slots: &mut ($(Option<($P, $V)>,)+)) -> ($join<$($P),+>, ($($V,)+)) {
+                    $(let $p = slots.$i.take().unwrap();)+
+                    ($join { $($p: $p.0),+ }, ($($p.1,)+))
+                }
+                let token = next.waiter_token().cloned();
+                let state = Arc::new(Mutex::new(ArrayJoinState {
+                    slots: <($(Option<($P, $P::Value)>,)+)>::default(),
+                    missing: $n,
+                    next: Some(next),
+                }));
+                $({
+                    let (state, token) = (state.clone(), token.clone());
+                    let $p = self.$p;
+                    runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
+                        $p.call_mut(runtime, carry(token, move|runtime: &mut Runtime, res: ($P, $P::Value)| {
+                            let completed = {
+                                let mut state = state.lock().unwrap();
+                                state.slots.$i = Some(res);
+                                state.missing -= 1;
+                                if state.missing == 0 {
+                                    let values = take_values(&mut state.slots);
+                                    state.next.take().map(|next| (next, values))
+                                } else {
+                                    None
+                                }
+                            };
+                            if let Some((next, values)) = completed {
+                                next.call(runtime, values);
+                            }
+                        }));
+                    }));
+                })+
+            }
+        }
+
+        impl<$($P),+> CloneProcess for $join<$($P),+> where $($P: CloneProcess),+ {
+            fn clone_process(&self) -> Self {
+                $join { $($p: self.$p.clone_process()),+ }
+            }
+        }
+
+        /// See `join!`.
+        pub fn $f<$($P),+>($($p: $P),+) -> $join<$($P),+> where $($P: Process),+ {
+            $join { $($p),+ }
+        }
+    }
+}
+
+join_tuple!(Join3, join3, 3; P1 V1 p1 0, P2 V2 p2 1, P3 V3 p3 2);
+join_tuple!(Join4, join4, 4; P1 V1 p1 0, P2 V2 p2 1, P3 V3 p3 2, P4 V4 p4 3);
+join_tuple!(Join5, join5, 5; P1 V1 p1 0, P2 V2 p2 1, P3 V3 p3 2, P4 V4 p4 3, P5 V5 p5 4);
+join_tuple!(Join6, join6, 6; P1 V1 p1 0, P2 V2 p2 1, P3 V3 p3 2, P4 V4 p4 3, P5 V5 p5 4, P6 V6 p6 5);
+join_tuple!(Join7, join7, 7; P1 V1 p1 0, P2 V2 p2 1, P3 V3 p3 2, P4 V4 p4 3, P5 V5 p5 4, P6 V6 p6 5, P7 V7 p7 6);
+join_tuple!(Join8, join8, 8; P1 V1 p1 0, P2 V2 p2 1, P3 V3 p3 2, P4 V4 p4 3, P5 V5 p5 4, P6 V6 p6 5, P7 V7 p7 6, P8 V8 p8 7);
+
+/// Executes up to eight processes jointly, and returns their values in a flat tuple instead of
+/// the nested pairs of chained `join`s: `join!(p1, p2, p3)` is `join3(p1, p2, p3)`, whose value
+/// is `(v1, v2, v3)`.
+#[macro_export]
+macro_rules! join {
+    ($p1:expr, $p2:expr) => { $crate::reactive::process::join($p1, $p2) };
+    ($p1:expr, $p2:expr, $p3:expr) => { $crate::reactive::process::join3($p1, $p2, $p3) };
+    ($p1:expr, $p2:expr, $p3:expr, $p4:expr) => { $crate::reactive::process::join4($p1, $p2, $p3, $p4) };
+    ($p1:expr, $p2:expr, $p3:expr, $p4:expr, $p5:expr) => {
+        $crate::reactive::process::join5($p1, $p2, $p3, $p4, $p5)
+    };
+    ($p1:expr, $p2:expr, $p3:expr, $p4:expr, $p5:expr, $p6:expr) => {
+        $crate::reactive::process::join6($p1, $p2, $p3, $p4, $p5, $p6)
+    };
+    ($p1:expr, $p2:expr, $p3:expr, $p4:expr, $p5:expr, $p6:expr, $p7:expr) => {
+        $crate::reactive::process::join7($p1, $p2, $p3, $p4, $p5, $p6, $p7)
+    };
+    ($p1:expr, $p2:expr, $p3:expr, $p4:expr, $p5:expr, $p6:expr, $p7:expr, $p8:expr) => {
+        $crate::reactive::process::join8($p1, $p2, $p3, $p4, $p5, $p6, $p7, $p8)
+    };
+}
+
+/// What a `while_loop` does once it spins, see `While::on_spin`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpinPolicy {
+    /// Panics with the name of the loop, which can be handled by `on_process_panic`.
+    Abort,
+    /// Carries on with the next iteration at the next instant, as if the body paused.
+    Pause,
+}
+
+/// Number of iterations of a loop executed in a row on the stack during an instant, the next one
+/// being queued on the current instant, so that a body which does not pause does not overflow the
+/// stack.
+const STACK_ITERATIONS: usize = 256;
+
+/// When an iteration of a loop which cannot be executed right away is, see `SpinGuard::enter`.
+enum Deferred {
+    CurrentInstant,
+    NextInstant,
+}
+
+impl Deferred {
+    fn queue(self, runtime: &mut Runtime, iteration: Box<Continuation<()>>) {
+        match self {
+            Deferred::CurrentInstant => runtime.on_current_instant(iteration),
+            Deferred::NextInstant => runtime.on_next_instant(iteration),
+        }
+    }
+}
+
+/// Counts the iterations of a loop executed during the same instant, without limit unless one is
+/// set.
+#[derive(Clone)]
+struct SpinGuard {
+    limit: usize,
+    policy: SpinPolicy,
+    name: Option<Arc<String>>,
+    instant: usize,
+    iterations: usize,
+    /// Whether the current iteration was queued to unwind the stack, and is already accounted.
+    queued: bool,
+}
+
+impl SpinGuard {
+    fn new() -> Self {
+        SpinGuard::with_limit(usize::max_value(), SpinPolicy::Abort)
+    }
+
+    fn with_limit(limit: usize, policy: SpinPolicy) -> Self {
+        SpinGuard { limit, policy, name: None, instant: 0, iterations: 0, queued: false }
+    }
+
+    /// Accounts an iteration starting during `instant`, returns when to execute it if not right
+    /// away.
+    fn enter(&mut self, instant: usize) -> Option<Deferred> {
+        if self.queued {
+            self.queued = false;
+            return None;
+        }
+        if instant != self.instant {
+            self.instant = instant;
+            self.iterations = 0;
+        }
+        self.iterations += 1;
+        if self.iterations <= self.limit {
+            if self.iterations % STACK_ITERATIONS == 0 {
+                self.queued = true;
+                return Some(Deferred::CurrentInstant);
+            }
+            return None;
+        }
+        match self.policy {
+            SpinPolicy::Abort => {
+                let name = self.name.as_ref().map_or("while_loop", |name| &name[..]);
+                panic!("{} completed {} iterations during instant {} without pausing", name, self.limit, instant);
+            },
+            SpinPolicy::Pause => Some(Deferred::NextInstant),
+        }
+    }
+}
+
+pub struct While<P> {
+    process: P,
+    spin: SpinGuard,
+}
+
+impl<P> While<P> {
+    /// Names the loop in the panic of a spinning loop.
+    pub fn named(mut self, name: &str) -> Self {
+        self.spin.name = Some(Arc::new(name.to_string()));
+        self
+    }
+
+    /// Panics with the name of the loop once its body completed `iterations` times in a row during
+    /// the same instant, to catch a body which never pauses.
+    pub fn spin_guard(self, iterations: usize) -> Self {
+        self.on_spin(iterations, SpinPolicy::Abort)
+    }
+
+    /// Applies `policy` once the body completed `iterations` times in a row during the same
+    /// instant, see `spin_guard`.
+    pub fn on_spin(mut self, iterations: usize, policy: SpinPolicy) -> Self {
+        self.spin.limit = iterations;
+        self.spin.policy = policy;
+        self
+    }
+}
+
+impl<P, V> Process for While<P> where P: ProcessMut<Value = LoopStatus<V>>, V: Send + Sync + 'static {
+    type Value = V;
+
+    fn call<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        if let Some(deferred) = self.spin.enter(runtime.instant_index()) {
+            return deferred.queue(runtime, Box::new(move|runtime: &mut Runtime, ()| self.call(runtime, next)));
+        }
+        let spin = self.spin;
+        self.process.call_mut(runtime, carry(next.waiter_token().cloned(), |runtime: &mut Runtime, (process, loop_status): (P, LoopStatus<V>)|
+            match loop_status {
+                LoopStatus::Continue => While {process, spin}.call(runtime, next),
+                LoopStatus::Exit(value) => return next.call(runtime, value)
+            }
+        ));
+    }
+}
+
+impl<P, V> ProcessMut for While<P> where P: ProcessMut<Value = LoopStatus<V>>, V: Send + Sync + 'static {
+    fn call_mut<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        if let Some(deferred) = self.spin.enter(runtime.instant_index()) {
+            return deferred.queue(runtime, Box::new(move|runtime: &mut Runtime, ()| self.call_mut(runtime, next)));
+        }
+        let spin = self.spin;
+        self.process.call_mut(runtime, carry(next.waiter_token().cloned(), |runtime: &mut Runtime, (process, loop_status): (P, LoopStatus<V>)|
+            match loop_status {
+                LoopStatus::Continue => While {process, spin}.call_mut(runtime, next),
+                LoopStatus::Exit(value) => return next.call(runtime, (While {process, spin}, value))
+            }
+        ));
+    }
+}
+
+impl<P, V> CloneProcess for While<P> where P: ProcessMut<Value = LoopStatus<V>> + CloneProcess, V: Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        let mut spin = self.spin.clone();
+        spin.iterations = 0;
+        spin.queued = false;
+        While {process: self.process.clone_process(), spin}
+    }
+}
+
+/// Calls `next` with the process, the folding function, the spin guard and the accumulator once
+/// the folding function returns `FoldStatus::Exit`.
+fn call_fold<P, F, A, C>(runtime: &mut Runtime, process: P, mut fold: F, mut spin: SpinGuard, acc: A, next: C)
+    where P: ProcessMut, F: FnMut(A, P::Value) -> FoldStatus<A> + Send + Sync + 'static, A: Send + Sync + 'static,
+          C: Continuation<(P, F, SpinGuard, A)> {
+    if let Some(deferred) = spin.enter(runtime.instant_index()) {
+        return deferred.queue(runtime, Box::new(move|runtime: &mut Runtime, ()| call_fold(runtime, process, fold, spin, acc, next)));
+    }
+    process.call_mut(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, (process, v): (P, P::Value)| match fold(acc, v) {
+        FoldStatus::Continue(acc) => call_fold(runtime, process, fold, spin, acc, next),
+        FoldStatus::Exit(acc) => next.call(runtime, (process, fold, spin, acc)),
+    }));
+}
+
+pub struct Fold<P, F, A> {
+    process: P,
+    fold: F,
+    init: A,
+    spin: SpinGuard,
+}
+
+impl<P, F, A> Fold<P, F, A> {
+    /// Panics once the body completed `iterations` times in a row during the same instant, see
+    /// `While::spin_guard`.
+    pub fn spin_guard(self, iterations: usize) -> Self {
+        self.on_spin(iterations, SpinPolicy::Abort)
+    }
+
+    /// Applies `policy` once the body completed `iterations` times in a row during the same
+    /// instant, see `While::on_spin`.
+    pub fn on_spin(mut self, iterations: usize, policy: SpinPolicy) -> Self {
+        self.spin.limit = iterations;
+        self.spin.policy = policy;
+        self
+    }
+}
+
+impl<P, F, A> Process for Fold<P, F, A>
+    where P: ProcessMut, F: FnMut(A, P::Value) -> FoldStatus<A> + Send + Sync + 'static, A: Send + Sync + 'static {
+    type Value = A;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let done = |(_, _, _, acc): (P, F, SpinGuard, A)| acc;
+        call_fold(runtime, self.process, self.fold, self.spin, self.init, next.map(done));
+    }
+}
+
+impl<P, F, A> ProcessMut for Fold<P, F, A>
+    where P: ProcessMut, F: FnMut(A, P::Value) -> FoldStatus<A> + Send + Sync + 'static, A: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let init = self.init;
+        let acc = init.clone();
+        let done = move|(process, fold, spin, acc): (P, F, SpinGuard, A)| (Fold {process, fold, init, spin}, acc);
+        call_fold(runtime, self.process, self.fold, self.spin, acc, next.map(done));
+    }
+}
+
+impl<P, F, A> CloneProcess for Fold<P, F, A>
+    where P: ProcessMut + CloneProcess, F: FnMut(A, P::Value) -> FoldStatus<A> + Clone + Send + Sync + 'static,
+          A: Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        let mut spin = self.spin.clone();
+        spin.iterations = 0;
+        spin.queued = false;
+        Fold {process: self.process.clone_process(), fold: self.fold.clone(), init: self.init.clone(), spin}
+    }
+}
+
+/// The accumulator of a `ProcessMut::scan`, updated with each value of the process.
+pub struct Scan<P, F, A> {
+    process: P,
+    scan: F,
+    acc: A,
+}
+
+impl<P, F, A> Process for Scan<P, F, A>
+    where P: ProcessMut, F: FnMut(A, P::Value) -> A + Send + Sync + 'static, A: Clone + Send + Sync + 'static {
+    type Value = A;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let (mut scan, acc) = (self.scan, self.acc);
+        self.process.call(runtime, next.map(move|v: P::Value| scan(acc, v)));
+    }
+}
+
+impl<P, F, A> ProcessMut for Scan<P, F, A>
+    where P: ProcessMut, F: FnMut(A, P::Value) -> A + Send + Sync + 'static, A: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let (mut scan, acc) = (self.scan, self.acc);
+        self.process.call_mut(runtime, next.map(move|(process, v): (P, P::Value)| {
+            let acc = scan(acc, v);
+            (Scan {process, scan, acc: acc.clone()}, acc)
+        }));
+    }
+}
+
+impl<P, F, A> CloneProcess for Scan<P, F, A>
+    where P: ProcessMut + CloneProcess, F: FnMut(A, P::Value) -> A + Clone + Send + Sync + 'static,
+          A: Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        Scan {process: self.process.clone_process(), scan: self.scan.clone(), acc: self.acc.clone()}
+    }
+}
+
+/// Calls `next` with the body, the spin guard and the value of the loop once an iteration returns
+/// `LoopState::Exit`.
+fn call_while_with<F, P, S, V, C>(runtime: &mut Runtime, mut body: F, mut spin: SpinGuard, state: S, next: C)
+    where F: FnMut(S) -> P + Send + Sync + 'static, P: Process<Value = LoopState<S, V>>, S: Send + Sync + 'static,
+          V: Send + Sync + 'static, C: Continuation<(F, SpinGuard, V)> {
+    if let Some(deferred) = spin.enter(runtime.instant_index()) {
+        return deferred.queue(runtime, Box::new(move|runtime: &mut Runtime, ()| call_while_with(runtime, body, spin, state, next)));
+    }
+    body(state).call(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, status: LoopState<S, V>| match status {
+        LoopState::Continue(state) => call_while_with(runtime, body, spin, state, next),
+        LoopState::Exit(value) => next.call(runtime, (body, spin, value)),
+    }));
+}
+
+pub struct WhileWith<F, S> {
+    body: F,
+    init: S,
+    spin: SpinGuard,
+}
+
+impl<F, S> WhileWith<F, S> {
+    /// Names the loop in the panic of a spinning loop.
+    pub fn named(mut self, name: &str) -> Self {
+        self.spin.name = Some(Arc::new(name.to_string()));
+        self
+    }
+
+    /// Panics once the body completed `iterations` times in a row during the same instant, see
+    /// `While::spin_guard`.
+    pub fn spin_guard(self, iterations: usize) -> Self {
+        self.on_spin(iterations, SpinPolicy::Abort)
+    }
+
+    /// Applies `policy` once the body completed `iterations` times in a row during the same
+    /// instant, see `While::on_spin`.
+    pub fn on_spin(mut self, iterations: usize, policy: SpinPolicy) -> Self {
+        self.spin.limit = iterations;
+        self.spin.policy = policy;
+        self
+    }
+}
+
+impl<F, P, S, V> Process for WhileWith<F, S>
+    where F: FnMut(S) -> P + Send + Sync + 'static, P: Process<Value = LoopState<S, V>>, S: Send + Sync + 'static,
+          V: Send + Sync + 'static {
+    type Value = V;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let done = |(_, _, value): (F, SpinGuard, V)| value;
+        call_while_with(runtime, self.body, self.spin, self.init, next.map(done));
+    }
+}
+
+impl<F, P, S, V> ProcessMut for WhileWith<F, S>
+    where F: FnMut(S) -> P + Send + Sync + 'static, P: Process<Value = LoopState<S, V>>, S: Clone + Send + Sync + 'static,
+          V: Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let init = self.init;
+        let state = init.clone();
+        let done = move|(body, spin, value): (F, SpinGuard, V)| (WhileWith {body, init, spin}, value);
+        call_while_with(runtime, self.body, self.spin, state, next.map(done));
+    }
+}
+
+impl<F, P, S, V> CloneProcess for WhileWith<F, S>
+    where F: FnMut(S) -> P + Clone + Send + Sync + 'static, P: Process<Value = LoopState<S, V>>,
+          S: Clone + Send + Sync + 'static, V: Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        let mut spin = self.spin.clone();
+        spin.iterations = 0;
+        spin.queued = false;
+        WhileWith {body: self.body.clone(), init: self.init.clone(), spin}
+    }
+}
+
+/// A loop whose iterations pass a state to the next, starting from `init`: `body` builds the
+/// process of each iteration from the state, which returns `LoopState::Continue` with the state
+/// of the next iteration, or `LoopState::Exit` with the value of the loop. The loop can be
+/// guarded against spinning like `while_loop`, see `WhileWith::spin_guard`.
+pub fn while_loop_with<F, P, S, V>(init: S, body: F) -> WhileWith<F, S>
+    where F: FnMut(S) -> P + Send + Sync + 'static, P: Process<Value = LoopState<S, V>>, S: Send + Sync + 'static,
+          V: Send + Sync + 'static {
+    WhileWith {body, init, spin: SpinGuard::new()}
+}
+
+pub struct Repeat<P> {
+    process: P,
+    times: usize,
+}
+
+/// Executes `process` `remaining` more times, then calls `next` with the process and all the
+/// values.
+fn call_repeat<P, C>(runtime: &mut Runtime, process: P, remaining: usize, mut values: Vec<P::Value>, next: C)
+    where P: ProcessMut, C: Continuation<(P, Vec<P::Value>)> {
+    if remaining == 0 {
+        return next.call(runtime, (process, values));
+    }
+    process.call_mut(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, (process, v): (P, P::Value)| {
+        values.push(v);
+        call_repeat(runtime, process, remaining - 1, values, next);
+    }));
+}
+
+impl<P> Process for Repeat<P> where P: ProcessMut {
+    type Value = Vec<P::Value>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let values = Vec::with_capacity(self.times);
+        call_repeat(runtime, self.process, self.times, values, next.map(|(_, values): (P, Vec<P::Value>)| values));
+    }
+}
+
+impl<P> ProcessMut for Repeat<P> where P: ProcessMut {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let times = self.times;
+        let values = Vec::with_capacity(times);
+        call_repeat(runtime, self.process, times, values, next.map(move|(process, values): (P, Vec<P::Value>)| {
+            (process.repeat(times), values)
+        }));
+    }
+}
+
+impl<P> CloneProcess for Repeat<P> where P: ProcessMut + CloneProcess {
+    fn clone_process(&self) -> Self {
+        self.process.clone_process().repeat(self.times)
+    }
+}
+
+pub struct IterationsPerInstant<P> {
+    process: P,
+    spin: SpinGuard,
+}
+
+impl<P> Process for IterationsPerInstant<P> where P: ProcessMut {
+    type Value = P::Value;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        self.process.call(runtime, next);
+    }
+}
+
+impl<P> ProcessMut for IterationsPerInstant<P> where P: ProcessMut {
+    fn call_mut<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        if let Some(deferred) = self.spin.enter(runtime.instant_index()) {
+            return deferred.queue(runtime, Box::new(move|runtime: &mut Runtime, ()| self.call_mut(runtime, next)));
+        }
+        if self.spin.iterations > 1 {
+            // Executed from the scheduler instead of the continuation of the previous iteration,
+            // so that the stack does not grow with the number of iterations.
+            return runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| self.execute(runtime, next)));
+        }
+        self.execute(runtime, next);
+    }
+}
+
+impl<P> IterationsPerInstant<P> where P: ProcessMut {
+    fn execute<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, P::Value)> {
+        let spin = self.spin;
+        self.process.call_mut(runtime, next.map(move|(process, v): (P, P::Value)| (IterationsPerInstant {process, spin}, v)));
+    }
+}
+
+impl<P> CloneProcess for IterationsPerInstant<P> where P: ProcessMut + CloneProcess {
+    fn clone_process(&self) -> Self {
+        self.process.clone_process().iterations_per_instant(self.spin.limit)
+    }
+}
+
+/// How `retry` spaces out its attempts, delays being counted in instants.
+#[derive(Copy, Clone, Debug)]
+pub struct RetryPolicy {
+    /// Number of attempts before giving up, the first one included.
+    pub max_attempts: usize,
+    /// Delay between the first and the second attempts.
+    pub initial_delay: usize,
+    /// Each delay is the previous one multiplied by `factor`, up to `max_delay`.
+    pub factor: usize,
+    pub max_delay: usize,
+}
+
+impl RetryPolicy {
+    /// Up to `max_attempts` attempts, waiting 1 instant, then 2, 4, 8...
+    pub fn new(max_attempts: usize) -> Self {
+        RetryPolicy { max_attempts, initial_delay: 1, factor: 2, max_delay: std::usize::MAX }
+    }
+}
+
+pub struct Retry<F> {
+    factory: F,
+    policy: RetryPolicy,
+}
+
+/// Calls `c` `delay` instants from now.
+fn on_instant_after(runtime: &mut Runtime, delay: usize, c: Box<Continuation<()>>) {
+    if delay == 0 {
+        runtime.on_current_instant(c);
+    } else {
+        runtime.on_next_instant(Box::new(move|run: &mut Runtime, ()| on_instant_after(run, delay - 1, c)));
+    }
+}
+
+impl<F, P, T, E> Retry<F>
+    where F: FnMut() -> P + Send + Sync + 'static, P: Process<Value = Result<T, E>>, T: Send + Sync, E: Send + Sync {
+    fn attempt<C>(mut self, runtime: &mut Runtime, attempt: usize, delay: usize, next: C)
+        where C: Continuation<(Self, Result<T, E>)> {
+        let process = (self.factory)();
+        process.call(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, result: Result<T, E>| {
+            match result {
+                Err(_) if attempt + 1 < self.policy.max_attempts => {
+                    let next_delay = std::cmp::min(delay.saturating_mul(self.policy.factor), self.policy.max_delay);
+                    on_instant_after(runtime, delay, Box::new(move|run: &mut Runtime, ()| {
+                        self.attempt(run, attempt + 1, next_delay, next)
+                    }));
+                },
+                result => next.call(runtime, (self, result)),
+            }
+        }));
+    }
+}
+
+impl<F, P, T, E> Process for Retry<F>
+    where F: FnMut() -> P + Send + Sync + 'static, P: Process<Value = Result<T, E>>, T: Send + Sync, E: Send + Sync {
+    type Value = Result<T, E>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let delay = self.policy.initial_delay;
+        self.attempt(runtime, 0, delay, next.map(|(_, result)| result));
+    }
+}
+
+impl<F, P, T, E> ProcessMut for Retry<F>
+    where F: FnMut() -> P + Send + Sync + 'static, P: Process<Value = Result<T, E>>, T: Send + Sync, E: Send + Sync {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let delay = self.policy.initial_delay;
+        self.attempt(runtime, 0, delay, next);
+    }
+}
+
+/// Executes the process built by `factory`, building and executing a new one after a delay each
+/// time it returns an `Err`, as specified by `policy`. Returns the first `Ok`, or the last `Err`
+/// once `policy.max_attempts` attempts failed.
+pub fn retry<F, P, T, E>(factory: F, policy: RetryPolicy) -> Retry<F>
+    where F: FnMut() -> P + Send + Sync + 'static, P: Process<Value = Result<T, E>> {
+    Retry { factory, policy }
+}
+
+impl<F, P, T, E> CloneProcess for Retry<F>
+    where F: FnMut() -> P + Clone + Send + Sync + 'static, P: Process<Value = Result<T, E>>, T: Send + Sync, E: Send + Sync {
+    fn clone_process(&self) -> Self {
+        retry(self.factory.clone(), self.policy)
+    }
+}
+
+pub fn if_else<P, Q, R>(r: R, p: P, q: Q) -> If<P, Q, R> {
+    If {process_if: p, process_else: q, process_cond: r}
+}
+
+pub struct If<P, Q, R> {
+    process_if: P,
+    process_else: Q,
+    process_cond: R,
+}
+
+impl<P, Q, R, V> Process for If<P, Q, R> where P: Process<Value = V>, Q: Process<Value = V>, R: Process<Value = bool>, V: Send + Sync {
+    type Value = V;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<V> {
+        let p = self.process_if;
+        let q = self.process_else;
+        let r = self.process_cond;
+        r.call(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, cond: bool| {
+            if cond {
                 p.call(runtime, next);
             } else {
                 q.call(runtime, next);
             }
-        });
+        }));
     }
 }
 
@@ -506,12 +1621,384 @@ impl<P, Q, R, V> ProcessMut for If<P, Q, R> where P: ProcessMut<Value = V>, Q: P
         let p = self.process_if;
         let q = self.process_else;
         let r = self.process_cond;
-        r.call_mut(runtime, move|runtime: &mut Runtime, (r, cond): (R, bool)| {
+        r.call_mut(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, (r, cond): (R, bool)| {
             if cond {
                 p.call_mut(runtime, next.map(|(p, v): (P, V)| (if_else(r, p, q), v)));
             } else {
                 q.call_mut(runtime, next.map(|(q, v): (Q, V)| (if_else(r, p, q), v)));
             }
+        }));
+    }
+}
+
+impl<P, Q, R, V> CloneProcess for If<P, Q, R> where P: CloneProcess<Value = V>, Q: CloneProcess<Value = V>, R: CloneProcess<Value = bool>, V: Send + Sync {
+    fn clone_process(&self) -> Self {
+        if_else(self.process_cond.clone_process(), self.process_if.clone_process(), self.process_else.clone_process())
+    }
+}
+
+/// Like `if_else`, for branches of different types: returns `Either::Left` with the value of `p`
+/// when `r` returns true, `Either::Right` with the value of `q` otherwise.
+pub fn if_else_either<P, Q, R>(r: R, p: P, q: Q) -> IfEither<P, Q, R> where P: Process, Q: Process, R: Process<Value = bool> {
+    IfEither {process_if: p, process_else: q, process_cond: r}
+}
+
+pub struct IfEither<P, Q, R> {
+    process_if: P,
+    process_else: Q,
+    process_cond: R,
+}
+
+impl<P, Q, R> Process for IfEither<P, Q, R> where P: Process, Q: Process, R: Process<Value = bool> {
+    type Value = Either<P::Value, Q::Value>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let p = self.process_if;
+        let q = self.process_else;
+        let r = self.process_cond;
+        r.call(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, cond: bool| {
+            if cond {
+                p.call(runtime, next.map(Either::Left));
+            } else {
+                q.call(runtime, next.map(Either::Right));
+            }
+        }));
+    }
+}
+
+impl<P, Q, R> ProcessMut for IfEither<P, Q, R> where P: ProcessMut, Q: ProcessMut, R: ProcessMut<Value = bool> {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let p = self.process_if;
+        let q = self.process_else;
+        let r = self.process_cond;
+        r.call_mut(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, (r, cond): (R, bool)| {
+            if cond {
+                p.call_mut(runtime, next.map(|(p, v): (P, P::Value)| (if_else_either(r, p, q), Either::Left(v))));
+            } else {
+                q.call_mut(runtime, next.map(|(q, v): (Q, Q::Value)| (if_else_either(r, p, q), Either::Right(v))));
+            }
+        }));
+    }
+}
+
+impl<P, Q, R> CloneProcess for IfEither<P, Q, R> where P: CloneProcess, Q: CloneProcess, R: CloneProcess<Value = bool> {
+    fn clone_process(&self) -> Self {
+        if_else_either(self.process_cond.clone_process(), self.process_if.clone_process(), self.process_else.clone_process())
+    }
+}
+
+/// The value of `select3`, telling which process completed first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Either3<A, B, C> { First(A), Second(B), Third(C) }
+
+/// The continuation of a process of a select, which completes the select with its value mapped
+/// by `wrap` if the process is the first to complete, and cancels the other processes.
+struct SelectBranch<C, F> {
+    next: Arc<Mutex<Option<C>>>,
+    token: WaiterToken,
+    wrap: F,
+}
+
+impl<C, F, V, W> Continuation<V> for SelectBranch<C, F>
+    where C: Continuation<W>, F: FnOnce(V) -> W + Send + Sync + 'static, V: Send + Sync, W: Send + Sync {
+    fn call(self, runtime: &mut Runtime, value: V) {
+        let next = self.next.lock().unwrap().take();
+        if let Some(next) = next {
+            self.token.cancel(runtime);
+            next.call(runtime, (self.wrap)(value));
+        }
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V) {
+        (*self).call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        Some(&self.token)
+    }
+}
+
+fn select_branch<C, F>(next: &Arc<Mutex<Option<C>>>, token: &WaiterToken, wrap: F) -> SelectBranch<C, F> {
+    SelectBranch { next: next.clone(), token: token.clone(), wrap }
+}
+
+pub struct Select2<P1, P2> { p1: P1, p2: P2 }
+
+impl<P1, P2> Process for Select2<P1, P2> where P1: Process, P2: Process {
+    type Value = Either<P1::Value, P2::Value>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let next = Arc::new(Mutex::new(Some(next)));
+        let token = WaiterToken::new();
+        let (p1, c1) = (self.p1, select_branch(&next, &token, Either::Left));
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| p1.call(runtime, c1)));
+        let (p2, c2) = (self.p2, select_branch(&next, &token, Either::Right));
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| p2.call(runtime, c2)));
+    }
+}
+
+impl<P1, P2> CloneProcess for Select2<P1, P2> where P1: CloneProcess, P2: CloneProcess {
+    fn clone_process(&self) -> Self {
+        select2(self.p1.clone_process(), self.p2.clone_process())
+    }
+}
+
+/// Executes the processes jointly until one of them completes, and returns its value. The other
+/// process is cancelled: its value is dropped, and the signals it is waiting for with its last
+/// step forget it, though the steps it already scheduled are still executed. When both complete
+/// during the same instant, the first one to be executed wins.
+pub fn select2<P1, P2>(p1: P1, p2: P2) -> Select2<P1, P2> where P1: Process, P2: Process {
+    Select2 {p1, p2}
+}
+
+pub struct Select3<P1, P2, P3> { p1: P1, p2: P2, p3: P3 }
+
+impl<P1, P2, P3> Process for Select3<P1, P2, P3> where P1: Process, P2: Process, P3: Process {
+    type Value = Either3<P1::Value, P2::Value, P3::Value>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let next = Arc::new(Mutex::new(Some(next)));
+        let token = WaiterToken::new();
+        let (p1, c1) = (self.p1, select_branch(&next, &token, Either3::First));
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| p1.call(runtime, c1)));
+        let (p2, c2) = (self.p2, select_branch(&next, &token, Either3::Second));
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| p2.call(runtime, c2)));
+        let (p3, c3) = (self.p3, select_branch(&next, &token, Either3::Third));
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| p3.call(runtime, c3)));
+    }
+}
+
+impl<P1, P2, P3> CloneProcess for Select3<P1, P2, P3> where P1: CloneProcess, P2: CloneProcess, P3: CloneProcess {
+    fn clone_process(&self) -> Self {
+        select3(self.p1.clone_process(), self.p2.clone_process(), self.p3.clone_process())
+    }
+}
+
+/// Like `select2`, for three processes.
+pub fn select3<P1, P2, P3>(p1: P1, p2: P2, p3: P3) -> Select3<P1, P2, P3> where P1: Process, P2: Process, P3: Process {
+    Select3 {p1, p2, p3}
+}
+
+/// `select!(p1, p2)` is `select2(p1, p2)`, and `select!(p1, p2, p3)` is `select3(p1, p2, p3)`.
+#[macro_export]
+macro_rules! select {
+    ($p1:expr, $p2:expr) => { $crate::reactive::process::select2($p1, $p2) };
+    ($p1:expr, $p2:expr, $p3:expr) => { $crate::reactive::process::select3($p1, $p2, $p3) };
+}
+
+pub struct Memoize<P, F, K> where P: Process {
+    process: P,
+    key: F,
+    window: usize,
+    cache: HashMap<K, (usize, P::Value)>,
+}
+
+impl<P, F, K> Process for Memoize<P, F, K>
+    where P: Process, F: FnMut() -> K + Send + Sync + 'static, K: Hash + Eq + Send + Sync + 'static, P::Value: Clone {
+    type Value = P::Value;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        self.process.call(runtime, next);
+    }
+}
+
+impl<P, F, K> ProcessMut for Memoize<P, F, K>
+    where P: ProcessMut, F: FnMut() -> K + Send + Sync + 'static, K: Hash + Eq + Send + Sync + 'static, P::Value: Clone {
+    fn call_mut<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let k = (self.key)();
+        let now = runtime.instant_index();
+        let cached = match self.cache.get(&k) {
+            Some(&(at, ref v)) if now - at < self.window => Some(v.clone()),
+            _ => None,
+        };
+        if let Some(v) = cached {
+            return next.call(runtime, (self, v));
+        }
+
+        let Memoize {process, key, window, mut cache} = self;
+        process.call_mut(runtime, next.map(move|(process, v): (P, P::Value)| {
+            cache.retain(|_, &mut (at, _)| now - at < window);
+            cache.insert(k, (now, v.clone()));
+            (Memoize {process, key, window, cache}, v)
+        }));
+    }
+}
+
+/// A process shared by several branches of a program, see `Process::shared`.
+///
+/// The first copy to be executed starts the process, the others wait for its value, which they
+/// all receive once it terminates. Copies executed later get it immediately.
+pub struct Shared<P> where P: Process, P::Value: Clone {
+    process: Arc<Mutex<Option<P>>>,
+    result: ResultSignal<P::Value>,
+}
+
+impl<P> Clone for Shared<P> where P: Process, P::Value: Clone {
+    fn clone(&self) -> Self {
+        Shared { process: self.process.clone(), result: self.result.clone() }
+    }
+}
+
+impl<P> Process for Shared<P> where P: Process, P::Value: Clone {
+    type Value = P::Value;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let process = self.process.lock().unwrap().take();
+        if let Some(process) = process {
+            let result = self.result.clone();
+            process.call(runtime, move|runtime: &mut Runtime, v| result.resolve(runtime, v));
+        }
+        self.result.await().call(runtime, next);
+    }
+}
+
+/// A process without value with its type erased, to be sent to an `executor` through a signal.
+///
+/// The copies made by the signal share the process, which is only started by the first executor
+/// receiving it.
+pub struct Task {
+    process: Arc<Mutex<Option<Box<Continuation<()>>>>>,
+}
+
+impl Clone for Task {
+    fn clone(&self) -> Self {
+        Task { process: self.process.clone() }
+    }
+}
+
+impl Task {
+    fn start(self, runtime: &mut Runtime) {
+        let process = self.process.lock().unwrap().take();
+        if let Some(process) = process {
+            process.call_box(runtime, ());
+        }
+    }
+}
+
+/// Erases the type of `process`, which starts when an executor receives the task.
+pub fn task<P>(process: P) -> Task where P: Process<Value = ()> {
+    let start = move|runtime: &mut Runtime, ()| process.call(runtime, |_: &mut Runtime, ()| ());
+    Task { process: Arc::new(Mutex::new(Some(Box::new(start)))) }
+}
+
+/// Like `task`, with a signal resolved with the value of `process` once it terminates, which lets
+/// the emitter of the task await its result.
+pub fn task_with_result<P>(process: P) -> (Task, ResultSignal<P::Value>) where P: Process, P::Value: Clone + Send + Sync {
+    let result = ResultSignal::new();
+    let result_ref = result.clone();
+    let start = move|runtime: &mut Runtime, ()| {
+        process.call(runtime, move|runtime: &mut Runtime, v| result_ref.resolve(runtime, v))
+    };
+    (Task { process: Arc::new(Mutex::new(Some(Box::new(start)))) }, result)
+}
+
+/// A signal gathering the tasks emitted during an instant, to be given to `executor`.
+pub fn task_signal() -> ValueSignal<Vec<Task>, Task> {
+    ValueSignal::new(Vec::new(), Box::new(|mut tasks: Vec<Task>, task| {
+        tasks.push(task);
+        tasks
+    }))
+}
+
+/// The process spawning the tasks of a signal, see `executor`.
+pub struct Executor {
+    await: VAwait<Vec<Task>, Task>,
+}
+
+impl Process for Executor {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        self.await.call_mut(runtime, move|runtime: &mut Runtime, (await, tasks): (VAwait<Vec<Task>, Task>, Vec<Task>)| {
+            for task in tasks {
+                task.start(runtime);
+            }
+            Executor { await }.call(runtime, next);
         });
     }
 }
+
+/// Starts the tasks emitted on `signal` at the instant following their emission, alongside the
+/// rest of the program. The returned process never terminates.
+pub fn executor<S>(signal: &S) -> Executor where S: VSignal<Vec<Task>, Task> {
+    Executor { await: signal.await() }
+}
+
+/// Number of iterations after which a `fixpoint` which did not converge panics, by default.
+pub const FIXPOINT_ITERATIONS: usize = 1 << 20;
+
+pub struct Fixpoint<P> where P: Process {
+    process: P,
+    last: Option<P::Value>,
+    iterations: usize,
+    limit: usize,
+}
+
+impl<P> Fixpoint<P> where P: Process {
+    /// Panics once the body completed `iterations` times without converging, instead of
+    /// `FIXPOINT_ITERATIONS` times, which can be handled by `on_process_panic`.
+    pub fn max_iterations(mut self, iterations: usize) -> Self {
+        self.limit = iterations;
+        self
+    }
+
+    /// The iteration following the `iterations`-th one, which returned `last`.
+    fn after(process: P, last: P::Value, iterations: usize, limit: usize) -> Self {
+        if iterations >= limit {
+            panic!("fixpoint did not converge after {} iterations", iterations);
+        }
+        Fixpoint { process, last: Some(last), iterations, limit }
+    }
+}
+
+/// Executes `iteration` right away, or queues it on the current instant every `STACK_ITERATIONS`
+/// iterations to unwind the stack.
+fn unwinding<F>(runtime: &mut Runtime, iterations: usize, iteration: F) where F: FnOnce(&mut Runtime) + Send + Sync + 'static {
+    if iterations % STACK_ITERATIONS == 0 {
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| iteration(runtime)));
+    } else {
+        iteration(runtime);
+    }
+}
+
+impl<P> Process for Fixpoint<P> where P: ProcessMut, P::Value: PartialEq {
+    type Value = P::Value;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let (last, iterations, limit) = (self.last, self.iterations + 1, self.limit);
+        self.process.call_mut(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, (process, v): (P, P::Value)| {
+            if last.as_ref() == Some(&v) {
+                return next.call(runtime, v);
+            }
+            let fixpoint = Fixpoint::after(process, v, iterations, limit);
+            unwinding(runtime, iterations, move|runtime: &mut Runtime| fixpoint.call(runtime, next));
+        }));
+    }
+}
+
+impl<P> ProcessMut for Fixpoint<P> where P: ProcessMut, P::Value: PartialEq {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let (last, iterations, limit) = (self.last, self.iterations + 1, self.limit);
+        self.process.call_mut(runtime, carry(next.waiter_token().cloned(), move|runtime: &mut Runtime, (process, v): (P, P::Value)| {
+            if last.as_ref() == Some(&v) {
+                return next.call(runtime, (fixpoint(process).max_iterations(limit), v));
+            }
+            let fixpoint = Fixpoint::after(process, v, iterations, limit);
+            unwinding(runtime, iterations, move|runtime: &mut Runtime| fixpoint.call_mut(runtime, next));
+        }));
+    }
+}
+
+impl<P> CloneProcess for Fixpoint<P> where P: ProcessMut + CloneProcess, P::Value: PartialEq + Clone {
+    fn clone_process(&self) -> Self {
+        Fixpoint { process: self.process.clone_process(), last: self.last.clone(), iterations: self.iterations, limit: self.limit }
+    }
+}
+
+/// Executes `body` again and again until it returns the same value twice in a row, and returns
+/// this value. As long as `body` does not pause, all the iterations happen in the same instant.
+/// Panics if `body` does not converge within `FIXPOINT_ITERATIONS` iterations, see
+/// `max_iterations`.
+pub fn fixpoint<P>(body: P) -> Fixpoint<P> where P: ProcessMut, P::Value: PartialEq {
+    Fixpoint { process: body, last: None, iterations: 0, limit: FIXPOINT_ITERATIONS }
+}
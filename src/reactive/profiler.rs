@@ -0,0 +1,130 @@
+use super::*;
+use std::time::{Duration, Instant};
+
+//  ____             __ _ _
+// |  _ \ _ __ ___  / _(_) | ___ _ __
+// | |_) | '__/ _ \| |_| | |/ _ \ '__|
+// |  __/| | | (_) |  _| | |  __/ |
+// |_|   |_|  \___/|_| |_|_|\___|_|
+
+/// Time accounted to a named process.
+#[derive(Clone, Debug)]
+pub struct ProfileEntry {
+    pub name: String,
+    /// Total time spent in the process.
+    pub total: Duration,
+    /// Number of executions of the process.
+    pub calls: usize,
+    /// Number of distinct instants in which the process was executed.
+    pub instants: usize,
+    /// Time spent in the process during its most expensive instant.
+    pub worst_instant: Duration,
+    current_instant: usize,
+    current_total: Duration,
+}
+
+/// Accumulates the time spent in the processes wrapped with `Process::profile`.
+#[derive(Clone)]
+pub struct Profiler {
+    entries: Arc<Mutex<HashMap<String, ProfileEntry>>>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler { entries: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    fn record(&self, name: &str, instant: usize, elapsed: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.entry(name.to_string()).or_insert_with(|| ProfileEntry {
+            name: name.to_string(),
+            total: Duration::from_secs(0),
+            calls: 0,
+            instants: 0,
+            worst_instant: Duration::from_secs(0),
+            current_instant: instant,
+            current_total: Duration::from_secs(0),
+        });
+        if entry.calls == 0 || entry.current_instant != instant {
+            entry.instants += 1;
+            entry.current_instant = instant;
+            entry.current_total = Duration::from_secs(0);
+        }
+        entry.total += elapsed;
+        entry.calls += 1;
+        entry.current_total += elapsed;
+        if entry.current_total > entry.worst_instant {
+            entry.worst_instant = entry.current_total;
+        }
+    }
+
+    /// The `n` processes with the largest total time, slowest first.
+    pub fn report(&self, n: usize) -> Vec<ProfileEntry> {
+        let mut entries: Vec<ProfileEntry> = self.entries.lock().unwrap().values().cloned().collect();
+        entries.sort_by(|a, b| b.total.cmp(&a.total));
+        entries.truncate(n);
+        entries
+    }
+
+    /// Forgets everything recorded so far.
+    pub fn reset(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// A process whose execution time is accounted to a `Profiler`.
+///
+/// Only the time spent in `call` before it returns or reaches its continuation is measured: the
+/// continuation itself is not accounted to the process, and neither is the execution of parts of
+/// the process resumed in later instants (after a `pause` or an `await`).
+pub struct Profiled<P> {
+    process: P,
+    profiler: Profiler,
+    name: String,
+}
+
+impl<P> Profiled<P> {
+    pub fn new(process: P, profiler: &Profiler, name: &str) -> Self {
+        Profiled { process, profiler: profiler.clone(), name: name.to_string() }
+    }
+}
+
+impl<P> Process for Profiled<P> where P: Process {
+    type Value = P::Value;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let start = Instant::now();
+        let instant = runtime.instant_index();
+        let reached = Arc::new(Mutex::new(None));
+        let reached_ref = reached.clone();
+        self.process.call(runtime, move|runtime: &mut Runtime, v| {
+            *reached_ref.lock().unwrap() = Some(start.elapsed());
+            next.call(runtime, v);
+        });
+        let elapsed = reached.lock().unwrap().take().unwrap_or_else(|| start.elapsed());
+        self.profiler.record(&self.name, instant, elapsed);
+    }
+}
+
+impl<P> ProcessMut for Profiled<P> where P: ProcessMut {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let start = Instant::now();
+        let instant = runtime.instant_index();
+        let reached = Arc::new(Mutex::new(None));
+        let reached_ref = reached.clone();
+        let profiler = self.profiler.clone();
+        let name = self.name.clone();
+        self.process.call_mut(runtime, move|runtime: &mut Runtime, (process, v): (P, P::Value)| {
+            *reached_ref.lock().unwrap() = Some(start.elapsed());
+            next.call(runtime, (Profiled {process, profiler, name}, v));
+        });
+        let elapsed = reached.lock().unwrap().take().unwrap_or_else(|| start.elapsed());
+        self.profiler.record(&self.name, instant, elapsed);
+    }
+}
+
+impl<P> CloneProcess for Profiled<P> where P: CloneProcess {
+    fn clone_process(&self) -> Self {
+        Profiled { process: self.process.clone_process(), profiler: self.profiler.clone(), name: self.name.clone() }
+    }
+}
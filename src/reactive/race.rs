@@ -0,0 +1,422 @@
+use super::*;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+//  ____
+// |  _ \ __ _  ___ ___
+// | |_) / _` |/ __/ _ \
+// |  _ < (_| | (_|  __/
+// |_| \_\__,_|\___\___|
+
+pub struct Race<P, Q> {
+    p: P,
+    q: Q,
+}
+
+impl<P, Q> Process for Race<P, Q> where P: Process, Q: Process {
+    type Value = Either<P::Value, Q::Value>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let next = Arc::new(Mutex::new(Some(next)));
+        let lanes = lanes(2);
+        let (p, c) = (self.p, RaceBranch { next: next.clone(), lanes: lanes.clone(), index: 0, wrap: Either::Left });
+        lanes[0].run(runtime, move|runtime: &mut Runtime| p.call(runtime, c));
+        let (q, c) = (self.q, RaceBranch { next, lanes: lanes.clone(), index: 1, wrap: Either::Right });
+        lanes[1].run(runtime, move|runtime: &mut Runtime| q.call(runtime, c));
+    }
+}
+
+impl<P, Q> CloneProcess for Race<P, Q> where P: CloneProcess, Q: CloneProcess {
+    fn clone_process(&self) -> Self {
+        race(self.p.clone_process(), self.q.clone_process())
+    }
+}
+
+/// Executes the processes jointly until one of them completes, and returns its value, like
+/// `select2`. The other process is cancelled with everything it scheduled on the runtime: its
+/// pending steps are dropped instead of being executed, and the signals it is waiting for with
+/// its last step forget it. When both complete during the same instant, the first one to be
+/// executed wins.
+///
+/// A step of the loser resumed by a signal before the end of the race is not cancelled, as with
+/// `select2`.
+pub fn race<P, Q>(p: P, q: Q) -> Race<P, Q> where P: Process, Q: Process {
+    Race { p, q }
+}
+
+pub struct MultiRace<P> {
+    processes: Vec<P>,
+}
+
+impl<P> Process for MultiRace<P> where P: Process {
+    type Value = (usize, P::Value);
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let next = Arc::new(Mutex::new(Some(next)));
+        let lanes = lanes(self.processes.len());
+        for (index, process) in self.processes.into_iter().enumerate() {
+            let c = RaceBranch { next: next.clone(), lanes: lanes.clone(), index, wrap: move|v| (index, v) };
+            lanes[index].run(runtime, move|runtime: &mut Runtime| process.call(runtime, c));
+        }
+    }
+}
+
+impl<P> CloneProcess for MultiRace<P> where P: CloneProcess {
+    fn clone_process(&self) -> Self {
+        multi_race(self.processes.iter().map(|p| p.clone_process()).collect())
+    }
+}
+
+/// Like `race`, for any number of processes: returns the index and the value of the first one to
+/// complete, and cancels the others. Never completes when there is no process.
+pub fn multi_race<P>(processes: Vec<P>) -> MultiRace<P> where P: Process {
+    MultiRace { processes }
+}
+
+pub struct Timeout<P> {
+    process: P,
+    instants: usize,
+}
+
+impl<P> Process for Timeout<P> where P: Process {
+    type Value = Option<P::Value>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let timer = Elapse { instants: self.instants };
+        race(self.process, timer).call(runtime, next.map(|v: Either<P::Value, ()>| match v {
+            Either::Left(v) => Some(v),
+            Either::Right(()) => None,
+        }));
+    }
+}
+
+impl<P> CloneProcess for Timeout<P> where P: CloneProcess {
+    fn clone_process(&self) -> Self {
+        timeout(self.process.clone_process(), self.instants)
+    }
+}
+
+/// Returns the value of `process` if it completes within `instants` instants, or `None` at the
+/// `instants`-th instant after its start, the process being cancelled like the loser of a `race`.
+/// A process completing during the instant of the timeout may still win.
+pub fn timeout<P>(process: P, instants: usize) -> Timeout<P> where P: Process {
+    Timeout { process, instants }
+}
+
+/// Completes `instants` instants after its start.
+struct Elapse {
+    instants: usize,
+}
+
+impl Process for Elapse {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        match self.instants {
+            0 => next.call(runtime, ()),
+            n => runtime.on_next_instant(Box::new(move|runtime: &mut Runtime, ()| {
+                Elapse { instants: n - 1 }.call(runtime, next);
+            })),
+        }
+    }
+}
+
+pub struct Until<P, Q> {
+    process: P,
+    trigger: Q,
+}
+
+impl<P, Q> Process for Until<P, Q> where P: Process, Q: Process {
+    type Value = Option<P::Value>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let next = Arc::new(Mutex::new(Some(next)));
+        let lanes = lanes(2);
+        let (p, c) = (self.process, RaceBranch { next: next.clone(), lanes: lanes.clone(), index: 0, wrap: Some });
+        lanes[0].run(runtime, move|runtime: &mut Runtime| p.call(runtime, c));
+        let (trigger, c) = (self.trigger, Preempt { next, lanes: lanes.clone(), value: PhantomData });
+        lanes[1].run(runtime, move|runtime: &mut Runtime| trigger.call(runtime, c));
+    }
+}
+
+impl<P, Q> CloneProcess for Until<P, Q> where P: CloneProcess, Q: CloneProcess {
+    fn clone_process(&self) -> Self {
+        until(self.process.clone_process(), self.trigger.clone_process())
+    }
+}
+
+/// Executes `process` until the end of the instant in which `trigger` completes, like `do p until
+/// s` in ReactiveML with `s.await_immediate()` as the trigger. Returns the value of the process if
+/// it completes first, even during the instant of the trigger, or `None` at the next instant if it
+/// is preempted: it is then cancelled like the loser of a `race`.
+pub fn until<P, Q>(process: P, trigger: Q) -> Until<P, Q> where P: Process, Q: Process {
+    Until { process, trigger }
+}
+
+enum CancelState {
+    Pending(Vec<Box<Continuation<()>>>),
+    Cancelled,
+}
+
+/// Cancels the processes made `cancellable` with it, which are then preempted like with `until`
+/// at the end of the instant of the cancellation: their pending steps are dropped, the signals
+/// they are waiting for forget them, and they return `None` at the next instant.
+///
+/// Processes made cancellable with an already cancelled token are preempted at the end of the
+/// instant in which they start.
+#[derive(Clone)]
+pub struct CancelToken {
+    state: Arc<Mutex<CancelState>>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken { state: Arc::new(Mutex::new(CancelState::Pending(Vec::new()))) }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        match *self.state.lock().unwrap() {
+            CancelState::Pending(_) => false,
+            CancelState::Cancelled => true,
+        }
+    }
+
+    /// Cancels the token, and completes.
+    pub fn cancel(&self) -> Cancel {
+        Cancel { token: self.clone() }
+    }
+
+    /// Cancels the token at the start of the next instant, from another thread, see
+    /// `ExternalInbox`.
+    pub fn cancel_external(&self, inbox: &ExternalInbox) {
+        let token = self.clone();
+        inbox.push(move|runtime: &mut Runtime, ()| token.cancel_now(runtime));
+    }
+
+    /// Completes once the token is cancelled.
+    pub fn await(&self) -> CancelAwait {
+        CancelAwait { token: self.clone() }
+    }
+
+    fn cancel_now(&self, runtime: &mut Runtime) {
+        let state = std::mem::replace(&mut *self.state.lock().unwrap(), CancelState::Cancelled);
+        if let CancelState::Pending(waiters) = state {
+            for c in waiters {
+                runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| c.call_box(runtime, ())));
+            }
+        }
+    }
+}
+
+pub struct Cancel {
+    token: CancelToken,
+}
+
+impl Process for Cancel {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        self.token.cancel_now(runtime);
+        next.call(runtime, ());
+    }
+}
+
+pub struct CancelAwait {
+    token: CancelToken,
+}
+
+impl Process for CancelAwait {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        match *self.token.state.lock().unwrap() {
+            CancelState::Pending(ref mut waiters) => {
+                // The waiters of the processes which completed before the cancellation.
+                waiters.retain(|c| !is_cancelled(&**c));
+                return waiters.push(Box::new(next));
+            },
+            CancelState::Cancelled => (),
+        }
+        next.call(runtime, ());
+    }
+}
+
+impl CloneProcess for CancelAwait {
+    fn clone_process(&self) -> Self {
+        self.token.await()
+    }
+}
+
+/// The continuation of the trigger of `until`, which preempts the process at the end of the
+/// instant unless it completed.
+struct Preempt<C, V> {
+    next: Arc<Mutex<Option<C>>>,
+    lanes: Arc<Vec<Lane>>,
+    value: PhantomData<V>,
+}
+
+impl<C, V, T> Continuation<T> for Preempt<C, V> where C: Continuation<Option<V>>, V: Send + Sync + 'static, T: Send + Sync {
+    fn call(self, runtime: &mut Runtime, _: T) {
+        let (next, lanes) = (self.next, self.lanes);
+        runtime.on_end_of_instant(Box::new(move|runtime: &mut Runtime, ()| {
+            let next = next.lock().unwrap().take();
+            if let Some(next) = next {
+                lanes[1].resolved.store(true, Ordering::SeqCst);
+                lanes[0].lost.cancel(runtime);
+                // Continuations registered for the current instant at the end of an instant are
+                // executed during the next one.
+                runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| next.call(runtime, None)));
+            }
+        }));
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: T) {
+        (*self).call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        Some(&self.lanes[1].lost)
+    }
+}
+
+/// One of the processes of a race.
+#[derive(Clone)]
+struct Lane {
+    /// Cancelled when another process wins.
+    lost: WaiterToken,
+    /// Set once a process won, after which the steps of the winner are not tracked anymore.
+    resolved: Arc<AtomicBool>,
+}
+
+/// The lanes of a race between `n` processes.
+fn lanes(n: usize) -> Arc<Vec<Lane>> {
+    let resolved = Arc::new(AtomicBool::new(false));
+    Arc::new((0..n).map(|_| Lane { lost: WaiterToken::new(), resolved: resolved.clone() }).collect())
+}
+
+impl Lane {
+    /// Calls `f` with a runtime tracking the steps it schedules, unless the race is over.
+    fn run<F>(&self, runtime: &mut Runtime, f: F) where F: FnOnce(&mut Runtime) {
+        if self.lost.is_cancelled() {
+            return;
+        }
+        if self.resolved.load(Ordering::SeqCst) {
+            return f(runtime);
+        }
+        f(&mut RacingRuntime { runtime, lane: self.clone() });
+    }
+}
+
+/// The continuation of a process of a race, which completes the race with its value mapped by
+/// `wrap` if the process is the first to complete, and cancels the other ones.
+struct RaceBranch<C, F> {
+    next: Arc<Mutex<Option<C>>>,
+    lanes: Arc<Vec<Lane>>,
+    index: usize,
+    wrap: F,
+}
+
+impl<C, F, V, W> Continuation<V> for RaceBranch<C, F>
+    where C: Continuation<W>, F: FnOnce(V) -> W + Send + Sync + 'static, V: Send + Sync, W: Send + Sync {
+    fn call(self, runtime: &mut Runtime, value: V) {
+        let next = self.next.lock().unwrap().take();
+        if let Some(next) = next {
+            self.lanes[self.index].resolved.store(true, Ordering::SeqCst);
+            for (index, lane) in self.lanes.iter().enumerate() {
+                if index != self.index {
+                    lane.lost.cancel(runtime);
+                }
+            }
+            next.call(runtime, (self.wrap)(value));
+        }
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V) {
+        (*self).call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        Some(&self.lanes[self.index].lost)
+    }
+}
+
+/// A step scheduled by a process of a race, dropped if the process lost.
+struct RaceStep {
+    continuation: Box<Continuation<()>>,
+    lane: Lane,
+}
+
+impl Continuation<()> for RaceStep {
+    fn call(self, runtime: &mut Runtime, (): ()) {
+        let continuation = self.continuation;
+        self.lane.run(runtime, move|runtime: &mut Runtime| continuation.call_box(runtime, ()));
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: ()) {
+        (*self).call(runtime, value);
+    }
+
+    // Lets the runtime forget the timers of the loser.
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        Some(&self.lane.lost)
+    }
+}
+
+/// The runtime given to a process of a race, tracking the continuations it schedules.
+struct RacingRuntime<'a> {
+    runtime: &'a mut Runtime,
+    lane: Lane,
+}
+
+impl<'a> RacingRuntime<'a> {
+    fn step(&self, c: Box<Continuation<()>>) -> Box<Continuation<()>> {
+        if self.lane.resolved.load(Ordering::SeqCst) && !self.lane.lost.is_cancelled() {
+            // The rest of the program after the race, or the winner finishing its instant.
+            return c;
+        }
+        Box::new(RaceStep { continuation: c, lane: self.lane.clone() })
+    }
+}
+
+impl<'a> Runtime for RacingRuntime<'a> {
+    fn on_current_instant(&mut self, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_current_instant(step);
+    }
+
+    fn on_next_instant(&mut self, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_next_instant(step);
+    }
+
+    fn on_deadline(&mut self, deadline: time::Instant, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_deadline(deadline, step);
+    }
+
+    // The signals emitted by the process reset themselves at the end of the instant, even if it
+    // loses during the instant.
+    fn on_end_of_instant(&mut self, c: Box<Continuation<()>>) {
+        self.runtime.on_end_of_instant(c);
+    }
+
+    fn instant_index(&self) -> usize {
+        self.runtime.instant_index()
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.runtime.is_deterministic()
+    }
+
+    fn external_inbox(&self) -> ExternalInbox {
+        self.runtime.external_inbox()
+    }
+
+    fn panic_handler(&self) -> Option<PanicHandler> {
+        self.runtime.panic_handler()
+    }
+
+    fn clock(&self) -> SharedClock {
+        self.runtime.clock()
+    }
+}
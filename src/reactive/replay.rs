@@ -0,0 +1,118 @@
+use super::*;
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Display};
+
+//  ____            _
+// |  _ \ ___ _ __ | | __ _ _   _
+// | |_) / _ \ '_ \| |/ _` | | | |
+// |  _ <  __/ |_) | | (_| | |_| |
+// |_| \_\___| .__/|_|\__,_|\__, |
+//           |_|            |___/
+
+/// The values gathered by a set of signals at every instant of a run, to compare runs of the same
+/// program, for instance with a sequential and a parallel runtime.
+///
+/// The values are kept as their `Debug` representation, so that signals of different types can be
+/// recorded in the same log.
+#[derive(Clone)]
+pub struct SignalLog {
+    /// The gathered values by instant, then by signal name.
+    emissions: Arc<Mutex<BTreeMap<usize, BTreeMap<String, String>>>>,
+}
+
+impl SignalLog {
+    pub fn new() -> Self {
+        SignalLog { emissions: Arc::new(Mutex::new(BTreeMap::new())) }
+    }
+
+    /// A process recording the values gathered by `signal` under `name` from now on, which
+    /// terminates immediately. The recording does not keep the runtime going.
+    pub fn watch<S, V, G>(&self, name: &str, signal: &S) -> Watch<V, G>
+        where S: VSignal<V, G>, V: Clone + Debug + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+        Watch { log: self.clone(), name: name.to_string(), await: signal.await() }
+    }
+
+    fn record(&self, instant: usize, name: &str, value: String) {
+        let mut emissions = self.emissions.lock().unwrap();
+        emissions.entry(instant).or_insert_with(BTreeMap::new).insert(name.to_string(), value);
+    }
+
+    /// The recorded values as `(instant, signal, value)`, by instant then signal name.
+    pub fn emissions(&self) -> Vec<(usize, String, String)> {
+        let emissions = self.emissions.lock().unwrap();
+        emissions.iter()
+            .flat_map(|(&instant, signals)| signals.iter().map(move|(name, value)| (instant, name.clone(), value.clone())))
+            .collect()
+    }
+
+    /// The first instant, and the first signal by name in that instant, where the values recorded
+    /// by the two logs differ, if any. A signal absent from an instant in one log only diverges.
+    pub fn first_divergence(&self, other: &SignalLog) -> Option<Divergence> {
+        let left = self.emissions.lock().unwrap().clone();
+        let right = other.emissions.lock().unwrap().clone();
+        let empty = BTreeMap::new();
+        let mut instants: Vec<usize> = left.keys().chain(right.keys()).cloned().collect();
+        instants.sort();
+        instants.dedup();
+        for instant in instants {
+            let left = left.get(&instant).unwrap_or(&empty);
+            let right = right.get(&instant).unwrap_or(&empty);
+            let mut names: Vec<&String> = left.keys().chain(right.keys()).collect();
+            names.sort();
+            names.dedup();
+            for name in names {
+                if left.get(name) != right.get(name) {
+                    return Some(Divergence {
+                        instant,
+                        signal: name.clone(),
+                        left: left.get(name).cloned(),
+                        right: right.get(name).cloned(),
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Where two `SignalLog`s differ, with the values of each, `None` if the signal was absent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    pub instant: usize,
+    pub signal: String,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |value: &Option<String>| value.clone().unwrap_or_else(|| String::from("absent"));
+        write!(f, "signal {} diverges at instant {}: {} != {}", self.signal, self.instant, show(&self.left), show(&self.right))
+    }
+}
+
+/// Records the values of a signal in a `SignalLog`, see `SignalLog::watch`.
+pub struct Watch<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    log: SignalLog,
+    name: String,
+    await: VAwait<V, G>,
+}
+
+impl<V, G> Watch<V, G> where V: Clone + Debug + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn record_next(self, runtime: &mut Runtime) {
+        self.await.clone_process().call(runtime, move|runtime: &mut Runtime, v: V| {
+            // `await` resumes on the instant following the emission.
+            self.log.record(runtime.instant_index() - 1, &self.name, format!("{:?}", v));
+            self.record_next(runtime);
+        });
+    }
+}
+
+impl<V, G> Process for Watch<V, G> where V: Clone + Debug + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        self.record_next(runtime);
+        next.call(runtime, ());
+    }
+}
@@ -1,4 +1,8 @@
 use super::*;
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::BinaryHeap;
+use std::sync::{Condvar, MutexGuard};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 pub mod sequential_runtime;
 pub mod parallel_runtime;
@@ -15,4 +19,634 @@ pub trait Runtime: Send {
     fn on_next_instant(&mut self, c: Box<Continuation<()>>);
 
     fn on_end_of_instant(&mut self, c: Box<Continuation<()>>);
+
+    /// Executes `c` during the first instant starting once the clock of the runtime reached
+    /// `deadline`, see `sleep`.
+    fn on_deadline(&mut self, deadline: time::Instant, c: Box<Continuation<()>>);
+
+    /// Number of instants completed so far, i.e. the index of the current instant.
+    fn instant_index(&self) -> usize;
+
+    /// Whether the values emitted on signals are gathered in the order of the emissions in the
+    /// program, rather than in execution order, so that they do not depend on the scheduling, see
+    /// `OrderKey`.
+    fn is_deterministic(&self) -> bool;
+
+    /// The inbox through which other threads act on the program between instants.
+    fn external_inbox(&self) -> ExternalInbox;
+
+    /// The handler deciding what to do with panicking processes, see `PanicPolicy`.
+    fn panic_handler(&self) -> Option<PanicHandler>;
+
+    /// The clock giving the wall-clock time to the processes, see `Clock`.
+    fn clock(&self) -> SharedClock;
+}
+
+/// Continuations queued by other threads, such as emissions of signals from outside of the
+/// program, which the runtime executes at the start of its next instant.
+///
+/// Signals must only be touched during instants: an emission from another thread while an
+/// instant is running, or between two instants, would race with the processes of the program.
+/// The inbox is drained all at once when an instant starts, before anything else is executed.
+/// A runtime with nothing else to do waits for the inbox while it has live sources, see
+/// `ExternalInbox::source`.
+///
+/// An inbox may hold a bounded number of continuations, see `SequentialRuntime::inbox_capacity`,
+/// so that a thread emitting faster than the program consumes cannot get ahead of it.
+#[derive(Clone)]
+pub struct ExternalInbox {
+    state: Arc<(Mutex<InboxState>, Condvar)>,
+}
+
+struct InboxState {
+    queue: Vec<Box<Continuation<()>>>,
+    capacity: Option<usize>,
+    /// The number of `ExternalSource`s alive.
+    sources: usize,
+}
+
+impl InboxState {
+    fn is_full(&self) -> bool {
+        self.capacity.map_or(false, |capacity| self.queue.len() >= capacity)
+    }
+}
+
+/// The continuation emitting `v` on `signal`.
+fn emission<S, V, G>(signal: &S, v: G) -> Box<Continuation<()>>
+    where S: VSignal<V, G> + Clone + Send + Sync, V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    let signal = signal.clone();
+    Box::new(move|runtime: &mut Runtime, ()| signal.emit(value(v)).call(runtime, |_: &mut Runtime, _| ()))
+}
+
+impl ExternalInbox {
+    pub fn new() -> Self {
+        let state = InboxState { queue: Vec::new(), capacity: None, sources: 0 };
+        ExternalInbox { state: Arc::new((Mutex::new(state), Condvar::new())) }
+    }
+
+    /// Executes `c` at the start of the next instant, even if the inbox is full.
+    pub fn push<C>(&self, c: C) where C: Continuation<()> {
+        let (ref state, ref changed) = *self.state;
+        state.lock().unwrap().queue.push(Box::new(c));
+        changed.notify_all();
+    }
+
+    /// Emits `v` on `signal` at the start of the next instant, blocking while the inbox is full.
+    ///
+    /// A full inbox is only drained by the runtime executing instants: the thread executing it
+    /// must not wait for it, nor a thread the runtime does not wait for, see `source`.
+    pub fn emit<S, V, G>(&self, signal: &S, v: G) where S: VSignal<V, G> + Clone + Send + Sync, V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+        let (ref state, ref changed) = *self.state;
+        let mut state = state.lock().unwrap();
+        while state.is_full() {
+            state = changed.wait(state).unwrap();
+        }
+        state.queue.push(emission(signal, v));
+        changed.notify_all();
+    }
+
+    /// Like `emit`, but gives `v` back instead of blocking if the inbox is full.
+    pub fn try_emit<S, V, G>(&self, signal: &S, v: G) -> Result<(), G> where S: VSignal<V, G> + Clone + Send + Sync, V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+        let (ref state, ref changed) = *self.state;
+        let mut state = state.lock().unwrap();
+        if state.is_full() {
+            return Err(v);
+        }
+        state.queue.push(emission(signal, v));
+        changed.notify_all();
+        Ok(())
+    }
+
+    /// A source of continuations, for a thread whose results a process waits for: the runtime
+    /// keeps executing instants, or blocks until the next push if it has nothing else to do,
+    /// instead of returning from `execute` while the source is alive.
+    pub fn source(&self) -> ExternalSource {
+        self.state.0.lock().unwrap().sources += 1;
+        ExternalSource { inbox: self.clone() }
+    }
+
+    pub(crate) fn set_capacity(&self, continuations: usize) {
+        assert!(continuations > 0, "a bounded inbox needs room for at least one continuation");
+        self.state.0.lock().unwrap().capacity = Some(continuations);
+    }
+
+    /// Takes the queued continuations, making room for the emitters waiting for it.
+    pub(crate) fn drain(&self) -> Vec<Box<Continuation<()>>> {
+        let (ref state, ref changed) = *self.state;
+        let drained = std::mem::replace(&mut state.lock().unwrap().queue, Vec::new());
+        changed.notify_all();
+        drained
+    }
+
+    /// Whether continuations are queued or may still be pushed by a source.
+    pub(crate) fn is_awaited(&self) -> bool {
+        let state = self.state.0.lock().unwrap();
+        state.sources > 0 || !state.queue.is_empty()
+    }
+
+    /// Blocks the thread of a runtime with nothing else to do until a continuation is queued, or
+    /// `timeout` elapsed. Without timeout, returns as well once no source is left.
+    pub(crate) fn wait(&self, timeout: Option<time::Duration>) {
+        let (ref state, ref changed) = *self.state;
+        let deadline = timeout.map(|timeout| time::Instant::now() + timeout);
+        let mut state = state.lock().unwrap();
+        while state.queue.is_empty() {
+            match deadline {
+                Some(deadline) => {
+                    let now = time::Instant::now();
+                    if deadline <= now {
+                        return;
+                    }
+                    state = changed.wait_timeout(state, deadline - now).unwrap().0;
+                },
+                None if state.sources == 0 => return,
+                None => state = changed.wait(state).unwrap(),
+            }
+        }
+    }
+}
+
+/// Pushes continuations to an `ExternalInbox` from another thread, keeping the runtime waiting
+/// for them until it is dropped, see `ExternalInbox::source`.
+pub struct ExternalSource {
+    inbox: ExternalInbox,
+}
+
+impl ExternalSource {
+    /// Executes `c` at the start of the next instant, see `ExternalInbox::push`.
+    pub fn push<C>(&self, c: C) where C: Continuation<()> {
+        self.inbox.push(c);
+    }
+
+    /// Emits `v` on `signal`, blocking while the inbox is full, see `ExternalInbox::emit`.
+    pub fn emit<S, V, G>(&self, signal: &S, v: G) where S: VSignal<V, G> + Clone + Send + Sync, V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+        self.inbox.emit(signal, v);
+    }
+
+    /// Emits `v` on `signal` unless the inbox is full, see `ExternalInbox::try_emit`.
+    pub fn try_emit<S, V, G>(&self, signal: &S, v: G) -> Result<(), G> where S: VSignal<V, G> + Clone + Send + Sync, V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+        self.inbox.try_emit(signal, v)
+    }
+}
+
+impl Drop for ExternalSource {
+    fn drop(&mut self) {
+        let (ref state, ref changed) = *self.inbox.state;
+        state.lock().unwrap().sources -= 1;
+        changed.notify_all();
+    }
+}
+
+/// Registers processes to be started by a runtime, from any thread, see
+/// `SequentialRuntime::register`.
+#[derive(Clone)]
+pub struct Registrar {
+    inbox: ExternalInbox,
+}
+
+impl Registrar {
+    /// Starts `process` at the first instant of the runtime, or at its next instant once it
+    /// started. The processes registered before the start are started in registration order.
+    pub fn register<P>(&self, process: P) where P: Process<Value = ()> {
+        self.inbox.push(move|runtime: &mut Runtime, ()| process.call(runtime, |_: &mut Runtime, ()| ()));
+    }
+}
+
+/// Number of instants a runtime keeps executing once it shuts down, by default.
+pub const SHUTDOWN_GRACE: usize = 10;
+
+/// Asks a runtime to shut down, from any thread or from a process, see
+/// `SequentialRuntime::on_shutdown`. The request is cleared when `execute` returns, so that the
+/// runtime executes again if it is given new processes.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    requested: Arc<AtomicBool>,
+}
+
+impl ShutdownHandle {
+    /// The runtime shuts down once the running instant completes, or before the next one.
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.requested.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Asks a runtime to return from `execute` at the end of the running instant, or before the next
+/// one, without shutting down: another runtime then resumes the program, see
+/// `SequentialRuntime::suspend`.
+#[derive(Clone)]
+pub struct SuspendHandle {
+    requested: Arc<AtomicBool>,
+    inbox: ExternalInbox,
+}
+
+impl SuspendHandle {
+    pub(crate) fn new(inbox: &ExternalInbox) -> Self {
+        SuspendHandle { requested: Arc::new(AtomicBool::new(false)), inbox: inbox.clone() }
+    }
+
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        // Wakes the runtime if it is waiting for its inbox.
+        self.inbox.push(|_: &mut Runtime, ()| ());
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.requested.store(false, Ordering::SeqCst);
+    }
+}
+
+/// What a runtime suspended between two instants has left to execute, which another runtime
+/// resumes, such as a parallel runtime taking over from a sequential one.
+///
+/// The resuming runtime takes over the inbox of the suspended one, so that the registrars, sources
+/// and handles of the inbox keep reaching the program, as well as its finalizers, timers and dt
+/// signal, and carries on with its instant indices. Its own configuration, such as its clock or
+/// panic handler, applies from then on.
+pub struct Suspended {
+    pub(crate) instant: usize,
+    pub(crate) current_instant: Vec<Box<Continuation<()>>>,
+    pub(crate) end_instant: Vec<Box<Continuation<()>>>,
+    /// Continuations queued for the instant after the next one by the end of the last instant.
+    pub(crate) next_instant: Vec<Box<Continuation<()>>>,
+    pub(crate) external: ExternalInbox,
+    pub(crate) timers: Timers,
+    pub(crate) finalizers: Vec<Box<Continuation<()>>>,
+    pub(crate) pacer: Option<Pacer>,
+}
+
+impl Suspended {
+    /// Whether the program has nothing left to execute, and would have completed.
+    pub fn is_empty(&self) -> bool {
+        self.current_instant.is_empty() && self.end_instant.is_empty() && self.next_instant.is_empty()
+            && self.timers.is_empty() && !self.external.is_awaited()
+    }
+}
+
+/// The processes started by a runtime when it shuts down, and how long they may run.
+pub(crate) struct Finalizers {
+    handle: ShutdownHandle,
+    processes: Mutex<Vec<Box<Continuation<()>>>>,
+    pub(crate) grace: usize,
+}
+
+impl Finalizers {
+    pub(crate) fn new() -> Self {
+        let handle = ShutdownHandle { requested: Arc::new(AtomicBool::new(false)) };
+        Finalizers { handle, processes: Mutex::new(Vec::new()), grace: SHUTDOWN_GRACE }
+    }
+
+    pub(crate) fn add<P>(&self, process: P) where P: Process<Value = ()> {
+        let start = move|runtime: &mut Runtime, ()| process.call(runtime, |_: &mut Runtime, ()| ());
+        self.processes.lock().unwrap().push(Box::new(start));
+    }
+
+    pub(crate) fn handle(&self) -> ShutdownHandle {
+        self.handle.clone()
+    }
+
+    /// The finalizers to start, which are only returned once.
+    pub(crate) fn take(&self) -> Vec<Box<Continuation<()>>> {
+        std::mem::replace(&mut *self.processes.lock().unwrap(), Vec::new())
+    }
+
+    /// The instant at which the finalizers started at `instant` are abandoned: they always execute
+    /// the instant starting them, even without grace, instead of staying queued.
+    pub(crate) fn deadline(&self, instant: usize) -> usize {
+        instant + std::cmp::max(self.grace, 1)
+    }
+
+    /// Adds the finalizers of a suspended runtime, before those added afterwards.
+    pub(crate) fn resume(&self, finalizers: Vec<Box<Continuation<()>>>) {
+        let mut processes = self.processes.lock().unwrap();
+        let added = std::mem::replace(&mut *processes, finalizers);
+        processes.extend(added);
+    }
+}
+
+/// A continuation waiting for a time, ordered so that the earliest timer is the greatest.
+struct Timer {
+    deadline: time::Instant,
+    /// Orders the timers with the same deadline by registration.
+    index: usize,
+    continuation: Box<Continuation<()>>,
+}
+
+impl PartialEq for Timer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for Timer {}
+
+impl PartialOrd for Timer {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timer {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        (other.deadline, other.index).cmp(&(self.deadline, self.index))
+    }
+}
+
+/// The continuations of a runtime waiting for a time on its clock, see `Runtime::on_deadline`.
+pub(crate) struct Timers {
+    timers: Mutex<BinaryHeap<Timer>>,
+    registered: AtomicUsize,
+}
+
+impl Timers {
+    pub(crate) fn new() -> Self {
+        Timers { timers: Mutex::new(BinaryHeap::new()), registered: AtomicUsize::new(0) }
+    }
+
+    pub(crate) fn add(&self, deadline: time::Instant, continuation: Box<Continuation<()>>) {
+        let index = self.registered.fetch_add(1, Ordering::SeqCst);
+        self.timers.lock().unwrap().push(Timer { deadline, index, continuation });
+    }
+
+    /// The timers, without those of the waiters cancelled since they were added, such as a
+    /// `sleep` losing a race.
+    fn live(&self) -> MutexGuard<BinaryHeap<Timer>> {
+        let mut timers = self.timers.lock().unwrap();
+        if timers.iter().any(|timer| is_cancelled(&*timer.continuation)) {
+            let all = std::mem::replace(&mut *timers, BinaryHeap::new()).into_vec();
+            *timers = all.into_iter().filter(|timer| !is_cancelled(&*timer.continuation)).collect();
+        }
+        timers
+    }
+
+    fn is_empty(&self) -> bool {
+        self.live().is_empty()
+    }
+
+    /// Removes every timer, for another runtime.
+    pub(crate) fn take(&self) -> Timers {
+        let timers = std::mem::replace(&mut *self.timers.lock().unwrap(), BinaryHeap::new());
+        Timers { timers: Mutex::new(timers), registered: AtomicUsize::new(self.registered.load(Ordering::SeqCst)) }
+    }
+
+    /// Whether a timer will expire: there is one, and either the clock runs or it already expired.
+    pub(crate) fn is_pending(&self, clock: &SharedClock) -> bool {
+        match self.live().peek() {
+            Some(timer) => clock.runs() || timer.deadline <= clock.now(),
+            None => false,
+        }
+    }
+
+    /// Removes the continuations whose deadline is at most `now`, by deadline.
+    pub(crate) fn expired(&self, now: time::Instant) -> Vec<Box<Continuation<()>>> {
+        let mut timers = self.timers.lock().unwrap();
+        let mut expired = Vec::new();
+        while timers.peek().map_or(false, |timer| timer.deadline <= now) {
+            expired.push(timers.pop().unwrap().continuation);
+        }
+        expired
+    }
+
+    /// The time left until the earliest deadline, which a runtime with nothing else to do waits
+    /// for, or `None` if no timer expires by itself.
+    ///
+    /// The time left is measured once on `clock` and waited for on the system clock, a clock which
+    /// does not follow the system time making the runtime check its timers again afterwards.
+    pub(crate) fn time_left(&self, clock: &SharedClock) -> Option<time::Duration> {
+        let deadline = match self.live().peek() {
+            Some(timer) => timer.deadline,
+            None => return None,
+        };
+        let now = clock.now();
+        if deadline <= now {
+            Some(time::Duration::from_secs(0))
+        } else if clock.runs() {
+            Some(deadline - now)
+        } else {
+            None
+        }
+    }
+}
+
+/// The position of a continuation in the execution of a deterministic runtime, which orders the
+/// emissions on signals and the continuations of an instant independently of the scheduling.
+///
+/// A deterministic runtime executes an instant in waves, the continuations queued during a wave
+/// forming the next one, and numbers the continuations of a wave in the order of the keys they
+/// were queued with. A continuation derives the keys of the continuations it queues and of the
+/// values it emits from its own, in the order it does so. The waiters of a signal are resumed
+/// with the key they registered with, rather than the one of the emission waking them, and the
+/// continuation of a join with the greatest key of its branches.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OrderKey(Vec<usize>);
+
+/// Keys of the end of instant continuations, after those of the waves.
+const END_OF_INSTANT_WAVE: usize = usize::max_value();
+
+thread_local! {
+    /// The key of the continuation executed by the thread in a deterministic runtime, and the
+    /// number of keys derived from it so far.
+    static ORDER: RefCell<Option<(OrderKey, usize)>> = RefCell::new(None);
+}
+
+/// Restores the key of the continuation interrupted by `with_order_key`.
+struct RestoreOrder(Option<(OrderKey, usize)>);
+
+impl Drop for RestoreOrder {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        let _ = ORDER.try_with(|order| *order.borrow_mut() = previous);
+    }
+}
+
+/// Executes `f` as the continuation of key `key`.
+pub(crate) fn with_order_key<F, R>(key: OrderKey, f: F) -> R where F: FnOnce() -> R {
+    let previous = ORDER.with(|order| order.borrow_mut().replace((key, 0)));
+    let _restore = RestoreOrder(previous);
+    f()
+}
+
+/// The next key derived from the one of the running continuation, `None` outside of a
+/// deterministic runtime.
+pub(crate) fn next_order_key() -> Option<OrderKey> {
+    ORDER.with(|order| order.borrow_mut().as_mut().map(|&mut (ref key, ref mut derived)| {
+        let mut child = key.0.clone();
+        child.push(*derived);
+        *derived += 1;
+        OrderKey(child)
+    }))
+}
+
+/// A continuation carrying its key, see `OrderKey`.
+struct Ordered<V> where V: Send + Sync + 'static {
+    key: OrderKey,
+    continuation: Box<Continuation<V>>,
+}
+
+impl<V> Continuation<V> for Ordered<V> where V: Send + Sync + 'static {
+    fn call(self, runtime: &mut Runtime, value: V) {
+        self.continuation.call_box(runtime, value);
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V) {
+        (*self).call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        self.continuation.waiter_token()
+    }
+
+    fn order_key(&self) -> Option<&OrderKey> {
+        Some(&self.key)
+    }
+
+    fn priority(&self) -> Priority {
+        self.continuation.priority()
+    }
+}
+
+/// `c` ordered by `key`, or `c` itself if there is no key.
+pub(crate) fn with_key<V>(key: Option<OrderKey>, c: Box<Continuation<V>>) -> Box<Continuation<V>>
+    where V: Send + Sync + 'static {
+    match key {
+        Some(key) => Box::new(Ordered { key, continuation: c }),
+        None => c,
+    }
+}
+
+/// `c` ordered by the next key of the running continuation, unless it already has one. Returns
+/// `c` itself outside of a deterministic runtime.
+pub(crate) fn ordered<V>(c: Box<Continuation<V>>) -> Box<Continuation<V>> where V: Send + Sync + 'static {
+    if c.order_key().is_some() {
+        return c;
+    }
+    with_key(next_order_key(), c)
+}
+
+/// Resumes the waiter `c` with `value` in the current instant, with the key and the priority it
+/// registered with.
+pub(crate) fn wake<V>(runtime: &mut Runtime, c: Box<Continuation<V>>, value: V) where V: Send + Sync + 'static {
+    let priority = c.priority();
+    let woken = match c.order_key().cloned() {
+        Some(key) => with_key(Some(key), Box::new(move|runtime: &mut Runtime, ()| c.call_box(runtime, value))),
+        None => recycled_box(move|runtime: &mut Runtime, ()| c.call_box(runtime, value)),
+    };
+    runtime.on_current_instant(with_priority(priority, woken));
+}
+
+/// Calls the waiter `c` with `value` right away, as the continuation of the key it registered
+/// with.
+pub(crate) fn call_ordered<V>(runtime: &mut Runtime, c: Box<Continuation<V>>, value: V) where V: Send + Sync + 'static {
+    match c.order_key().cloned() {
+        Some(key) => with_order_key(key, || c.call_box(runtime, value)),
+        None => c.call_box(runtime, value),
+    }
+}
+
+/// Sorts the continuations of a wave or of the end of an instant by key, the continuations
+/// without a key, queued from outside of the program, first and in queuing order.
+pub(crate) fn sort_by_order_key<V>(continuations: &mut Vec<Box<Continuation<V>>>) where V: Send + Sync + 'static {
+    continuations.sort_by(|c1, c2| c1.order_key().cmp(&c2.order_key()));
+}
+
+/// The key of the `index`-th continuation of the `wave`-th wave of the instant `instant`.
+pub(crate) fn wave_key(instant: usize, wave: usize, index: usize) -> OrderKey {
+    OrderKey(vec![instant, wave, index])
+}
+
+/// Executes the end of instant continuations of a deterministic runtime one at a time, by key,
+/// each as the continuation of its own key.
+pub(crate) fn run_end_of_instant(runtime: &mut Runtime, mut continuations: Vec<Box<Continuation<()>>>, handler: &Option<PanicHandler>) {
+    sort_by_order_key(&mut continuations);
+    let instant = runtime.instant_index();
+    for (index, c) in continuations.into_iter().enumerate() {
+        let key = c.order_key().cloned().unwrap_or_else(|| wave_key(instant, END_OF_INSTANT_WAVE, index));
+        with_order_key(key, || call_handling_panics(c, runtime, handler));
+    }
+}
+
+/// The priority of a continuation: the runtimes execute the continuations of an instant with a
+/// greater priority before the others, see `ValueSignal::with_priority`.
+///
+/// A signal resumes its waiters with the greatest of its priority and theirs, and the signals
+/// forwarding the values of others, such as the outputs of a `Graph`, inherit the greatest
+/// priority of their sources. A value emitted on a high priority input thus crosses each
+/// forwarding process ahead of the low priority continuations of the instant. Priorities only
+/// order the continuations within an instant, and deterministic runtimes, which order them by
+/// key, ignore them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Priority(pub u8);
+
+impl Priority {
+    pub const NORMAL: Priority = Priority(0);
+}
+
+/// A continuation carrying a priority, see `Priority`.
+pub(crate) struct Prioritized<V> where V: Send + Sync + 'static {
+    priority: Priority,
+    continuation: Box<Continuation<V>>,
+}
+
+impl<V> Continuation<V> for Prioritized<V> where V: Send + Sync + 'static {
+    fn call(self, runtime: &mut Runtime, value: V) {
+        self.continuation.call_box(runtime, value);
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: V) {
+        (*self).call(runtime, value);
+    }
+
+    fn waiter_token(&self) -> Option<&WaiterToken> {
+        self.continuation.waiter_token()
+    }
+
+    fn order_key(&self) -> Option<&OrderKey> {
+        self.continuation.order_key()
+    }
+
+    fn priority(&self) -> Priority {
+        self.priority
+    }
+}
+
+/// `c` executed with `priority`, or with its own priority if it is greater.
+pub(crate) fn prioritized<V>(priority: Priority, c: Box<Continuation<V>>) -> Prioritized<V> where V: Send + Sync + 'static {
+    let priority = std::cmp::max(priority, c.priority());
+    Prioritized { priority, continuation: c }
+}
+
+/// Like `prioritized`, but returns `c` itself if it already has at least `priority`.
+pub(crate) fn with_priority<V>(priority: Priority, c: Box<Continuation<V>>) -> Box<Continuation<V>> where V: Send + Sync + 'static {
+    if priority <= c.priority() {
+        c
+    } else {
+        Box::new(prioritized(priority, c))
+    }
+}
+
+/// The external inbox of the runtime executing the process, to be handed to other threads.
+pub struct GetExternalInbox;
+
+impl Process for GetExternalInbox {
+    type Value = ExternalInbox;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<ExternalInbox> {
+        let inbox = runtime.external_inbox();
+        next.call(runtime, inbox);
+    }
+}
+
+pub fn external_inbox() -> GetExternalInbox {
+    GetExternalInbox
 }
\ No newline at end of file
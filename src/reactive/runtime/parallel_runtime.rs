@@ -1,8 +1,12 @@
 extern crate crossbeam;
+#[cfg(feature = "affinity")]
+extern crate core_affinity;
 
 use super::*;
 use self::crossbeam::sync::MsQueue;
-use self::std::sync::Condvar;
+use self::std::panic::{self, AssertUnwindSafe};
+use self::std::sync::{Barrier, Condvar};
+use self::std::sync::atomic::{AtomicUsize, Ordering};
 
 //  ____            ____              _   _
 // |  _ \ __ _ _ __|  _ \ _   _ _ __ | |_(_)_ __ ___   ___
@@ -10,31 +14,53 @@ use self::std::sync::Condvar;
 // |  __/ (_| | |  |  _ <| |_| | | | | |_| | | | | | |  __/
 // |_|   \__,_|_|  |_| \_\\__,_|_| |_|\__|_|_| |_| |_|\___|
 
+thread_local!(static WORKER: Cell<Option<usize>> = Cell::new(None));
+
+/// The index of the worker of a `ParallelRuntime` executing the current thread, if any.
+pub fn current_worker() -> Option<usize> {
+    WORKER.with(|worker| worker.get())
+}
+
 pub struct TodoQueue {
-    queue: MsQueue<Box<Continuation<()>>>,
+    /// The continuations to execute by each partition of the workers, `None` stopping the worker
+    /// taking it, see `RuntimeBuilder::partition_by_socket`.
+    queues: Vec<MsQueue<Option<Box<Continuation<()>>>>>,
     count: Arc<Mutex<i32>>,
     notify: Condvar,
 }
 
 impl TodoQueue {
-    fn new() -> Self {
+    fn new(partitions: usize) -> Self {
         TodoQueue {
-            queue: MsQueue::new(),
+            queues: (0..partitions).map(|_| MsQueue::new()).collect(),
             count: Arc::new(Mutex::new(0)),
             notify: Condvar::new()
         }
     }
 
-    fn push(&self, elem: Box<Continuation<()>>) {
+    /// Gives `elem` to the workers of `partition`.
+    fn push(&self, partition: usize, elem: Box<Continuation<()>>) {
         {
             let mut ct = self.count.lock().unwrap();
             *ct = *ct + 1;
         }
-        self.queue.push(elem);
+        self.queues[partition].push(Some(elem));
+    }
+
+    /// Gives the continuations to the partitions in turn.
+    fn spread(&self, continuations: Vec<Box<Continuation<()>>>) {
+        for (i, c) in continuations.into_iter().enumerate() {
+            self.push(i % self.queues.len(), c);
+        }
+    }
+
+    fn pop(&self, partition: usize) -> Option<Box<Continuation<()>>> {
+        self.queues[partition].pop()
     }
 
-    fn pop(&self) -> Box<Continuation<()>> {
-        self.queue.pop()
+    /// Stops a worker of `partition`, once it completed the continuations pushed before.
+    fn push_stop(&self, partition: usize) {
+        self.queues[partition].push(None);
     }
 
     fn done(&self) {
@@ -51,36 +77,317 @@ impl TodoQueue {
 }
 
 pub struct ParallelRuntime {
-    current_instant: MsQueue<Box<Continuation<()>>>,
+    current_instant: Partitioned,
     end_instant: MsQueue<Box<Continuation<()>>>,
-    next_current_instant: MsQueue<Box<Continuation<()>>>,
+    next_current_instant: Partitioned,
     todo: TodoQueue,
     worker_count: usize,
+    instant: AtomicUsize,
+    deterministic: bool,
+    /// Cores the workers are pinned to, none if empty.
+    cores: Vec<usize>,
+    /// The partition of each worker, see `RuntimeBuilder::partition_by_socket`.
+    partitions: Vec<usize>,
+    external: ExternalInbox,
+    chaos: Option<Chaos>,
+    tracer: Option<Tracer>,
+    panic_handler: Option<PanicHandler>,
+    memory: Option<MemoryAccounting>,
+    clock: SharedClock,
+    boundary: InstantBoundary,
+    finalizers: Finalizers,
+    suspend: SuspendHandle,
+    timers: Timers,
+    pacer: Mutex<Option<Pacer>>,
+    /// Identifies the `WorkerLocal`s of the runtime.
+    id: usize,
+    /// The payload of the first panic a worker did not recover from, propagated by `execute`.
+    panicked: Mutex<Option<Box<Any + Send>>>,
 }
 
-impl ParallelRuntime {
+/// The perturbations of the scheduling of a runtime in chaos mode, see `RuntimeBuilder::chaos`.
+struct Chaos {
+    seed: u64,
+    /// Shuffles the continuations moved from a queue to another.
+    rng: Mutex<Rng>,
+}
+
+/// Continuations queued by the workers of each partition, see `RuntimeBuilder::partition_by_socket`.
+struct Partitioned {
+    queues: Vec<MsQueue<Box<Continuation<()>>>>,
+}
+
+impl Partitioned {
+    fn new(partitions: usize) -> Self {
+        Partitioned { queues: (0..partitions).map(|_| MsQueue::new()).collect() }
+    }
+
+    fn push(&self, partition: usize, c: Box<Continuation<()>>) {
+        self.queues[partition].push(c);
+    }
+
+    /// Gives the continuations to the partitions in turn.
+    fn spread(&self, continuations: Vec<Box<Continuation<()>>>) {
+        for (i, c) in continuations.into_iter().enumerate() {
+            self.push(i % self.queues.len(), c);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.queues.iter().all(|queue| queue.is_empty())
+    }
+}
+
+/// Longest delay injected before a continuation in chaos mode, in microseconds.
+const MAX_CHAOS_DELAY_US: i32 = 200;
+
+/// Configures a `ParallelRuntime`.
+pub struct RuntimeBuilder {
+    worker_count: usize,
+    deterministic: bool,
+    cores: Vec<usize>,
+    /// The cores of each socket, if the queues are partitioned by socket.
+    sockets: Vec<Vec<usize>>,
+    chaos_seed: Option<u64>,
+    tracer: Option<Tracer>,
+    panic_handler: Option<PanicHandler>,
+    memory: Option<MemoryAccounting>,
+    clock: SharedClock,
+    shutdown_grace: usize,
+    inbox_capacity: Option<usize>,
+}
+
+impl RuntimeBuilder {
     pub fn new(worker_count: usize) -> Self {
+        RuntimeBuilder {
+            worker_count,
+            deterministic: false,
+            cores: Vec::new(),
+            sockets: Vec::new(),
+            chaos_seed: None,
+            tracer: None,
+            panic_handler: None,
+            memory: None,
+            clock: Arc::new(SystemClock),
+            shutdown_grace: SHUTDOWN_GRACE,
+            inbox_capacity: None,
+        }
+    }
+
+    /// Builds a runtime resuming the program of a suspended runtime at its next instant, see
+    /// `Suspended`.
+    pub fn resume(self, suspended: Suspended) -> ParallelRuntime {
+        let runtime = self.build_with(suspended.external, suspended.timers, suspended.instant);
+        runtime.current_instant.spread(suspended.current_instant);
+        for c in suspended.end_instant {
+            runtime.end_instant.push(c);
+        }
+        runtime.next_current_instant.spread(suspended.next_instant);
+        runtime.finalizers.resume(suspended.finalizers);
+        *runtime.pacer.lock().unwrap() = suspended.pacer;
+        runtime
+    }
+
+    /// Gathers signal values in the order of the emissions in the program rather than in
+    /// execution order, see `OrderKey`, and runs the end of instant continuations one at a time.
+    /// Processes whose results only depend on signal values then behave the same at every run, and
+    /// as with `SequentialRuntime::new_deterministic`.
+    ///
+    /// The continuations of an instant are executed in waves, each one waiting for the previous
+    /// one to complete. Only `ValueSignal`, `CopyValueSignal` and `PureSignal` resume their waiters
+    /// in a deterministic order: the values of the other kinds of signals, and the branch
+    /// completing first in a `race`, still depend on the scheduling.
+    pub fn deterministic(mut self) -> Self {
+        self.deterministic = true;
+        self
+    }
+
+    /// Pins the `i`-th worker thread to the core `cores[i % cores.len()]`.
+    ///
+    /// Giving only the cores of a single socket keeps the continuations, and the signals they
+    /// access, on that socket. See `partition_by_socket` to use several sockets.
+    #[cfg(feature = "affinity")]
+    pub fn pin_workers(mut self, cores: Vec<usize>) -> Self {
+        self.cores = cores;
+        self
+    }
+
+    /// Pins the workers to the cores of `sockets`, which gives the cores of each socket, and
+    /// partitions the queues of the runtime by socket, instead of `pin_workers`.
+    ///
+    /// The `i`-th worker is pinned to a core of the socket `i % sockets.len()`, its cores being
+    /// used in turn. The continuations a worker queues are then executed by the workers of its
+    /// socket, rather than by whichever worker is free, so that a process and the signals it
+    /// emits stay on one socket from instant to instant. The other continuations, such as those
+    /// of the inbox, of the timers and of the ends of the instants, are dealt to the sockets in
+    /// turn. A socket does not take the continuations of another one even when its workers are
+    /// idle: the processes should be spread across the sockets, which needs a worker each.
+    #[cfg(feature = "affinity")]
+    pub fn partition_by_socket(mut self, sockets: Vec<Vec<usize>>) -> Self {
+        assert!(!sockets.is_empty() && sockets.iter().all(|cores| !cores.is_empty()), "each socket needs at least one core");
+        assert!(self.worker_count >= sockets.len(), "{} workers cannot cover {} sockets", self.worker_count, sockets.len());
+        self.cores = (0..self.worker_count).map(|i| {
+            let cores = &sockets[i % sockets.len()];
+            cores[(i / sockets.len()) % cores.len()]
+        }).collect();
+        self.sockets = sockets;
+        self
+    }
+
+    /// Stresses the program with unusual interleavings: the workers wait a random delay before
+    /// some continuations, and the continuations moved between the queues of the runtime are
+    /// shuffled. The random choices derive from `seed`, though the interleavings also depend on
+    /// the timing of the threads. Meant for tests, see `assert_chaos_invariant`.
+    pub fn chaos(mut self, seed: u64) -> Self {
+        self.chaos_seed = Some(seed);
+        self
+    }
+
+    /// Records the continuations executed by each worker and the instants in `tracer`.
+    pub fn trace(mut self, tracer: &Tracer) -> Self {
+        self.tracer = Some(tracer.clone());
+        self
+    }
+
+    /// Installs `handler`, called with the name of the process and the payload of the panic when
+    /// a continuation panics, see `SequentialRuntime::on_process_panic`.
+    pub fn on_process_panic<F>(mut self, handler: F) -> Self where F: Fn(&str, &(Any + Send)) -> PanicPolicy + Send + Sync + 'static {
+        self.panic_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Reports the memory used at the end of every instant to `accounting`.
+    pub fn account_memory(mut self, accounting: &MemoryAccounting) -> Self {
+        self.memory = Some(accounting.clone());
+        self
+    }
+
+    /// Gives the time of `clock` to the processes instead of the time of the system.
+    pub fn clock<C>(mut self, clock: C) -> Self where C: Clock + 'static {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Executes at most `instants` instants once the runtime shuts down, see
+    /// `ParallelRuntime::on_shutdown`.
+    pub fn shutdown_grace(mut self, instants: usize) -> Self {
+        self.shutdown_grace = instants;
+        self
+    }
+
+    /// Bounds the inbox of the runtime, see `SequentialRuntime::inbox_capacity`.
+    pub fn inbox_capacity(mut self, continuations: usize) -> Self {
+        self.inbox_capacity = Some(continuations);
+        self
+    }
+
+    pub fn build(self) -> ParallelRuntime {
+        self.build_with(ExternalInbox::new(), Timers::new(), 0)
+    }
+
+    fn build_with(self, external: ExternalInbox, timers: Timers, instant: usize) -> ParallelRuntime {
+        let mut finalizers = Finalizers::new();
+        finalizers.grace = self.shutdown_grace;
+        if let Some(continuations) = self.inbox_capacity {
+            external.set_capacity(continuations);
+        }
+        let suspend = SuspendHandle::new(&external);
+        let partition_count = std::cmp::max(self.sockets.len(), 1);
         ParallelRuntime {
-            current_instant: MsQueue::new(),
+            current_instant: Partitioned::new(partition_count),
             end_instant: MsQueue::new(),
-            next_current_instant: MsQueue::new(),
-            todo: TodoQueue::new(),
-            worker_count,
+            next_current_instant: Partitioned::new(partition_count),
+            todo: TodoQueue::new(partition_count),
+            worker_count: self.worker_count,
+            instant: AtomicUsize::new(instant),
+            deterministic: self.deterministic,
+            cores: self.cores,
+            partitions: (0..self.worker_count).map(|i| i % partition_count).collect(),
+            external,
+            chaos: self.chaos_seed.map(|seed| Chaos { seed, rng: Mutex::new(Rng::new(seed)) }),
+            tracer: self.tracer,
+            panic_handler: self.panic_handler,
+            memory: self.memory,
+            clock: self.clock,
+            boundary: InstantBoundary::new(),
+            finalizers,
+            suspend,
+            timers,
+            pacer: Mutex::new(None),
+            id: runtime_id(),
+            panicked: Mutex::new(None),
         }
     }
 }
 
+/// The identifiers of the cores worker threads can be pinned to.
+#[cfg(feature = "affinity")]
+pub fn available_cores() -> Vec<usize> {
+    core_affinity::get_core_ids().unwrap_or(Vec::new()).into_iter().map(|core| core.id).collect()
+}
+
+#[cfg(feature = "affinity")]
+fn pin_current_thread(core: usize) {
+    core_affinity::set_for_current(core_affinity::CoreId { id: core });
+}
+
+#[cfg(not(feature = "affinity"))]
+fn pin_current_thread(_: usize) {}
+
+impl ParallelRuntime {
+    pub fn new(worker_count: usize) -> Self {
+        RuntimeBuilder::new(worker_count).build()
+    }
+
+    /// A deterministic runtime, see `RuntimeBuilder::deterministic`.
+    pub fn new_deterministic(worker_count: usize) -> Self {
+        RuntimeBuilder::new(worker_count).deterministic().build()
+    }
+}
+
 impl ParallelRuntime {
     pub fn start(self) -> Arc<Self> {
         let mut workers = Vec::with_capacity(self.worker_count);
         let runtime = Arc::new(self);
-        for _ in 0..runtime.worker_count {
+        if let Some(ref tracer) = runtime.tracer {
+            for i in 0..runtime.worker_count {
+                tracer.name_lane(i, &format!("worker {}", i));
+            }
+            tracer.name_lane(runtime.worker_count, "instants");
+        }
+        for i in 0..runtime.worker_count {
             let runtime = runtime.clone();
             let worker = move || {
+                if !runtime.cores.is_empty() {
+                    pin_current_thread(runtime.cores[i % runtime.cores.len()]);
+                }
+                WORKER.with(|worker| worker.set(Some(i)));
+                let _runtime = start_execution(runtime.id);
                 let mut local_runtime = LocalParallelRuntime { runtime: runtime.clone() };
-                loop {
-                    let c = runtime.todo.pop();
-                    c.call_box(&mut local_runtime, ());
+                let handler = runtime.panic_handler.clone();
+                let mut delays = runtime.chaos.as_ref().map(|chaos| Rng::new(chaos.seed.wrapping_add(i as u64 + 1)));
+                while let Some(c) = runtime.todo.pop(runtime.partitions[i]) {
+                    if let Some(ref mut rng) = delays {
+                        if rng.range(0, 4) == 0 {
+                            thread::sleep(time::Duration::new(0, rng.range(0, MAX_CHAOS_DELAY_US) as u32 * 1000));
+                        }
+                    }
+                    let called = panic::catch_unwind(AssertUnwindSafe(|| match runtime.tracer {
+                        Some(ref tracer) => {
+                            let start = time::Instant::now();
+                            call_handling_panics(c, &mut local_runtime, &handler);
+                            let name = take_span_name().unwrap_or_else(|| String::from("continuation"));
+                            tracer.span(&name, i, start, time::Instant::now());
+                        },
+                        None => call_handling_panics(c, &mut local_runtime, &handler),
+                    }));
+                    if let Err(payload) = called {
+                        // The instant completes without the continuation, then `execute` aborts.
+                        let mut panicked = runtime.panicked.lock().unwrap();
+                        if panicked.is_none() {
+                            *panicked = Some(payload);
+                        }
+                    }
                     runtime.todo.done();
                 }
             };
@@ -90,23 +397,86 @@ impl ParallelRuntime {
     }
 
     pub fn execute(&self) {
-        while self.instant() {}
+        self.reset_worker_locals();
+        let shutdown = self.finalizers.handle();
+        while !shutdown.is_requested() && !self.suspend.is_requested() && self.instant() {
+            self.propagate_panic();
+            if self.current_instant.is_empty() && self.end_instant.is_empty() {
+                self.external.wait(self.timers.time_left(&self.clock));
+            }
+        }
+        self.propagate_panic();
+        if self.suspend.is_requested() {
+            self.suspend.clear();
+            return;
+        }
+        let finalizers = self.finalizers.take();
+        if !finalizers.is_empty() {
+            self.current_instant.spread(finalizers);
+            let deadline = self.finalizers.deadline(self.instant.load(Ordering::SeqCst));
+            while self.instant.load(Ordering::SeqCst) < deadline && self.instant() {
+                self.propagate_panic();
+            }
+            self.propagate_panic();
+        }
+        shutdown.clear();
+    }
+
+    /// Drops the copies of the `WorkerLocal`s of the workers, before the first instant of an
+    /// execution rather than at each continuation: each worker takes one of the continuations
+    /// pushed, as they wait for each other.
+    fn reset_worker_locals(&self) {
+        let workers = Arc::new(Barrier::new(self.worker_count));
+        let id = self.id;
+        for &partition in self.partitions.iter() {
+            let workers = workers.clone();
+            self.todo.push(partition, Box::new(move|_: &mut Runtime, ()| {
+                start_execution(id);
+                workers.wait();
+            }));
+        }
+        self.wait_todo();
+    }
+
+    /// Resumes, on the thread calling `execute`, the panic of a continuation which aborted the
+    /// execution.
+    fn propagate_panic(&self) {
+        let payload = self.panicked.lock().unwrap().take();
+        if let Some(payload) = payload {
+            panic::resume_unwind(payload);
+        }
     }
 
     fn instant(&self) -> bool {
         assert!(!self.todo.is_active());
-        while !self.current_instant.is_empty() {
-            self.todo.push(self.current_instant.pop());
+        self.boundary.start_instant();
+        let start = time::Instant::now();
+        let index = self.instant.load(Ordering::SeqCst);
+        if let Some(ref tracer) = self.tracer {
+            tracer.marker(&format!("instant {}", index), self.worker_count);
         }
-        {
+        let now = self.clock.now();
+        if let Some(c) = self.pacer.lock().unwrap().as_mut().map(|pacer| pacer.start_instant(now)) {
+            self.on_current_instant(c);
+        }
+        if self.deterministic {
+            self.execute_waves(index);
+        } else {
+            self.todo.spread(self.external.drain());
+            self.todo.spread(self.timers.expired(self.clock.now()));
+            self.wait_todo();
+            for (partition, c) in self.take_partitions(&self.current_instant) {
+                self.todo.push(partition, c);
+            }
             let mut work_remaining;
             {
                 let ct = self.todo.count.lock().unwrap();
-                work_remaining = *ct > 0;
+                // The continuations pushed above may already have completed, queueing others.
+                work_remaining = *ct > 0 || !self.current_instant.is_empty();
             }
             while work_remaining {
-                while !self.current_instant.is_empty() {
-                    self.todo.push(self.current_instant.pop());
+                for (partition, c) in self.take_partitions(&self.current_instant) {
+                    self.todo.push(partition, c);
                 }
                 let mut ct = self.todo.count.lock().unwrap();
                 if *ct > 0 {
@@ -115,34 +485,310 @@ impl ParallelRuntime {
                 work_remaining = *ct > 0 || !self.current_instant.is_empty();
             }
         }
-        while !self.end_instant.is_empty() {
-            self.todo.push(self.end_instant.pop());
+        if self.deterministic {
+            let end_instant = self.take_all(&self.end_instant);
+            if !end_instant.is_empty() {
+                let handler = self.panic_handler.clone();
+                self.todo.push(0, Box::new(move|runtime: &mut Runtime, ()| run_end_of_instant(runtime, end_instant, &handler)));
+            }
+        } else {
+            self.todo.spread(self.take_all(&self.end_instant));
         }
-        while !self.next_current_instant.is_empty() {
-            self.current_instant.push(self.next_current_instant.pop());
+        for (partition, c) in self.take_partitions(&self.next_current_instant) {
+            self.current_instant.push(partition, c);
         }
-        {
-            let mut ct = self.todo.count.lock().unwrap();
-            while *ct > 0 {
-                ct = self.todo.notify.wait(ct).unwrap();
+        self.wait_todo();
+        if let Some(ref memory) = self.memory {
+            memory.record(index, self.queued_memory());
+        }
+        if let Some(ref tracer) = self.tracer {
+            tracer.span(&format!("instant {}", index), self.worker_count, start, time::Instant::now());
+        }
+        self.instant.fetch_add(1, Ordering::SeqCst);
+        self.boundary.end_instant();
+        !(self.current_instant.is_empty() && self.end_instant.is_empty() && self.next_current_instant.is_empty()
+            && !self.timers.is_pending(&self.clock) && !self.external.is_awaited())
+    }
+
+    /// Executes the continuations of the instant in waves, by key, see `OrderKey`. The
+    /// continuations of a wave are executed in parallel, the next wave starting once they all
+    /// completed.
+    fn execute_waves(&self, instant: usize) {
+        let mut wave = self.external.drain();
+        wave.extend(self.timers.expired(self.clock.now()));
+        wave.extend(self.take_partitions(&self.current_instant).into_iter().map(|(_, c)| c));
+        let mut index = 0;
+        while !wave.is_empty() {
+            sort_by_order_key(&mut wave);
+            let mut keyed: Vec<Box<Continuation<()>>> = Vec::with_capacity(wave.len());
+            for (i, c) in wave.into_iter().enumerate() {
+                let key = wave_key(instant, index, i);
+                keyed.push(Box::new(move|runtime: &mut Runtime, ()| with_order_key(key, || c.call_box(runtime, ()))));
+            }
+            self.todo.spread(keyed);
+            self.wait_todo();
+            wave = self.take_partitions(&self.current_instant).into_iter().map(|(_, c)| c).collect();
+            index += 1;
+        }
+    }
+
+    /// Blocks until the workers completed every continuation pushed so far.
+    fn wait_todo(&self) {
+        let mut ct = self.todo.count.lock().unwrap();
+        while *ct > 0 {
+            ct = self.todo.notify.wait(ct).unwrap();
+        }
+    }
+
+    /// The memory used by the continuations queued for the next instants, which are taken out of
+    /// their queues to be measured.
+    fn queued_memory(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage::default();
+        let partitioned = self.current_instant.queues.iter().chain(self.next_current_instant.queues.iter());
+        for queue in partitioned.chain(Some(&self.end_instant)) {
+            let continuations = self.take_all(queue);
+            usage.merge(continuations_usage(continuations.iter()));
+            for c in continuations {
+                queue.push(c);
             }
         }
-        !(self.current_instant.is_empty() && self.end_instant.is_empty() && self.next_current_instant.is_empty())
+        usage
+    }
+
+    /// Empties `queue`, the continuations with a greater `Priority` first, then in order unless
+    /// in chaos mode. The workers thus start the continuations of a batch by priority.
+    fn take_all(&self, queue: &MsQueue<Box<Continuation<()>>>) -> Vec<Box<Continuation<()>>> {
+        let mut continuations = Vec::new();
+        while !queue.is_empty() {
+            continuations.push(queue.pop());
+        }
+        if let Some(ref chaos) = self.chaos {
+            let mut rng = chaos.rng.lock().unwrap();
+            for i in (1..continuations.len()).rev() {
+                let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+                continuations.swap(i, j);
+            }
+        }
+        continuations.sort_by(|c1, c2| c2.priority().cmp(&c1.priority()));
+        continuations
+    }
+
+    /// Empties the queues of every partition, see `take_all`, with the partition of each
+    /// continuation.
+    fn take_partitions(&self, queues: &Partitioned) -> Vec<(usize, Box<Continuation<()>>)> {
+        let mut continuations = Vec::new();
+        for (partition, queue) in queues.queues.iter().enumerate() {
+            continuations.extend(self.take_all(queue).into_iter().map(|c| (partition, c)));
+        }
+        continuations
+    }
+
+    /// The partition of the worker executing the current thread, the first one outside of the
+    /// workers.
+    fn partition(&self) -> usize {
+        current_worker().and_then(|worker| self.partitions.get(worker).cloned()).unwrap_or(0)
+    }
+
+    /// A handle through which other threads wait for the ends of the instants, see
+    /// `InstantBoundary`.
+    pub fn instant_boundary(&self) -> InstantBoundary {
+        self.boundary.clone()
+    }
+
+    /// Blocks until no instant is running, see `InstantBoundary::wait_instant_end`.
+    pub fn wait_instant_end(&self) -> InstantEnd {
+        self.boundary.wait_instant_end()
+    }
+
+    /// Blocks until no instant is running after the first `completed` ones, see
+    /// `InstantBoundary::wait_instant_end_after`.
+    pub fn wait_instant_end_after(&self, completed: usize) -> InstantEnd {
+        self.boundary.wait_instant_end_after(completed)
+    }
+
+    /// Holds back the next instant if none is running, see `InstantBoundary::try_instant_end`.
+    pub fn try_instant_end(&self) -> Option<InstantEnd> {
+        self.boundary.try_instant_end()
+    }
+
+    /// The inbox through which other threads act on the program, see `ExternalInbox`.
+    pub fn external_inbox(&self) -> ExternalInbox {
+        self.external.clone()
+    }
+
+    /// Starts `process` at the first instant, see `SequentialRuntime::register`.
+    pub fn register<P>(&self, process: P) where P: Process<Value = ()> {
+        self.registrar().register(process);
+    }
+
+    /// A handle through which other threads register processes before `start`, see `Registrar`.
+    pub fn registrar(&self) -> Registrar {
+        Registrar { inbox: self.external.clone() }
+    }
+
+    /// Starts the finalizer `process` when the runtime shuts down, see
+    /// `SequentialRuntime::on_shutdown`.
+    pub fn on_shutdown<P>(&self, process: P) where P: Process<Value = ()> {
+        self.finalizers.add(process);
+    }
+
+    /// A handle through which processes and other threads request the runtime to shut down.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.finalizers.handle()
+    }
+
+    /// A signal the runtime emits at the start of every instant with its logical duration, see
+    /// `SequentialRuntime::dt_signal`.
+    pub fn dt_signal(&self, pacing: Pacing) -> ValueSignal<time::Duration, time::Duration> {
+        let pacer = Pacer::new(pacing);
+        let signal = pacer.signal();
+        *self.pacer.lock().unwrap() = Some(pacer);
+        signal
+    }
+
+    /// A handle through which processes and other threads make `execute` return between two
+    /// instants, see `SequentialRuntime::suspend_handle`.
+    pub fn suspend_handle(&self) -> SuspendHandle {
+        self.suspend.clone()
+    }
+
+    /// Takes what the program has left to execute once `execute` returned, for another runtime to
+    /// resume it, and stops the workers: the runtime cannot execute anymore.
+    pub fn suspend(&self) -> Suspended {
+        self.stop();
+        Suspended {
+            instant: self.instant.load(Ordering::SeqCst),
+            current_instant: self.take_partitions(&self.current_instant).into_iter().map(|(_, c)| c).collect(),
+            end_instant: self.take_all(&self.end_instant),
+            next_instant: self.take_partitions(&self.next_current_instant).into_iter().map(|(_, c)| c).collect(),
+            external: self.external.clone(),
+            timers: self.timers.take(),
+            finalizers: self.finalizers.take(),
+            pacer: self.pacer.lock().unwrap().take(),
+        }
+    }
+
+    /// Stops the workers once `execute` returned, for a runtime which will not execute anymore.
+    pub fn stop(&self) {
+        for &partition in self.partitions.iter() {
+            self.todo.push_stop(partition);
+        }
     }
 
     pub fn on_current_instant(&self, c: Box<Continuation<()>>) {
-        self.current_instant.push(c);
+        let c = if self.deterministic { ordered(c) } else { c };
+        self.current_instant.push(self.partition(), c);
     }
 
     fn on_next_instant(&self, c: Box<Continuation<()>>) {
-        self.next_current_instant.push(c);
+        let c = if self.deterministic { ordered(c) } else { c };
+        self.next_current_instant.push(self.partition(), c);
     }
 
     fn on_end_of_instant(&self, c: Box<Continuation<()>>) {
+        let c = if self.deterministic { ordered(c) } else { c };
         self.end_instant.push(c);
     }
 }
 
+struct BoundaryState {
+    completed: usize,
+    /// Whether an instant is running, or about to start once the readers are done.
+    running: bool,
+    /// The number of `InstantEnd`s alive.
+    readers: usize,
+}
+
+/// Lets threads outside of a `ParallelRuntime`, such as a rendering thread, read the outputs of
+/// the program between two instants rather than while they are half updated.
+///
+/// The runtime waits for the readers of a boundary before starting the next instant, but not for
+/// a reader to come back: a reader slower than the program skips the boundaries in between, see
+/// `InstantEnd::completed_instants`.
+#[derive(Clone)]
+pub struct InstantBoundary {
+    state: Arc<(Mutex<BoundaryState>, Condvar)>,
+}
+
+/// Holds back the start of the next instant while it is alive, see `InstantBoundary`.
+pub struct InstantEnd<'a> {
+    boundary: &'a InstantBoundary,
+    completed: usize,
+}
+
+impl<'a> InstantEnd<'a> {
+    /// Number of instants completed so far.
+    pub fn completed_instants(&self) -> usize {
+        self.completed
+    }
+}
+
+impl<'a> Drop for InstantEnd<'a> {
+    fn drop(&mut self) {
+        let (ref lock, ref changed) = *self.boundary.state;
+        lock.lock().unwrap().readers -= 1;
+        changed.notify_all();
+    }
+}
+
+impl InstantBoundary {
+    fn new() -> Self {
+        let state = BoundaryState { completed: 0, running: false, readers: 0 };
+        InstantBoundary { state: Arc::new((Mutex::new(state), Condvar::new())) }
+    }
+
+    /// Waits for the readers of the previous boundary, new readers waiting for the next one.
+    fn start_instant(&self) {
+        let (ref lock, ref changed) = *self.state;
+        let mut state = lock.lock().unwrap();
+        state.running = true;
+        while state.readers > 0 {
+            state = changed.wait(state).unwrap();
+        }
+    }
+
+    fn end_instant(&self) {
+        let (ref lock, ref changed) = *self.state;
+        let mut state = lock.lock().unwrap();
+        state.completed += 1;
+        state.running = false;
+        changed.notify_all();
+    }
+
+    /// Blocks until the running instant ends, or returns immediately between two instants. The
+    /// next instant starts once the returned guard is dropped, the runtime waiting for it.
+    pub fn wait_instant_end(&self) -> InstantEnd {
+        self.wait_until(|state| !state.running)
+    }
+
+    /// Like `wait_instant_end`, for a boundary after the first `completed` instants, so that a
+    /// thread reading the outputs in a loop reads each of them once, passing the number of
+    /// instants completed at its previous read.
+    pub fn wait_instant_end_after(&self, completed: usize) -> InstantEnd {
+        self.wait_until(|state| !state.running && state.completed > completed)
+    }
+
+    fn wait_until<F>(&self, ready: F) -> InstantEnd where F: Fn(&BoundaryState) -> bool {
+        let (ref lock, ref changed) = *self.state;
+        let mut state = lock.lock().unwrap();
+        while !ready(&state) {
+            state = changed.wait(state).unwrap();
+        }
+        state.readers += 1;
+        InstantEnd { boundary: self, completed: state.completed }
+    }
+
+    /// Like `wait_instant_end`, without blocking: `None` while an instant is running.
+    pub fn try_instant_end(&self) -> Option<InstantEnd> {
+        let mut state = (self.state.0).lock().unwrap();
+        if state.running {
+            return None;
+        }
+        state.readers += 1;
+        Some(InstantEnd { boundary: self, completed: state.completed })
+    }
+}
+
 pub struct LocalParallelRuntime {
     runtime: Arc<ParallelRuntime>
 }
@@ -159,4 +805,29 @@ impl Runtime for LocalParallelRuntime {
     fn on_end_of_instant(&mut self, c: Box<Continuation<()>>) {
         self.runtime.on_end_of_instant(c);
     }
+
+    fn on_deadline(&mut self, deadline: time::Instant, c: Box<Continuation<()>>) {
+        let c = if self.runtime.deterministic { ordered(c) } else { c };
+        self.runtime.timers.add(deadline, c);
+    }
+
+    fn instant_index(&self) -> usize {
+        self.runtime.instant.load(Ordering::SeqCst)
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.runtime.deterministic
+    }
+
+    fn external_inbox(&self) -> ExternalInbox {
+        self.runtime.external.clone()
+    }
+
+    fn panic_handler(&self) -> Option<PanicHandler> {
+        self.runtime.panic_handler.clone()
+    }
+
+    fn clock(&self) -> SharedClock {
+        self.runtime.clock.clone()
+    }
 }
\ No newline at end of file
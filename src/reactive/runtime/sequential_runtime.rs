@@ -1,4 +1,5 @@
 use super::*;
+use std::collections::BTreeMap;
 
 //  ____             ____              _   _
 // / ___|  ___  __ _|  _ \ _   _ _ __ | |_(_)_ __ ___   ___
@@ -7,56 +8,354 @@ use super::*;
 // |____/ \___|\__, |_| \_\\__,_|_| |_|\__|_|_| |_| |_|\___|
 //                |_|
 
-
+/// Executes the continuations of an instant in the order they were registered, those with a
+/// greater `Priority` first.
 pub struct SequentialRuntime {
-    current_instant: Vec<Box<Continuation<()>>>,
-    end_instant: Vec<Box<Continuation<()>>>,
-    next_current_instant: Vec<Box<Continuation<()>>>,
-    next_end_instant: Vec<Box<Continuation<()>>>,
+    current_instant: VecDeque<Box<Continuation<()>>>,
+    end_instant: VecDeque<Box<Continuation<()>>>,
+    next_current_instant: VecDeque<Box<Continuation<()>>>,
+    next_end_instant: VecDeque<Box<Continuation<()>>>,
+    /// The continuations of the current and of the next instant with a priority, by priority.
+    prioritized: BTreeMap<Priority, VecDeque<Box<Continuation<()>>>>,
+    next_prioritized: BTreeMap<Priority, VecDeque<Box<Continuation<()>>>>,
+    instant: usize,
+    deterministic: bool,
+    external: ExternalInbox,
+    panic_handler: Option<PanicHandler>,
+    memory: Option<MemoryAccounting>,
+    clock: SharedClock,
+    /// The number of continuations each queue holds without growing, if fixed.
+    capacity: Option<usize>,
+    finalizers: Finalizers,
+    suspend: SuspendHandle,
+    timers: Timers,
+    pacer: Option<Pacer>,
+    /// Identifies the `WorkerLocal`s of the runtime.
+    id: usize,
 }
 
 impl SequentialRuntime {
     pub fn new() -> Self {
+        let external = ExternalInbox::new();
+        SequentialRuntime {
+            current_instant: VecDeque::new(),
+            end_instant: VecDeque::new(),
+            next_current_instant: VecDeque::new(),
+            next_end_instant: VecDeque::new(),
+            prioritized: BTreeMap::new(),
+            next_prioritized: BTreeMap::new(),
+            instant: 0,
+            deterministic: false,
+            suspend: SuspendHandle::new(&external),
+            external,
+            panic_handler: None,
+            memory: None,
+            clock: Arc::new(SystemClock),
+            capacity: None,
+            finalizers: Finalizers::new(),
+            timers: Timers::new(),
+            pacer: None,
+            id: runtime_id(),
+        }
+    }
+
+    /// A runtime whose queues are allocated once with room for `continuations` continuations each,
+    /// and which panics instead of growing them, for targets which must not allocate while they
+    /// run. See `ValueSignal::with_capacity` for the signals.
+    ///
+    /// The continuations themselves are still boxed, loops only reaching a state without
+    /// allocation once their boxes are recycled, see `recycled_box`. Only the queues of the
+    /// instants are bounded: `inbox_capacity` bounds the inbox, blocking emitters instead of
+    /// panicking, deadlines still grow the timers, and continuations with a `Priority` their own
+    /// queues. `ParallelRuntime` has no such mode, its lock-free queues allocating a node for each
+    /// continuation.
+    pub fn with_capacity(continuations: usize) -> Self {
         SequentialRuntime {
-            current_instant: Vec::new(),
-            end_instant: Vec::new(),
-            next_current_instant: Vec::new(),
-            next_end_instant: Vec::new(),
+            current_instant: VecDeque::with_capacity(continuations),
+            end_instant: VecDeque::with_capacity(continuations),
+            next_current_instant: VecDeque::with_capacity(continuations),
+            next_end_instant: VecDeque::with_capacity(continuations),
+            capacity: Some(continuations),
+            ..SequentialRuntime::new()
         }
     }
+
+    /// Bounds the inbox to `continuations` continuations waiting for the next instant, so that
+    /// other threads emitting with `ExternalInbox::emit` wait for the program once they are that
+    /// far ahead of it, and `try_emit` fails.
+    pub fn inbox_capacity(&mut self, continuations: usize) {
+        self.external.set_capacity(continuations);
+    }
+
+    /// A runtime ordering signal values and continuations like a deterministic `ParallelRuntime`,
+    /// yielding the same results, see `RuntimeBuilder::deterministic`.
+    pub fn new_deterministic() -> Self {
+        SequentialRuntime { deterministic: true, ..SequentialRuntime::new() }
+    }
+
+    /// Installs `handler`, called with the name of the process and the payload of the panic when
+    /// a continuation panics, to decide whether to restart the process, skip it or abort.
+    pub fn on_process_panic<F>(&mut self, handler: F) where F: Fn(&str, &(Any + Send)) -> PanicPolicy + Send + Sync + 'static {
+        self.panic_handler = Some(Arc::new(handler));
+    }
+
+    /// Reports the memory used at the end of every instant to `accounting`.
+    pub fn account_memory(&mut self, accounting: &MemoryAccounting) {
+        self.memory = Some(accounting.clone());
+    }
+
+    /// Gives the time of `clock` to the processes instead of the time of the system.
+    pub fn set_clock<C>(&mut self, clock: C) where C: Clock + 'static {
+        self.clock = Arc::new(clock);
+    }
+
+    /// A signal the runtime emits at the start of every instant with its logical duration,
+    /// according to `pacing`, for processes integrating over time instead of counting instants.
+    /// The signal of an earlier call is not emitted anymore.
+    pub fn dt_signal(&mut self, pacing: Pacing) -> ValueSignal<time::Duration, time::Duration> {
+        let pacer = Pacer::new(pacing);
+        let signal = pacer.signal();
+        self.pacer = Some(pacer);
+        signal
+    }
+
+    /// Starts `process` at the first instant, for programs whose modules contribute their own
+    /// processes instead of being composed into one. See `registrar` to register processes from
+    /// other threads before calling `execute`.
+    pub fn register<P>(&self, process: P) where P: Process<Value = ()> {
+        self.registrar().register(process);
+    }
+
+    /// A handle through which other threads register processes, see `Registrar`.
+    pub fn registrar(&self) -> Registrar {
+        Registrar { inbox: self.external.clone() }
+    }
+
+    /// Starts the finalizer `process` when the runtime shuts down, to flush buffers or emit final
+    /// states before `execute` returns.
+    ///
+    /// The runtime shuts down when a shutdown is requested through `shutdown_handle`, or when the
+    /// program completes. The finalizers then start at the next instant, in registration order,
+    /// and the runtime executes them along with the rest of the program for at most
+    /// `shutdown_grace` instants, abandoning what is still running afterwards, but at least for the
+    /// instant starting them. Without finalizers, the runtime returns as soon as a shutdown is
+    /// requested.
+    pub fn on_shutdown<P>(&self, process: P) where P: Process<Value = ()> {
+        self.finalizers.add(process);
+    }
+
+    /// A handle through which processes and other threads request the runtime to shut down.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.finalizers.handle()
+    }
+
+    /// Executes at most `instants` instants once the runtime shuts down, `SHUTDOWN_GRACE` by
+    /// default. The finalizers still execute the instant starting them with a grace of 0.
+    pub fn shutdown_grace(&mut self, instants: usize) {
+        self.finalizers.grace = instants;
+    }
+
+    /// A handle through which processes and other threads make `execute` return between two
+    /// instants, for `suspend`.
+    pub fn suspend_handle(&self) -> SuspendHandle {
+        self.suspend.clone()
+    }
+
+    /// Takes what the program has left to execute once `execute` returned, for another runtime to
+    /// resume it, see `Suspended`.
+    pub fn suspend(self) -> Suspended {
+        Suspended {
+            instant: self.instant,
+            current_instant: by_priority(self.prioritized).into_iter().chain(self.current_instant).collect(),
+            end_instant: self.end_instant.into_iter().collect(),
+            next_instant: by_priority(self.next_prioritized).into_iter().chain(self.next_current_instant).collect(),
+            external: self.external,
+            timers: self.timers,
+            finalizers: self.finalizers.take(),
+            pacer: self.pacer,
+        }
+    }
+
+    /// A runtime resuming the program of a suspended runtime at its next instant.
+    pub fn resume(suspended: Suspended) -> Self {
+        let runtime = SequentialRuntime {
+            instant: suspended.instant,
+            current_instant: suspended.current_instant.into_iter().collect(),
+            end_instant: suspended.end_instant.into_iter().collect(),
+            next_current_instant: suspended.next_instant.into_iter().collect(),
+            suspend: SuspendHandle::new(&suspended.external),
+            external: suspended.external,
+            timers: suspended.timers,
+            pacer: suspended.pacer,
+            ..SequentialRuntime::new()
+        };
+        runtime.finalizers.resume(suspended.finalizers);
+        runtime
+    }
 }
 
 impl SequentialRuntime {
     pub fn execute(&mut self) {
-        while self.instant() {}
+        let _runtime = start_execution(self.id);
+        let shutdown = self.finalizers.handle();
+        while !shutdown.is_requested() && !self.suspend.is_requested() && self.instant() {
+            self.wait_idle();
+        }
+        if self.suspend.is_requested() {
+            self.suspend.clear();
+            return;
+        }
+        let finalizers = self.finalizers.take();
+        if !finalizers.is_empty() {
+            for c in finalizers {
+                self.on_current_instant(c);
+            }
+            let deadline = self.finalizers.deadline(self.instant);
+            while self.instant < deadline && self.instant() {}
+        }
+        shutdown.clear();
     }
 
     pub fn instant(&mut self) -> bool {
-        while let Some(cont) = self.current_instant.pop() {
-            cont.call_box(self, ());
+        let now = self.clock.now();
+        if let Some(c) = self.pacer.as_mut().map(|pacer| pacer.start_instant(now)) {
+            self.on_current_instant(c);
+        }
+        let handler = self.panic_handler.clone();
+        if self.deterministic {
+            self.execute_waves(&handler);
+        } else {
+            for cont in self.external.drain() {
+                call_handling_panics(cont, self, &handler);
+            }
+            for cont in self.timers.expired(self.clock.now()) {
+                call_handling_panics(cont, self, &handler);
+            }
+            while let Some(cont) = self.pop_current() {
+                call_handling_panics(cont, self, &handler);
+            }
         }
         std::mem::swap(&mut self.current_instant, &mut self.next_current_instant);
+        std::mem::swap(&mut self.prioritized, &mut self.next_prioritized);
         std::mem::swap(&mut self.end_instant, &mut self.next_end_instant);
-        while let Some(cont) = self.next_end_instant.pop() {
-            cont.call_box(self, ());
+        if self.deterministic {
+            let end_instant = self.next_end_instant.drain(..).collect();
+            run_end_of_instant(self, end_instant, &handler);
         }
+        while let Some(cont) = self.next_end_instant.pop_front() {
+            call_handling_panics(cont, self, &handler);
+        }
+        if let Some(ref memory) = self.memory {
+            let mut queued = continuations_usage(self.current_instant.iter());
+            queued.merge(continuations_usage(self.end_instant.iter()));
+            queued.merge(continuations_usage(self.next_current_instant.iter()));
+            queued.merge(continuations_usage(self.next_end_instant.iter()));
+            queued.merge(continuations_usage(self.prioritized.values().flat_map(|queue| queue.iter())));
+            queued.merge(continuations_usage(self.next_prioritized.values().flat_map(|queue| queue.iter())));
+            memory.record(self.instant, queued);
+        }
+        self.instant += 1;
 
         (!self.current_instant.is_empty())
+            || (!self.prioritized.is_empty())
             || (!self.end_instant.is_empty())
             || (!self.next_end_instant.is_empty())
+            || self.timers.is_pending(&self.clock)
+            || self.external.is_awaited()
+    }
+
+    /// Blocks until the next timer expires or a continuation is pushed to the inbox, if the next
+    /// instant has nothing to execute yet.
+    pub(crate) fn wait_idle(&self) {
+        if self.current_instant.is_empty() && self.prioritized.is_empty() && self.end_instant.is_empty() {
+            self.external.wait(self.timers.time_left(&self.clock));
+        }
+    }
+
+    /// Takes the next continuation of the current instant, those with the greatest priority first.
+    fn pop_current(&mut self) -> Option<Box<Continuation<()>>> {
+        let priority = match self.prioritized.keys().next_back() {
+            Some(&priority) => priority,
+            None => return self.current_instant.pop_front(),
+        };
+        let c = self.prioritized.get_mut(&priority).and_then(|queue| queue.pop_front());
+        if self.prioritized[&priority].is_empty() {
+            self.prioritized.remove(&priority);
+        }
+        c
+    }
+
+    /// Executes the continuations of the instant in waves, by key, see `OrderKey`.
+    fn execute_waves(&mut self, handler: &Option<PanicHandler>) {
+        let mut wave = self.external.drain();
+        wave.extend(self.timers.expired(self.clock.now()));
+        wave.extend(self.current_instant.drain(..));
+        let mut index = 0;
+        while !wave.is_empty() {
+            sort_by_order_key(&mut wave);
+            for (i, cont) in wave.into_iter().enumerate() {
+                let key = wave_key(self.instant, index, i);
+                with_order_key(key, || call_handling_panics(cont, self, handler));
+            }
+            wave = self.current_instant.drain(..).collect();
+            index += 1;
+        }
     }
 }
 
 impl Runtime for SequentialRuntime {
     fn on_current_instant(&mut self, c: Box<Continuation<()>>) {
-        self.current_instant.push(c);
+        if self.deterministic {
+            push_bounded(&mut self.current_instant, self.capacity, ordered(c), "the queue of the current instant");
+        } else if c.priority() > Priority::NORMAL {
+            self.prioritized.entry(c.priority()).or_insert_with(VecDeque::new).push_back(c);
+        } else {
+            push_bounded(&mut self.current_instant, self.capacity, c, "the queue of the current instant");
+        }
     }
 
     fn on_next_instant(&mut self, c: Box<Continuation<()>>) {
-        self.next_current_instant.push(c);
+        if self.deterministic {
+            push_bounded(&mut self.next_current_instant, self.capacity, ordered(c), "the queue of the next instant");
+        } else if c.priority() > Priority::NORMAL {
+            self.next_prioritized.entry(c.priority()).or_insert_with(VecDeque::new).push_back(c);
+        } else {
+            push_bounded(&mut self.next_current_instant, self.capacity, c, "the queue of the next instant");
+        }
     }
 
     fn on_end_of_instant(&mut self, c: Box<Continuation<()>>) {
-        self.end_instant.push(c);
+        let c = if self.deterministic { ordered(c) } else { c };
+        push_bounded(&mut self.end_instant, self.capacity, c, "the queue of the end of the instant");
+    }
+
+    fn on_deadline(&mut self, deadline: time::Instant, c: Box<Continuation<()>>) {
+        let c = if self.deterministic { ordered(c) } else { c };
+        self.timers.add(deadline, c);
+    }
+
+    fn external_inbox(&self) -> ExternalInbox {
+        self.external.clone()
+    }
+
+    fn instant_index(&self) -> usize {
+        self.instant
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    fn panic_handler(&self) -> Option<PanicHandler> {
+        self.panic_handler.clone()
+    }
+
+    fn clock(&self) -> SharedClock {
+        self.clock.clone()
     }
-}
\ No newline at end of file
+}
+
+/// Empties `queues`, the continuations of the greatest priority first.
+fn by_priority(queues: BTreeMap<Priority, VecDeque<Box<Continuation<()>>>>) -> Vec<Box<Continuation<()>>> {
+    queues.into_iter().rev().flat_map(|(_, queue)| queue).collect()
+}
@@ -0,0 +1,75 @@
+use super::*;
+
+//     _       _             _
+//    / \   __| | __ _ _ __ | |_ ___ _ __
+//   / _ \ / _` |/ _` | '_ \| __/ _ \ '__|
+//  / ___ \ (_| | (_| | |_) | ||  __/ |
+// /_/   \_\__,_|\__,_| .__/ \__\___|_|
+//                    |_|
+
+// The adapters let the producers of a signal of one kind feed the consumers written for another
+// kind, each returning the new signal along with the process forwarding the emissions to it,
+// which never terminates. The new signal is only emitted while this process runs.
+
+/// The process forwarding the emissions of a signal to a signal of another kind.
+pub struct Forward<A> where A: ProcessMut {
+    await: A,
+    /// Whether `await` completes during the instant of the emission, in which case the next one
+    /// is only awaited from the next instant on.
+    immediate: bool,
+    emit: Arc<Fn(&mut Runtime, A::Value) + Send + Sync>,
+}
+
+impl<A> Process for Forward<A> where A: ProcessMut {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        let Forward {await, immediate, emit} = self;
+        await.call_mut(runtime, move|runtime: &mut Runtime, (await, v): (A, A::Value)| {
+            emit(runtime, v);
+            let forward = Forward {await, immediate, emit};
+            if immediate {
+                runtime.on_next_instant(Box::new(move|runtime: &mut Runtime, ()| forward.call(runtime, next)));
+            } else {
+                forward.call(runtime, next);
+            }
+        });
+    }
+}
+
+/// Calls `emit` with the values of `await`, over and over, see `Forward`.
+pub(crate) fn forward<A, F>(await: A, immediate: bool, emit: F) -> Forward<A>
+    where A: ProcessMut, F: Fn(&mut Runtime, A::Value) + Send + Sync + 'static {
+    Forward { await, immediate, emit: Arc::new(emit) }
+}
+
+/// A pure signal present during the instants in which `signal` is emitted, for consumers which
+/// only care about the presence of the signal.
+pub fn pure_from_value<V, G>(signal: &ValueSignal<V, G>) -> (PureSignal, Forward<VAwaitImmediate<V, G>>)
+    where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    let pure = PureSignal::new();
+    let target = pure.clone();
+    let emit = move|runtime: &mut Runtime, ()| target.emit().call(runtime, |_: &mut Runtime, ()| ());
+    (pure, forward(signal.await_immediate(), true, emit))
+}
+
+/// A value signal emitted with `v` during the instants in which `signal` is present, its value
+/// being `v` whether it is emitted or not.
+pub fn value_from_pure<V>(signal: &PureSignal, v: V) -> (ValueSignal<V, V>, Forward<PAwaitImmediate>)
+    where V: Clone + Send + Sync + 'static {
+    let valued = ValueSignal::new(v.clone(), Box::new(|_, v| v));
+    let target = valued.clone();
+    let emit = move|runtime: &mut Runtime, ()| target.emit(value(v.clone())).call(runtime, |_: &mut Runtime, _| ());
+    (valued, forward(signal.await_immediate(), true, emit))
+}
+
+/// A signal with a unique producer, the forwarding process, emitted with the gathered value of
+/// `signal` at the instant following each of its emissions, like `await` resumes. Its default
+/// value is the one of `signal`.
+pub fn unique_from_shared<V, G>(signal: &ValueSignal<V, G>) -> (UniqueProducerSignalConsumer<V>, Forward<VAwait<V, G>>)
+    where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    let (producer, consumer) = UniqueProducerSignalProducer::new(signal.default_value());
+    let target = producer.runtime();
+    let emit = move|runtime: &mut Runtime, v: V| target.clone().emit(runtime, v);
+    (consumer, forward(signal.await(), false, emit))
+}
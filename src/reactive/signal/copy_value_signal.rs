@@ -0,0 +1,319 @@
+use super::*;
+use std::sync::Weak;
+
+//   ____                __     __    _            ____  _                   _
+//  / ___|___  _ __  _   \ \   / /_ _| |_   _  ___/ ___|(_) __ _ _ __   __ _| |
+// | |   / _ \| '_ \| | | \ \ / / _` | | | | |/ _ \___ \| |/ _` | '_ \ / _` | |
+// | |__| (_) | |_) | |_| |\ V / (_| | | |_| |  __/___) | | (_| | | | | (_| | |
+//  \____\___/| .__/ \__, | \_/ \__,_|_|\__,_|\___|____/|_|\__, |_| |_|\__,_|_|
+//            |_|    |___/                                 |___/
+
+pub struct CSignalRuntimeRef<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    signal_runtime: Arc<Mutex<CSignalRuntime<V, G>>>,
+}
+
+impl<V, G> Clone for CSignalRuntimeRef<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        CSignalRuntimeRef {signal_runtime: self.signal_runtime.clone()}
+    }
+}
+
+struct CSignalRuntime<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    /// Woken in registration order, like `waiting_present` and `waiting_await`.
+    callbacks: VecDeque<Box<Continuation<()>>>,
+    waiting_present: VecDeque<Box<Continuation<bool>>>,
+    waiting_await: VecDeque<Box<Continuation<V>>>,
+    status: bool,
+    gather: fn(V, G) -> V,
+    default_value: V,
+    current_value: V,
+    /// Emissions of the instant in a deterministic runtime and their keys, gathered at the end of
+    /// the instant.
+    pending: Vec<(Option<OrderKey>, G)>,
+}
+
+impl<V, G> CSignalRuntime<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn gathered(&mut self) -> V {
+        self.pending.sort_by(|&(ref key1, _), &(ref key2, _)| key1.cmp(key2));
+        let gather = self.gather;
+        self.pending.iter().fold(self.current_value, |value, &(_, v)| gather(value, v))
+    }
+
+    fn forget_cancelled(&mut self) {
+        self.callbacks.retain(|c| !is_cancelled(&**c));
+        self.waiting_present.retain(|c| !is_cancelled(&**c));
+        self.waiting_await.retain(|c| !is_cancelled(&**c));
+    }
+}
+
+impl<V, G> CSignalRuntimeRef<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    /// Wraps `c` before storing it, to forget it if its token is cancelled, see `waiting`.
+    fn forget_when_cancelled<C, T>(&self, c: C) -> Waiting<C> where C: Continuation<T>, T: Send + Sync {
+        let sig = Arc::downgrade(&self.signal_runtime);
+        waiting(c, &*self.signal_runtime as *const _ as usize, move|| {
+            if let Some(sig) = sig.upgrade() {
+                sig.lock().unwrap().forget_cancelled();
+            }
+        })
+    }
+
+    /// Only the first emission of an instant registers the end of instant continuation, the
+    /// following ones just lock the signal.
+    fn emit(&self, runtime: &mut Runtime, value: G) {
+        let mut sig = self.signal_runtime.lock().unwrap();
+        if runtime.is_deterministic() {
+            sig.pending.push((next_order_key(), value));
+        } else {
+            sig.current_value = (sig.gather)(sig.current_value, value);
+        }
+        if sig.status {
+            return;
+        }
+        sig.status = true;
+        while let Some(c) = sig.callbacks.pop_front() {
+            runtime.on_current_instant(c);
+        }
+        while let Some(c) = sig.waiting_present.pop_front() {
+            wake(runtime, c, true);
+        }
+
+        let sig_run = self.signal_runtime.clone();
+        runtime.on_end_of_instant(recycled_box(move|runtime: &mut Runtime, ()| {
+            let mut sig = sig_run.lock().unwrap();
+            let value = sig.gathered();
+            while let Some(c) = sig.waiting_await.pop_front() {
+                wake(runtime, c, value);
+            }
+            sig.pending.clear();
+            sig.current_value = sig.default_value;
+            sig.status = false;
+        }));
+    }
+
+    fn on_signal<C>(&self, runtime: &mut Runtime, c: C) where C: Continuation<()> {
+        let mut sig = self.signal_runtime.lock().unwrap();
+        if sig.status {
+            runtime.on_current_instant(recycled_box(c));
+        } else {
+            let c = self.forget_when_cancelled(c);
+            sig.callbacks.push_back(ordered(recycled_box(c)));
+        }
+    }
+
+    fn await<C>(&self, c: C) where C: Continuation<V> {
+        let c = self.forget_when_cancelled(c);
+        self.signal_runtime.lock().unwrap().waiting_await.push_back(ordered(recycled_box(c)));
+    }
+
+    fn test_present<C>(&self, runtime: &mut Runtime, c: C) where C: Continuation<bool> {
+        let mut sig = self.signal_runtime.lock().unwrap();
+        if sig.status {
+            c.call(runtime, true);
+        } else {
+            if sig.waiting_present.is_empty() {
+                let sig_run = self.signal_runtime.clone();
+                runtime.on_end_of_instant(Box::new(move|runtime: &mut Runtime, ()| {
+                    let mut sig = sig_run.lock().unwrap();
+                    while let Some(c) = sig.waiting_present.pop_front() {
+                        call_ordered(runtime, c, false);
+                    }
+                }));
+            }
+            let c = self.forget_when_cancelled(c);
+            sig.waiting_present.push_back(ordered(Box::new(c)));
+        }
+    }
+}
+
+pub trait CSignal<V, G>: 'static where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn runtime(&self) -> CSignalRuntimeRef<V, G>;
+
+    fn await_immediate(&self) -> CAwaitImmediate<V, G> where Self: Sized {
+        CAwaitImmediate {signal: self.runtime()}
+    }
+
+    fn await(&self) -> CAwait<V, G> where Self: Sized {
+        CAwait {signal: self.runtime()}
+    }
+
+    fn emit<P>(&self, value: P) -> CEmit<V, G, P> where Self: Sized, P: Process<Value = G> {
+        CEmit {signal: self.runtime(), value}
+    }
+
+    fn present(&self) -> CPresent<V, G> where Self: Sized {
+        CPresent {signal: self.runtime()}
+    }
+}
+
+/// A `ValueSignal` for small `Copy` values, like powers or coordinates. Values are copied instead
+/// of cloned, gathered with a plain function instead of a boxed closure, and the processes keep
+/// their reference to the signal across iterations instead of cloning it at each emission.
+pub struct CopyValueSignal<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    runtime: CSignalRuntimeRef<V, G>
+}
+
+impl<V, G> CopyValueSignal<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    pub fn new(default_value: V, gather: fn(V, G) -> V) -> CopyValueSignal<V, G> {
+        let runtime = CSignalRuntime {
+            status: false,
+            callbacks: VecDeque::new(),
+            waiting_present: VecDeque::new(),
+            waiting_await: VecDeque::new(),
+            current_value: default_value,
+            default_value,
+            gather,
+            pending: Vec::new(),
+        };
+        CopyValueSignal {
+            runtime: CSignalRuntimeRef {signal_runtime: Arc::new(Mutex::new(runtime))}
+        }
+    }
+
+    /// A handle which does not keep the signal alive.
+    pub fn downgrade(&self) -> WeakCopyValueSignal<V, G> {
+        WeakCopyValueSignal {signal_runtime: Arc::downgrade(&self.runtime.signal_runtime)}
+    }
+}
+
+/// A `CopyValueSignal` handle which does not keep the signal alive, see
+/// `CopyValueSignal::downgrade`.
+pub struct WeakCopyValueSignal<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    signal_runtime: Weak<Mutex<CSignalRuntime<V, G>>>,
+}
+
+impl<V, G> WeakCopyValueSignal<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    /// The signal, unless every `CopyValueSignal` handle and every process using it was dropped.
+    pub fn upgrade(&self) -> Option<CopyValueSignal<V, G>> {
+        self.signal_runtime.upgrade().map(|signal_runtime| CopyValueSignal {
+            runtime: CSignalRuntimeRef {signal_runtime}
+        })
+    }
+}
+
+impl<V, G> Clone for WeakCopyValueSignal<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        WeakCopyValueSignal {signal_runtime: self.signal_runtime.clone()}
+    }
+}
+
+impl<V, G> Clone for CopyValueSignal<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        CopyValueSignal {runtime: self.runtime.clone()}
+    }
+}
+
+impl<V, G> CSignal<V, G> for CopyValueSignal<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn runtime(&self) -> CSignalRuntimeRef<V, G> {
+        self.runtime.clone()
+    }
+}
+
+pub struct CAwaitImmediate<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    signal: CSignalRuntimeRef<V, G>
+}
+
+impl<V, G> Process for CAwaitImmediate<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<()> {
+        self.signal.on_signal(runtime, c);
+    }
+}
+
+impl<V, G> ProcessMut for CAwaitImmediate<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, ())> {
+        let sig = self.signal.clone();
+        self.signal.on_signal(runtime, next.map(move|()| (CAwaitImmediate {signal: sig}, ())));
+    }
+}
+
+impl<V, G> CloneProcess for CAwaitImmediate<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        CAwaitImmediate {signal: self.signal.clone()}
+    }
+}
+
+pub struct CAwait<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    signal: CSignalRuntimeRef<V, G>
+}
+
+impl<V, G> Process for CAwait<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    type Value = V;
+
+    fn call<C>(self, _: &mut Runtime, c: C) where C: Continuation<V> {
+        self.signal.await(c);
+    }
+}
+
+impl<V, G> ProcessMut for CAwait<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn call_mut<C>(self, _: &mut Runtime, next: C) where C: Continuation<(Self, V)> {
+        let sig = self.signal.clone();
+        self.signal.await(next.map(move|v| (CAwait {signal: sig}, v)));
+    }
+}
+
+impl<V, G> CloneProcess for CAwait<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        CAwait {signal: self.signal.clone()}
+    }
+}
+
+pub struct CEmit<V, G, P> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static, P: Process<Value = G> {
+    signal: CSignalRuntimeRef<V, G>,
+    value: P,
+}
+
+impl<V, G, P> Process for CEmit<V, G, P> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static, P: Process<Value = G> {
+    type Value = G;
+
+    fn call<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<G> {
+        let sig = self.signal;
+
+        self.value.call(runtime, move|runtime: &mut Runtime, v: G| {
+            sig.emit(runtime, v);
+            c.call(runtime, v);
+        });
+    }
+}
+
+impl<V, G, P> ProcessMut for CEmit<V, G, P> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static, P: ProcessMut<Value = G> {
+    fn call_mut<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<(Self, G)> {
+        let sig = self.signal;
+
+        self.value.call_mut(runtime, move|runtime: &mut Runtime, (process, v): (P, G)| {
+            sig.emit(runtime, v);
+            c.call(runtime, (CEmit {signal: sig, value: process}, v));
+        });
+    }
+}
+
+impl<V, G, P> CloneProcess for CEmit<V, G, P> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static, P: CloneProcess<Value = G> {
+    fn clone_process(&self) -> Self {
+        CEmit {signal: self.signal.clone(), value: self.value.clone_process()}
+    }
+}
+
+pub struct CPresent<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    signal: CSignalRuntimeRef<V, G>
+}
+
+impl<V, G> Process for CPresent<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    type Value = bool;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<bool> {
+        self.signal.test_present(runtime, next);
+    }
+}
+
+impl<V, G> ProcessMut for CPresent<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, bool)> {
+        let sig = self.signal.clone();
+        self.signal.test_present(runtime, next.map(move|status: bool| (CPresent {signal: sig}, status)));
+    }
+}
+
+impl<V, G> CloneProcess for CPresent<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        CPresent {signal: self.signal.clone()}
+    }
+}
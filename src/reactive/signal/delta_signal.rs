@@ -0,0 +1,192 @@
+use super::*;
+
+//  ____       _ _        ____  _                   _
+// |  _ \  ___| | |_ __ _ / ___|(_) __ _ _ __   __ _| |
+// | | | |/ _ \ | __/ _` |\___ \| |/ _` | '_ \ / _` | |
+// | |_| |  __/ | || (_| | ___) | | (_| | | | | (_| | |
+// |____/ \___|_|\__\__,_||____/|_|\__, |_| |_|\__,_|_|
+//                                 |___/
+
+struct DSignalRuntime<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    value: Arc<V>,
+    apply: Box<Fn(&mut V, &D) + Send + Sync>,
+    /// The deltas emitted during the instant, with their emitter.
+    pending: Vec<(usize, D)>,
+    waiting_value: Vec<Box<Continuation<Arc<V>>>>,
+    waiting_deltas: Vec<Box<Continuation<Vec<D>>>>,
+    /// Whether the deltas are applied at the end of the current instant.
+    scheduled: bool,
+}
+
+/// A signal holding a large value, such as a grid or a map, updated by the deltas its producers
+/// emit instead of the whole value.
+///
+/// At the end of an instant with emissions, the deltas are applied in place to the held value
+/// with `apply`, in the creation order of their emitters. The consumers resume at the next
+/// instant either with the updated value, shared without being copied, or with the deltas of the
+/// instant. The held value is only copied when a consumer still holds its previous version.
+pub struct DeltaSignal<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    signal_runtime: Arc<Mutex<DSignalRuntime<V, D>>>,
+}
+
+impl<V, D> Clone for DeltaSignal<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        DeltaSignal { signal_runtime: self.signal_runtime.clone() }
+    }
+}
+
+impl<V, D> DeltaSignal<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    pub fn new(value: V, apply: Box<Fn(&mut V, &D) + Send + Sync>) -> Self {
+        let runtime = DSignalRuntime {
+            value: Arc::new(value),
+            apply,
+            pending: Vec::new(),
+            waiting_value: Vec::new(),
+            waiting_deltas: Vec::new(),
+            scheduled: false,
+        };
+        DeltaSignal { signal_runtime: Arc::new(Mutex::new(runtime)) }
+    }
+
+    /// Emits the delta returned by `delta`, applied to the value at the end of the instant.
+    pub fn emit<P>(&self, delta: P) -> DEmit<V, D, P> where P: Process<Value = D> {
+        DEmit { signal: self.clone(), emitter: next_emitter(), delta }
+    }
+
+    /// Waits for the signal to be emitted, and resumes at the next instant with the updated value.
+    pub fn await_value(&self) -> DAwaitValue<V, D> {
+        DAwaitValue { signal: self.clone() }
+    }
+
+    /// Waits for the signal to be emitted, and resumes at the next instant with the deltas of the
+    /// instant, in the order in which they were applied.
+    pub fn await_deltas(&self) -> DAwaitDeltas<V, D> {
+        DAwaitDeltas { signal: self.clone() }
+    }
+
+    /// The value held by the signal, to be read from outside of the program.
+    pub fn current(&self) -> Arc<V> {
+        self.signal_runtime.lock().unwrap().value.clone()
+    }
+
+    fn add_delta(&self, runtime: &mut Runtime, emitter: usize, delta: D) {
+        let mut sig = self.signal_runtime.lock().unwrap();
+        sig.pending.push((emitter, delta));
+        if !sig.scheduled {
+            sig.scheduled = true;
+            let sig_run = self.signal_runtime.clone();
+            runtime.on_end_of_instant(Box::new(move|runtime: &mut Runtime, ()| apply_deltas(sig_run, runtime)));
+        }
+    }
+}
+
+/// Applies the deltas of the instant to the value, and wakes the consumers for the next instant.
+fn apply_deltas<V, D>(sig_run: Arc<Mutex<DSignalRuntime<V, D>>>, runtime: &mut Runtime)
+    where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    let mut guard = sig_run.lock().unwrap();
+    let sig = &mut *guard;
+    sig.scheduled = false;
+    // The sort is stable, an emitter emitting several times keeps its own order.
+    sig.pending.sort_by_key(|&(emitter, _)| emitter);
+    let deltas: Vec<D> = sig.pending.drain(..).map(|(_, d)| d).collect();
+    {
+        let value = Arc::make_mut(&mut sig.value);
+        for delta in deltas.iter() {
+            (sig.apply)(value, delta);
+        }
+    }
+    // Continuations registered for the current instant at the end of an instant are executed
+    // during the next one.
+    for c in sig.waiting_value.drain(..) {
+        let value = sig.value.clone();
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| c.call_box(runtime, value)));
+    }
+    for c in sig.waiting_deltas.drain(..) {
+        let deltas = deltas.clone();
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| c.call_box(runtime, deltas)));
+    }
+}
+
+pub struct DEmit<V, D, P> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static, P: Process<Value = D> {
+    signal: DeltaSignal<V, D>,
+    emitter: usize,
+    delta: P,
+}
+
+impl<V, D, P> Process for DEmit<V, D, P> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static, P: Process<Value = D> {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        let (signal, emitter) = (self.signal, self.emitter);
+        self.delta.call(runtime, move|runtime: &mut Runtime, d: D| {
+            signal.add_delta(runtime, emitter, d);
+            next.call(runtime, ());
+        });
+    }
+}
+
+impl<V, D, P> ProcessMut for DEmit<V, D, P> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static, P: ProcessMut<Value = D> {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, ())> {
+        let (signal, emitter) = (self.signal, self.emitter);
+        self.delta.call_mut(runtime, move|runtime: &mut Runtime, (delta, d): (P, D)| {
+            signal.add_delta(runtime, emitter, d);
+            next.call(runtime, (DEmit { signal, emitter, delta }, ()));
+        });
+    }
+}
+
+impl<V, D, P> CloneProcess for DEmit<V, D, P> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static, P: CloneProcess<Value = D> {
+    fn clone_process(&self) -> Self {
+        self.signal.emit(self.delta.clone_process())
+    }
+}
+
+pub struct DAwaitValue<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    signal: DeltaSignal<V, D>,
+}
+
+impl<V, D> Process for DAwaitValue<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    type Value = Arc<V>;
+
+    fn call<C>(self, _: &mut Runtime, next: C) where C: Continuation<Arc<V>> {
+        self.signal.signal_runtime.lock().unwrap().waiting_value.push(Box::new(next));
+    }
+}
+
+impl<V, D> ProcessMut for DAwaitValue<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Arc<V>)> {
+        let signal = self.signal.clone();
+        self.call(runtime, next.map(move|value| (DAwaitValue { signal }, value)));
+    }
+}
+
+impl<V, D> CloneProcess for DAwaitValue<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        self.signal.await_value()
+    }
+}
+
+pub struct DAwaitDeltas<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    signal: DeltaSignal<V, D>,
+}
+
+impl<V, D> Process for DAwaitDeltas<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    type Value = Vec<D>;
+
+    fn call<C>(self, _: &mut Runtime, next: C) where C: Continuation<Vec<D>> {
+        self.signal.signal_runtime.lock().unwrap().waiting_deltas.push(Box::new(next));
+    }
+}
+
+impl<V, D> ProcessMut for DAwaitDeltas<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Vec<D>)> {
+        let signal = self.signal.clone();
+        self.call(runtime, next.map(move|deltas| (DAwaitDeltas { signal }, deltas)));
+    }
+}
+
+impl<V, D> CloneProcess for DAwaitDeltas<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        self.signal.await_deltas()
+    }
+}
@@ -0,0 +1,340 @@
+use super::*;
+use super::copy_value_signal::*;
+use super::latch_signal::*;
+use super::delta_signal::*;
+use super::transaction_signal::*;
+
+//  ____  _               _   _
+// |  _ \(_)_ __ ___  ___| |_(_) ___  _ __
+// | | | | | '__/ _ \/ __| __| |/ _ \| '_ \
+// | |_| | | | |  __/ (__| |_| | (_) | | | |
+// |____/|_|_|  \___|\___|\__|_|\___/|_| |_|
+
+// Like the producers and consumers of the unique signals, the readers and writers of the other
+// signals restrict what can be done with them: a reader can only wait for and read the signal, a
+// writer can only emit on it. Neither can be turned back into the signal.
+
+impl PureSignal {
+    /// A handle which can only wait for the signal.
+    pub fn reader(&self) -> PureSignalReader {
+        PureSignalReader { signal: self.clone() }
+    }
+
+    /// A handle which can only emit the signal.
+    pub fn writer(&self) -> PureSignalWriter {
+        PureSignalWriter { signal: self.clone() }
+    }
+}
+
+#[derive(Clone)]
+pub struct PureSignalReader {
+    signal: PureSignal,
+}
+
+impl PureSignalReader {
+    pub fn await_immediate(&self) -> PAwaitImmediate {
+        self.signal.await_immediate()
+    }
+
+    pub fn present(&self) -> PPresent {
+        self.signal.present()
+    }
+}
+
+#[derive(Clone)]
+pub struct PureSignalWriter {
+    signal: PureSignal,
+}
+
+impl PureSignalWriter {
+    pub fn emit(&self) -> PEmit {
+        self.signal.emit()
+    }
+
+    /// An emission to perform atomically with others, see `emit_group`.
+    pub fn grouped(&self) -> GroupEmission {
+        self.signal.grouped()
+    }
+}
+
+impl<V, G> ValueSignal<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    /// A handle which can only wait for the signal and read its values.
+    pub fn reader(&self) -> ValueSignalReader<V, G> {
+        ValueSignalReader { signal: self.clone() }
+    }
+
+    /// A handle which can only emit values on the signal.
+    pub fn writer(&self) -> ValueSignalWriter<V, G> {
+        ValueSignalWriter { signal: self.clone() }
+    }
+}
+
+pub struct ValueSignalReader<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    signal: ValueSignal<V, G>,
+}
+
+impl<V, G> Clone for ValueSignalReader<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        ValueSignalReader { signal: self.signal.clone() }
+    }
+}
+
+impl<V, G> ValueSignalReader<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    pub fn await_immediate(&self) -> VAwaitImmediate<V, G> {
+        self.signal.await_immediate()
+    }
+
+    pub fn await(&self) -> VAwait<V, G> {
+        self.signal.await()
+    }
+
+    /// See `VSignal::await_or`.
+    pub fn await_or(&self, default: V) -> VAwaitOr<V, G> {
+        self.signal.await_or(default)
+    }
+
+    /// See `ValueSignal::await_detailed`.
+    pub fn await_detailed(&self) -> VAwaitDetailed<V, G> {
+        self.signal.await_detailed()
+    }
+
+    pub fn present(&self) -> VPresent<V, G> {
+        self.signal.present()
+    }
+
+    /// See `VSignal::capture`.
+    pub fn capture(&self) -> Recording<V> {
+        self.signal.capture()
+    }
+}
+
+pub struct ValueSignalWriter<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    signal: ValueSignal<V, G>,
+}
+
+impl<V, G> Clone for ValueSignalWriter<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        ValueSignalWriter { signal: self.signal.clone() }
+    }
+}
+
+impl<V, G> ValueSignalWriter<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    pub fn emit<P>(&self, value: P) -> VEmit<V, G, P> where P: Process<Value = G> {
+        self.signal.emit(value)
+    }
+
+    /// See `VSignal::emit_named`.
+    pub fn emit_named<P>(&self, name: &str, value: P) -> VEmit<V, G, P> where P: Process<Value = G> {
+        self.signal.emit_named(name, value)
+    }
+
+    /// An emission of `value` to perform atomically with others, see `emit_group`.
+    pub fn grouped(&self, value: G) -> GroupEmission {
+        self.signal.grouped(value)
+    }
+}
+
+impl<V, G> CopyValueSignal<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    /// A handle which can only wait for the signal and read its values.
+    pub fn reader(&self) -> CopyValueSignalReader<V, G> {
+        CopyValueSignalReader { signal: self.clone() }
+    }
+
+    /// A handle which can only emit values on the signal.
+    pub fn writer(&self) -> CopyValueSignalWriter<V, G> {
+        CopyValueSignalWriter { signal: self.clone() }
+    }
+}
+
+pub struct CopyValueSignalReader<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    signal: CopyValueSignal<V, G>,
+}
+
+impl<V, G> Clone for CopyValueSignalReader<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        CopyValueSignalReader { signal: self.signal.clone() }
+    }
+}
+
+impl<V, G> CopyValueSignalReader<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    pub fn await_immediate(&self) -> CAwaitImmediate<V, G> {
+        self.signal.await_immediate()
+    }
+
+    pub fn await(&self) -> CAwait<V, G> {
+        self.signal.await()
+    }
+
+    pub fn present(&self) -> CPresent<V, G> {
+        self.signal.present()
+    }
+}
+
+pub struct CopyValueSignalWriter<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    signal: CopyValueSignal<V, G>,
+}
+
+impl<V, G> Clone for CopyValueSignalWriter<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        CopyValueSignalWriter { signal: self.signal.clone() }
+    }
+}
+
+impl<V, G> CopyValueSignalWriter<V, G> where V: Copy + Send + Sync + 'static, G: Copy + Send + Sync + 'static {
+    pub fn emit<P>(&self, value: P) -> CEmit<V, G, P> where P: Process<Value = G> {
+        self.signal.emit(value)
+    }
+}
+
+impl<V> LatchSignal<V> where V: Clone + Send + Sync + 'static {
+    /// A handle which can only read the held value.
+    pub fn reader(&self) -> LatchSignalReader<V> {
+        LatchSignalReader { signal: self.clone() }
+    }
+
+    /// A handle which can only emit values on the signal.
+    pub fn writer(&self) -> LatchSignalWriter<V> {
+        LatchSignalWriter { signal: self.clone() }
+    }
+}
+
+pub struct LatchSignalReader<V> where V: Clone + Send + Sync + 'static {
+    signal: LatchSignal<V>,
+}
+
+impl<V> Clone for LatchSignalReader<V> where V: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        LatchSignalReader { signal: self.signal.clone() }
+    }
+}
+
+impl<V> LatchSignalReader<V> where V: Clone + Send + Sync + 'static {
+    pub fn read(&self) -> LRead<V> {
+        self.signal.read()
+    }
+
+    pub fn current(&self) -> V {
+        self.signal.current()
+    }
+}
+
+pub struct LatchSignalWriter<V> where V: Clone + Send + Sync + 'static {
+    signal: LatchSignal<V>,
+}
+
+impl<V> Clone for LatchSignalWriter<V> where V: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        LatchSignalWriter { signal: self.signal.clone() }
+    }
+}
+
+impl<V> LatchSignalWriter<V> where V: Clone + Send + Sync + 'static {
+    pub fn emit<P>(&self, value: P) -> LEmit<V, P> where P: Process<Value = V> {
+        self.signal.emit(value)
+    }
+
+    /// See `LatchSignal::emit_external`.
+    pub fn emit_external(&self, inbox: &ExternalInbox, v: V) {
+        self.signal.emit_external(inbox, v)
+    }
+}
+
+impl<V, D> DeltaSignal<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    /// A handle which can only wait for the signal and read its value.
+    pub fn reader(&self) -> DeltaSignalReader<V, D> {
+        DeltaSignalReader { signal: self.clone() }
+    }
+
+    /// A handle which can only emit deltas on the signal.
+    pub fn writer(&self) -> DeltaSignalWriter<V, D> {
+        DeltaSignalWriter { signal: self.clone() }
+    }
+}
+
+pub struct DeltaSignalReader<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    signal: DeltaSignal<V, D>,
+}
+
+impl<V, D> Clone for DeltaSignalReader<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        DeltaSignalReader { signal: self.signal.clone() }
+    }
+}
+
+impl<V, D> DeltaSignalReader<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    pub fn await_value(&self) -> DAwaitValue<V, D> {
+        self.signal.await_value()
+    }
+
+    pub fn await_deltas(&self) -> DAwaitDeltas<V, D> {
+        self.signal.await_deltas()
+    }
+
+    pub fn current(&self) -> Arc<V> {
+        self.signal.current()
+    }
+}
+
+pub struct DeltaSignalWriter<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    signal: DeltaSignal<V, D>,
+}
+
+impl<V, D> Clone for DeltaSignalWriter<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        DeltaSignalWriter { signal: self.signal.clone() }
+    }
+}
+
+impl<V, D> DeltaSignalWriter<V, D> where V: Clone + Send + Sync + 'static, D: Clone + Send + Sync + 'static {
+    pub fn emit<P>(&self, delta: P) -> DEmit<V, D, P> where P: Process<Value = D> {
+        self.signal.emit(delta)
+    }
+}
+
+impl<G> TransactionSignal<G> where G: Clone + Send + Sync + 'static {
+    /// A handle which can only wait for the committed or rejected batches.
+    pub fn reader(&self) -> TransactionSignalReader<G> {
+        TransactionSignalReader { signal: self.clone() }
+    }
+
+    /// A handle which can only propose values.
+    pub fn writer(&self) -> TransactionSignalWriter<G> {
+        TransactionSignalWriter { signal: self.clone() }
+    }
+}
+
+pub struct TransactionSignalReader<G> where G: Clone + Send + Sync + 'static {
+    signal: TransactionSignal<G>,
+}
+
+impl<G> Clone for TransactionSignalReader<G> where G: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        TransactionSignalReader { signal: self.signal.clone() }
+    }
+}
+
+impl<G> TransactionSignalReader<G> where G: Clone + Send + Sync + 'static {
+    pub fn await_commit(&self) -> TAwaitCommit<G> {
+        self.signal.await_commit()
+    }
+
+    pub fn await_rejection(&self) -> TAwaitRejection<G> {
+        self.signal.await_rejection()
+    }
+}
+
+pub struct TransactionSignalWriter<G> where G: Clone + Send + Sync + 'static {
+    signal: TransactionSignal<G>,
+}
+
+impl<G> Clone for TransactionSignalWriter<G> where G: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        TransactionSignalWriter { signal: self.signal.clone() }
+    }
+}
+
+impl<G> TransactionSignalWriter<G> where G: Clone + Send + Sync + 'static {
+    pub fn propose<P>(&self, value: P) -> TPropose<G, P> where P: Process<Value = G> {
+        self.signal.propose(value)
+    }
+}
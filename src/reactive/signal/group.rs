@@ -0,0 +1,47 @@
+use super::*;
+
+//   ____
+//  / ___|_ __ ___  _   _ _ __
+// | |  _| '__/ _ \| | | | '_ \
+// | |_| | | | (_) | |_| | |_) |
+//  \____|_|  \___/ \__,_| .__/
+//                       |_|
+
+/// An emission of a signal to be performed along with others by `emit_group`, created with
+/// `VSignal::grouped` or `PSignal::grouped`.
+pub struct GroupEmission {
+    emission: Box<Continuation<()>>,
+}
+
+impl GroupEmission {
+    pub(crate) fn new<C>(emission: C) -> Self where C: Continuation<()> {
+        GroupEmission { emission: Box::new(emission) }
+    }
+}
+
+pub struct EmitGroup {
+    emissions: Vec<GroupEmission>,
+}
+
+impl Process for EmitGroup {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        for group_emission in self.emissions {
+            group_emission.emission.call_box(runtime, ());
+        }
+        next.call(runtime, ());
+    }
+}
+
+/// Emits all the signals of `emissions` in a single step, so that they are all present in the
+/// same instant with their values, and their awaiting processes resume together at the next one.
+///
+/// Unlike a sequence of emissions, whose values may be computed over several instants, the group
+/// holds values that are already known: a process observing one of the signals never sees the
+/// others missing from the instant. Values computed by a process are grouped with `and_then`:
+///
+/// `values.and_then(move|(x, y)| emit_group(vec![s1.grouped(x), s2.grouped(y)]))`
+pub fn emit_group(emissions: Vec<GroupEmission>) -> EmitGroup {
+    EmitGroup { emissions }
+}
@@ -0,0 +1,176 @@
+use super::*;
+
+//  _           _       _     ____  _                   _
+// | |     __ _| |_ ___| |__ / ___|(_) __ _ _ __   __ _| |
+// | |    / _` | __/ __| '_ \\___ \| |/ _` | '_ \ / _` | |
+// | |___| (_| | || (__| | | |___) | | (_| | | | | (_| | |
+// |_____|\__,_|\__\___|_| |_|____/|_|\__, |_| |_|\__,_|_|
+//                                    |___/
+
+struct LSignalRuntime<V> where V: Clone + Send + Sync + 'static {
+    value: V,
+    /// The value emitted during the instant, held from the next one.
+    latched: Option<V>,
+}
+
+/// A signal holding a value from one instant to the next, such as the parameters of a process
+/// tuned while the program runs, see `configurable`.
+///
+/// A value emitted during an instant replaces the held value at the end of the instant, so that
+/// the processes reading the signal during an instant all read the same value. When several values
+/// are emitted in the same instant the last one wins, which is only deterministic with a
+/// sequential runtime.
+pub struct LatchSignal<V> where V: Clone + Send + Sync + 'static {
+    signal_runtime: Arc<Mutex<LSignalRuntime<V>>>,
+}
+
+impl<V> Clone for LatchSignal<V> where V: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        LatchSignal { signal_runtime: self.signal_runtime.clone() }
+    }
+}
+
+impl<V> LatchSignal<V> where V: Clone + Send + Sync + 'static {
+    pub fn new(value: V) -> Self {
+        LatchSignal { signal_runtime: Arc::new(Mutex::new(LSignalRuntime { value, latched: None })) }
+    }
+
+    /// Holds the value of `value` from the next instant on.
+    pub fn emit<P>(&self, value: P) -> LEmit<V, P> where P: Process<Value = V> {
+        LEmit { signal: self.clone(), value }
+    }
+
+    /// Emits `v` at the start of the next instant, from another thread such as a console or a GUI,
+    /// see `ExternalInbox`.
+    pub fn emit_external(&self, inbox: &ExternalInbox, v: V) {
+        let signal = self.clone();
+        inbox.push(move|runtime: &mut Runtime, ()| signal.latch(runtime, v));
+    }
+
+    /// Returns the value held during the current instant.
+    pub fn read(&self) -> LRead<V> {
+        LRead { signal: self.clone() }
+    }
+
+    /// The value held by the signal, to be read from outside of the program.
+    pub fn current(&self) -> V {
+        self.signal_runtime.lock().unwrap().value.clone()
+    }
+
+    fn latch(&self, runtime: &mut Runtime, v: V) {
+        let mut sig = self.signal_runtime.lock().unwrap();
+        if sig.latched.is_none() {
+            let sig_run = self.signal_runtime.clone();
+            runtime.on_end_of_instant(Box::new(move|_: &mut Runtime, ()| {
+                let mut sig = sig_run.lock().unwrap();
+                if let Some(v) = sig.latched.take() {
+                    sig.value = v;
+                }
+            }));
+        }
+        sig.latched = Some(v);
+    }
+}
+
+pub struct LEmit<V, P> where V: Clone + Send + Sync + 'static, P: Process<Value = V> {
+    signal: LatchSignal<V>,
+    value: P,
+}
+
+impl<V, P> Process for LEmit<V, P> where V: Clone + Send + Sync + 'static, P: Process<Value = V> {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        let signal = self.signal;
+        self.value.call(runtime, move|runtime: &mut Runtime, v: V| {
+            signal.latch(runtime, v);
+            next.call(runtime, ());
+        });
+    }
+}
+
+impl<V, P> ProcessMut for LEmit<V, P> where V: Clone + Send + Sync + 'static, P: ProcessMut<Value = V> {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, ())> {
+        let signal = self.signal;
+        self.value.call_mut(runtime, move|runtime: &mut Runtime, (value, v): (P, V)| {
+            signal.latch(runtime, v);
+            next.call(runtime, (LEmit { signal, value }, ()));
+        });
+    }
+}
+
+impl<V, P> CloneProcess for LEmit<V, P> where V: Clone + Send + Sync + 'static, P: CloneProcess<Value = V> {
+    fn clone_process(&self) -> Self {
+        LEmit { signal: self.signal.clone(), value: self.value.clone_process() }
+    }
+}
+
+pub struct LRead<V> where V: Clone + Send + Sync + 'static {
+    signal: LatchSignal<V>,
+}
+
+impl<V> Process for LRead<V> where V: Clone + Send + Sync + 'static {
+    type Value = V;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<V> {
+        let v = self.signal.current();
+        next.call(runtime, v);
+    }
+}
+
+impl<V> ProcessMut for LRead<V> where V: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, V)> {
+        let v = self.signal.current();
+        next.call(runtime, (self, v));
+    }
+}
+
+impl<V> CloneProcess for LRead<V> where V: Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        LRead { signal: self.signal.clone() }
+    }
+}
+
+/// A loop reading its configuration from a latch signal, see `configurable`.
+pub struct Configurable<V, F> where V: Clone + Send + Sync + 'static {
+    config: LatchSignal<V>,
+    body: F,
+}
+
+impl<V, F, P, W> Configurable<V, F>
+    where V: Clone + Send + Sync + 'static, F: FnMut(V) -> P + Send + Sync + 'static, P: Process<Value = LoopStatus<W>>, W: Send + Sync {
+    fn iterate<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<W> {
+        let body = (self.body)(self.config.current());
+        body.call(runtime, move|runtime: &mut Runtime, status| match status {
+            LoopStatus::Continue => self.iterate(runtime, next),
+            LoopStatus::Exit(w) => next.call(runtime, w),
+        });
+    }
+}
+
+impl<V, F, P, W> Process for Configurable<V, F>
+    where V: Clone + Send + Sync + 'static, F: FnMut(V) -> P + Send + Sync + 'static, P: Process<Value = LoopStatus<W>>, W: Send + Sync {
+    type Value = W;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<W> {
+        self.iterate(runtime, next);
+    }
+}
+
+impl<V, F, P, W> CloneProcess for Configurable<V, F>
+    where V: Clone + Send + Sync + 'static, F: FnMut(V) -> P + Clone + Send + Sync + 'static, P: Process<Value = LoopStatus<W>>, W: Send + Sync {
+    fn clone_process(&self) -> Self {
+        configurable(&self.config, self.body.clone())
+    }
+}
+
+/// A loop executing the process returned by `body` for the value held by `config` at the start of
+/// each iteration, until it returns `LoopStatus::Exit`. The parameters of the loop can thus be
+/// tuned while it runs by emitting on `config`, without rebuilding the process.
+///
+/// Like with `while_loop`, an iteration completing during the instant it started executes the next
+/// one during the same instant.
+pub fn configurable<V, F, P, W>(config: &LatchSignal<V>, body: F) -> Configurable<V, F>
+    where V: Clone + Send + Sync + 'static, F: FnMut(V) -> P + Send + Sync + 'static, P: Process<Value = LoopStatus<W>>, W: Send + Sync {
+    Configurable { config: config.clone(), body }
+}
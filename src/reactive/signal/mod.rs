@@ -1,11 +1,19 @@
 use self::pure_signal::*;
 use self::value_signal::*;
 use self::unique_producer_signal::*;
-use self::unique_producer_signal::*;
 
 use super::*;
 
 pub mod pure_signal;
 pub mod value_signal;
+pub mod copy_value_signal;
 pub mod unique_consumer_signal;
-pub mod unique_producer_signal;
\ No newline at end of file
+pub mod unique_producer_signal;
+pub mod result_signal;
+pub mod transaction_signal;
+pub mod group;
+pub mod latch_signal;
+pub mod delta_signal;
+pub mod direction;
+pub mod spsc_signal;
+pub mod adapter;
@@ -1,4 +1,5 @@
 use super::*;
+use std::sync::Weak;
 
 // ____                 ____  _                   _
 //|  _ \ _   _ _ __ ___/ ___|(_) __ _ _ __   __ _| |
@@ -13,27 +14,43 @@ pub struct PSignalRuntimeRef {
 }
 
 pub struct PSignalRuntime {
-    callbacks: Vec<Box<Continuation<()>>>,
-    waiting_present: Vec<Box<Continuation<bool>>>,
+    /// Waiters are woken in registration order, as are `waiting_present` ones.
+    callbacks: VecDeque<Box<Continuation<()>>>,
+    waiting_present: VecDeque<Box<Continuation<bool>>>,
     pub status: bool,
 }
 
 impl PSignalRuntime {
     fn add_callback<C>(&mut self, c: C) where C: Continuation<()> {
-        self.callbacks.push(Box::new(c));
+        self.callbacks.push_back(ordered(Box::new(c)));
+    }
+
+    fn forget_cancelled(&mut self) {
+        self.callbacks.retain(|c| !is_cancelled(&**c));
+        self.waiting_present.retain(|c| !is_cancelled(&**c));
     }
 }
 
 impl PSignalRuntimeRef {
+    /// Wraps `c` before storing it, to forget it if its token is cancelled, see `waiting`.
+    fn forget_when_cancelled<C, T>(&self, c: C) -> Waiting<C> where C: Continuation<T>, T: Send + Sync {
+        let sig = Arc::downgrade(&self.signal_runtime);
+        waiting(c, &*self.signal_runtime as *const _ as usize, move|| {
+            if let Some(sig) = sig.upgrade() {
+                sig.lock().unwrap().forget_cancelled();
+            }
+        })
+    }
+
     fn emit(self, runtime: &mut Runtime) {
         {
             let sig_run = self.signal_runtime.clone();
             let mut sig = sig_run.lock().unwrap();
-            while let Some(c) = sig.callbacks.pop() {
+            while let Some(c) = sig.callbacks.pop_front() {
                 runtime.on_current_instant(c);
             }
-            while let Some(c) = sig.waiting_present.pop() {
-                runtime.on_current_instant(Box::new(|runtime: &mut Runtime, ()| c.call_box(runtime, true)));
+            while let Some(c) = sig.waiting_present.pop_front() {
+                wake(runtime, c, true);
             }
             sig.status = true;
         }
@@ -53,6 +70,7 @@ impl PSignalRuntimeRef {
         if sig.status {
             runtime.on_current_instant(Box::new(c));
         } else {
+            let c = self.forget_when_cancelled(c);
             sig.add_callback(c);
         }
     }
@@ -67,12 +85,13 @@ impl PSignalRuntimeRef {
                 let sig_run = self.signal_runtime.clone();
                 runtime.on_end_of_instant(Box::new(move|runtime: &mut Runtime, ()| {
                     let mut sig = sig_run.lock().unwrap();
-                    while let Some(c) = sig.waiting_present.pop() {
-                        c.call_box(runtime, false)
+                    while let Some(c) = sig.waiting_present.pop_front() {
+                        call_ordered(runtime, c, false);
                     }
                 }));
             }
-            sig.waiting_present.push(Box::new(c));
+            let c = self.forget_when_cancelled(c);
+            sig.waiting_present.push_back(ordered(Box::new(c)));
         }
     }
 }
@@ -91,6 +110,12 @@ pub trait PSignal: 'static {
     fn present(&self) -> PPresent where Self: Sized {
         PPresent {signal: self.runtime()}
     }
+
+    /// An emission to perform atomically with others, see `emit_group`.
+    fn grouped(&self) -> GroupEmission where Self: Sized {
+        let signal = self.runtime();
+        GroupEmission::new(move|runtime: &mut Runtime, ()| signal.emit(runtime))
+    }
 }
 
 pub struct PureSignal {
@@ -99,11 +124,31 @@ pub struct PureSignal {
 
 impl PureSignal {
     pub fn new() -> PureSignal {
-        let runtime = PSignalRuntime {status: false, callbacks: vec!(), waiting_present: vec!()};
+        let runtime = PSignalRuntime {status: false, callbacks: VecDeque::new(), waiting_present: VecDeque::new()};
         PureSignal {
             runtime: PSignalRuntimeRef {signal_runtime: Arc::new(Mutex::new(runtime))}
         }
     }
+
+    /// A handle which does not keep the signal alive.
+    pub fn downgrade(&self) -> WeakPureSignal {
+        WeakPureSignal {signal_runtime: Arc::downgrade(&self.runtime.signal_runtime)}
+    }
+}
+
+/// A `PureSignal` handle which does not keep the signal alive, see `PureSignal::downgrade`.
+#[derive(Clone)]
+pub struct WeakPureSignal {
+    signal_runtime: Weak<Mutex<PSignalRuntime>>,
+}
+
+impl WeakPureSignal {
+    /// The signal, unless every `PureSignal` handle and every process using it was dropped.
+    pub fn upgrade(&self) -> Option<PureSignal> {
+        self.signal_runtime.upgrade().map(|signal_runtime| PureSignal {
+            runtime: PSignalRuntimeRef {signal_runtime}
+        })
+    }
 }
 
 impl Clone for PureSignal {
@@ -133,9 +178,13 @@ impl Process for PAwaitImmediate {
 impl ProcessMut for PAwaitImmediate {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, ())> {
         let sig = self.signal.clone();
-        self.signal.on_signal(runtime, |runtime: &mut Runtime, ()| {
-            next.call(runtime, (PAwaitImmediate {signal: sig}, ()))
-        });
+        self.signal.on_signal(runtime, next.map(move|()| (PAwaitImmediate {signal: sig}, ())));
+    }
+}
+
+impl CloneProcess for PAwaitImmediate {
+    fn clone_process(&self) -> Self {
+        PAwaitImmediate {signal: self.signal.clone()}
     }
 }
 
@@ -160,6 +209,12 @@ impl ProcessMut for PEmit {
     }
 }
 
+impl CloneProcess for PEmit {
+    fn clone_process(&self) -> Self {
+        PEmit {signal: self.signal.clone()}
+    }
+}
+
 pub struct PPresent {
     signal: PSignalRuntimeRef
 }
@@ -175,8 +230,12 @@ impl Process for PPresent {
 impl ProcessMut for PPresent {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, bool)> {
         let sig = self.signal.clone();
-        self.signal.test_present(runtime, move|runtime: &mut Runtime, status: bool| {
-            next.call(runtime, (PPresent {signal: sig}, status))
-        });
+        self.signal.test_present(runtime, next.map(move|status: bool| (PPresent {signal: sig}, status)));
+    }
+}
+
+impl CloneProcess for PPresent {
+    fn clone_process(&self) -> Self {
+        PPresent {signal: self.signal.clone()}
     }
 }
\ No newline at end of file
@@ -0,0 +1,89 @@
+use super::*;
+
+//  ____                 _ _   ____  _                   _
+// |  _ \ ___  ___ _   _| | |_/ ___|(_) __ _ _ __   __ _| |
+// | |_) / _ \/ __| | | | | __\___ \| |/ _` | '_ \ / _` | |
+// |  _ <  __/\__ \ |_| | | |_ ___) | | (_| | | | | (_| | |
+// |_| \_\___||___/\__,_|_|\__|____/|_|\__, |_| |_|\__,_|_|
+//                                     |___/
+
+enum ResultState<V> {
+    Pending(VecDeque<Box<Continuation<V>>>),
+    Resolved(V),
+}
+
+/// A one-shot signal, resolved with the value of a process once it terminates, see
+/// `task_with_result`.
+///
+/// Unlike the other signals the value is kept: awaiting a resolved signal resumes immediately with
+/// it, at any later instant.
+pub struct ResultSignal<V> where V: Clone + Send + Sync + 'static {
+    state: Arc<Mutex<ResultState<V>>>,
+}
+
+impl<V> Clone for ResultSignal<V> where V: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        ResultSignal { state: self.state.clone() }
+    }
+}
+
+impl<V> ResultSignal<V> where V: Clone + Send + Sync + 'static {
+    pub fn new() -> Self {
+        ResultSignal { state: Arc::new(Mutex::new(ResultState::Pending(VecDeque::new()))) }
+    }
+
+    /// Resolves the signal, waking its waiters during the current instant. Panics if the signal
+    /// was already resolved.
+    pub fn resolve(&self, runtime: &mut Runtime, value: V) {
+        let mut state = ResultState::Resolved(value.clone());
+        std::mem::swap(&mut state, &mut *self.state.lock().unwrap());
+        match state {
+            ResultState::Pending(waiters) => {
+                for c in waiters {
+                    let value = value.clone();
+                    runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| c.call_box(runtime, value)));
+                }
+            },
+            ResultState::Resolved(_) => panic!("ResultSignal resolved twice"),
+        }
+    }
+
+    /// The value of the signal, if it was resolved.
+    pub fn get(&self) -> Option<V> {
+        match *self.state.lock().unwrap() {
+            ResultState::Pending(_) => None,
+            ResultState::Resolved(ref value) => Some(value.clone()),
+        }
+    }
+
+    /// Waits for the signal to be resolved, and returns its value.
+    pub fn await(&self) -> ResultAwait<V> {
+        ResultAwait { signal: self.clone() }
+    }
+}
+
+pub struct ResultAwait<V> where V: Clone + Send + Sync + 'static {
+    signal: ResultSignal<V>,
+}
+
+impl<V> Process for ResultAwait<V> where V: Clone + Send + Sync + 'static {
+    type Value = V;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<V> {
+        let value = match *self.signal.state.lock().unwrap() {
+            ResultState::Pending(ref mut waiters) => {
+                waiters.push_back(Box::new(next));
+                return;
+            },
+            ResultState::Resolved(ref value) => value.clone(),
+        };
+        next.call(runtime, value);
+    }
+}
+
+impl<V> ProcessMut for ResultAwait<V> where V: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, V)> {
+        let signal = self.signal.clone();
+        self.call(runtime, next.map(move|v| (ResultAwait { signal }, v)));
+    }
+}
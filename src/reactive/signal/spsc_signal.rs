@@ -0,0 +1,205 @@
+extern crate crossbeam;
+
+use super::*;
+use self::crossbeam::sync::AtomicOption;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+//  ____  ____  ____   ____ ____  _                   _
+// / ___||  _ \/ ___| / ___/ ___|(_) __ _ _ __   __ _| |
+// \___ \| |_) \___ \| |   \___ \| |/ _` | '_ \ / _` | |
+//  ___) |  __/ ___) | |___ ___) | | (_| | | | | (_| | |
+// |____/|_|   |____/ \____|____/|_|\__, |_| |_|\__,_|_|
+//                                  |___/
+
+const ABSENT: usize = 0;
+/// The consumer is waiting, its continuation is in `waiter`.
+const WAITING: usize = 1;
+/// The signal was emitted during the current instant, its value is in `value`.
+const PRESENT: usize = 2;
+/// The signal is present and its value is being read or replaced, which only takes a swap.
+const BUSY: usize = 3;
+
+pub(crate) struct SPSCSignalRuntime<V> where V: Clone + Send + Sync + 'static {
+    status: AtomicUsize,
+    waiter: AtomicOption<Box<Continuation<V>>>,
+    value: AtomicOption<V>,
+}
+
+impl<V> SPSCSignalRuntime<V> where V: Clone + Send + Sync + 'static {
+    pub(crate) fn new() -> Self {
+        SPSCSignalRuntime { status: AtomicUsize::new(ABSENT), waiter: AtomicOption::new(), value: AtomicOption::new() }
+    }
+
+    /// A copy of the value of the instant, only called while it cannot be replaced.
+    fn read(&self) -> V {
+        let v = self.value.take(Ordering::SeqCst).expect("the value of a present SPSC signal is missing");
+        let copy = v.clone();
+        self.value.swap(v, Ordering::SeqCst);
+        copy
+    }
+
+    /// Runs `f` while the signal is held `BUSY`, once it is present.
+    fn holding_present<F, R>(&self, f: F) -> R where F: FnOnce() -> R {
+        while self.status.compare_exchange(PRESENT, BUSY, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            thread::yield_now();
+        }
+        let result = f();
+        self.status.store(PRESENT, Ordering::SeqCst);
+        result
+    }
+
+    /// Emits `value`, replacing the value of an earlier emission of the instant like the unique
+    /// producer signals do.
+    pub(crate) fn emit(sig: &Arc<Self>, runtime: &mut Runtime, value: V) {
+        let mut value = Some(value);
+        loop {
+            match sig.status.load(Ordering::SeqCst) {
+                PRESENT | BUSY => {
+                    let value = value.take();
+                    // Already present, the consumer may be reading the value of an earlier emission.
+                    return sig.holding_present(|| if let Some(value) = value { sig.value.swap(value, Ordering::SeqCst); });
+                },
+                status => {
+                    // The consumer only reads the value once the signal is present, and the
+                    // producer is unique, so nobody else touches it now.
+                    if let Some(value) = value.take() {
+                        sig.value.swap(value, Ordering::SeqCst);
+                    }
+                    if sig.status.compare_exchange(status, PRESENT, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+                        // The consumer started or stopped waiting in the meantime.
+                        continue;
+                    }
+                    if status == WAITING {
+                        let c = sig.waiter.take(Ordering::SeqCst).expect("the waiter of a SPSC signal is missing");
+                        let value = sig.holding_present(|| sig.read());
+                        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| c.call_box(runtime, value)));
+                    }
+                    let sig = sig.clone();
+                    return runtime.on_end_of_instant(Box::new(move|_: &mut Runtime, ()| sig.reset()));
+                },
+            }
+        }
+    }
+
+    /// Makes the signal present with `value`, for a signal switching to this runtime during an
+    /// instant in which it was emitted, before anyone uses it.
+    pub(crate) fn set_present(&self, value: V) {
+        self.value.swap(value, Ordering::SeqCst);
+        self.status.store(PRESENT, Ordering::SeqCst);
+    }
+
+    /// Makes the signal absent at the end of an instant in which it was emitted, unless it was
+    /// already reset.
+    pub(crate) fn reset(&self) {
+        loop {
+            match self.status.compare_exchange(PRESENT, ABSENT, Ordering::SeqCst, Ordering::SeqCst) {
+                Err(BUSY) => thread::yield_now(),
+                _ => return,
+            }
+        }
+    }
+
+    pub(crate) fn on_signal<C>(&self, runtime: &mut Runtime, c: C) where C: Continuation<V> {
+        let mut c: Box<Continuation<V>> = Box::new(c);
+        loop {
+            match self.status.load(Ordering::SeqCst) {
+                ABSENT => {
+                    self.waiter.swap(c, Ordering::SeqCst);
+                    if self.status.compare_exchange(ABSENT, WAITING, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+                        return;
+                    }
+                    // Emitted in the meantime, the producer did not see the waiter.
+                    c = self.waiter.take(Ordering::SeqCst).expect("the waiter of a SPSC signal is missing");
+                },
+                WAITING => panic!("the consumer of a SPSC signal waits twice"),
+                _ => {
+                    let value = self.holding_present(|| self.read());
+                    return runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| c.call_box(runtime, value)));
+                },
+            }
+        }
+    }
+}
+
+/// The producer of a signal with a single producer and a single consumer, whose emissions and
+/// waits only use atomic operations instead of locking the signal.
+///
+/// Neither handle can be cloned, and both are moved into the process using them, which gives
+/// them back when executed as a `ProcessMut`: the consumer thus never has more than one waiting
+/// process. In exchange for this, the signal cannot be tested for presence. An emission replaces
+/// the value of an earlier one of the same instant, like with the unique producer signals.
+///
+/// The unique producer signals switch to the same runtime by themselves once their consumer is
+/// provably unique, see `UPAwaitImmediate`: this signal only spares the check.
+pub struct SPSCSignalProducer<V> where V: Clone + Send + Sync + 'static {
+    runtime: Arc<SPSCSignalRuntime<V>>,
+}
+
+impl<V> SPSCSignalProducer<V> where V: Clone + Send + Sync + 'static {
+    pub fn new() -> (SPSCSignalProducer<V>, SPSCSignalConsumer<V>) {
+        let runtime = Arc::new(SPSCSignalRuntime::new());
+        (SPSCSignalProducer { runtime: runtime.clone() }, SPSCSignalConsumer { runtime })
+    }
+
+    pub fn emit<P>(self, value: P) -> SPSCEmit<V, P> where P: Process<Value = V> {
+        SPSCEmit { signal: self.runtime, value }
+    }
+}
+
+/// The consumer of a signal with a single producer and a single consumer, see
+/// `SPSCSignalProducer`.
+pub struct SPSCSignalConsumer<V> where V: Clone + Send + Sync + 'static {
+    runtime: Arc<SPSCSignalRuntime<V>>,
+}
+
+impl<V> SPSCSignalConsumer<V> where V: Clone + Send + Sync + 'static {
+    pub fn await_immediate(self) -> SPSCAwaitImmediate<V> {
+        SPSCAwaitImmediate { signal: self.runtime }
+    }
+}
+
+pub struct SPSCAwaitImmediate<V> where V: Clone + Send + Sync + 'static {
+    signal: Arc<SPSCSignalRuntime<V>>,
+}
+
+impl<V> Process for SPSCAwaitImmediate<V> where V: Clone + Send + Sync + 'static {
+    type Value = V;
+
+    fn call<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<V> {
+        self.signal.on_signal(runtime, c);
+    }
+}
+
+impl<V> ProcessMut for SPSCAwaitImmediate<V> where V: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, V)> {
+        let sig = self.signal.clone();
+        self.signal.on_signal(runtime, next.map(move|v| (SPSCAwaitImmediate { signal: sig }, v)));
+    }
+}
+
+pub struct SPSCEmit<V, P> where V: Clone + Send + Sync + 'static, P: Process<Value = V> {
+    signal: Arc<SPSCSignalRuntime<V>>,
+    value: P,
+}
+
+impl<V, P> Process for SPSCEmit<V, P> where V: Clone + Send + Sync + 'static, P: Process<Value = V> {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<()> {
+        let sig = self.signal;
+        self.value.call(runtime, move|runtime: &mut Runtime, v| {
+            SPSCSignalRuntime::emit(&sig, runtime, v);
+            c.call(runtime, ());
+        });
+    }
+}
+
+impl<V, P> ProcessMut for SPSCEmit<V, P> where V: Clone + Send + Sync + 'static, P: ProcessMut<Value = V> {
+    fn call_mut<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<(Self, ())> {
+        let sig = self.signal;
+        self.value.call_mut(runtime, move|runtime: &mut Runtime, (process, v)| {
+            SPSCSignalRuntime::emit(&sig, runtime, v);
+            c.call(runtime, (SPSCEmit { signal: sig, value: process }, ()));
+        });
+    }
+}
@@ -0,0 +1,252 @@
+use super::*;
+
+//  _____                               _   _             ____  _                   _
+// |_   _| __ __ _ _ __  ___  __ _  ___| |_(_) ___  _ __ / ___|(_) __ _ _ __   __ _| |
+//   | || '__/ _` | '_ \/ __|/ _` |/ __| __| |/ _ \| '_ \\___ \| |/ _` | '_ \ / _` | |
+//   | || | | (_| | | | \__ \ (_| | (__| |_| | (_) | | | |___) | | (_| | | | | (_| | |
+//   |_||_|  \__,_|_| |_|___/\__,_|\___|\__|_|\___/|_| |_|____/|_|\__, |_| |_|\__,_|_|
+//                                                                |___/
+
+/// What becomes of a batch rejected by the arbiter of a `TransactionSignal`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectionPolicy {
+    /// The batch is dropped.
+    Drop,
+    /// The batch is submitted again, alone, at the next end of instant, at most the given number of
+    /// times, and dropped once rejected again.
+    Retry(usize),
+    /// The processes waiting with `await_rejection` resume with the batch, which is dropped.
+    Report,
+}
+
+/// Builds the process deciding whether a batch is committed.
+type Arbiter<G> = Arc<Fn(Vec<G>) -> ProcessBox<bool> + Send + Sync>;
+
+struct TSignalRuntime<G> where G: Clone + Send + Sync + 'static {
+    /// The proposals of the instant.
+    proposals: Vec<G>,
+    /// The rejected batches to submit again at the end of the instant, with their number of
+    /// rejections.
+    retried: Vec<(Vec<G>, usize)>,
+    /// The batches waiting for the arbiter, with their number of rejections.
+    submitted: VecDeque<(Vec<G>, usize)>,
+    /// Whether the arbiter is deciding on a batch.
+    arbitrating: bool,
+    /// Whether the batches are submitted at the end of the current instant.
+    scheduled: bool,
+    arbiter: Arbiter<G>,
+    policy: RejectionPolicy,
+    waiting_commit: VecDeque<Box<Continuation<Vec<G>>>>,
+    waiting_rejection: VecDeque<Box<Continuation<Vec<G>>>>,
+}
+
+type SharedTSignalRuntime<G> = Arc<Mutex<TSignalRuntime<G>>>;
+
+/// A signal whose producers propose values during an instant, which are all applied or none.
+///
+/// At the end of an instant with proposals, the arbiter process decides whether to accept the
+/// whole batch of the instant, the batches of the next instants waiting for its verdict. An
+/// accepted batch is committed: the awaiting processes resume with it, at the next instant for an
+/// arbiter deciding without waiting. A rejected batch is never merged with other proposals, what
+/// becomes of it depends on the `RejectionPolicy` of the signal.
+///
+/// The order of the proposals of an instant is the order of their emissions, which is only
+/// deterministic with a sequential runtime.
+pub struct TransactionSignal<G> where G: Clone + Send + Sync + 'static {
+    signal_runtime: SharedTSignalRuntime<G>,
+}
+
+impl<G> Clone for TransactionSignal<G> where G: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        TransactionSignal { signal_runtime: self.signal_runtime.clone() }
+    }
+}
+
+impl<G> TransactionSignal<G> where G: Clone + Send + Sync + 'static {
+    /// A signal whose batches are decided on by the process returned by `arbiter`, which is
+    /// started at the end of the instant with the batch.
+    pub fn new<F, P>(arbiter: F, policy: RejectionPolicy) -> Self
+        where F: Fn(Vec<G>) -> P + Send + Sync + 'static, P: Process<Value = bool> {
+        let runtime = TSignalRuntime {
+            proposals: Vec::new(),
+            retried: Vec::new(),
+            submitted: VecDeque::new(),
+            arbitrating: false,
+            scheduled: false,
+            arbiter: Arc::new(move|batch| arbiter(batch).boxed()),
+            policy,
+            waiting_commit: VecDeque::new(),
+            waiting_rejection: VecDeque::new(),
+        };
+        TransactionSignal { signal_runtime: Arc::new(Mutex::new(runtime)) }
+    }
+
+    /// Proposes the value of `value` for the batch of the instant.
+    pub fn propose<P>(&self, value: P) -> TPropose<G, P> where P: Process<Value = G> {
+        TPropose { signal: self.clone(), value }
+    }
+
+    /// Waits for a batch to be committed, and resumes with it.
+    pub fn await_commit(&self) -> TAwaitCommit<G> {
+        TAwaitCommit { signal: self.clone() }
+    }
+
+    /// Waits for a batch to be rejected, and resumes with it. Batches are only reported with
+    /// `RejectionPolicy::Report`.
+    pub fn await_rejection(&self) -> TAwaitRejection<G> {
+        TAwaitRejection { signal: self.clone() }
+    }
+
+    fn add_proposal(&self, runtime: &mut Runtime, value: G) {
+        let mut sig = self.signal_runtime.lock().unwrap();
+        sig.proposals.push(value);
+        schedule_submission(&self.signal_runtime, &mut sig, runtime);
+    }
+
+    fn on_commit<C>(&self, c: C) where C: Continuation<Vec<G>> {
+        self.signal_runtime.lock().unwrap().waiting_commit.push_back(Box::new(c));
+    }
+
+    fn on_rejection<C>(&self, c: C) where C: Continuation<Vec<G>> {
+        self.signal_runtime.lock().unwrap().waiting_rejection.push_back(Box::new(c));
+    }
+}
+
+/// Submits the batches of the instant to the arbiter at its end, once per instant.
+fn schedule_submission<G>(sig_run: &SharedTSignalRuntime<G>, sig: &mut TSignalRuntime<G>, runtime: &mut Runtime)
+    where G: Clone + Send + Sync + 'static {
+    if sig.scheduled {
+        return;
+    }
+    sig.scheduled = true;
+    let sig_run = sig_run.clone();
+    runtime.on_end_of_instant(Box::new(move|runtime: &mut Runtime, ()| {
+        {
+            let mut guard = sig_run.lock().unwrap();
+            let sig = &mut *guard;
+            sig.scheduled = false;
+            sig.submitted.extend(sig.retried.drain(..));
+            if !sig.proposals.is_empty() {
+                let batch = std::mem::replace(&mut sig.proposals, Vec::new());
+                sig.submitted.push_back((batch, 0));
+            }
+        }
+        arbitrate_next(sig_run, runtime);
+    }));
+}
+
+/// Starts the arbiter on the next submitted batch, unless it is deciding on another one.
+fn arbitrate_next<G>(sig_run: SharedTSignalRuntime<G>, runtime: &mut Runtime) where G: Clone + Send + Sync + 'static {
+    let (batch, rejections, arbiter) = {
+        let mut sig = sig_run.lock().unwrap();
+        if sig.arbitrating {
+            return;
+        }
+        match sig.submitted.pop_front() {
+            Some((batch, rejections)) => {
+                sig.arbitrating = true;
+                (batch, rejections, sig.arbiter.clone())
+            },
+            None => return,
+        }
+    };
+    arbiter(batch.clone()).call(runtime, move|runtime: &mut Runtime, accepted: bool| {
+        {
+            let mut guard = sig_run.lock().unwrap();
+            let sig = &mut *guard;
+            sig.arbitrating = false;
+            if accepted {
+                resume_all(runtime, &mut sig.waiting_commit, batch);
+            } else {
+                match sig.policy {
+                    RejectionPolicy::Retry(retries) if rejections < retries => {
+                        sig.retried.push((batch, rejections + 1));
+                        schedule_submission(&sig_run, sig, runtime);
+                    },
+                    RejectionPolicy::Report => resume_all(runtime, &mut sig.waiting_rejection, batch),
+                    _ => (),
+                }
+            }
+        }
+        arbitrate_next(sig_run, runtime);
+    });
+}
+
+/// Resumes the `waiting` continuations with `batch`, at the next instant when called at the end of
+/// an instant.
+fn resume_all<G>(runtime: &mut Runtime, waiting: &mut VecDeque<Box<Continuation<Vec<G>>>>, batch: Vec<G>)
+    where G: Clone + Send + Sync + 'static {
+    while let Some(c) = waiting.pop_front() {
+        let batch = batch.clone();
+        runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| c.call_box(runtime, batch)));
+    }
+}
+
+pub struct TPropose<G, P> where G: Clone + Send + Sync + 'static, P: Process<Value = G> {
+    signal: TransactionSignal<G>,
+    value: P,
+}
+
+impl<G, P> Process for TPropose<G, P> where G: Clone + Send + Sync + 'static, P: Process<Value = G> {
+    type Value = ();
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        let signal = self.signal;
+        self.value.call(runtime, move|runtime: &mut Runtime, v: G| {
+            signal.add_proposal(runtime, v);
+            next.call(runtime, ());
+        });
+    }
+}
+
+impl<G, P> ProcessMut for TPropose<G, P> where G: Clone + Send + Sync + 'static, P: ProcessMut<Value = G> {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, ())> {
+        let signal = self.signal;
+        self.value.call_mut(runtime, move|runtime: &mut Runtime, (value, v): (P, G)| {
+            signal.add_proposal(runtime, v);
+            next.call(runtime, (TPropose { signal, value }, ()));
+        });
+    }
+}
+
+pub struct TAwaitCommit<G> where G: Clone + Send + Sync + 'static {
+    signal: TransactionSignal<G>,
+}
+
+impl<G> Process for TAwaitCommit<G> where G: Clone + Send + Sync + 'static {
+    type Value = Vec<G>;
+
+    fn call<C>(self, _: &mut Runtime, next: C) where C: Continuation<Vec<G>> {
+        self.signal.on_commit(next);
+    }
+}
+
+impl<G> ProcessMut for TAwaitCommit<G> where G: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, _: &mut Runtime, next: C) where C: Continuation<(Self, Vec<G>)> {
+        let signal = self.signal.clone();
+        self.signal.on_commit(move|runtime: &mut Runtime, batch| {
+            next.call(runtime, (TAwaitCommit { signal }, batch))
+        });
+    }
+}
+
+pub struct TAwaitRejection<G> where G: Clone + Send + Sync + 'static {
+    signal: TransactionSignal<G>,
+}
+
+impl<G> Process for TAwaitRejection<G> where G: Clone + Send + Sync + 'static {
+    type Value = Vec<G>;
+
+    fn call<C>(self, _: &mut Runtime, next: C) where C: Continuation<Vec<G>> {
+        self.signal.on_rejection(next);
+    }
+}
+
+impl<G> ProcessMut for TAwaitRejection<G> where G: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, _: &mut Runtime, next: C) where C: Continuation<(Self, Vec<G>)> {
+        let signal = self.signal.clone();
+        self.signal.on_rejection(move|runtime: &mut Runtime, batch| {
+            next.call(runtime, (TAwaitRejection { signal }, batch))
+        });
+    }
+}
@@ -18,35 +18,72 @@ impl<V, G> Clone for UCSignalRuntimeRef<V, G> where V: Sized + Send + Sync + 'st
 }
 
 struct UCSignalRuntime<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
-    callbacks: Vec<Box<Continuation<()>>>,
-    waiting_present: Vec<Box<Continuation<bool>>>,
+    /// Woken first-in first-out, like `waiting_present`.
+    callbacks: VecDeque<Box<Continuation<()>>>,
+    waiting_present: VecDeque<Box<Continuation<bool>>>,
     waiting_await: Option<Box<Continuation<V>>>,
+    /// Whether `waiting_await` must only be woken once every producer emitted in the instant.
+    await_all_producers: bool,
     status: bool,
     gather: Box<Fn(V, G) -> V + Send + Sync>,
     default_value: Box<Fn() -> V + Send + Sync>,
     current_value: V,
+    /// Number of live producer handles.
+    producers: usize,
+    next_producer_id: usize,
+    /// Producers that emitted during the current instant.
+    emitted_by: HashSet<usize>,
 }
 
 impl<V, G> UCSignalRuntime<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
     fn add_callback<C>(&mut self, c: C) where C: Continuation<()> {
-        self.callbacks.push(Box::new(c));
+        self.callbacks.push_back(Box::new(c));
+    }
+
+    fn forget_cancelled(&mut self) {
+        self.callbacks.retain(|c| !is_cancelled(&**c));
+        self.waiting_present.retain(|c| !is_cancelled(&**c));
+        if self.waiting_await.as_ref().map_or(false, |c| is_cancelled(&**c)) {
+            self.waiting_await = None;
+        }
+    }
+
+    /// Stores the waiter of the consumer, which must not be waiting already unless its token was
+    /// cancelled.
+    fn set_waiting_await(&mut self, c: Box<Continuation<V>>, all_producers: bool) {
+        if self.waiting_await.as_ref().map_or(false, |c| !is_cancelled(&**c)) {
+            unreachable!();
+        }
+        self.waiting_await = Some(c);
+        self.await_all_producers = all_producers;
     }
 }
 
 impl<V, G> UCSignalRuntimeRef<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
-    fn emit(self, runtime: &mut Runtime, value: G) {
+    /// Wraps `c` before storing it, to forget it if its token is cancelled, see `waiting`.
+    fn forget_when_cancelled<C, T>(&self, c: C) -> Waiting<C> where C: Continuation<T>, T: Send + Sync {
+        let sig = Arc::downgrade(&self.signal_runtime);
+        waiting(c, &*self.signal_runtime as *const _ as usize, move|| {
+            if let Some(sig) = sig.upgrade() {
+                sig.lock().unwrap().forget_cancelled();
+            }
+        })
+    }
+
+    fn emit(self, runtime: &mut Runtime, producer: usize, value: G) {
         {
             let sig_run = self.signal_runtime.clone();
             let mut sig = sig_run.lock().unwrap();
-            while let Some(c) = sig.callbacks.pop() {
+            while let Some(c) = sig.callbacks.pop_front() {
                 runtime.on_current_instant(c);
             }
-            while let Some(c) = sig.waiting_present.pop() {
+            while let Some(c) = sig.waiting_present.pop_front() {
                 runtime.on_current_instant(Box::new(|runtime: &mut Runtime, ()| c.call_box(runtime, true)));
             }
             let mut prev_value = (sig.default_value)();
             std::mem::swap(&mut prev_value, &mut sig.current_value);
             sig.current_value = (sig.gather)(prev_value, value);
+            sig.emitted_by.insert(producer);
             sig.status = true;
         }
 
@@ -57,7 +94,10 @@ impl<V, G> UCSignalRuntimeRef<V, G> where V: Sized + Send + Sync + 'static, G: '
                 let mut prev_value = (sig.default_value)();
                 std::mem::swap(&mut prev_value, &mut sig.current_value);
                 let mut waiting: Option<Box<Continuation<V>>> = None;
-                std::mem::swap(&mut waiting, &mut sig.waiting_await);
+                if !sig.await_all_producers || sig.emitted_by.len() >= sig.producers {
+                    std::mem::swap(&mut waiting, &mut sig.waiting_await);
+                }
+                sig.emitted_by.clear();
                 if let Some(c) = waiting {
                     runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
                         c.call_box(runtime, prev_value);
@@ -74,17 +114,36 @@ impl<V, G> UCSignalRuntimeRef<V, G> where V: Sized + Send + Sync + 'static, G: '
         if sig.status {
             runtime.on_current_instant(Box::new(c));
         } else {
+            let c = self.forget_when_cancelled(c);
             sig.add_callback(c);
         }
     }
 
     fn await<C>(self, c: C) where C: Continuation<V> {
-        let sig_ref = self.clone();
-        let mut sig = sig_ref.signal_runtime.lock().unwrap();
-        if let Some(_) = sig.waiting_await {
-            unreachable!();
-        }
-        sig.waiting_await = Some(Box::new(c));
+        let c = self.forget_when_cancelled(c);
+        self.signal_runtime.lock().unwrap().set_waiting_await(Box::new(c), false);
+    }
+
+    fn await_all<C>(self, c: C) where C: Continuation<V> {
+        let c = self.forget_when_cancelled(c);
+        self.signal_runtime.lock().unwrap().set_waiting_await(Box::new(c), true);
+    }
+
+    fn register_producer(&self) -> usize {
+        let mut sig = self.signal_runtime.lock().unwrap();
+        let id = sig.next_producer_id;
+        sig.next_producer_id += 1;
+        sig.producers += 1;
+        id
+    }
+
+    fn unregister_producer(&self) {
+        let mut sig = self.signal_runtime.lock().unwrap();
+        sig.producers -= 1;
+    }
+
+    fn producer_count(&self) -> usize {
+        self.signal_runtime.lock().unwrap().producers
     }
 
     fn test_present<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<bool> {
@@ -97,12 +156,13 @@ impl<V, G> UCSignalRuntimeRef<V, G> where V: Sized + Send + Sync + 'static, G: '
                 let sig_run = self.signal_runtime.clone();
                 runtime.on_end_of_instant(Box::new(move|runtime: &mut Runtime, ()| {
                     let mut sig = sig_run.lock().unwrap();
-                    while let Some(c) = sig.waiting_present.pop() {
+                    while let Some(c) = sig.waiting_present.pop_front() {
                         c.call_box(runtime, false)
                     }
                 }));
             }
-            sig.waiting_present.push(Box::new(c));
+            let c = self.forget_when_cancelled(c);
+            sig.waiting_present.push_back(Box::new(c));
         }
     }
 }
@@ -110,12 +170,15 @@ impl<V, G> UCSignalRuntimeRef<V, G> where V: Sized + Send + Sync + 'static, G: '
 pub trait UCSignal<V, G>: 'static where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
     fn runtime(&self) -> UCSignalRuntimeRef<V, G>;
 
+    /// Identifies the producer handle in `UCSignalConsumer::await_all_producers`.
+    fn producer_id(&self) -> usize;
+
     fn await_immediate(&self) -> UCAwaitImmediate<V, G> where Self: Sized {
         UCAwaitImmediate {signal: self.runtime()}
     }
 
     fn emit<P>(&self, value: P) -> UCEmit<V, G, P> where Self: Sized, P: Process<Value = G> {
-        UCEmit {signal: self.runtime(), value}
+        UCEmit {signal: self.runtime(), producer: self.producer_id(), value}
     }
 
     fn present(&self) -> UCPresent<V, G> where Self: Sized {
@@ -129,26 +192,43 @@ pub trait UCSignalConsumer<V, G>: 'static where V: Sized + Send + Sync + 'static
     fn await(self) -> UCAwait<V, G> where Self: Sized {
         UCAwait {signal: self.runtime()}
     }
+
+    /// Like `await`, but only resumes with the value of an instant in which every live producer
+    /// handle emitted. Values gathered during the other instants are discarded.
+    fn await_all_producers(self) -> UCAwaitAllProducers<V, G> where Self: Sized {
+        UCAwaitAllProducers {signal: self.runtime()}
+    }
+
+    /// Number of live producer handles, each clone of a producer counting as one.
+    fn producer_count(&self) -> usize {
+        self.runtime().producer_count()
+    }
 }
 
 pub struct UniqueConsumerSignalProducer<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
-    runtime: UCSignalRuntimeRef<V, G>
+    runtime: UCSignalRuntimeRef<V, G>,
+    id: usize,
 }
 
 impl<V, G> UniqueConsumerSignalProducer<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
     pub fn new(default_value: Box<Fn() -> V + Send + Sync>, gather: Box<Fn(V, G) -> V + Send + Sync>) -> (UniqueConsumerSignalProducer<V, G>, UniqueConsumerSignalConsumer<V, G>) {
         let runtime = UCSignalRuntime {
             status: false,
-            callbacks: vec!(),
-            waiting_present: vec!(),
+            callbacks: VecDeque::new(),
+            waiting_present: VecDeque::new(),
             waiting_await: None,
+            await_all_producers: false,
             current_value: default_value(),
             default_value,
             gather,
+            producers: 0,
+            next_producer_id: 0,
+            emitted_by: HashSet::new(),
         };
         let signal_run = UCSignalRuntimeRef {signal_runtime: Arc::new(Mutex::new(runtime))};
         (
             UniqueConsumerSignalProducer {
+                id: signal_run.register_producer(),
                 runtime: signal_run.clone()
             },
             UniqueConsumerSignalConsumer {
@@ -160,7 +240,13 @@ impl<V, G> UniqueConsumerSignalProducer<V, G> where V: Sized + Send + Sync + 'st
 
 impl<V, G> Clone for UniqueConsumerSignalProducer<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
     fn clone(&self) -> Self {
-        UniqueConsumerSignalProducer {runtime: self.runtime.clone()}
+        UniqueConsumerSignalProducer {id: self.runtime.register_producer(), runtime: self.runtime.clone()}
+    }
+}
+
+impl<V, G> Drop for UniqueConsumerSignalProducer<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
+    fn drop(&mut self) {
+        self.runtime.unregister_producer();
     }
 }
 
@@ -168,6 +254,10 @@ impl<V, G> UCSignal<V, G> for UniqueConsumerSignalProducer<V, G> where V: Sized
     fn runtime(&self) -> UCSignalRuntimeRef<V, G> {
         self.runtime.clone()
     }
+
+    fn producer_id(&self) -> usize {
+        self.id
+    }
 }
 
 pub struct UniqueConsumerSignalConsumer<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
@@ -195,9 +285,7 @@ impl<V, G> Process for UCAwaitImmediate<V, G> where V: Sized + Send + Sync + 'st
 impl<V, G> ProcessMut for UCAwaitImmediate<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, ())> {
         let sig = self.signal.clone();
-        self.signal.on_signal(runtime, |runtime: &mut Runtime, ()| {
-            next.call(runtime, (UCAwaitImmediate {signal: sig}, ()))
-        });
+        self.signal.on_signal(runtime, next.map(move|()| (UCAwaitImmediate {signal: sig}, ())));
     }
 }
 
@@ -216,14 +304,32 @@ impl<V, G> Process for UCAwait<V, G> where V: Sized + Send + Sync + 'static, G:
 impl<V, G> ProcessMut for UCAwait<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
     fn call_mut<C>(self, _: &mut Runtime, next: C) where C: Continuation<(Self, V)> {
         let sig = self.signal.clone();
-        self.signal.await(|runtime: &mut Runtime, v| {
-            next.call(runtime, (UCAwait {signal: sig}, v))
-        });
+        self.signal.await(next.map(move|v| (UCAwait {signal: sig}, v)));
+    }
+}
+
+pub struct UCAwaitAllProducers<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync  {
+    signal: UCSignalRuntimeRef<V, G>
+}
+
+impl<V, G> Process for UCAwaitAllProducers<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
+    type Value = V;
+
+    fn call<C>(self, _: &mut Runtime, c: C) where C: Continuation<V> {
+        self.signal.await_all(c);
+    }
+}
+
+impl<V, G> ProcessMut for UCAwaitAllProducers<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
+    fn call_mut<C>(self, _: &mut Runtime, next: C) where C: Continuation<(Self, V)> {
+        let sig = self.signal.clone();
+        self.signal.await_all(next.map(move|v| (UCAwaitAllProducers {signal: sig}, v)));
     }
 }
 
 pub struct UCEmit<V, G, P> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync, P: Process<Value = G> {
     signal: UCSignalRuntimeRef<V, G>,
+    producer: usize,
     value: P,
 }
 
@@ -233,8 +339,9 @@ impl<V, G, P> Process for UCEmit<V, G, P> where V: Sized + Send + Sync + 'static
     fn call<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<()> {
         let sig = self.signal.clone();
 
+        let producer = self.producer;
         self.value.call(runtime, move|runtime: &mut Runtime, v| {
-            sig.emit(runtime, v);
+            sig.emit(runtime, producer, v);
             c.call(runtime, ());
         });
     }
@@ -244,9 +351,10 @@ impl<V, G, P> ProcessMut for UCEmit<V, G, P> where V: Sized + Send + Sync + 'sta
     fn call_mut<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<(Self, ())> {
         let sig = self.signal.clone();
 
+        let producer = self.producer;
         self.value.call_mut(runtime, move|runtime: &mut Runtime, (process, v)| {
-            sig.clone().emit(runtime, v);
-            c.call(runtime, (UCEmit {signal: sig, value: process}, ()));
+            sig.clone().emit(runtime, producer, v);
+            c.call(runtime, (UCEmit {signal: sig, producer, value: process}, ()));
         });
     }
 }
@@ -266,8 +374,6 @@ impl<V, G> Process for UCPresent<V, G> where V: Sized + Send + Sync + 'static, G
 impl<V, G> ProcessMut for UCPresent<V, G> where V: Sized + Send + Sync + 'static, G: 'static + Send + Sync {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, bool)> {
         let sig = self.signal.clone();
-        self.signal.test_present(runtime, move|runtime: &mut Runtime, status: bool| {
-            next.call(runtime, (UCPresent {signal: sig}, status))
-        });
+        self.signal.test_present(runtime, next.map(move|status: bool| (UCPresent {signal: sig}, status)));
     }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 use super::*;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 //  _   _ ____  ____  _                   _
 // | | | |  _ \/ ___|(_) __ _ _ __   __ _| |
@@ -9,17 +10,29 @@ use super::*;
 
 pub struct UPSignalRuntimeRef<V> where V: Clone + Send + Sync + Sized + 'static {
     signal_runtime: Arc<Mutex<UPSignalRuntime<V>>>,
+    /// The lock-free runtime the signal switches to for good once its consumer is provably
+    /// unique, see `specialize`.
+    spsc: Arc<SPSCSignalRuntime<V>>,
+    specialized: Arc<AtomicBool>,
+    /// Counts the handles and processes of the consumer, `None` for those of the producer.
+    consumers: Option<Arc<()>>,
 }
 
 impl<V> Clone for UPSignalRuntimeRef<V> where V: Clone + Send + Sync + Sized + 'static {
     fn clone(&self) -> Self {
-        UPSignalRuntimeRef {signal_runtime: self.signal_runtime.clone()}
+        UPSignalRuntimeRef {
+            signal_runtime: self.signal_runtime.clone(),
+            spsc: self.spsc.clone(),
+            specialized: self.specialized.clone(),
+            consumers: self.consumers.clone(),
+        }
     }
 }
 
 struct UPSignalRuntime<V> where V: Clone + Send + Sync + Sized + 'static {
-    callbacks: Vec<Box<Continuation<V>>>,
-    waiting_present: Vec<Box<Continuation<bool>>>,
+    /// Woken in registration order with the emitted value.
+    callbacks: VecDeque<Box<Continuation<V>>>,
+    waiting_present: VecDeque<Box<Continuation<bool>>>,
     status: bool,
     default_value: V,
     current_value: V,
@@ -27,43 +40,101 @@ struct UPSignalRuntime<V> where V: Clone + Send + Sync + Sized + 'static {
 
 impl<V> UPSignalRuntime<V> where V: Clone + Send + Sync + Sized + 'static {
     fn add_callback<C>(&mut self, c: C) where C: Continuation<V> {
-        self.callbacks.push(Box::new(c));
+        self.callbacks.push_back(Box::new(c));
+    }
+
+    fn forget_cancelled(&mut self) {
+        self.callbacks.retain(|c| !is_cancelled(&**c));
+        self.waiting_present.retain(|c| !is_cancelled(&**c));
     }
 }
 
 impl<V> UPSignalRuntimeRef<V> where V: Clone + Send + Sync + Sized + 'static {
-    fn emit(self, runtime: &mut Runtime, value: V) {
+    /// Wraps `c` before storing it, to forget it if its token is cancelled, see `waiting`.
+    fn forget_when_cancelled<C, T>(&self, c: C) -> Waiting<C> where C: Continuation<T>, T: Send + Sync {
+        let sig = Arc::downgrade(&self.signal_runtime);
+        waiting(c, &*self.signal_runtime as *const _ as usize, move|| {
+            if let Some(sig) = sig.upgrade() {
+                sig.lock().unwrap().forget_cancelled();
+            }
+        })
+    }
+
+    /// Whether this is the last handle or process of the consumer, which then cannot be cloned
+    /// anymore.
+    fn has_unique_consumer(&self) -> bool {
+        self.consumers.as_ref().map_or(false, |consumers| Arc::strong_count(consumers) == 1)
+    }
+
+    /// Switches the signal to its lock-free runtime if `unique`, see `has_unique_consumer`, and no
+    /// other waiter is stored, the consumer then waiting once at a time. Returns whether the
+    /// signal is switched.
+    fn specialize(&self, unique: bool) -> bool {
+        if self.specialized.load(Ordering::SeqCst) {
+            return true;
+        }
+        if !unique {
+            return false;
+        }
+        let sig = self.signal_runtime.lock().unwrap();
+        if !sig.callbacks.is_empty() || !sig.waiting_present.is_empty() {
+            return false;
+        }
+        if sig.status {
+            self.spsc.set_present(sig.current_value.clone());
+        }
+        // The producer checks it again under the lock before emitting.
+        self.specialized.store(true, Ordering::SeqCst);
+        true
+    }
+
+    pub(crate) fn emit(self, runtime: &mut Runtime, value: V) {
+        if self.specialized.load(Ordering::SeqCst) {
+            return SPSCSignalRuntime::emit(&self.spsc, runtime, value);
+        }
         {
             let sig_run = self.signal_runtime.clone();
             let mut sig = sig_run.lock().unwrap();
+            if self.specialized.load(Ordering::SeqCst) {
+                drop(sig);
+                return SPSCSignalRuntime::emit(&self.spsc, runtime, value);
+            }
             sig.current_value = value;
             sig.status = true;
-            while let Some(c) = sig.callbacks.pop() {
+            while let Some(c) = sig.callbacks.pop_front() {
                 let value = sig.current_value.clone();
                 runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| c.call_box(runtime, value)));
             }
-            while let Some(c) = sig.waiting_present.pop() {
+            while let Some(c) = sig.waiting_present.pop_front() {
                 runtime.on_current_instant(Box::new(|runtime: &mut Runtime, ()| c.call_box(runtime, true)));
             }
         }
 
         {
-            let sig_run = self.signal_runtime.clone();
+            let (sig_run, spsc, specialized) = (self.signal_runtime.clone(), self.spsc.clone(), self.specialized.clone());
             runtime.on_end_of_instant(Box::new(move|_: &mut Runtime, ()| {
                 let mut sig = sig_run.lock().unwrap();
                 sig.current_value = sig.default_value.clone();
                 sig.status = false;
+                if specialized.load(Ordering::SeqCst) {
+                    // Switched during the instant, with the emission.
+                    spsc.reset();
+                }
             }))
         }
     }
 
-    fn on_signal<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<V> {
+    fn on_signal<C>(self, runtime: &mut Runtime, unique: bool, c: C) where C: Continuation<V> {
+        if self.specialize(unique) {
+            return self.spsc.on_signal(runtime, c);
+        }
         let sig_run = self.signal_runtime.clone();
         let mut sig = sig_run.lock().unwrap();
         if sig.status {
             let value = sig.current_value.clone();
             runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| c.call(runtime, value)));
         } else {
+            let c = self.forget_when_cancelled(c);
             sig.add_callback(c);
         }
     }
@@ -78,12 +149,13 @@ impl<V> UPSignalRuntimeRef<V> where V: Clone + Send + Sync + Sized + 'static {
                 let sig_run = self.signal_runtime.clone();
                 runtime.on_end_of_instant(Box::new(move|runtime: &mut Runtime, ()| {
                     let mut sig = sig_run.lock().unwrap();
-                    while let Some(c) = sig.waiting_present.pop() {
+                    while let Some(c) = sig.waiting_present.pop_front() {
                         c.call_box(runtime, false)
                     }
                 }));
             }
-            sig.waiting_present.push(Box::new(c));
+            let c = self.forget_when_cancelled(c);
+            sig.waiting_present.push_back(Box::new(c));
         }
     }
 }
@@ -108,6 +180,8 @@ pub trait UPSignalConsumer<V>: 'static where V: Clone + Send + Sync + Sized + 's
     }
 }
 
+/// The producer of a signal with a single producer. The signal switches to a lock-free runtime
+/// once its consumer is provably unique as well, see `UPAwaitImmediate`.
 pub struct UniqueProducerSignalProducer<V> where V: Clone + Send + Sync + Sized + 'static {
     runtime: UPSignalRuntimeRef<V>
 }
@@ -116,18 +190,23 @@ impl<V> UniqueProducerSignalProducer<V> where V: Clone + Send + Sync + Sized + '
     pub fn new(default_value: V) -> (UniqueProducerSignalProducer<V>, UniqueProducerSignalConsumer<V>) {
         let runtime = UPSignalRuntime {
             status: false,
-            callbacks: vec!(),
-            waiting_present: vec!(),
+            callbacks: VecDeque::new(),
+            waiting_present: VecDeque::new(),
             current_value: default_value.clone(),
             default_value,
         };
-        let signal_run = UPSignalRuntimeRef {signal_runtime: Arc::new(Mutex::new(runtime))};
+        let signal_run = UPSignalRuntimeRef {
+            signal_runtime: Arc::new(Mutex::new(runtime)),
+            spsc: Arc::new(SPSCSignalRuntime::new()),
+            specialized: Arc::new(AtomicBool::new(false)),
+            consumers: None,
+        };
         (
             UniqueProducerSignalProducer {
                 runtime: signal_run.clone()
             },
             UniqueProducerSignalConsumer {
-                runtime: signal_run.clone()
+                runtime: UPSignalRuntimeRef {consumers: Some(Arc::new(())), ..signal_run}
             }
         )
     }
@@ -156,6 +235,12 @@ impl<V> Clone for UniqueProducerSignalConsumer<V> where V: Clone + Send + Sync +
     }
 }
 
+/// Waits for the signal to be emitted and resumes with its value.
+///
+/// Once every other handle and process of the consumer was dropped, the waiting process is the
+/// only one left, and it cannot be cloned: the signal then switches for good to the lock-free
+/// runtime of `SPSCSignalProducer` when it waits, rather than locking the signal at each
+/// emission and wait.
 pub struct UPAwaitImmediate<V> where V: Clone + Send + Sync + Sized + 'static  {
     signal: UPSignalRuntimeRef<V>
 }
@@ -164,16 +249,16 @@ impl<V> Process for UPAwaitImmediate<V> where V: Clone + Send + Sync + Sized + '
     type Value = V;
 
     fn call<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<V> {
-        self.signal.on_signal(runtime, c);
+        let unique = self.signal.has_unique_consumer();
+        self.signal.on_signal(runtime, unique, c);
     }
 }
 
 impl<V> ProcessMut for UPAwaitImmediate<V> where V: Clone + Send + Sync + Sized + 'static {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, V)> {
+        let unique = self.signal.has_unique_consumer();
         let sig = self.signal.clone();
-        self.signal.on_signal(runtime, |runtime: &mut Runtime, v| {
-            next.call(runtime, (UPAwaitImmediate {signal: sig}, v))
-        });
+        self.signal.on_signal(runtime, unique, next.map(move|v| (UPAwaitImmediate {signal: sig}, v)));
     }
 }
 
@@ -221,8 +306,6 @@ impl<V> Process for UPPresent<V> where V: Clone + Send + Sync + Sized + 'static
 impl<V> ProcessMut for UPPresent<V> where V: Clone + Send + Sync + Sized + 'static {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, bool)> {
         let sig = self.signal.clone();
-        self.signal.test_present(runtime, move|runtime: &mut Runtime, status: bool| {
-            next.call(runtime, (UPPresent {signal: sig}, status))
-        });
+        self.signal.test_present(runtime, next.map(move|status: bool| (UPPresent {signal: sig}, status)));
     }
 }
\ No newline at end of file
@@ -1,4 +1,7 @@
 use super::*;
+use std::mem;
+use std::sync::Weak;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 // __     __    _            ____  _                   _
 // \ \   / /_ _| |_   _  ___/ ___|(_) __ _ _ __   __ _| |
@@ -18,58 +21,263 @@ impl<V, G> Clone for VSignalRuntimeRef<V, G> where V: Clone + Send + Sync + 'sta
 }
 
 struct VSignalRuntime<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
-    callbacks: Vec<Box<Continuation<()>>>,
-    waiting_present: Vec<Box<Continuation<bool>>>,
-    waiting_await: Vec<Box<Continuation<V>>>,
+    /// Woken in registration order, like `waiting_present` and `waiting_await`.
+    callbacks: VecDeque<Box<Continuation<()>>>,
+    waiting_present: VecDeque<Box<Continuation<bool>>>,
+    waiting_await: VecDeque<Box<Continuation<V>>>,
+    /// Called with `None` at the end of the instant if the signal was absent.
+    waiting_await_or: VecDeque<Box<Continuation<Option<V>>>>,
     status: bool,
     gather: Box<Fn(V, G) -> V + Send + Sync>,
     default_value: V,
     current_value: V,
+    /// Emissions of the instant in a deterministic runtime and their keys, gathered at the end of
+    /// the instant.
+    pending: Vec<(Option<OrderKey>, G)>,
+    /// Number of instants without emission after which the buffers are freed, if any.
+    hibernate_after: Option<usize>,
+    last_emission: usize,
+    /// Whether a continuation checking for hibernation is scheduled.
+    sweeping: bool,
+    hibernating: bool,
+    /// Emissions of the instant, if the signal is instrumented.
+    log: Option<Vec<Emission<G>>>,
+    waiting_detailed: VecDeque<Box<Continuation<Vec<Emission<G>>>>>,
+    /// Checks the gathered value at the end of each instant with an emission, reporting the
+    /// failures, see `ValueSignal::with_validator`.
+    validator: Option<Box<Fn(&mut Runtime, &V) -> bool + Send + Sync>>,
+    /// The audit checking the emissions of the readers of the signal, see `ValueSignal::audited`.
+    audit: Option<SignalAudit>,
+    /// The number of waiters of each kind, and of emissions per instant, the signal holds without
+    /// growing its buffers, if fixed.
+    capacity: Option<usize>,
+    /// The least priority with which the waiters are resumed, see `ValueSignal::with_priority`.
+    priority: Priority,
+}
+
+struct SignalAudit {
+    audit: EmitAudit,
+    name: String,
+    /// The address of the signal, identifying its reads.
+    signal: usize,
+}
+
+/// An emission recorded by an instrumented signal, see `ValueSignal::instrumented`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Emission<G> {
+    pub value: G,
+    /// Identifies the emitting process, see `next_emitter`.
+    pub emitter: usize,
+    /// The name given to the emitting process with `emit_named`, if any.
+    pub name: Option<String>,
+    /// The worker of the parallel runtime which executed the emission, `None` for a sequential
+    /// runtime.
+    pub worker: Option<usize>,
+}
+
+/// Identifies emitting processes in the emissions recorded by instrumented signals.
+static NEXT_EMITTER: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn next_emitter() -> usize {
+    NEXT_EMITTER.fetch_add(1, Ordering::SeqCst)
 }
 
 impl<V, G> VSignalRuntime<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn gathered(&mut self) -> V {
+        self.pending.sort_by(|&(ref key1, _), &(ref key2, _)| key1.cmp(key2));
+        let mut value = self.current_value.clone();
+        for &(_, ref v) in self.pending.iter() {
+            value = (self.gather)(value, v.clone());
+        }
+        value
+    }
+
+    /// The waiter `c` resumed with the priority of the signal if it is greater than its own, and
+    /// accounted as a reader of the signal if it is audited.
+    fn reader<T>(&self, c: Box<Continuation<T>>) -> Box<Continuation<T>> where T: Send + Sync + 'static {
+        let priority = std::cmp::max(self.priority, c.priority());
+        let c = match self.audit {
+            Some(ref audit) => {
+                let signal = audit.signal;
+                let key = c.order_key().cloned();
+                with_key(key, Box::new(move|runtime: &mut Runtime, v: T| reading(signal, || c.call_box(runtime, v))))
+            },
+            None => c,
+        };
+        with_priority(priority, c)
+    }
+
     fn add_callback<C>(&mut self, c: C) where C: Continuation<()> {
-        self.callbacks.push(Box::new(c));
+        let capacity = self.capacity;
+        push_bounded(&mut self.callbacks, capacity, ordered(recycled_box(c)), "the waiters of a signal");
+    }
+
+    fn forget_cancelled(&mut self) {
+        self.callbacks.retain(|c| !is_cancelled(&**c));
+        self.waiting_present.retain(|c| !is_cancelled(&**c));
+        self.waiting_await.retain(|c| !is_cancelled(&**c));
+        self.waiting_await_or.retain(|c| !is_cancelled(&**c));
+        self.waiting_detailed.retain(|c| !is_cancelled(&**c));
+    }
+
+    fn memory(&self, name: &str) -> SignalMemory where V: SizeHint, G: SizeHint {
+        let mut waiters = continuations_usage(self.callbacks.iter());
+        waiters.merge(continuations_usage(self.waiting_present.iter()));
+        waiters.merge(continuations_usage(self.waiting_await.iter()));
+        waiters.merge(continuations_usage(self.waiting_await_or.iter()));
+        waiters.merge(continuations_usage(self.waiting_detailed.iter()));
+        let mut values = MemoryUsage::default();
+        values.add(self.current_value.size_hint());
+        for &(_, ref value) in self.pending.iter() {
+            values.add(mem::size_of::<Option<OrderKey>>() + value.size_hint());
+        }
+        for emission in self.log.iter().flat_map(|log| log.iter()) {
+            values.add(mem::size_of::<Emission<G>>() - mem::size_of::<G>() + emission.value.size_hint());
+        }
+        SignalMemory { name: name.to_string(), waiters, values }
+    }
+
+    /// Frees the memory of the buffers, which grow again on their next use, unless their capacity
+    /// is fixed.
+    fn hibernate(&mut self) {
+        if self.capacity.is_none() {
+            self.callbacks.shrink_to_fit();
+            self.waiting_present.shrink_to_fit();
+            self.waiting_await.shrink_to_fit();
+            self.waiting_await_or.shrink_to_fit();
+            self.waiting_detailed.shrink_to_fit();
+            if let Some(ref mut log) = self.log {
+                log.shrink_to_fit();
+            }
+            self.pending = Vec::new();
+        }
+        self.current_value = self.default_value.clone();
+        self.hibernating = true;
     }
 }
 
 impl<V, G> VSignalRuntimeRef<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
-    fn emit(self, runtime: &mut Runtime, value: G) {
-        {
+    /// Emits `value` on behalf of the emitter `emitter`, named `name` if it was created with
+    /// `emit_named`. In a deterministic runtime the value is only gathered at the end of the
+    /// instant, in the order of the emissions in the program.
+    fn emit(self, runtime: &mut Runtime, emitter: usize, name: Option<&str>, value: G) {
+        self.emit_peeking(runtime, emitter, name, value, false);
+    }
+
+    /// Like `emit`, but returns the value gathered right after the emission, under the same lock.
+    fn emit_and_peek(self, runtime: &mut Runtime, emitter: usize, name: Option<&str>, value: G) -> V {
+        self.emit_peeking(runtime, emitter, name, value, true).unwrap()
+    }
+
+    fn emit_peeking(self, runtime: &mut Runtime, emitter: usize, name: Option<&str>, value: G, peek: bool) -> Option<V> {
+        let peeked = {
             let sig_run = self.signal_runtime.clone();
             let mut sig = sig_run.lock().unwrap();
-            while let Some(c) = sig.callbacks.pop() {
-                runtime.on_current_instant(c);
+            if let Some(ref audit) = sig.audit {
+                if has_read(audit.signal) {
+                    let process = name.map_or_else(|| format!("emitter {}", emitter), |name| name.to_string());
+                    let instant = runtime.instant_index();
+                    audit.audit.warn(AuditWarning { instant, signal: audit.name.clone(), process });
+                }
             }
-            while let Some(c) = sig.waiting_present.pop() {
-                runtime.on_current_instant(Box::new(|runtime: &mut Runtime, ()| c.call_box(runtime, true)));
+            while let Some(c) = sig.callbacks.pop_front() {
+                runtime.on_current_instant(sig.reader(c));
+            }
+            while let Some(c) = sig.waiting_present.pop_front() {
+                let c = sig.reader(c);
+                wake(runtime, c, true);
+            }
+            if sig.log.is_some() {
+                let emission = Emission {
+                    value: value.clone(),
+                    emitter,
+                    name: name.map(|name| name.to_string()),
+                    worker: current_worker(),
+                };
+                sig.log.as_mut().unwrap().push(emission);
+            }
+            if runtime.is_deterministic() {
+                if let Some(capacity) = sig.capacity {
+                    if sig.pending.len() >= capacity {
+                        panic!("the emissions of a signal overflowed its capacity of {}", capacity);
+                    }
+                }
+                sig.pending.push((next_order_key(), value));
+            } else {
+                sig.current_value = (sig.gather)(sig.current_value.clone(), value);
             }
-            sig.current_value = (sig.gather)(sig.current_value.clone(), value);
             sig.status = true;
-        }
+            sig.hibernating = false;
+            if let Some(silent_instants) = sig.hibernate_after {
+                sig.last_emission = runtime.instant_index();
+                if !sig.sweeping {
+                    sig.sweeping = true;
+                    schedule_sweep(Arc::downgrade(&self.signal_runtime), runtime, silent_instants);
+                }
+            }
+            if !peek {
+                None
+            } else if runtime.is_deterministic() {
+                Some(sig.gathered())
+            } else {
+                Some(sig.current_value.clone())
+            }
+        };
 
         {
             let sig_run = self.signal_runtime.clone();
-            runtime.on_end_of_instant(Box::new(move|runtime: &mut Runtime, ()| {
+            runtime.on_end_of_instant(recycled_box(move|runtime: &mut Runtime, ()| {
                 let mut sig = sig_run.lock().unwrap();
-                while let Some(c) = sig.waiting_await.pop() {
-                    let value = sig.current_value.clone();
-                    runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| {
-                       c.call_box(runtime, value);
-                    }));
+                if !sig.status {
+                    // The waiters were woken by the continuation of an earlier emission.
+                    return;
                 }
+                let value = sig.gathered();
+                let valid = match sig.validator {
+                    Some(ref validator) => validator(runtime, &value),
+                    None => true,
+                };
+                if valid {
+                    while let Some(c) = sig.waiting_await.pop_front() {
+                        let c = sig.reader(c);
+                        wake(runtime, c, value.clone());
+                    }
+                }
+                while let Some(c) = sig.waiting_await_or.pop_front() {
+                    let c = sig.reader(c);
+                    let value = if valid { Some(value.clone()) } else { None };
+                    wake(runtime, c, value);
+                }
+                let log = sig.log.as_mut().map_or(Vec::new(), |log| log.drain(..).collect());
+                while let Some(c) = sig.waiting_detailed.pop_front() {
+                    let c = sig.reader(c);
+                    wake(runtime, c, log.clone());
+                }
+                sig.pending.clear();
                 sig.current_value = sig.default_value.clone();
                 sig.status = false;
             }))
         }
+        peeked
+    }
+
+    /// Wraps `c` before storing it, to forget it if its token is cancelled, see `waiting`.
+    fn forget_when_cancelled<C, T>(&self, c: C) -> Waiting<C> where C: Continuation<T>, T: Send + Sync {
+        let sig = Arc::downgrade(&self.signal_runtime);
+        waiting(c, &*self.signal_runtime as *const _ as usize, move|| {
+            if let Some(sig) = sig.upgrade() {
+                sig.lock().unwrap().forget_cancelled();
+            }
+        })
     }
 
     fn on_signal<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<()> {
         let sig_run = self.signal_runtime.clone();
         let mut sig = sig_run.lock().unwrap();
         if sig.status {
-            runtime.on_current_instant(Box::new(c));
+            runtime.on_current_instant(sig.reader(recycled_box(c)));
         } else {
+            let c = self.forget_when_cancelled(c);
             sig.add_callback(c);
         }
     }
@@ -77,25 +285,63 @@ impl<V, G> VSignalRuntimeRef<V, G> where V: Clone + Send + Sync + 'static, G: Cl
     fn await<C>(self, c: C) where C: Continuation<V> {
         let sig_ref = self.clone();
         let mut sig = sig_ref.signal_runtime.lock().unwrap();
-        sig.waiting_await.push(Box::new(c));
+        let c = self.forget_when_cancelled(c);
+        let capacity = sig.capacity;
+        push_bounded(&mut sig.waiting_await, capacity, ordered(recycled_box(c)), "the waiters of a signal");
+    }
+
+    fn await_detailed<C>(self, c: C) where C: Continuation<Vec<Emission<G>>> {
+        let mut sig = self.signal_runtime.lock().unwrap();
+        let c = self.forget_when_cancelled(c);
+        let capacity = sig.capacity;
+        push_bounded(&mut sig.waiting_detailed, capacity, ordered(Box::new(c)), "the waiters of a signal");
+    }
+
+    /// Like `await`, but `c` is called with `None` at the next instant if the signal is absent.
+    pub(crate) fn await_or<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<Option<V>> {
+        let mut sig = self.signal_runtime.lock().unwrap();
+        if sig.waiting_await_or.is_empty() {
+            // If the signal is emitted, the end of instant continuation of the emission wakes the
+            // waiters instead.
+            let sig_run = self.signal_runtime.clone();
+            runtime.on_end_of_instant(Box::new(move|runtime: &mut Runtime, ()| {
+                let mut sig = sig_run.lock().unwrap();
+                if sig.status {
+                    return;
+                }
+                while let Some(c) = sig.waiting_await_or.pop_front() {
+                    let c = sig.reader(c);
+                    wake(runtime, c, None);
+                }
+            }));
+        }
+        let c = self.forget_when_cancelled(c);
+        let capacity = sig.capacity;
+        push_bounded(&mut sig.waiting_await_or, capacity, ordered(Box::new(c)), "the waiters of a signal");
     }
 
     fn test_present<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<bool> {
         let sig_run = self.signal_runtime.clone();
         let mut sig = sig_run.lock().unwrap();
         if sig.status {
-            c.call(runtime, true);
+            match sig.audit {
+                Some(ref audit) => reading(audit.signal, || c.call(runtime, true)),
+                None => c.call(runtime, true),
+            }
         } else {
             if sig.waiting_present.is_empty() {
                 let sig_run = self.signal_runtime.clone();
                 runtime.on_end_of_instant(Box::new(move|runtime: &mut Runtime, ()| {
                     let mut sig = sig_run.lock().unwrap();
-                    while let Some(c) = sig.waiting_present.pop() {
-                        c.call_box(runtime, false)
+                    while let Some(c) = sig.waiting_present.pop_front() {
+                        let c = sig.reader(c);
+                        call_ordered(runtime, c, false);
                     }
                 }));
             }
-            sig.waiting_present.push(Box::new(c));
+            let c = self.forget_when_cancelled(c);
+            let capacity = sig.capacity;
+            push_bounded(&mut sig.waiting_present, capacity, ordered(Box::new(c)), "the waiters of a signal");
         }
     }
 }
@@ -111,13 +357,89 @@ pub trait VSignal<V, G>: 'static where V: Clone + Send + Sync + 'static, G: Clon
         VAwait {signal: self.runtime()}
     }
 
+    /// Resumes at the next instant with the value gathered in the current one, or with `default`
+    /// if the signal was absent.
+    fn await_or(&self, default: V) -> VAwaitOr<V, G> where Self: Sized {
+        VAwaitOr {signal: self.runtime(), default}
+    }
+
     fn emit<P>(&self, value: P) -> VEmit<V, G, P> where Self: Sized, P: Process<Value = G> {
-        VEmit {signal: self.runtime(), value}
+        VEmit {signal: self.runtime(), emitter: next_emitter(), name: None, value}
+    }
+
+    /// Like `emit`, with a name identifying the emitting process in the emissions recorded by an
+    /// instrumented signal.
+    fn emit_named<P>(&self, name: &str, value: P) -> VEmit<V, G, P> where Self: Sized, P: Process<Value = G> {
+        VEmit {signal: self.runtime(), emitter: next_emitter(), name: Some(Arc::new(name.to_string())), value}
+    }
+
+    /// Like `emit`, but returns the value gathered by the signal right after this emission.
+    ///
+    /// In a deterministic runtime the peeked value depends on which emissions happened before,
+    /// hence on the scheduling: only the value of the awaiting processes is deterministic.
+    fn emit_and_peek<P>(&self, value: P) -> VEmitAndPeek<V, G, P> where Self: Sized, P: Process<Value = G> {
+        VEmitAndPeek {signal: self.runtime(), emitter: next_emitter(), name: None, value}
     }
 
     fn present(&self) -> VPresent<V, G> where Self: Sized {
         VPresent {signal: self.runtime()}
     }
+
+    /// Records the gathered value of every instant in which the signal is present from now on,
+    /// with any runtime and without spawning a process. The recording does not keep the signal
+    /// alive.
+    fn capture(&self) -> Recording<V> where Self: Sized {
+        let recording = Recording::new();
+        capture_next(Arc::downgrade(&self.runtime().signal_runtime), recording.clone());
+        recording
+    }
+
+    /// An emission of `value` to perform atomically with others, see `emit_group`.
+    fn grouped(&self, value: G) -> GroupEmission where Self: Sized {
+        let signal = self.runtime();
+        let emitter = next_emitter();
+        GroupEmission::new(move|runtime: &mut Runtime, ()| signal.emit(runtime, emitter, None, value))
+    }
+}
+
+/// Records the next value gathered by the signal in `recording`, then waits for the following one.
+fn capture_next<V, G>(sig: Weak<Mutex<VSignalRuntime<V, G>>>, recording: Recording<V>)
+    where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    if let Some(signal_runtime) = sig.upgrade() {
+        VSignalRuntimeRef { signal_runtime }.await(move|runtime: &mut Runtime, v: V| {
+            // `await` resumes on the instant following the emission.
+            recording.push(runtime.instant_index() - 1, v);
+            capture_next(sig, recording);
+        });
+    }
+}
+
+/// Checks at the end of the instant whether the signal was silent long enough to hibernate, and
+/// checks again at the end of the next instant if not. The check does not keep the signal alive,
+/// and stops once it is dropped.
+fn schedule_sweep<V, G>(sig: Weak<Mutex<VSignalRuntime<V, G>>>, runtime: &mut Runtime, silent_instants: usize)
+    where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    runtime.on_end_of_instant(recycled_box(move|runtime: &mut Runtime, ()| {
+        let silent = match sig.upgrade() {
+            Some(sig_run) => {
+                let mut sig_run = sig_run.lock().unwrap();
+                let silent = runtime.instant_index() - sig_run.last_emission >= silent_instants;
+                if silent {
+                    sig_run.hibernate();
+                    sig_run.sweeping = false;
+                }
+                silent
+            },
+            None => return,
+        };
+        if !silent {
+            // Continuations registered for the current instant at the end of an instant are
+            // executed during the next one.
+            runtime.on_current_instant(recycled_box(move|runtime: &mut Runtime, ()| {
+                schedule_sweep(sig, runtime, silent_instants);
+            }));
+        }
+    }));
 }
 
 pub struct ValueSignal<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
@@ -128,17 +450,164 @@ impl<V, G> ValueSignal<V, G> where V: Clone + Send + Sync + 'static, G: Clone +
     pub fn new(default_value: V, gather: Box<Fn(V, G) -> V + Send + Sync>) -> ValueSignal<V, G> {
         let runtime = VSignalRuntime {
             status: false,
-            callbacks: vec!(),
-            waiting_present: vec!(),
-            waiting_await: vec!(),
+            callbacks: VecDeque::new(),
+            waiting_present: VecDeque::new(),
+            waiting_await: VecDeque::new(),
+            waiting_await_or: VecDeque::new(),
             current_value: default_value.clone(),
             default_value,
             gather,
+            pending: Vec::new(),
+            hibernate_after: None,
+            last_emission: 0,
+            sweeping: false,
+            hibernating: false,
+            log: None,
+            waiting_detailed: VecDeque::new(),
+            validator: None,
+            audit: None,
+            capacity: None,
+            priority: Priority::NORMAL,
         };
         ValueSignal {
             runtime: VSignalRuntimeRef {signal_runtime: Arc::new(Mutex::new(runtime))}
         }
     }
+
+    /// Frees the buffers of the signal once it has not been emitted for `silent_instants`
+    /// instants, for sparse simulations where most signals rarely change.
+    ///
+    /// While the signal is awake, a continuation is executed at the end of each instant to check
+    /// for silence, which also keeps the runtime going for up to `silent_instants` instants after
+    /// the last emission.
+    pub fn hibernate_after(self, silent_instants: usize) -> Self {
+        self.runtime.signal_runtime.lock().unwrap().hibernate_after = Some(silent_instants);
+        self
+    }
+
+    /// Allocates the buffers of the signal once, with room for `capacity` waiters of each kind and
+    /// `capacity` emissions per instant, and panics instead of growing them, for targets which
+    /// must not allocate while they run. See `SequentialRuntime::with_capacity` for the runtime.
+    ///
+    /// Emissions only take room in a deterministic runtime, which gathers them at the end of the
+    /// instant: other runtimes gather each value as it is emitted. The buffers are kept when the
+    /// signal hibernates, but the log of an `instrumented` signal still grows.
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        {
+            let mut sig = self.runtime.signal_runtime.lock().unwrap();
+            sig.callbacks.reserve_exact(capacity);
+            sig.waiting_present.reserve_exact(capacity);
+            sig.waiting_await.reserve_exact(capacity);
+            sig.waiting_await_or.reserve_exact(capacity);
+            sig.waiting_detailed.reserve_exact(capacity);
+            sig.pending.reserve_exact(capacity);
+            sig.capacity = Some(capacity);
+        }
+        self
+    }
+
+    /// Records every emission of each instant, with the emitting process and worker, to debug
+    /// the gathered values with `await_detailed`.
+    pub fn instrumented(self) -> Self {
+        self.runtime.signal_runtime.lock().unwrap().log = Some(Vec::new());
+        self
+    }
+
+    /// Checks the value gathered at the end of each instant with `validator`. An invalid value is
+    /// withheld from the processes awaiting it, which keep waiting, or resume with the default of
+    /// `await_or`, and its error is emitted on `errors` at the next instant.
+    ///
+    /// Several signals can report to the same error signal, to enforce invariants in one place
+    /// rather than in every consumer.
+    pub fn with_validator<W, E, F>(self, errors: &ValueSignal<W, E>, validator: F) -> Self
+        where W: Clone + Send + Sync + 'static, E: Clone + Send + Sync + 'static, F: Fn(&V) -> Result<(), E> + Send + Sync + 'static {
+        let errors = errors.runtime();
+        let emitter = next_emitter();
+        let check = move|runtime: &mut Runtime, value: &V| match validator(value) {
+            Ok(()) => true,
+            Err(error) => {
+                let errors = errors.clone();
+                runtime.on_current_instant(Box::new(move|runtime: &mut Runtime, ()| errors.emit(runtime, emitter, None, error)));
+                false
+            },
+        };
+        self.runtime.signal_runtime.lock().unwrap().validator = Some(Box::new(check));
+        self
+    }
+
+    /// Warns through `audit` when a process emits on the signal after reading it during the same
+    /// instant, the signal being called `name` in the warnings, see `EmitAudit`.
+    pub fn audited(self, audit: &EmitAudit, name: &str) -> Self {
+        let signal = &*self.runtime.signal_runtime as *const _ as usize;
+        let audit = SignalAudit { audit: audit.clone(), name: name.to_string(), signal };
+        self.runtime.signal_runtime.lock().unwrap().audit = Some(audit);
+        self
+    }
+
+    /// Resumes the processes waiting for the signal with at least `priority`, ahead of the
+    /// continuations of lower priority of the instant, see `Priority`.
+    pub fn with_priority(self, priority: Priority) -> Self {
+        self.runtime.signal_runtime.lock().unwrap().priority = priority;
+        self
+    }
+
+    /// The priority with which the signal resumes its waiters, see `with_priority`.
+    pub fn priority(&self) -> Priority {
+        self.runtime.signal_runtime.lock().unwrap().priority
+    }
+
+    /// Reports the waiters and the values of the signal to `accounting` under `name`, see
+    /// `MemoryAccounting`.
+    pub fn account_memory(self, accounting: &MemoryAccounting, name: &str) -> Self where V: SizeHint, G: SizeHint {
+        let name = name.to_string();
+        accounting.add_signal(Arc::downgrade(&self.runtime.signal_runtime), move|sig: &Mutex<VSignalRuntime<V, G>>| {
+            sig.lock().unwrap().memory(&name)
+        });
+        self
+    }
+
+    /// Waits for the signal to be emitted, and resumes at the next instant with the emissions of
+    /// the instant, in the order they happened, the `k`-th being the `k`-th given to the gather
+    /// function unless the runtime is deterministic. Only instrumented signals record their
+    /// emissions, others resume with none.
+    pub fn await_detailed(&self) -> VAwaitDetailed<V, G> {
+        VAwaitDetailed {signal: self.runtime.clone()}
+    }
+
+    /// The value of the signal during the instants in which it is not emitted.
+    pub(crate) fn default_value(&self) -> V {
+        self.runtime.signal_runtime.lock().unwrap().default_value.clone()
+    }
+
+    /// Whether the buffers of the signal are currently freed, see `hibernate_after`.
+    pub fn is_hibernating(&self) -> bool {
+        self.runtime.signal_runtime.lock().unwrap().hibernating
+    }
+
+    /// A handle which does not keep the signal alive.
+    pub fn downgrade(&self) -> WeakValueSignal<V, G> {
+        WeakValueSignal {signal_runtime: Arc::downgrade(&self.runtime.signal_runtime)}
+    }
+}
+
+/// A `ValueSignal` handle which does not keep the signal alive, see `ValueSignal::downgrade`.
+pub struct WeakValueSignal<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    signal_runtime: Weak<Mutex<VSignalRuntime<V, G>>>,
+}
+
+impl<V, G> WeakValueSignal<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    /// The signal, unless every `ValueSignal` handle and every process using it was dropped.
+    pub fn upgrade(&self) -> Option<ValueSignal<V, G>> {
+        self.signal_runtime.upgrade().map(|signal_runtime| ValueSignal {
+            runtime: VSignalRuntimeRef {signal_runtime}
+        })
+    }
+}
+
+impl<V, G> Clone for WeakValueSignal<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn clone(&self) -> Self {
+        WeakValueSignal {signal_runtime: self.signal_runtime.clone()}
+    }
 }
 
 impl<V, G> Clone for ValueSignal<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
@@ -168,9 +637,13 @@ impl<V, G> Process for VAwaitImmediate<V, G> where V: Clone + Send + Sync + 'sta
 impl<V, G> ProcessMut for VAwaitImmediate<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, ())> {
         let sig = self.signal.clone();
-        self.signal.on_signal(runtime, |runtime: &mut Runtime, ()| {
-            next.call(runtime, (VAwaitImmediate {signal: sig}, ()))
-        });
+        self.signal.on_signal(runtime, next.map(move|()| (VAwaitImmediate {signal: sig}, ())));
+    }
+}
+
+impl<V, G> CloneProcess for VAwaitImmediate<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        VAwaitImmediate {signal: self.signal.clone()}
     }
 }
 
@@ -189,14 +662,77 @@ impl<V, G> Process for VAwait<V, G> where V: Clone + Send + Sync + 'static, G: C
 impl<V, G> ProcessMut for VAwait<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
     fn call_mut<C>(self, _: &mut Runtime, next: C) where C: Continuation<(Self, V)> {
         let sig = self.signal.clone();
-        self.signal.await(|runtime: &mut Runtime, v| {
-            next.call(runtime, (VAwait {signal: sig}, v))
-        });
+        self.signal.await(next.map(move|v| (VAwait {signal: sig}, v)));
+    }
+}
+
+impl<V, G> CloneProcess for VAwait<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        VAwait {signal: self.signal.clone()}
+    }
+}
+
+pub struct VAwaitOr<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static  {
+    signal: VSignalRuntimeRef<V, G>,
+    default: V,
+}
+
+impl<V, G> Process for VAwaitOr<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    type Value = V;
+
+    fn call<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<V> {
+        let default = self.default;
+        self.signal.await_or(runtime, c.map(|v: Option<V>| v.unwrap_or(default)));
+    }
+}
+
+impl<V, G> ProcessMut for VAwaitOr<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, V)> {
+        let sig = self.signal.clone();
+        let default = self.default;
+        self.signal.await_or(runtime, next.map(move|v: Option<V>| {
+            let v = v.unwrap_or_else(|| default.clone());
+            (VAwaitOr {signal: sig, default}, v)
+        }));
+    }
+}
+
+impl<V, G> CloneProcess for VAwaitOr<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        VAwaitOr {signal: self.signal.clone(), default: self.default.clone()}
+    }
+}
+
+pub struct VAwaitDetailed<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static  {
+    signal: VSignalRuntimeRef<V, G>
+}
+
+impl<V, G> Process for VAwaitDetailed<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    type Value = Vec<Emission<G>>;
+
+    fn call<C>(self, _: &mut Runtime, c: C) where C: Continuation<Vec<Emission<G>>> {
+        self.signal.await_detailed(c);
+    }
+}
+
+impl<V, G> ProcessMut for VAwaitDetailed<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn call_mut<C>(self, _: &mut Runtime, next: C) where C: Continuation<(Self, Vec<Emission<G>>)> {
+        let sig = self.signal.clone();
+        self.signal.await_detailed(next.map(move|log| (VAwaitDetailed {signal: sig}, log)));
+    }
+}
+
+impl<V, G> CloneProcess for VAwaitDetailed<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        VAwaitDetailed {signal: self.signal.clone()}
     }
 }
 
 pub struct VEmit<V, G, P> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static, P: Process<Value = G> {
     signal: VSignalRuntimeRef<V, G>,
+    emitter: usize,
+    /// The name given with `emit_named`, carried by the process rather than kept by the signal.
+    name: Option<Arc<String>>,
     value: P,
 }
 
@@ -204,10 +740,10 @@ impl<V, G, P> Process for VEmit<V, G, P> where V: Clone + Send + Sync + 'static,
     type Value = G;
 
     fn call<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<G> {
-        let sig = self.signal.clone();
+        let (sig, emitter, name) = (self.signal, self.emitter, self.name);
 
         self.value.call(runtime, move|runtime: &mut Runtime, v: G| {
-            sig.emit(runtime, v.clone());
+            sig.emit(runtime, emitter, name.as_ref().map(|name| &name[..]), v.clone());
             c.call(runtime, v);
         });
     }
@@ -215,15 +751,59 @@ impl<V, G, P> Process for VEmit<V, G, P> where V: Clone + Send + Sync + 'static,
 
 impl<V, G, P> ProcessMut for VEmit<V, G, P> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static, P: ProcessMut<Value = G> {
     fn call_mut<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<(Self, G)> {
-        let sig = self.signal.clone();
+        let (sig, emitter, name) = (self.signal, self.emitter, self.name);
 
         self.value.call_mut(runtime, move|runtime: &mut Runtime, (process, v): (P, G)| {
-            sig.clone().emit(runtime, v.clone());
-            c.call(runtime, (VEmit {signal: sig, value: process}, v));
+            sig.clone().emit(runtime, emitter, name.as_ref().map(|name| &name[..]), v.clone());
+            c.call(runtime, (VEmit {signal: sig, emitter, name, value: process}, v));
         });
     }
 }
 
+impl<V, G, P> CloneProcess for VEmit<V, G, P> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static, P: CloneProcess<Value = G> {
+    fn clone_process(&self) -> Self {
+        VEmit {signal: self.signal.clone(), emitter: next_emitter(), name: self.name.clone(), value: self.value.clone_process()}
+    }
+}
+
+pub struct VEmitAndPeek<V, G, P> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static, P: Process<Value = G> {
+    signal: VSignalRuntimeRef<V, G>,
+    emitter: usize,
+    /// The name given with `emit_named`, carried by the process rather than kept by the signal.
+    name: Option<Arc<String>>,
+    value: P,
+}
+
+impl<V, G, P> Process for VEmitAndPeek<V, G, P> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static, P: Process<Value = G> {
+    type Value = V;
+
+    fn call<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<V> {
+        let (sig, emitter, name) = (self.signal, self.emitter, self.name);
+
+        self.value.call(runtime, move|runtime: &mut Runtime, v: G| {
+            let gathered = sig.emit_and_peek(runtime, emitter, name.as_ref().map(|name| &name[..]), v);
+            c.call(runtime, gathered);
+        });
+    }
+}
+
+impl<V, G, P> ProcessMut for VEmitAndPeek<V, G, P> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static, P: ProcessMut<Value = G> {
+    fn call_mut<C>(self, runtime: &mut Runtime, c: C) where C: Continuation<(Self, V)> {
+        let (sig, emitter, name) = (self.signal, self.emitter, self.name);
+
+        self.value.call_mut(runtime, move|runtime: &mut Runtime, (process, v): (P, G)| {
+            let gathered = sig.clone().emit_and_peek(runtime, emitter, name.as_ref().map(|name| &name[..]), v);
+            c.call(runtime, (VEmitAndPeek {signal: sig, emitter, name, value: process}, gathered));
+        });
+    }
+}
+
+impl<V, G, P> CloneProcess for VEmitAndPeek<V, G, P> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static, P: CloneProcess<Value = G> {
+    fn clone_process(&self) -> Self {
+        VEmitAndPeek {signal: self.signal.clone(), emitter: next_emitter(), name: self.name.clone(), value: self.value.clone_process()}
+    }
+}
+
 pub struct VPresent<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
     signal: VSignalRuntimeRef<V, G>
 }
@@ -239,8 +819,12 @@ impl<V, G> Process for VPresent<V, G> where V: Clone + Send + Sync + 'static, G:
 impl<V, G> ProcessMut for VPresent<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
     fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, bool)> {
         let sig = self.signal.clone();
-        self.signal.test_present(runtime, move|runtime: &mut Runtime, status: bool| {
-            next.call(runtime, (VPresent {signal: sig}, status))
-        });
+        self.signal.test_present(runtime, next.map(move|status: bool| (VPresent {signal: sig}, status)));
+    }
+}
+
+impl<V, G> CloneProcess for VPresent<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn clone_process(&self) -> Self {
+        VPresent {signal: self.signal.clone()}
     }
 }
\ No newline at end of file
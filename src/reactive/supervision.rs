@@ -0,0 +1,466 @@
+use super::*;
+use std::panic::{self, AssertUnwindSafe};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+//  ____                              _     _
+// / ___| _   _ _ __   ___ _ ____   _(_)___(_) ___  _ __
+// \___ \| | | | '_ \ / _ \ '__\ \ / / / __| |/ _ \| '_ \
+//  ___) | |_| | |_) |  __/ |   \ V /| \__ \ | (_) | | | |
+// |____/ \__,_| .__/ \___|_|    \_/ |_|___/_|\___/|_| |_|
+//             |_|
+
+/// What to do with a process that panicked, as decided by the handler installed with
+/// `on_process_panic`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Executes a new copy of the supervised process at the next instant, in place of the one
+    /// that panicked. Processes that are not supervised are skipped instead.
+    Restart,
+    /// Drops the process that panicked, which never completes, and carries on with the rest of
+    /// the program.
+    Skip,
+    /// Propagates the panic, which ends the execution.
+    Abort,
+}
+
+/// Decides what to do with a panicking process given its name and the payload of the panic.
+pub type PanicHandler = Arc<Fn(&str, &(Any + Send)) -> PanicPolicy + Send + Sync>;
+
+/// The name given to the panic handler when the panicking continuation is not part of a
+/// supervised process.
+pub const UNSUPERVISED: &'static str = "continuation";
+
+/// The payload of a panic aborted by a handler, which is propagated without asking the handlers
+/// of the enclosing supervised processes.
+struct Aborted(Box<Any + Send>);
+
+/// Calls `c`, letting `handler` decide what to do if it panics.
+pub(crate) fn call_handling_panics(c: Box<Continuation<()>>, runtime: &mut Runtime, handler: &Option<PanicHandler>) {
+    let handler = match *handler {
+        Some(ref handler) => handler,
+        None => return c.call_box(runtime, ()),
+    };
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| c.call_box(runtime, ()))) {
+        let payload = match payload.downcast::<Aborted>() {
+            Ok(aborted) => panic::resume_unwind(aborted.0),
+            Err(payload) => payload,
+        };
+        if handler(UNSUPERVISED, &*payload) == PanicPolicy::Abort {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+pub struct Supervised<P> {
+    process: P,
+    name: String,
+}
+
+impl<P> Process for Supervised<P> where P: CloneProcess {
+    type Value = P::Value;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        match runtime.panic_handler() {
+            None => self.process.call(runtime, next),
+            Some(handler) => {
+                let supervision = Arc::new(Supervision {
+                    process: self.process,
+                    name: self.name,
+                    handler,
+                    state: Mutex::new(SupervisionState { next: Some(next), generation: 0, completed: false }),
+                });
+                start(&supervision, runtime, 0);
+            },
+        }
+    }
+}
+
+impl<P> CloneProcess for Supervised<P> where P: CloneProcess {
+    fn clone_process(&self) -> Self {
+        supervised(self.process.clone_process(), &self.name)
+    }
+}
+
+/// Hands the panics of `process` to the handler of the runtime, under the name `name`, which can
+/// restart it from a copy of the original. Without handler, the panics are propagated as usual.
+///
+/// The supervision covers the execution of the process and of the continuations it schedules on
+/// the runtime, for instance after a `pause`. A step resumed by a signal is not supervised: its
+/// panics are handled as the ones of any other continuation.
+pub fn supervised<P>(process: P, name: &str) -> Supervised<P> where P: CloneProcess {
+    Supervised { process, name: name.to_string() }
+}
+
+struct SupervisionState<C> {
+    next: Option<C>,
+    /// Number of copies of the process started before the current one. The steps of the previous
+    /// copies are dropped.
+    generation: usize,
+    /// Whether the process completed, after which the panics are not its own anymore.
+    completed: bool,
+}
+
+struct Supervision<P, C> {
+    process: P,
+    name: String,
+    handler: PanicHandler,
+    state: Mutex<SupervisionState<C>>,
+}
+
+/// Starts the `generation`-th copy of the process.
+fn start<P, C>(supervision: &Arc<Supervision<P, C>>, runtime: &mut Runtime, generation: usize)
+    where P: CloneProcess, C: Continuation<P::Value> {
+    let copy = supervision.process.clone_process();
+    let done = SupervisedDone { supervision: supervision.clone(), generation };
+    let supervisor: Arc<Supervisor> = Arc::new(SupervisionRef(supervision.clone()));
+    supervise(&supervisor, runtime, generation, move|runtime: &mut Runtime| copy.call(runtime, done));
+}
+
+/// Calls `f` with a runtime supervising the continuations it schedules, and hands its panic to
+/// `supervisor`.
+fn supervise<F>(supervisor: &Arc<Supervisor>, runtime: &mut Runtime, generation: usize, f: F) where F: FnOnce(&mut Runtime) {
+    let result = {
+        let mut supervised = SupervisedRuntime { runtime: &mut *runtime, supervisor: supervisor.clone(), generation };
+        panic::catch_unwind(AssertUnwindSafe(|| f(&mut supervised)))
+    };
+    if let Err(payload) = result {
+        supervisor.handle(runtime, generation, payload);
+    }
+}
+
+/// The type-erased side of a `Supervision`, shared by the steps of its process.
+trait Supervisor: Send + Sync {
+    /// Executes `c`, a step of the `generation`-th copy of the process.
+    fn step(&self, this: &Arc<Supervisor>, runtime: &mut Runtime, generation: usize, c: Box<Continuation<()>>);
+
+    /// Handles a panic of the `generation`-th copy of the process.
+    fn handle(&self, runtime: &mut Runtime, generation: usize, payload: Box<Any + Send>);
+}
+
+struct SupervisionRef<P, C>(Arc<Supervision<P, C>>);
+
+impl<P, C> Supervisor for SupervisionRef<P, C> where P: CloneProcess, C: Continuation<P::Value> {
+    fn step(&self, this: &Arc<Supervisor>, runtime: &mut Runtime, generation: usize, c: Box<Continuation<()>>) {
+        let (completed, current) = {
+            let state = self.0.state.lock().unwrap();
+            (state.completed, state.generation)
+        };
+        if completed {
+            // A continuation of the rest of the program, scheduled once the process completed.
+            c.call_box(runtime, ());
+        } else if generation == current {
+            supervise(this, runtime, generation, move|runtime: &mut Runtime| c.call_box(runtime, ()));
+        }
+    }
+
+    fn handle(&self, runtime: &mut Runtime, generation: usize, payload: Box<Any + Send>) {
+        let (completed, current) = {
+            let state = self.0.state.lock().unwrap();
+            (state.completed, state.generation)
+        };
+        if completed || (*payload).is::<Aborted>() {
+            panic::resume_unwind(payload);
+        }
+        if generation != current {
+            return;
+        }
+        match (self.0.handler)(&self.0.name, &*payload) {
+            PanicPolicy::Restart => {
+                let generation = generation + 1;
+                self.0.state.lock().unwrap().generation = generation;
+                let supervision = self.0.clone();
+                runtime.on_next_instant(Box::new(move|runtime: &mut Runtime, ()| start(&supervision, runtime, generation)));
+            },
+            PanicPolicy::Skip => {
+                let next = {
+                    let mut state = self.0.state.lock().unwrap();
+                    state.generation += 1;
+                    state.next.take()
+                };
+                drop(next);
+            },
+            PanicPolicy::Abort => panic::resume_unwind(Box::new(Aborted(payload))),
+        }
+    }
+}
+
+/// The continuation of a copy of a supervised process, completing the supervised process unless
+/// the copy was replaced.
+struct SupervisedDone<P, C> {
+    supervision: Arc<Supervision<P, C>>,
+    generation: usize,
+}
+
+impl<P, C> Continuation<P::Value> for SupervisedDone<P, C> where P: CloneProcess, C: Continuation<P::Value> {
+    fn call(self, runtime: &mut Runtime, value: P::Value) {
+        let next = {
+            let mut state = self.supervision.state.lock().unwrap();
+            if state.generation != self.generation {
+                return;
+            }
+            state.completed = true;
+            state.next.take()
+        };
+        if let Some(next) = next {
+            next.call(runtime, value);
+        }
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: P::Value) {
+        (*self).call(runtime, value);
+    }
+}
+
+/// A step scheduled by a supervised process.
+struct SupervisedStep {
+    continuation: Box<Continuation<()>>,
+    supervisor: Arc<Supervisor>,
+    generation: usize,
+}
+
+impl Continuation<()> for SupervisedStep {
+    fn call(self, runtime: &mut Runtime, (): ()) {
+        self.supervisor.step(&self.supervisor, runtime, self.generation, self.continuation);
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: ()) {
+        (*self).call(runtime, value);
+    }
+}
+
+/// The runtime given to a supervised process, supervising the continuations it schedules.
+struct SupervisedRuntime<'a> {
+    runtime: &'a mut Runtime,
+    supervisor: Arc<Supervisor>,
+    generation: usize,
+}
+
+impl<'a> SupervisedRuntime<'a> {
+    fn step(&self, c: Box<Continuation<()>>) -> Box<Continuation<()>> {
+        Box::new(SupervisedStep { continuation: c, supervisor: self.supervisor.clone(), generation: self.generation })
+    }
+}
+
+impl<'a> Runtime for SupervisedRuntime<'a> {
+    fn on_current_instant(&mut self, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_current_instant(step);
+    }
+
+    fn on_next_instant(&mut self, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_next_instant(step);
+    }
+
+    fn on_deadline(&mut self, deadline: time::Instant, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_deadline(deadline, step);
+    }
+
+    // The signals emitted by the process reset themselves at the end of the instant, even if it
+    // panics and is replaced during the instant.
+    fn on_end_of_instant(&mut self, c: Box<Continuation<()>>) {
+        self.runtime.on_end_of_instant(c);
+    }
+
+    fn instant_index(&self) -> usize {
+        self.runtime.instant_index()
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.runtime.is_deterministic()
+    }
+
+    fn external_inbox(&self) -> ExternalInbox {
+        self.runtime.external_inbox()
+    }
+
+    fn panic_handler(&self) -> Option<PanicHandler> {
+        self.runtime.panic_handler()
+    }
+
+    fn clock(&self) -> SharedClock {
+        self.runtime.clock()
+    }
+}
+
+/// The payload of a panic caught by `catch_panic`.
+pub struct PanicPayload {
+    payload: Mutex<Box<Any + Send>>,
+}
+
+impl PanicPayload {
+    /// The message of the panic, when it was raised with a string as with `panic!`.
+    pub fn message(&self) -> Option<String> {
+        let payload = self.payload.lock().unwrap();
+        payload.downcast_ref::<&str>().map(|s| s.to_string()).or_else(|| payload.downcast_ref::<String>().cloned())
+    }
+
+    pub fn into_inner(self) -> Box<Any + Send> {
+        self.payload.into_inner().unwrap()
+    }
+
+    /// Propagates the panic again.
+    pub fn resume(self) -> ! {
+        panic::resume_unwind(self.into_inner())
+    }
+}
+
+impl std::fmt::Debug for PanicPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "PanicPayload({:?})", self.message())
+    }
+}
+
+pub struct CatchPanic<P> {
+    process: P,
+}
+
+impl<P> Process for CatchPanic<P> where P: Process {
+    type Value = Result<P::Value, PanicPayload>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let catch = Arc::new(Catch { next: Mutex::new(Some(next)), failed: AtomicBool::new(false), value: PhantomData });
+        let (process, done) = (self.process, catch.clone());
+        let catcher: Arc<Catcher> = catch;
+        catcher.run(&catcher, runtime, Box::new(move|runtime: &mut Runtime, ()| {
+            process.call(runtime, move|runtime: &mut Runtime, v: P::Value| {
+                let next = done.next.lock().unwrap().take();
+                if let Some(next) = next {
+                    next.call(runtime, Ok(v));
+                }
+            });
+        }));
+    }
+}
+
+impl<P> CloneProcess for CatchPanic<P> where P: CloneProcess {
+    fn clone_process(&self) -> Self {
+        catch_panic(self.process.clone_process())
+    }
+}
+
+/// Returns `Err` with the payload of the first panic of `process`, instead of propagating it to
+/// the runtime, where it would end the execution, or kill a worker of a parallel runtime. The
+/// remaining steps of the process are then dropped. The panics of the handlers installed with
+/// `on_process_panic` aborting the execution are still propagated.
+///
+/// Like with `supervised`, the panics of the continuations the process schedules on the runtime
+/// are caught, but not the ones of a step resumed by a signal.
+pub fn catch_panic<P>(process: P) -> CatchPanic<P> where P: Process {
+    CatchPanic { process }
+}
+
+struct Catch<C, V> {
+    /// Taken once the process completed or panicked.
+    next: Mutex<Option<C>>,
+    /// Set once the process panicked, after which its steps are dropped.
+    failed: AtomicBool,
+    value: PhantomData<V>,
+}
+
+/// The type-erased side of a `Catch`, shared by the steps of its process.
+trait Catcher: Send + Sync {
+    /// Executes `c`, a step of the process, with a runtime catching the panics of the steps it
+    /// schedules.
+    fn run(&self, this: &Arc<Catcher>, runtime: &mut Runtime, c: Box<Continuation<()>>);
+}
+
+impl<C, V> Catcher for Catch<C, V> where C: Continuation<Result<V, PanicPayload>>, V: Send + Sync {
+    fn run(&self, this: &Arc<Catcher>, runtime: &mut Runtime, c: Box<Continuation<()>>) {
+        if self.failed.load(Ordering::SeqCst) {
+            return;
+        }
+        if self.next.lock().unwrap().is_none() {
+            // A continuation of the rest of the program, scheduled once the process completed.
+            return c.call_box(runtime, ());
+        }
+        let result = {
+            let mut catching = CatchingRuntime { runtime: &mut *runtime, catcher: this.clone() };
+            panic::catch_unwind(AssertUnwindSafe(|| c.call_box(&mut catching, ())))
+        };
+        if let Err(payload) = result {
+            let next = if (*payload).is::<Aborted>() { None } else { self.next.lock().unwrap().take() };
+            match next {
+                Some(next) => {
+                    self.failed.store(true, Ordering::SeqCst);
+                    next.call(runtime, Err(PanicPayload { payload: Mutex::new(payload) }));
+                },
+                None => panic::resume_unwind(payload),
+            }
+        }
+    }
+}
+
+/// A step scheduled by a process whose panics are caught.
+struct CatchingStep {
+    continuation: Box<Continuation<()>>,
+    catcher: Arc<Catcher>,
+}
+
+impl Continuation<()> for CatchingStep {
+    fn call(self, runtime: &mut Runtime, (): ()) {
+        self.catcher.run(&self.catcher, runtime, self.continuation);
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: ()) {
+        (*self).call(runtime, value);
+    }
+}
+
+/// The runtime given to a process whose panics are caught, wrapping the continuations it
+/// schedules.
+struct CatchingRuntime<'a> {
+    runtime: &'a mut Runtime,
+    catcher: Arc<Catcher>,
+}
+
+impl<'a> CatchingRuntime<'a> {
+    fn step(&self, c: Box<Continuation<()>>) -> Box<Continuation<()>> {
+        Box::new(CatchingStep { continuation: c, catcher: self.catcher.clone() })
+    }
+}
+
+impl<'a> Runtime for CatchingRuntime<'a> {
+    fn on_current_instant(&mut self, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_current_instant(step);
+    }
+
+    fn on_next_instant(&mut self, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_next_instant(step);
+    }
+
+    fn on_deadline(&mut self, deadline: time::Instant, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_deadline(deadline, step);
+    }
+
+    // The signals emitted by the process reset themselves at the end of the instant, even if it
+    // panics during the instant.
+    fn on_end_of_instant(&mut self, c: Box<Continuation<()>>) {
+        self.runtime.on_end_of_instant(c);
+    }
+
+    fn instant_index(&self) -> usize {
+        self.runtime.instant_index()
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.runtime.is_deterministic()
+    }
+
+    fn external_inbox(&self) -> ExternalInbox {
+        self.runtime.external_inbox()
+    }
+
+    fn panic_handler(&self) -> Option<PanicHandler> {
+        self.runtime.panic_handler()
+    }
+
+    fn clock(&self) -> SharedClock {
+        self.runtime.clock()
+    }
+}
@@ -0,0 +1,107 @@
+use super::*;
+
+//  ____
+// / ___|_      ____ _ _ __
+// \___ \ \ /\ / / _` | '_ \
+//  ___) \ V  V / (_| | |_) |
+// |____/ \_/\_/ \__,_| .__/
+//                    |_|
+
+/// Builds the process executed by an iteration of a swappable loop.
+type Body<V> = Arc<Fn() -> ProcessBox<LoopStatus<V>> + Send + Sync>;
+
+/// The body of the loops registered with an identifier, and how many times it was swapped.
+struct Slot<V> where V: Send + Sync + 'static {
+    body: Body<V>,
+    version: usize,
+}
+
+type SharedSlot<V> = Arc<Mutex<Slot<V>>>;
+
+fn body<F, P, V>(body: F) -> Body<V>
+    where F: Fn() -> P + Send + Sync + 'static, P: Process<Value = LoopStatus<V>>, V: Send + Sync + 'static {
+    Arc::new(move|| body().boxed())
+}
+
+/// The loops whose body can be replaced while they run, by identifier, see
+/// `SwapRegistry::swappable`.
+///
+/// Handles are cheap to clone and can be used from any thread, including one outside of the
+/// runtime.
+#[derive(Clone)]
+pub struct SwapRegistry {
+    slots: Arc<Mutex<HashMap<String, Box<Any + Send>>>>,
+}
+
+impl SwapRegistry {
+    pub fn new() -> Self {
+        SwapRegistry { slots: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// A loop executing the process returned by its body until it returns `LoopStatus::Exit`, like
+    /// `while_loop`, whose body can be replaced with `swap` without restarting it.
+    ///
+    /// The loops registered with the same identifier share their body, the one given first, so
+    /// that a single swap changes the behaviour of every copy of a process. Panics if `id` is
+    /// registered with another value type.
+    pub fn swappable<F, P, V>(&self, id: &str, initial_body: F) -> While<Swappable<V>>
+        where F: Fn() -> P + Send + Sync + 'static, P: Process<Value = LoopStatus<V>>, V: Send + Sync + 'static {
+        let mut slots = self.slots.lock().unwrap();
+        let slot = slots.entry(id.to_string()).or_insert_with(|| {
+            let slot: SharedSlot<V> = Arc::new(Mutex::new(Slot { body: body(initial_body), version: 0 }));
+            Box::new(slot)
+        });
+        let slot = slot.downcast_ref::<SharedSlot<V>>()
+            .unwrap_or_else(|| panic!("the swappable loop {} is registered with another value type", id));
+        Swappable { slot: slot.clone() }.while_loop().named(id)
+    }
+
+    /// Replaces the body of the loops registered as `id`, returns the number of times it was
+    /// swapped so far.
+    ///
+    /// The new body takes effect at the next iteration of each loop, the iterations already
+    /// started completing with the previous one.
+    pub fn swap<F, P, V>(&self, id: &str, new_body: F) -> Result<usize, String>
+        where F: Fn() -> P + Send + Sync + 'static, P: Process<Value = LoopStatus<V>>, V: Send + Sync + 'static {
+        let slot = self.slot::<V>(id)?;
+        let mut slot = slot.lock().unwrap();
+        slot.body = body(new_body);
+        slot.version += 1;
+        Ok(slot.version)
+    }
+
+    /// The number of times the body of the loops registered as `id` was swapped.
+    pub fn version<V>(&self, id: &str) -> Result<usize, String> where V: Send + Sync + 'static {
+        let slot = self.slot::<V>(id)?;
+        let version = slot.lock().unwrap().version;
+        Ok(version)
+    }
+
+    fn slot<V>(&self, id: &str) -> Result<SharedSlot<V>, String> where V: Send + Sync + 'static {
+        let slots = self.slots.lock().unwrap();
+        let slot = slots.get(id).ok_or_else(|| format!("no swappable loop is registered as {}", id))?;
+        slot.downcast_ref::<SharedSlot<V>>().cloned()
+            .ok_or_else(|| format!("the swappable loop {} is registered with another value type", id))
+    }
+}
+
+/// An iteration of a swappable loop, executing the current body, see `SwapRegistry::swappable`.
+pub struct Swappable<V> where V: Send + Sync + 'static {
+    slot: SharedSlot<V>,
+}
+
+impl<V> Process for Swappable<V> where V: Send + Sync + 'static {
+    type Value = LoopStatus<V>;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let body = self.slot.lock().unwrap().body.clone();
+        body().call(runtime, next);
+    }
+}
+
+impl<V> ProcessMut for Swappable<V> where V: Send + Sync + 'static {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        let body = self.slot.lock().unwrap().body.clone();
+        body().call(runtime, move|runtime: &mut Runtime, status| next.call(runtime, (self, status)));
+    }
+}
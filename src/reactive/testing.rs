@@ -0,0 +1,379 @@
+use super::*;
+use std::fmt::Debug;
+
+//  _____         _   _
+// |_   _|__  ___| |_(_)_ __   __ _
+//   | |/ _ \/ __| __| | '_ \ / _` |
+//   | |  __/\__ \ |_| | | | | (_| |
+//   |_|\___||___/\__|_|_| |_|\__, |
+//                            |___/
+
+/// A sequential runtime that is stepped one instant at a time by the test itself.
+pub struct TestScheduler {
+    runtime: SequentialRuntime,
+    instant: Arc<Mutex<usize>>,
+}
+
+impl TestScheduler {
+    pub fn new() -> Self {
+        TestScheduler {
+            runtime: SequentialRuntime::new(),
+            instant: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Installs a panic handler on the runtime, see `SequentialRuntime::on_process_panic`.
+    pub fn on_process_panic<F>(&mut self, handler: F) where F: Fn(&str, &(Any + Send)) -> PanicPolicy + Send + Sync + 'static {
+        self.runtime.on_process_panic(handler);
+    }
+
+    /// Reports the memory used at the end of every instant, see `SequentialRuntime::account_memory`.
+    pub fn account_memory(&mut self, accounting: &MemoryAccounting) {
+        self.runtime.account_memory(accounting);
+    }
+
+    /// Gives the time of `clock` to the processes, see `SequentialRuntime::set_clock`.
+    pub fn set_clock<C>(&mut self, clock: C) where C: Clock + 'static {
+        self.runtime.set_clock(clock);
+    }
+
+    /// A signal emitted at the start of every instant with its logical duration, see
+    /// `SequentialRuntime::dt_signal`.
+    pub fn dt_signal(&mut self, pacing: Pacing) -> ValueSignal<time::Duration, time::Duration> {
+        self.runtime.dt_signal(pacing)
+    }
+
+    /// Schedules `p` on the next instant to be executed, discarding its value.
+    pub fn spawn<P>(&mut self, p: P) where P: Process {
+        self.runtime.on_current_instant(Box::new(|run: &mut Runtime, ()| {
+            p.call(run, |_: &mut Runtime, _| ());
+        }));
+    }
+
+    /// Schedules `p` and returns a handle to its value, available once `p` terminated.
+    pub fn spawn_result<P>(&mut self, p: P) -> TestResult<P::Value> where P: Process {
+        let result = TestResult { value: Arc::new(Mutex::new(None)) };
+        let result_ref = result.value.clone();
+        self.spawn(p.map(move|v| {
+            *result_ref.lock().unwrap() = Some(v);
+        }));
+        result
+    }
+
+    /// Index of the next instant to be executed.
+    pub fn instant(&self) -> usize {
+        *self.instant.lock().unwrap()
+    }
+
+    /// Executes a single instant, returns `true` if there is work left for the next ones.
+    pub fn step(&mut self) -> bool {
+        let remaining = self.runtime.instant();
+        *self.instant.lock().unwrap() += 1;
+        remaining
+    }
+
+    /// Executes `n` instants, or less if the runtime runs out of work.
+    pub fn step_n(&mut self, n: usize) -> bool {
+        for _ in 0..n {
+            if !self.step() {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Executes instants until the runtime runs out of work, blocking instead of executing empty
+    /// instants while it only waits for timers or other threads.
+    pub fn run(&mut self) {
+        while self.step() {
+            self.runtime.wait_idle();
+        }
+    }
+
+    /// Records the gathered value of `signal` for every instant it is emitted from now on.
+    pub fn record<S, V, G>(&mut self, signal: &S) -> Recording<V>
+        where S: VSignal<V, G>, V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+        let recording = Recording { emissions: Arc::new(Mutex::new(Vec::new())) };
+        let emissions = recording.emissions.clone();
+        let instant = self.instant.clone();
+        let continue_loop: LoopStatus<()> = LoopStatus::Continue;
+        // `await` resumes on the instant following the emission.
+        let record = move|v: V| {
+            let emitted_at = *instant.lock().unwrap() - 1;
+            emissions.lock().unwrap().push((emitted_at, v));
+        };
+        self.spawn(signal.await().map(record).then(value(continue_loop)).while_loop());
+        recording
+    }
+}
+
+/// The value of a process spawned in a `TestScheduler`.
+pub struct TestResult<V> {
+    value: Arc<Mutex<Option<V>>>,
+}
+
+impl<V> TestResult<V> where V: Clone {
+    /// The value of the process, or `None` if it has not terminated yet.
+    pub fn get(&self) -> Option<V> {
+        self.value.lock().unwrap().clone()
+    }
+}
+
+/// Emissions of a signal observed by `TestScheduler::record` or `VSignal::capture`, as
+/// `(instant, value)` pairs.
+pub struct Recording<V> {
+    emissions: Arc<Mutex<Vec<(usize, V)>>>,
+}
+
+impl<V> Clone for Recording<V> {
+    fn clone(&self) -> Self {
+        Recording { emissions: self.emissions.clone() }
+    }
+}
+
+impl<V> Recording<V> {
+    pub(crate) fn new() -> Self {
+        Recording { emissions: Arc::new(Mutex::new(Vec::new())) }
+    }
+
+    pub(crate) fn push(&self, instant: usize, value: V) {
+        self.emissions.lock().unwrap().push((instant, value));
+    }
+}
+
+impl<V> Recording<V> where V: Clone {
+    pub fn emissions(&self) -> Vec<(usize, V)> {
+        self.emissions.lock().unwrap().clone()
+    }
+
+    /// The gathered value of the signal at `instant`, or `None` if it was absent.
+    pub fn at(&self, instant: usize) -> Option<V> {
+        self.emissions.lock().unwrap().iter()
+            .find(|&&(i, _)| i == instant)
+            .map(|&(_, ref v)| v.clone())
+    }
+}
+
+pub fn assert_emitted_at<V>(recording: &Recording<V>, instant: usize, value: V) where V: Clone + PartialEq + Debug {
+    assert_eq!(recording.at(instant), Some(value), "unexpected emission at instant {}", instant);
+}
+
+pub fn assert_absent_at<V>(recording: &Recording<V>, instant: usize) where V: Clone + PartialEq + Debug {
+    assert_eq!(recording.at(instant), None, "unexpected emission at instant {}", instant);
+}
+
+/// A value signal whose emissions are scripted per instant.
+pub struct MockSignal<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    signal: ValueSignal<V, G>,
+    script: Vec<(usize, G)>,
+}
+
+impl<V, G> MockSignal<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    pub fn new(default_value: V, gather: Box<Fn(V, G) -> V + Send + Sync>) -> Self {
+        MockSignal {
+            signal: ValueSignal::new(default_value, gather),
+            script: Vec::new(),
+        }
+    }
+
+    /// Adds an emission of `value` at `instant` (relative to the start of the driver process).
+    pub fn at(mut self, instant: usize, value: G) -> Self {
+        self.script.push((instant, value));
+        self
+    }
+
+    pub fn signal(&self) -> ValueSignal<V, G> {
+        self.signal.clone()
+    }
+
+    /// The process performing the scripted emissions, terminates after the last one.
+    pub fn driver(&self) -> MockDriver<V, G> {
+        let mut script = self.script.clone();
+        script.sort_by_key(|&(i, _)| i);
+        script.reverse();
+        MockDriver { signal: self.signal.clone(), script, instant: 0 }
+    }
+}
+
+impl<V, G> VSignal<V, G> for MockSignal<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    fn runtime(&self) -> VSignalRuntimeRef<V, G> {
+        self.signal.runtime()
+    }
+}
+
+pub struct MockDriver<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    signal: ValueSignal<V, G>,
+    /// Remaining emissions, the next one last.
+    script: Vec<(usize, G)>,
+    instant: usize,
+}
+
+impl<V, G> Process for MockDriver<V, G> where V: Clone + Send + Sync + 'static, G: Clone + Send + Sync + 'static {
+    type Value = ();
+
+    fn call<C>(mut self, runtime: &mut Runtime, next: C) where C: Continuation<()> {
+        while self.script.last().map_or(false, |&(i, _)| i == self.instant) {
+            let (_, v) = self.script.pop().unwrap();
+            self.signal.emit(value(v)).call(runtime, |_: &mut Runtime, _| ());
+        }
+        if self.script.is_empty() {
+            next.call(runtime, ());
+        } else {
+            self.instant += 1;
+            runtime.on_next_instant(Box::new(move|run: &mut Runtime, ()| self.call(run, next)));
+        }
+    }
+}
+
+/// A small xorshift generator, so that failing trials can be replayed from their seed.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed ^ 0x9E37_79B9_7F4A_7C15 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    /// A value in `low..high`, which must not be empty.
+    pub fn range(&mut self, low: i32, high: i32) -> i32 {
+        assert!(low < high, "Rng::range needs a non-empty range, got {}..{}", low, high);
+        low + (self.next_u64() % ((high as i64 - low as i64) as u64)) as i32
+    }
+}
+
+/// Configuration of `assert_equivalent`.
+#[derive(Copy, Clone)]
+pub struct Equivalence {
+    /// Number of random input scripts tried.
+    pub trials: usize,
+    /// Number of instants executed for each script.
+    pub instants: usize,
+    pub seed: u64,
+}
+
+impl Equivalence {
+    pub fn new() -> Self {
+        Equivalence { trials: 100, instants: 20, seed: 0 }
+    }
+}
+
+/// Number of workers of the parallel runtime of `assert_equivalent`.
+const EQUIVALENCE_WORKERS: usize = 4;
+
+/// Checks that the processes built by `build1` and `build2` emit the same values on their
+/// output signal at every instant, when their input signal is emitted according to random scripts
+/// of values drawn by `generate`. The first process is executed by a sequential runtime and the
+/// second one by a parallel runtime, so that processes can also be checked against themselves.
+///
+/// Each builder receives the input signal and the output signal, both gathering the emissions of
+/// an instant in a `Vec`, the outputs being compared regardless of emission order. The script of
+/// a diverging trial is shrunk before being reported, by dropping the inputs without which the
+/// processes still diverge; the values themselves are not shrunk.
+pub fn assert_equivalent<I, O, R, F1, F2, P1, P2>(config: Equivalence, mut generate: R, build1: F1, build2: F2)
+    where I: Clone + Debug + Send + Sync + 'static, O: Clone + Ord + Debug + Send + Sync + 'static,
+          R: FnMut(&mut Rng) -> I,
+          F1: Fn(ValueSignal<Vec<I>, I>, ValueSignal<Vec<O>, O>) -> P1, P1: Process,
+          F2: Fn(ValueSignal<Vec<I>, I>, ValueSignal<Vec<O>, O>) -> P2, P2: Process {
+    let parallel = ParallelRuntime::new(EQUIVALENCE_WORKERS).start();
+    // The outputs of both processes for `script`, if they differ.
+    let diverge = |script: &[(usize, I)]| {
+        let normalize = |recording: Recording<Vec<O>>, first: usize| {
+            recording.emissions().into_iter().map(|(i, mut vs)| {
+                vs.sort();
+                (i - first, vs)
+            }).filter(|&(i, _)| i < config.instants).collect::<Vec<_>>()
+        };
+
+        let (process, recording) = equivalence_run(script, config.instants, &build1);
+        execute_process(process);
+        let outputs1 = normalize(recording, 0);
+
+        let (process, recording) = equivalence_run(script, config.instants, &build2);
+        // The instants of the parallel runtime are counted from its first execution.
+        let first = parallel.wait_instant_end().completed_instants();
+        parallel.on_current_instant(Box::new(|runtime: &mut Runtime, ()| process.call(runtime, |_: &mut Runtime, ()| ())));
+        parallel.execute();
+        let outputs2 = normalize(recording, first);
+        if outputs1 == outputs2 { None } else { Some((outputs1, outputs2)) }
+    };
+
+    let mut rng = Rng::new(config.seed);
+    for trial in 0..config.trials {
+        let mut script = Vec::new();
+        for instant in 0..config.instants {
+            if rng.range(0, 2) == 0 {
+                for _ in 0..rng.range(1, 4) {
+                    let v = generate(&mut rng);
+                    script.push((instant, v));
+                }
+            }
+        }
+        let mut outputs = match diverge(&script) {
+            Some(outputs) => outputs,
+            None => continue,
+        };
+        let inputs = script.len();
+        let mut i = 0;
+        while i < script.len() {
+            let mut shrunk = script.clone();
+            shrunk.remove(i);
+            match diverge(&shrunk) {
+                Some(shrunk_outputs) => {
+                    script = shrunk;
+                    outputs = shrunk_outputs;
+                },
+                None => i += 1,
+            }
+        }
+        parallel.stop();
+        panic!("processes diverge on trial {} (seed {}), input script ({} of {} inputs): {:?}\n  left: {:?}\n right: {:?}",
+               trial, config.seed, script.len(), inputs, script, outputs.0, outputs.1);
+    }
+    parallel.stop();
+}
+
+/// The process built by `build` along with the emissions of its inputs according to `script`,
+/// cancelled after `instants` instants, and the recording of its output.
+fn equivalence_run<I, O, F, P>(script: &[(usize, I)], instants: usize, build: &F) -> (ProcessBox<()>, Recording<Vec<O>>)
+    where I: Clone + Send + Sync + 'static, O: Clone + Send + Sync + 'static,
+          F: Fn(ValueSignal<Vec<I>, I>, ValueSignal<Vec<O>, O>) -> P, P: Process {
+    let gather = |mut vs: Vec<I>, v| {
+        vs.push(v);
+        vs
+    };
+    let mut input: MockSignal<Vec<I>, I> = MockSignal::new(vec!(), Box::new(gather));
+    for &(instant, ref v) in script {
+        input = input.at(instant, v.clone());
+    }
+    let output: ValueSignal<Vec<O>, O> = ValueSignal::new(vec!(), Box::new(|mut vs: Vec<O>, v| {
+        vs.push(v);
+        vs
+    }));
+    let recording = output.capture();
+    let process = input.driver().join(build(input.signal(), output));
+    (timeout(process, instants + 1).map(|_| ()).boxed(), recording)
+}
+
+/// Number of workers of the runtimes of `assert_chaos_invariant`.
+const CHAOS_WORKERS: usize = 4;
+
+/// Executes copies of `process` on `iterations` parallel runtimes in chaos mode, seeded with the
+/// iteration index, and checks that `invariant` holds for the value of each run.
+pub fn assert_chaos_invariant<P, F>(process: P, invariant: F, iterations: usize)
+    where P: CloneProcess, P::Value: Debug, F: Fn(&P::Value) -> bool {
+    for seed in 0..iterations as u64 {
+        let runtime = RuntimeBuilder::new(CHAOS_WORKERS).chaos(seed).build();
+        let v = execute_process_par_with(runtime, process.clone_process());
+        assert!(invariant(&v), "invariant broken with chaos seed {}: {:?}", seed, v);
+    }
+}
@@ -1,6 +1,7 @@
 extern crate timebomb;
 
 use std::thread;
+use std::io::Write;
 use self::timebomb::{timeout_ms};
 
 use super::*;
@@ -67,6 +68,56 @@ fn test_process_pause() {
     assert_eq!(*n.lock().unwrap(), 42);
 }
 
+#[test]
+fn test_inspect() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen_ref = seen.clone();
+    let mut count = 0;
+    let step = move|()| {
+        count += 1;
+        if count == 3 { LoopStatus::Exit(count) } else { LoopStatus::Continue }
+    };
+    let log = move|status: &LoopStatus<i32>| seen_ref.lock().unwrap().push(match *status {
+        LoopStatus::Continue => None,
+        LoopStatus::Exit(v) => Some(v),
+    });
+    let p = value(()).map(step).inspect(log).pause().while_loop();
+    assert_eq!(execute_process(p), 3);
+    assert_eq!(*seen.lock().unwrap(), vec![None, None, Some(3)]);
+}
+
+#[test]
+fn test_instant_index() {
+    let p = instant_index().join(instant_index().pause().pause());
+    assert_eq!(execute_process(p), (0, 2));
+
+    let mut stamps = Vec::new();
+    let stamp = move|index| {
+        stamps.push(index);
+        if stamps.len() == 3 { LoopStatus::Exit(stamps.clone()) } else { LoopStatus::Continue }
+    };
+    // Each iteration waits for the next instant before reading the index.
+    assert_eq!(execute_process(instant_index().map(stamp).pause().while_loop()), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_parallel_instant_index() {
+    assert_eq!(execute_process_par(instant_index().join(instant_index().pause())), (0, 1));
+}
+
+#[test]
+fn test_yield_now() {
+    let steps = Arc::new(Mutex::new(Vec::new()));
+    let step = |name: &'static str| {
+        let steps = steps.clone();
+        move|()| steps.lock().unwrap().push(name)
+    };
+    let long = value(()).map(step("long 1")).then(yield_now()).map(step("long 2"));
+    let short = value(()).map(step("short"));
+    execute_process(join(long, short));
+    assert_eq!(*steps.lock().unwrap(), vec!["long 1", "short", "long 2"]);
+}
+
 #[test]
 fn test_process_join() {
     let n = Arc::new(Mutex::new((0, 0)));
@@ -110,6 +161,64 @@ fn test_process_while() {
     assert_eq!(*n.lock().unwrap(), 42);
 }
 
+#[test]
+fn test_fold() {
+    let count = |n: i32, ()| if n + 1 == 42 { FoldStatus::Exit(n + 1) } else { FoldStatus::Continue(n + 1) };
+    assert_eq!(execute_process(value(()).pause().fold(0, count)), 42);
+
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let collect = |mut values: Vec<i32>, v: i32| {
+        values.push(v);
+        if values.len() == 3 { FoldStatus::Exit(values) } else { FoldStatus::Continue(values) }
+    };
+    let emitter = s.emit(value(1)).pause().repeat(6);
+    let p = s.await().fold(vec![], collect).repeat(2).join(emitter);
+    assert_eq!(execute_process(p).0, vec![vec![1, 1, 1], vec![1, 1, 1]]);
+
+    let spinning = value(()).fold((), |(), ()| FoldStatus::Continue(())).spin_guard(1000);
+    let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| execute_process(spinning))).unwrap_err();
+    assert_eq!(payload.downcast_ref::<String>().map(|s| &s[..]),
+               Some("fold completed 1000 iterations during instant 0 without pausing"));
+}
+
+#[test]
+fn test_scan() {
+    let totals: ValueSignal<Vec<i32>, i32> = ValueSignal::new(vec![], Box::new(|mut v: Vec<i32>, x| {
+        v.push(x);
+        v
+    }));
+    let prices: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let mut scheduler = TestScheduler::new();
+    let running_total = prices.await().scan(0, |total, price| total + price, &totals).repeat(3);
+    let result = scheduler.spawn_result(running_total);
+    let recording = totals.capture();
+    scheduler.spawn(prices.emit(value(3)).then(prices.emit(value(4)).pause()).then(prices.emit(value(5)).pause()).map(|_| ()));
+    scheduler.run();
+    assert_eq!(result.get(), Some(vec![3, 7, 12]));
+    assert_eq!(recording.emissions(), vec![(1, vec![3]), (2, vec![7]), (3, vec![12])]);
+}
+
+#[test]
+fn test_while_loop_with() {
+    let count = |n: i32| value(if n == 42 { LoopState::Exit(n) } else { LoopState::Continue(n + 1) });
+    assert_eq!(execute_process(while_loop_with(0, count)), 42);
+
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let signal = s.clone();
+    let track = move|(last, changes): (i32, usize)| {
+        let compare = move|v: i32| match (v != last, changes) {
+            (true, 2) => LoopState::Exit(v),
+            (true, _) => LoopState::Continue((v, changes + 1)),
+            (false, _) => LoopState::Continue((v, changes)),
+        };
+        signal.await().map(compare)
+    };
+    let emitter = s.emit(value(1)).then(s.emit(value(1)).pause()).then(s.emit(value(2)).pause()).then(s.emit(value(2)).pause())
+        .then(s.emit(value(3)).pause()).then(s.emit(value(4)).pause());
+    let p = while_loop_with((0, 0), track).repeat(1).join(emitter);
+    assert_eq!(execute_process(p).0, vec![3]);
+}
+
 #[test]
 fn test_process_if() {
     let p = if_else(value(false),
@@ -169,6 +278,35 @@ fn test_signal_await_2() {
     assert_eq!(*n.lock().unwrap(), 43);
 }
 
+#[test]
+fn test_external_inbox() {
+    let s = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let mut scheduler = TestScheduler::new();
+    let inbox = scheduler.spawn_result(external_inbox());
+    let recording = scheduler.record(&s);
+    scheduler.step();
+    let inbox = inbox.get().unwrap();
+    let s_ref = s.clone();
+    thread::spawn(move|| {
+        inbox.emit(&s_ref, 2);
+        inbox.emit(&s_ref, 3);
+    }).join().unwrap();
+    scheduler.step_n(2);
+    assert_eq!(recording.emissions(), vec![(1, 5)]);
+
+    // A signal emitted from outside of the program is present from the start of the instant.
+    let p = PureSignal::new();
+    let mut runtime = SequentialRuntime::new();
+    let p_ref = p.clone();
+    runtime.external_inbox().push(move|runtime: &mut Runtime, ()| p_ref.emit().call(runtime, |_: &mut Runtime, ()| ()));
+    let awaited = Arc::new(Mutex::new(None));
+    let awaited_ref = awaited.clone();
+    let p = p.await_immediate().then(instant_index()).map(move|i| *awaited_ref.lock().unwrap() = Some(i));
+    runtime.on_current_instant(Box::new(|runtime: &mut Runtime, ()| p.call(runtime, |_: &mut Runtime, ()| ())));
+    runtime.execute();
+    assert_eq!(*awaited.lock().unwrap(), Some(0));
+}
+
 #[test]
 fn test_signal_present() {
     let s = PureSignal::new();
@@ -257,6 +395,30 @@ fn test_unique_producer_signal() {
     assert_eq!(execute_process(join(s_prod.emit(value(1)), join(s_cons.await_immediate(), s_cons.await_immediate()))), ((), (1, 1)));
 }
 
+#[test]
+fn test_signal_adapters() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let (pure, to_pure) = pure_from_value(&s);
+    let (constant, to_constant) = value_from_pure(&pure, 7);
+    let (unique, to_unique) = unique_from_shared(&s);
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(to_pure);
+    scheduler.spawn(to_constant);
+    scheduler.spawn(to_unique);
+    let constants = scheduler.record(&constant);
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let received_ref = received.clone();
+    let receive = move|v| {
+        received_ref.lock().unwrap().push(v);
+        LoopStatus::Continue::<()>
+    };
+    scheduler.spawn(unique.await_immediate().map(receive).pause().while_loop());
+    scheduler.spawn(s.emit(value(1)).then(s.emit(value(2))).then(s.emit(value(3)).pause().pause()));
+    scheduler.step_n(5);
+    assert_eq!(constants.emissions(), vec![(0, 7), (2, 7)]);
+    assert_eq!(*received.lock().unwrap(), vec![3, 3]);
+}
+
 #[test]
 fn test_parallel() {
     assert_eq!(execute_process_par(join(value(15), value(1337))), (15, 1337));
@@ -282,4 +444,2209 @@ fn test_parallel_signal() {
                        ((), 10));
         }
     }, 5000);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_parallel_chaos() {
+    timeout_ms(|| {
+        let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+        let p = join(
+            s.emit(value(2)).then(s.emit(value(5)).pause()).then(s.emit(value(15)).pause()).then(value(())),
+            join(
+                s.await(),
+                s.await().then(s.await())
+            ).map(|(x, y)| {
+                x * y
+            })
+        );
+        assert_chaos_invariant(p, |&((), v)| v == 10, 50);
+    }, 5000);
+}
+
+#[test]
+fn test_parallel_instant_boundary() {
+    timeout_ms(|| {
+        const INSTANTS: usize = 20;
+        let runtime = ParallelRuntime::new(4);
+        let boundary = runtime.instant_boundary();
+        let writes = Arc::new(Mutex::new(0));
+        let writer = |writes: Arc<Mutex<usize>>| {
+            let write = move|()| *writes.lock().unwrap() += 1;
+            value(()).map(write).pause().repeat(INSTANTS)
+        };
+        let reads = writes.clone();
+        let reader = thread::spawn(move|| {
+            let mut seen = 0;
+            while seen < INSTANTS + 1 {
+                let end = boundary.wait_instant_end_after(seen);
+                assert!(end.completed_instants() > seen);
+                seen = end.completed_instants();
+                // Both writers write once per instant from the second one, and before it ends.
+                assert_eq!(*reads.lock().unwrap(), 2 * seen.saturating_sub(1));
+                // The next instant waits for the reader.
+                thread::sleep(time::Duration::from_millis(1));
+                assert_eq!(*reads.lock().unwrap(), 2 * seen.saturating_sub(1));
+            }
+        });
+        execute_process_par_with(runtime, writer(writes.clone()).join(writer(writes)));
+        reader.join().unwrap();
+    }, 5000);
+}
+
+#[test]
+fn test_parallel_process_panic() {
+    timeout_ms(|| {
+        let panicking = || join(value(()).pause().map(|()| -> i32 { panic!("abort") }), value(2).pause().pause());
+        let payload = std::panic::catch_unwind(|| execute_process_par(panicking())).unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"abort"));
+
+        let runtime = RuntimeBuilder::new(4).on_process_panic(|_, _| PanicPolicy::Abort).build();
+        let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| execute_process_par_with(runtime, panicking()))).unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"abort"));
+
+        let runtime = RuntimeBuilder::new(4).on_process_panic(|_, _| PanicPolicy::Skip).build();
+        runtime.register(value(()).map(|()| panic!("skipped")));
+        assert_eq!(execute_process_par_with(runtime, value(2).pause().pause()), 2);
+    }, 5000);
+}
+
+#[cfg(feature = "affinity")]
+#[test]
+fn test_parallel_pinned() {
+    let cores = available_cores();
+    assert!(!cores.is_empty());
+    let runtime = RuntimeBuilder::new(2 * cores.len()).pin_workers(cores.clone()).build();
+    assert_eq!(execute_process_par_with(runtime, join(value(15), value(1337))), (15, 1337));
+
+    // Two sockets sharing the cores: the even workers form the first one.
+    let half = (cores.len() + 1) / 2;
+    let sockets = vec![cores[..half].to_vec(), cores[cores.len() - half..].to_vec()];
+    let runtime = RuntimeBuilder::new(4).partition_by_socket(sockets).build();
+    let socket = |()| current_worker().unwrap() % 2;
+    let sockets = value(()).map(socket).pause().repeat(20);
+    let visited = execute_process_par_with(runtime, sockets);
+    assert!(visited.iter().all(|&socket| socket == visited[0]), "{:?}", visited);
+}
+
+#[test]
+fn test_mock_signal() {
+    let mock: MockSignal<i32, i32> = MockSignal::new(0, Box::new(|x, y| x + y))
+        .at(0, 1)
+        .at(0, 2)
+        .at(3, 5);
+    let mut scheduler = TestScheduler::new();
+    let recording = scheduler.record(&mock);
+    scheduler.spawn(mock.driver());
+    scheduler.run();
+
+    assert_emitted_at(&recording, 0, 3);
+    assert_absent_at(&recording, 1);
+    assert_emitted_at(&recording, 3, 5);
+    assert_eq!(recording.emissions().len(), 2);
+}
+
+#[test]
+fn test_scheduler_step() {
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(value(42).pause().pause());
+    assert!(scheduler.step());
+    assert_eq!(result.get(), None);
+    assert!(scheduler.step());
+    assert_eq!(result.get(), None);
+    assert!(!scheduler.step());
+    assert_eq!(result.get(), Some(42));
+    assert_eq!(scheduler.instant(), 3);
+}
+
+#[test]
+fn test_assert_equivalent() {
+    let continue_loop: LoopStatus<()> = LoopStatus::Continue;
+    let sum = |xs: Vec<i32>| xs.into_iter().sum::<i32>();
+    let draw = |rng: &mut Rng| rng.range(-100, 100);
+    assert_equivalent(Equivalence::new(), draw,
+        move|input, output| {
+            let double = |x| 2 * x;
+            output.emit(input.await().map(sum).map(double)).then(value(continue_loop)).while_loop()
+        },
+        move|input, output| {
+            let double = |x| x + x;
+            let emit = move|x| output.emit(value(x));
+            input.await().map(sum).map(double).map(emit).flatten()
+                .then(value(continue_loop)).while_loop()
+        });
+
+    // Counting the inputs of each instant, and counting one too many.
+    let config = Equivalence { trials: 10, instants: 5, seed: 3 };
+    let count = |xs: Vec<u8>| xs.len();
+    let miscount = |xs: Vec<u8>| xs.len() + 1;
+    let divergence = std::panic::catch_unwind(|| assert_equivalent(config, |rng: &mut Rng| rng.range(0, 10) as u8,
+        move|input, output| output.emit(input.await().map(count)).then(value(continue_loop)).while_loop(),
+        move|input, output| output.emit(input.await().map(miscount)).then(value(continue_loop)).while_loop()));
+    let message = divergence.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains("processes diverge on trial 0 (seed 3)"), "{}", message);
+    // Any input is enough for the counts to differ.
+    assert!(message.contains("input script (1 of "), "{}", message);
+    assert!(std::panic::catch_unwind(|| Rng::new(0).range(3, 3)).is_err());
+}
+
+#[test]
+fn test_signal_wake_order() {
+    let s = PureSignal::new();
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let mut waiters = Vec::new();
+    for i in 0..5 {
+        let order = order.clone();
+        waiters.push(s.await_immediate().map(move|()| order.lock().unwrap().push(i)));
+    }
+
+    execute_process(multi_join(waiters).join(s.emit().pause()));
+    assert_eq!(*order.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn test_join_cell() {
+    let mut runtime = SequentialRuntime::new();
+    let result = Arc::new(Mutex::new(None));
+    let result_ref = result.clone();
+    let jp = JoinCell::new(3);
+    jp.fill(&mut runtime, 2, 'c');
+    jp.on_complete(&mut runtime, move|_: &mut Runtime, values| *result_ref.lock().unwrap() = Some(values));
+    jp.fill(&mut runtime, 0, 'a');
+    assert_eq!(*result.lock().unwrap(), None);
+    jp.fill(&mut runtime, 1, 'b');
+    assert_eq!(*result.lock().unwrap(), Some(vec!['a', 'b', 'c']));
+}
+
+#[test]
+fn test_join_continuations() {
+    let mut runtime = SequentialRuntime::new();
+    let result = Arc::new(Mutex::new(None));
+    let result_ref = result.clone();
+    let (left, right) = join_continuations(move|_: &mut Runtime, values| *result_ref.lock().unwrap() = Some(values));
+    right.call(&mut runtime, "right");
+    assert_eq!(*result.lock().unwrap(), None);
+    left.call(&mut runtime, 1);
+    assert_eq!(*result.lock().unwrap(), Some((1, "right")));
+
+    let result = Arc::new(Mutex::new(None));
+    let result_ref = result.clone();
+    let mut slots = multi_join_continuations(2, move|_: &mut Runtime, values| *result_ref.lock().unwrap() = Some(values));
+    slots.pop().unwrap().call(&mut runtime, 'b');
+    slots.pop().unwrap().call(&mut runtime, 'a');
+    assert_eq!(*result.lock().unwrap(), Some(vec!['a', 'b']));
+}
+
+#[test]
+fn test_replicate() {
+    let s: ValueSignal<Vec<usize>, usize> = ValueSignal::new(Vec::new(), Box::new(|mut v: Vec<usize>, x| {
+        v.push(x);
+        v
+    }));
+    let n = Arc::new(Mutex::new(0));
+    let nn = n.clone();
+    let count = move|()| {
+        let mut n = nn.lock().unwrap();
+        *n += 1;
+        *n
+    };
+    let workers = replicate(s.emit(value(()).map(count)), 4);
+    assert_eq!(workers.len(), 4);
+    assert_eq!(execute_process(multi_join(workers).join(s.await())), (vec![1, 2, 3, 4], vec![1, 2, 3, 4]));
+
+    // Each copy has its own copy of the state of the closure.
+    let mut iterations = 0;
+    let step = move|()| {
+        iterations += 1;
+        if iterations == 3 { LoopStatus::Exit(iterations) } else { LoopStatus::Continue }
+    };
+    let loops = replicate(value(()).map(step).pause().while_loop(), 3);
+    assert_eq!(execute_process(multi_join(loops)), vec![3, 3, 3]);
+    assert!(replicate(value(0), 0).is_empty());
+}
+
+#[test]
+fn test_process_memoize() {
+    let runs = Arc::new(Mutex::new(0));
+    let runs_ref = runs.clone();
+    let iteration = Arc::new(Mutex::new(0));
+    let iteration_ref = iteration.clone();
+
+    let body = move|()| {
+        *runs_ref.lock().unwrap() += 1;
+    };
+    // Keys 0, 0, 0, 1, 1, 1, 2, ... one iteration per instant.
+    let key = move|| *iteration_ref.lock().unwrap() / 3;
+    let step = move|()| {
+        let mut i = iteration.lock().unwrap();
+        *i += 1;
+        if *i == 9 { LoopStatus::Exit(()) } else { LoopStatus::Continue }
+    };
+    let p = value(()).map(body).memoize_by(key, 2).map(step).pause().while_loop();
+
+    execute_process(p);
+    // Each key is computed once, then again once its cached value is two instants old.
+    assert_eq!(*runs.lock().unwrap(), 6);
+}
+
+#[test]
+fn test_shared() {
+    let runs = Arc::new(Mutex::new(0));
+    let runs_ref = runs.clone();
+    let expensive = move|()| {
+        *runs_ref.lock().unwrap() += 1;
+        42
+    };
+    let shared = value(()).map(expensive).pause().shared();
+    let double = |v: i32| v * 2;
+    let p = join(shared.clone().map(double), join(shared.clone(), value(()).pause().pause().then(shared)));
+    assert_eq!(execute_process(p), (84, (42, 42)));
+    assert_eq!(*runs.lock().unwrap(), 1);
+}
+
+#[test]
+fn test_measure_latency() {
+    let src: ValueSignal<Vec<Tagged<i32>>, Tagged<i32>> = ValueSignal::new(vec![], Box::new(|mut v: Vec<Tagged<i32>>, x| {
+        v.push(x);
+        v
+    }));
+    let dst: ValueSignal<Option<Tagged<i32>>, Tagged<i32>> = ValueSignal::new(None, Box::new(|_, x| Some(x)));
+    let (latencies, monitor) = measure_latency(&src, &dst);
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(monitor);
+    let recording = scheduler.record(&latencies);
+    // A stage forwarding the values of `src` to `dst` as soon as it receives them.
+    let dst_ref = dst.clone();
+    let stage = move|values: Vec<Tagged<i32>>| {
+        let dst = dst_ref.clone();
+        let forwarded = values.into_iter().map(move|v| dst.emit(value(v.map(|x| x * 10)))).collect();
+        multi_join(forwarded).map(|_| LoopStatus::Continue::<()>)
+    };
+    scheduler.spawn(src.await().then_with(stage).while_loop());
+    scheduler.spawn(emit_tagged(&src, 1).then(emit_tagged(&src, 2).pause().pause()));
+    scheduler.step_n(6);
+    // Received one instant after their emission, the values are forwarded during that instant.
+    assert_eq!(recording.emissions(), vec![(2, vec![1]), (4, vec![1])]);
+
+    // A value emitted on both signals in the same instant, `dst` being read first, is measured
+    // once and then forgotten.
+    let tagged = Tagged::new(3);
+    let (dst_ref, src_ref, again) = (dst.clone(), src.clone(), tagged.clone());
+    let emit_both = move|_| dst_ref.emit(value(tagged.clone())).then(src_ref.emit(value(tagged)));
+    scheduler.spawn(value(()).then_with(emit_both).then(value(()).pause().pause()).then(dst.emit(value(again))));
+    scheduler.step_n(6);
+    assert_eq!(recording.emissions(), vec![(2, vec![1]), (4, vec![1]), (7, vec![0])]);
+
+    // Each execution of a tagged emission gets its own identifier.
+    let emit = emit_tagged(&src, 1);
+    let ids = |values: Vec<Tagged<i32>>| values.ids();
+    let ids = execute_process(join(emit.clone_process(), emit).then(src.await().map(ids)));
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids[0], ids[1]);
+}
+
+#[test]
+fn test_profiler() {
+    let profiler = Profiler::new();
+    let slow = |()| thread::sleep(time::Duration::from_millis(5));
+    let fast = |()| ();
+    let p = join(
+        value(()).map(slow).profile(&profiler, "slow")
+            .then(value(()).pause())
+            .then(value(()).map(slow).profile(&profiler, "slow")),
+        value(()).map(fast).profile(&profiler, "fast")
+    );
+    execute_process(p);
+
+    let report = profiler.report(1);
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].name, "slow");
+    assert_eq!(report[0].calls, 2);
+    assert_eq!(report[0].instants, 2);
+    assert!(report[0].total >= time::Duration::from_millis(10));
+}
+
+#[test]
+fn test_tracer() {
+    let tracer = Tracer::new();
+    let slow = |()| thread::sleep(time::Duration::from_millis(2));
+    let p = value(()).map(slow).traced(&tracer, "inner").traced(&tracer, "outer \"step\"")
+        .then(value(()).pause().traced(&tracer, "pause"));
+    execute_process(p);
+
+    let events = tracer.events();
+    let names: Vec<&str> = events.iter().map(|e| e.name.as_str()).collect();
+    // Spans are recorded once their process reaches its continuation, which executes the rest of
+    // the instant.
+    assert_eq!(names, vec!["pause", "inner", "outer \"step\""]);
+    assert!(events[2].start <= events[1].start);
+    assert!(events[2].duration.unwrap() >= events[1].duration.unwrap());
+    assert!(events[1].duration.unwrap() >= time::Duration::from_millis(2));
+
+    let mut trace = Vec::new();
+    tracer.write_chrome_trace(&mut trace).unwrap();
+    let trace = String::from_utf8(trace).unwrap();
+    assert!(trace.starts_with("[\n{\"name\":\"pause\",\"ph\":\"X\",\"dur\":"));
+    assert!(trace.contains("{\"name\":\"outer \\\"step\\\"\",\"ph\":\"X\""));
+    assert_eq!(trace.lines().count(), 5);
+}
+
+#[test]
+fn test_unique_consumer_await_all_producers() {
+    let (s_prod, s_cons): (UniqueConsumerSignalProducer<Vec<i32>, i32>, UniqueConsumerSignalConsumer<Vec<i32>, i32>) =
+        UniqueConsumerSignalProducer::new(
+            Box::new(|| vec![]),
+            Box::new(|mut v, x| {
+                v.push(x);
+                v
+            }));
+    let s_prod2 = s_prod.clone();
+    assert_eq!(s_cons.producer_count(), 2);
+    {
+        let s_prod3 = s_prod.clone();
+        assert_eq!(s_cons.producer_count(), 3);
+        drop(s_prod3);
+    }
+    assert_eq!(s_cons.producer_count(), 2);
+
+    // Only the first producer emits at the first instant, both emit at the second one.
+    let p = s_prod.emit(value(1)).then(value(()).pause()).then(s_prod.emit(value(2)));
+    let q = value(()).pause().then(s_prod2.emit(value(3)));
+    assert_eq!(execute_process(join(p.join(q), s_cons.await_all_producers())), (((), ()), vec![2, 3]));
+}
+
+#[test]
+fn test_value_signal_emit_and_peek() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+
+    assert_eq!(execute_process(s.emit(value(1)).then(s.emit_and_peek(value(5)))), 6);
+    assert_eq!(execute_process(s.emit(value(1)).then(s.emit_and_peek(value(5)).pause())), 5);
+}
+
+#[test]
+fn test_deterministic_gather() {
+    let s: ValueSignal<Vec<i32>, i32> = ValueSignal::new(vec![], Box::new(|mut v: Vec<i32>, x| {
+        v.push(x);
+        v
+    }));
+    let first = s.emit(value(1));
+    let second = s.emit(value(2));
+    let third = s.emit(value(3));
+    let result = Arc::new(Mutex::new(None));
+    let result_ref = result.clone();
+    let p = join(join(third, second.pause()), join(first, s.await()))
+        .map(move|(_, (_, v))| *result_ref.lock().unwrap() = Some(v));
+
+    let mut runtime = SequentialRuntime::new_deterministic();
+    runtime.on_current_instant(Box::new(|run: &mut Runtime, ()| p.call(run, |_: &mut Runtime, ()| ())));
+    runtime.execute();
+    // The values are gathered in the order of the emissions in the program, whatever the order in
+    // which the emitters were created. `second` is emitted in the next instant.
+    assert_eq!(*result.lock().unwrap(), Some(vec![3, 1]));
+
+    let first = s.emit(value(1));
+    let second = s.emit(value(2));
+    let p = join(second, first).then(s.await_immediate()).then(s.await());
+    let mut runtime = SequentialRuntime::new_deterministic();
+    let result = Arc::new(Mutex::new(None));
+    let result_ref = result.clone();
+    runtime.on_current_instant(Box::new(|run: &mut Runtime, ()| {
+        p.call(run, move|_: &mut Runtime, v| *result_ref.lock().unwrap() = Some(v))
+    }));
+    runtime.execute();
+    assert_eq!(*result.lock().unwrap(), Some(vec![2, 1]));
+}
+
+#[test]
+fn test_deterministic_parallel_gather() {
+    timeout_ms(|| {
+        let program = || {
+            let s: ValueSignal<Vec<i32>, i32> = ValueSignal::new(vec![], Box::new(|mut v: Vec<i32>, x| {
+                v.push(x);
+                v
+            }));
+            let ready = PureSignal::new();
+            // Created in the reverse order of their place in the program.
+            let mut emitters: Vec<_> = (0..8).rev().map(|i| s.emit(value(i))).collect();
+            emitters.reverse();
+            let late = ready.await_immediate().then(s.emit(value(8)));
+            join(late, multi_join(emitters).then(ready.emit())).then(s.await())
+        };
+        let expected: Vec<i32> = (0..9).collect();
+        let result = Arc::new(Mutex::new(None));
+        let result_ref = result.clone();
+        let p = program();
+        let mut runtime = SequentialRuntime::new_deterministic();
+        runtime.on_current_instant(Box::new(|run: &mut Runtime, ()| {
+            p.call(run, move|_: &mut Runtime, v| *result_ref.lock().unwrap() = Some(v))
+        }));
+        runtime.execute();
+        assert_eq!(*result.lock().unwrap(), Some(expected.clone()));
+        for seed in 0..20 {
+            let runtime = RuntimeBuilder::new(4).deterministic().chaos(seed).build();
+            assert_eq!(execute_process_par_with(runtime, program()), expected);
+        }
+    }, 10000);
+}
+
+#[test]
+fn test_either() {
+    let left = || -> Value<Either<i32, bool>> { value(Either::Left(2)) };
+    let right = || -> Value<Either<i32, bool>> { value(Either::Right(true)) };
+    assert_eq!(execute_process(left().map_left(|x| x * 3)), Either::Left(6));
+    assert_eq!(execute_process(right().map_left(|x| x * 3)), Either::Right(true));
+    assert_eq!(execute_process(right().map_right(|b| !b)), Either::Right(false));
+
+    let fold = |p: Value<Either<i32, bool>>| p.fold_either(|x| x.to_string(), |b| b.to_string());
+    assert_eq!(execute_process(fold(left())), "2");
+    assert_eq!(execute_process(fold(right())), "true");
+
+    let n = Arc::new(Mutex::new(0));
+    let nn = n.clone();
+    let count = move|x: i32| {
+        let mut n = nn.lock().unwrap();
+        *n += x;
+        if *n >= 6 { LoopStatus::Exit(*n) } else { LoopStatus::Continue }
+    };
+    let never = |_: bool| LoopStatus::Continue;
+    assert_eq!(execute_process(left().fold_either(count, never).while_loop()), 6);
+}
+
+#[test]
+fn test_if_else_either() {
+    let branch = |cond: bool| if_else_either(value(cond), value(1), value("one").pause());
+    assert_eq!(execute_process(branch(true)), Either::Left(1));
+    assert_eq!(execute_process(branch(false)), Either::Right("one"));
+
+    let mut toggle = false;
+    let flip = move|()| {
+        toggle = !toggle;
+        toggle
+    };
+    let p = if_else_either(value(()).map(flip), value(2), value('b')).repeat(3);
+    assert_eq!(execute_process(p.clone_process()), vec![Either::Left(2), Either::Right('b'), Either::Left(2)]);
+}
+
+#[test]
+fn test_retry() {
+    let attempts = Arc::new(Mutex::new(0));
+    let attempts_ref = attempts.clone();
+    let factory = move|| {
+        let mut attempts = attempts_ref.lock().unwrap();
+        *attempts += 1;
+        value(if *attempts < 3 { Err(*attempts) } else { Ok(*attempts) })
+    };
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(retry(factory, RetryPolicy::new(5)));
+    // Attempts at instants 0, 1 and 3.
+    let mut attempts_after_step = vec![];
+    for _ in 0..4 {
+        scheduler.step();
+        attempts_after_step.push(*attempts.lock().unwrap());
+    }
+    assert_eq!(attempts_after_step, vec![1, 2, 2, 3]);
+    assert_eq!(result.get(), Some(Ok(3)));
+
+    let factory = || value(Err::<(), &str>("down"));
+    let policy = RetryPolicy { max_attempts: 3, initial_delay: 0, factor: 1, max_delay: 0 };
+    assert_eq!(execute_process(retry(factory, policy)), Err("down"));
+}
+
+#[test]
+fn test_copy_value_signal() {
+    fn max(x: u8, y: u8) -> u8 {
+        std::cmp::max(x, y)
+    }
+    let s = CopyValueSignal::new(0, max);
+    let continue_loop: LoopStatus<()> = LoopStatus::Continue;
+    let emitters = multi_join(vec![s.emit(value(3)), s.emit(value(7)), s.emit(value(5))]);
+    let p = emitters.then(s.present()).join(s.await()).join(s.present().pause());
+    assert_eq!(execute_process(p), ((true, 7), false));
+
+    let n = Arc::new(Mutex::new(0));
+    let nn = n.clone();
+    let count = move|v: u8| {
+        let mut n = nn.lock().unwrap();
+        *n += 1;
+        if *n == 3 { LoopStatus::Exit(v) } else { LoopStatus::Continue }
+    };
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(s.emit(value(4)).then(value(continue_loop).pause()).while_loop());
+    let result = scheduler.spawn_result(s.await().map(count).while_loop());
+    scheduler.step_n(3);
+    assert_eq!(result.get(), None);
+    scheduler.step();
+    assert_eq!(result.get(), Some(4));
+}
+
+#[test]
+fn test_join_array() {
+    assert_eq!(execute_process(join_array([value(1), value(2), value(3), value(4)])), [1, 2, 3, 4]);
+
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let emit = |x| s.emit(value(x));
+    let p = join_array([emit(1), emit(2), emit(4)]).join(s.await());
+    assert_eq!(execute_process(p), ([1, 2, 4], 7));
+
+    let n = Arc::new(Mutex::new(0));
+    let nn = n.clone();
+    let count = move|values: [i32; 2]| {
+        let mut n = nn.lock().unwrap();
+        *n += values[0] + values[1];
+        if *n >= 9 { LoopStatus::Exit(*n) } else { LoopStatus::Continue }
+    };
+    assert_eq!(execute_process(join_array([value(1), value(2)]).map(count).pause().while_loop()), 9);
+}
+
+#[test]
+fn test_join_macro() {
+    assert_eq!(execute_process(join!(value(1), value('b'))), (1, 'b'));
+    assert_eq!(execute_process(join!(value(1), value('b').pause(), value("c"))), (1, 'b', "c"));
+
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let p = join!(s.emit(value(1)), s.emit(value(2)), value(3).pause(), s.await(), value(5), value(6), value(7), value(8));
+    assert_eq!(execute_process(p), (1, 2, 3, 3, 5, 6, 7, 8));
+
+    let n = Arc::new(Mutex::new(0));
+    let nn = n.clone();
+    let count = move|(a, b, c, d): (i32, i32, i32, i32)| {
+        let mut n = nn.lock().unwrap();
+        *n += a + b + c + d;
+        if *n >= 20 { LoopStatus::Exit(*n) } else { LoopStatus::Continue }
+    };
+    let p = join!(value(1), value(2), value(3), value(4).pause()).map(count).while_loop();
+    assert_eq!(execute_process(p.clone_process()), 20);
+}
+
+#[test]
+fn test_ops() {
+    use super::ops::*;
+
+    assert_eq!(execute_process(flow(value(1)) >> value(2)), 2);
+    assert_eq!(execute_process(value(1).flow() | value(2)), (1, 2));
+    assert_eq!(execute_process(value(1).flow() >> value(2) | value(3).pause()), (2, 3));
+
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let p = s.emit(value(1)).flow() | s.emit(value(2)) | s.await();
+    assert_eq!(execute_process(p), ((1, 2), 3));
+}
+
+#[test]
+fn test_await_or() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let mut scheduler = TestScheduler::new();
+    let absent = scheduler.spawn_result(s.await_or(-1));
+    let emitted = scheduler.spawn_result(s.await_or(-1).join(s.emit(value(2))).pause());
+    scheduler.step();
+    assert_eq!(absent.get(), None);
+    scheduler.step();
+    assert_eq!(absent.get(), Some(-1));
+    assert_eq!(emitted.get(), None);
+    scheduler.step();
+    assert_eq!(emitted.get(), Some((2, 2)));
+
+    let n = Arc::new(Mutex::new(vec![]));
+    let nn = n.clone();
+    let record = move|v: i32| {
+        let mut n = nn.lock().unwrap();
+        n.push(v);
+        if n.len() == 3 { LoopStatus::Exit(()) } else { LoopStatus::Continue }
+    };
+    let emit_once = s.emit(value(5)).pause();
+    execute_process(s.await_or(0).map(record).while_loop().join(emit_once));
+    assert_eq!(*n.lock().unwrap(), vec![0, 5, 0]);
+}
+
+#[test]
+fn test_signal_hibernation() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y)).hibernate_after(2);
+    let mut scheduler = TestScheduler::new();
+    let recording = scheduler.record(&s);
+    scheduler.spawn(s.emit(value(1)).then(s.emit(value(2)).pause()));
+    // Silent at instants 2 and 3.
+    scheduler.step_n(3);
+    assert!(!s.is_hibernating());
+    scheduler.step();
+    assert!(s.is_hibernating());
+
+    scheduler.spawn(s.emit(value(3)));
+    scheduler.step();
+    assert!(!s.is_hibernating());
+    scheduler.step_n(2);
+    assert!(s.is_hibernating());
+    assert_eq!(recording.emissions(), vec![(0, 1), (1, 2), (4, 3)]);
+}
+
+#[test]
+fn test_multi_join_empty() {
+    let empty: Vec<Value<i32>> = vec![];
+    assert_eq!(execute_process(multi_join(empty)), vec![]);
+
+    let n = Arc::new(Mutex::new(0));
+    let nn = n.clone();
+    let iter = move|v: Vec<i32>| {
+        assert!(v.is_empty());
+        let mut x = nn.lock().unwrap();
+        *x += 1;
+        if *x == 3 { LoopStatus::Exit(()) } else { LoopStatus::Continue }
+    };
+    let empty: Vec<Value<i32>> = vec![];
+    execute_process(multi_join(empty).map(iter).pause().while_loop());
+    assert_eq!(*n.lock().unwrap(), 3);
+}
+
+#[test]
+fn test_join_completed_branches() {
+    // Both branches complete during the instant the join is executed.
+    assert_eq!(execute_process(join(value(1), value(2)).then(join(value(3), value(4).pause()))), (3, 4));
+}
+
+#[test]
+fn test_read_file_process() {
+    use std::env;
+    use std::fs::File;
+    use std::io::Write;
+
+    let path = env::temp_dir().join("reactive_rs_test_read_file_process.txt");
+    File::create(&path).unwrap().write_all(b"redstone").unwrap();
+
+    assert_eq!(execute_process(blocking_io::read_file_process(path)).unwrap(), "redstone");
+    assert!(execute_process(blocking_io::read_file_process(env::temp_dir().join("reactive_rs_no_such_file"))).is_err());
+}
+
+#[test]
+fn test_blocking() {
+    let slow = blocking_io::blocking(|| {
+        thread::sleep(time::Duration::from_millis(20));
+        1
+    });
+    assert_eq!(execute_process(slow), 1);
+
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let names_ref = names.clone();
+    let mut scheduler = TestScheduler::new();
+    scheduler.on_process_panic(move|name, _| {
+        names_ref.lock().unwrap().push(name.to_string());
+        PanicPolicy::Skip
+    });
+    let result = scheduler.spawn_result(blocking_io::blocking(|| -> i32 { panic!("blocking") }));
+    scheduler.run();
+    assert_eq!(result.get(), None);
+    assert_eq!(*names.lock().unwrap(), vec![UNSUPERVISED]);
+
+    // A cancelled process does not keep the runtime waiting for its thread.
+    let stuck = blocking_io::blocking(|| thread::sleep(time::Duration::from_secs(3)));
+    timeout_ms(move|| assert_eq!(execute_process(timeout(stuck, 1)), None), 1000);
+}
+
+#[test]
+fn test_tcp_listener_signal() {
+    use std::net::TcpStream;
+
+    let (connections, accept) = blocking_io::tcp_listener_signal("127.0.0.1:0").unwrap();
+    let addr = accept.local_addr();
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(accept);
+    let result = scheduler.spawn_result(connections.await().map(|streams| streams.len()));
+
+    let _client = TcpStream::connect(addr).unwrap();
+    for _ in 0..1000 {
+        if result.get().is_some() {
+            break;
+        }
+        scheduler.step();
+        thread::sleep(time::Duration::from_millis(1));
+    }
+    assert_eq!(result.get(), Some(1));
+
+    // The listener thread stops once the process is cancelled, and the runtime returns.
+    let (_connections, accept) = blocking_io::tcp_listener_signal("127.0.0.1:0").unwrap();
+    timeout_ms(move|| assert_eq!(execute_process(timeout(accept, 2)), None), 1000);
+}
+
+#[test]
+fn test_lockstep() {
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let peer = |player: usize, input: i32| {
+        move|(signal, inputs, process): (ValueSignal<Vec<lockstep::PlayerInput<i32>>, _>, lockstep::LockstepInputs<i32>, _)| {
+            let mut scheduler = TestScheduler::new();
+            let recording = scheduler.record(&signal);
+            scheduler.spawn(process);
+            scheduler.step();
+            inputs.push(input);
+            scheduler.step_n(2);
+            let emissions = recording.emissions();
+            assert_eq!(emissions.len(), 1, "player {}", player);
+            emissions
+        }
+    };
+    let guest = thread::spawn(move|| peer(1, 2)(lockstep::lockstep_join(addr).unwrap()));
+    let host = peer(0, 1)(lockstep::lockstep_host(&listener).unwrap());
+    let guest = guest.join().unwrap();
+
+    assert_eq!(host, guest);
+    assert_eq!(host, vec![(1, vec![
+        lockstep::PlayerInput { player: 0, input: 1 },
+        lockstep::PlayerInput { player: 1, input: 2 },
+    ])]);
+
+    use self::lockstep::LockstepInput;
+    let input = String::from("a\tb\\n\nc\r");
+    assert!(!input.encode().contains(|c| c == '\t' || c == '\n' || c == '\r'));
+    assert_eq!(String::decode(&input.encode()), Some(input));
+    assert_eq!(String::decode("a\\"), None);
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_http_signal() {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    let (requests, responder, endpoint) = http::http_signal("127.0.0.1:0").unwrap();
+    let addr = endpoint.local_addr();
+    let client = thread::spawn(move|| {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "POST /power HTTP/1.1\r\nContent-Length: 9\r\n\r\n{{\"p\": 15}}").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    });
+
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(endpoint);
+    let respond = move|requests: Vec<http::HttpRequest>| {
+        for request in &requests {
+            responder.respond(request.id, 200, &request.body);
+        }
+        requests.len()
+    };
+    let result = scheduler.spawn_result(requests.await().map(respond));
+    for _ in 0..1000 {
+        if result.get().is_some() {
+            break;
+        }
+        scheduler.step();
+        thread::sleep(time::Duration::from_millis(1));
+    }
+    assert_eq!(result.get(), Some(1));
+    let response = client.join().unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("{\"p\": 15}"));
+
+    // Bodies too large are refused without being read.
+    for length in &["1048577", "99999999999999999999999"] {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "POST /power HTTP/1.1\r\nContent-Length: {}\r\n\r\n", length).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large"));
+    }
+
+    // A cancelled endpoint stops waiting for requests.
+    let (_, _, endpoint) = http::http_signal("127.0.0.1:0").unwrap();
+    timeout_ms(move|| assert_eq!(execute_process(endpoint.race(value(()).pause())), Either::Right(())), 5000);
+}
+
+#[test]
+fn test_input_signals() {
+    use self::devices::{emit_input, InputEvent, InputSignals};
+
+    let signals: InputSignals<u8, u8> = InputSignals::new();
+    let (button, axis) = (signals.button(1), signals.axis(0));
+    let mut runtime = SequentialRuntime::new();
+    let received = Arc::new(Mutex::new(None));
+    let received_ref = received.clone();
+    button.await().join(axis.await()).call(&mut runtime, move|_: &mut Runtime, v| {
+        *received_ref.lock().unwrap() = Some(v);
+    });
+    // The last change of an instant wins, and the changes of unused inputs are dropped.
+    let events = vec![InputEvent::Button(1, true), InputEvent::Axis(0, 0.5), InputEvent::Axis(0, -0.25), InputEvent::Button(2, true)];
+    for event in events {
+        emit_input(&mut runtime, &signals, event);
+    }
+    while runtime.instant() {}
+    assert_eq!(*received.lock().unwrap(), Some((true, -0.25)));
+}
+
+#[test]
+fn test_worker_local() {
+    let calls: WorkerLocal<Vec<usize>> = WorkerLocal::new(Vec::new);
+    let record = move|i: usize| calls.with(|calls| {
+        calls.push(i);
+        calls.clone()
+    });
+    let mut runtime = SequentialRuntime::new();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    for _ in 0..2 {
+        let record = record.clone();
+        let results = results.clone();
+        let process = value(1).map(record.clone()).then(value(2).map(record).pause()).map(move|calls| results.lock().unwrap().push(calls));
+        runtime.on_current_instant(Box::new(|runtime: &mut Runtime, ()| process.call(runtime, |_: &mut Runtime, ()| ())));
+        runtime.execute();
+    }
+    assert_eq!(*results.lock().unwrap(), vec![vec![1, 2], vec![1, 2]]);
+
+    // A runtime executed by a continuation of another one keeps its own copies.
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let (nested_record, nested_results) = (record.clone(), results.clone());
+    let execute_nested = move|calls: Vec<usize>| {
+        let mut nested = SequentialRuntime::new();
+        let process = value(3).map(nested_record.clone()).map(move|calls| nested_results.lock().unwrap().push(calls));
+        nested.on_current_instant(Box::new(|runtime: &mut Runtime, ()| process.call(runtime, |_: &mut Runtime, ()| ())));
+        nested.execute();
+        calls
+    };
+    let results_ref = results.clone();
+    let process = value(1).map(record.clone()).map(execute_nested).then(value(2).map(record.clone()).pause())
+        .map(move|calls| results_ref.lock().unwrap().push(calls));
+    runtime.on_current_instant(Box::new(|runtime: &mut Runtime, ()| process.call(runtime, |_: &mut Runtime, ()| ())));
+    runtime.execute();
+    assert_eq!(*results.lock().unwrap(), vec![vec![3], vec![1, 2]]);
+
+    let parallel = ParallelRuntime::new(2).start();
+    let results = Arc::new(Mutex::new(Vec::new()));
+    for _ in 0..2 {
+        let results = results.clone();
+        let process = value(1).map(record.clone()).map(move|calls| results.lock().unwrap().push(calls));
+        parallel.on_current_instant(Box::new(|runtime: &mut Runtime, ()| process.call(runtime, |_: &mut Runtime, ()| ())));
+        parallel.execute();
+    }
+    assert_eq!(*results.lock().unwrap(), vec![vec![1], vec![1]]);
+
+    let counter: WorkerLocal<usize> = WorkerLocal::new(|| 0);
+    let nested = counter.with(|outer| {
+        *outer += 1;
+        counter.with(|inner| *inner)
+    });
+    assert_eq!(nested, 0);
+    assert_eq!(counter.with(|count| *count), 1);
+}
+
+#[test]
+fn test_fixed_capacity() {
+    let run = |waiters: usize, continuations: usize, signal_capacity: usize| {
+        let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y)).with_capacity(signal_capacity);
+        let mut runtime = SequentialRuntime::with_capacity(continuations);
+        let emit_forever = |_| LoopStatus::Continue::<()>;
+        let process = s.emit(value(1)).map(emit_forever).pause().while_loop();
+        runtime.on_current_instant(Box::new(|runtime: &mut Runtime, ()| process.call(runtime, |_: &mut Runtime, ()| ())));
+        for _ in 0..waiters {
+            let await_forever = |_| LoopStatus::Continue::<()>;
+            let process = s.await().map(await_forever).while_loop();
+            runtime.on_current_instant(Box::new(|runtime: &mut Runtime, ()| process.call(runtime, |_: &mut Runtime, ()| ())));
+        }
+        for _ in 0..10 {
+            runtime.instant();
+        }
+    };
+    run(2, 3, 2);
+
+    let payload = std::panic::catch_unwind(|| run(3, 3, 3)).unwrap_err();
+    assert_eq!(payload.downcast_ref::<String>().map(|s| &s[..]),
+               Some("the queue of the current instant overflowed its capacity of 3"));
+    let payload = std::panic::catch_unwind(|| run(3, 4, 2)).unwrap_err();
+    assert_eq!(payload.downcast_ref::<String>().map(|s| &s[..]),
+               Some("the waiters of a signal overflowed its capacity of 2"));
+}
+
+#[test]
+fn test_executor() {
+    let tasks = task_signal();
+    let n = Arc::new(Mutex::new(vec![]));
+    let task_at = |i: usize| {
+        let n = n.clone();
+        task(value(()).map(move|()| n.lock().unwrap().push(i)))
+    };
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(executor(&tasks));
+    scheduler.spawn(executor(&tasks));
+    scheduler.spawn(tasks.emit(value(task_at(0))).join(tasks.emit(value(task_at(1)))).map(|_| ()));
+    scheduler.step();
+    assert!(n.lock().unwrap().is_empty());
+    scheduler.step();
+    assert_eq!(*n.lock().unwrap(), vec![0, 1]);
+    scheduler.spawn(tasks.emit(value(task_at(2))).map(|_| ()));
+    scheduler.step_n(2);
+    assert_eq!(*n.lock().unwrap(), vec![0, 1, 2]);
+}
+
+#[test]
+fn test_await_detailed() {
+    let s = ValueSignal::new(0, Box::new(|x, y| x + y)).instrumented();
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(s.emit_named("left", value(1)).join(s.emit(value(2))));
+    let log = scheduler.spawn_result(s.await_detailed());
+    scheduler.step_n(2);
+    let log = log.get().unwrap();
+    assert_eq!(log.iter().map(|emission| emission.value).collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(log[0].name, Some(String::from("left")));
+    assert_eq!(log[1].name, None);
+    assert!(log.iter().all(|emission| emission.worker.is_none()));
+    assert!(log[0].emitter != log[1].emitter);
+
+    // The copies of a named emitter keep its name.
+    let named = s.emit_named("right", value(3));
+    scheduler.spawn(named.clone_process().join(named));
+    let log = scheduler.spawn_result(s.await_detailed());
+    scheduler.step_n(2);
+    let log = log.get().unwrap();
+    assert!(log.iter().all(|emission| emission.name == Some(String::from("right"))));
+    assert!(log[0].emitter != log[1].emitter);
+
+    let plain = ValueSignal::new(0, Box::new(|x, y| x + y));
+    scheduler.spawn(plain.emit(value(1)));
+    let log = scheduler.spawn_result(plain.await_detailed());
+    scheduler.step_n(2);
+    assert_eq!(log.get(), Some(vec![]));
+}
+
+#[test]
+fn test_transaction_signal() {
+    // Only batches of at least two proposals are accepted, the others are dropped.
+    let s = TransactionSignal::new(|batch: Vec<i32>| value(batch.len() >= 2), RejectionPolicy::Drop);
+    let mut scheduler = TestScheduler::new();
+    let commit = scheduler.spawn_result(s.await_commit());
+    scheduler.spawn(s.propose(value(1)));
+    scheduler.step_n(2);
+    assert_eq!(commit.get(), None);
+    scheduler.spawn(s.propose(value(2)).join(s.propose(value(3))));
+    scheduler.step();
+    assert_eq!(commit.get(), None);
+    scheduler.step();
+    assert_eq!(commit.get(), Some(vec![2, 3]));
+
+    let commit = scheduler.spawn_result(s.await_commit());
+    scheduler.spawn(s.propose(value(4)).join(s.propose(value(5))));
+    scheduler.step_n(2);
+    assert_eq!(commit.get(), Some(vec![4, 5]));
+
+    // A rejected batch is submitted again alone, a limited number of times.
+    let submitted = Arc::new(Mutex::new(Vec::new()));
+    let submitted_ref = submitted.clone();
+    let s = TransactionSignal::new(move|batch: Vec<i32>| {
+        submitted_ref.lock().unwrap().push(batch.clone());
+        value(batch.len() >= 2)
+    }, RejectionPolicy::Retry(2));
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(s.propose(value(1)));
+    scheduler.step();
+    scheduler.spawn(s.propose(value(2)));
+    scheduler.run();
+    assert_eq!(*submitted.lock().unwrap(), vec![vec![1], vec![1], vec![2], vec![1], vec![2], vec![2]]);
+
+    // The arbiter is a process, the batches proposed while it decides waiting for its verdict.
+    let s = TransactionSignal::new(|batch: Vec<i32>| value(batch.len() >= 2).pause(), RejectionPolicy::Report);
+    let mut scheduler = TestScheduler::new();
+    let rejection = scheduler.spawn_result(s.await_rejection());
+    let commit = scheduler.spawn_result(s.await_commit());
+    scheduler.spawn(s.propose(value(1)));
+    scheduler.step();
+    scheduler.spawn(s.propose(value(4)).join(s.propose(value(5))));
+    scheduler.run();
+    assert_eq!(rejection.get(), Some(vec![1]));
+    assert_eq!(commit.get(), Some(vec![4, 5]));
+}
+
+#[test]
+fn test_dt_signal() {
+    use super::pacing::*;
+    use std::time::Duration;
+
+    let mut scheduler = TestScheduler::new();
+    let dt = scheduler.dt_signal(Pacing::Fixed(Duration::from_millis(20)));
+    let elapsed = Arc::new(Mutex::new(Duration::from_secs(0)));
+    let elapsed_ref = elapsed.clone();
+    let integrate = move|dt| *elapsed_ref.lock().unwrap() += dt;
+    let continue_loop: LoopStatus<()> = LoopStatus::Continue;
+    scheduler.spawn(dt.await().map(integrate).then(value(continue_loop)).while_loop());
+    scheduler.step_n(4);
+    assert_eq!(*elapsed.lock().unwrap(), Duration::from_millis(60));
+
+    let mut scheduler = TestScheduler::new();
+    let dt = scheduler.dt_signal(Pacing::WallClock);
+    let recording = scheduler.record(&dt);
+    scheduler.step();
+    thread::sleep(Duration::from_millis(5));
+    scheduler.step_n(2);
+    let emissions = recording.emissions();
+    assert_eq!(emissions[0], (0, Duration::from_secs(0)));
+    assert!(emissions[1].1 >= Duration::from_millis(5));
+
+    // The runtime stops emitting the signal once the program completed.
+    let runtime = ParallelRuntime::new(2);
+    let dt = runtime.dt_signal(Pacing::Fixed(Duration::from_millis(10)));
+    let sum = |(a, b): (Duration, Duration)| a + b;
+    let total = Arc::new(Mutex::new(None));
+    let total_ref = total.clone();
+    let p = dt.await().join(dt.await().pause()).map(sum).map(move|total| *total_ref.lock().unwrap() = Some(total));
+    runtime.on_current_instant(Box::new(|runtime: &mut Runtime, ()| p.call(runtime, |_: &mut Runtime, ()| ())));
+    runtime.start().execute();
+    assert_eq!(*total.lock().unwrap(), Some(Duration::from_millis(20)));
+}
+
+#[test]
+fn test_clock() {
+    use super::pacing::*;
+    use std::time::Duration;
+
+    let clock = ManualClock::new();
+    let mut scheduler = TestScheduler::new();
+    scheduler.set_clock(clock.clone());
+    let dt = scheduler.dt_signal(Pacing::WallClock);
+    let recording = scheduler.record(&dt);
+    scheduler.step();
+    clock.advance(Duration::from_millis(30));
+    scheduler.step();
+    scheduler.step_n(2);
+    assert_eq!(recording.emissions(), vec![
+        (0, Duration::from_secs(0)),
+        (1, Duration::from_millis(30)),
+        (2, Duration::from_secs(0)),
+    ]);
+
+    let start = clock.now();
+    let scaled = ScaledClock::new(clock.clone(), 10.);
+    let scaled_start = scaled.now();
+    clock.advance(Duration::from_millis(15));
+    assert_eq!(clock.now() - start, Duration::from_millis(15));
+    assert_eq!(scaled.now() - scaled_start, Duration::from_millis(150));
+}
+
+#[test]
+fn test_sleep() {
+    use super::pacing::*;
+    use std::time::Duration;
+
+    let clock = ManualClock::new();
+    let mut scheduler = TestScheduler::new();
+    scheduler.set_clock(clock.clone());
+    let slept = scheduler.spawn_result(sleep(Duration::from_millis(20)).then(instant_index()));
+    let ticks = scheduler.spawn_result(value(()).pause().repeat(2).then(instant_index()));
+    scheduler.step();
+    clock.advance(Duration::from_millis(15));
+    scheduler.step_n(2);
+    assert_eq!(ticks.get(), Some(2));
+    assert_eq!(slept.get(), None);
+    clock.advance(Duration::from_millis(5));
+    scheduler.step();
+    assert_eq!(slept.get(), Some(3));
+
+    // Without anything else to do, the runtime waits for the deadline on the system clock.
+    let start = time::Instant::now();
+    assert_eq!(execute_process(sleep(Duration::from_millis(30)).then(instant_index())), 1);
+    assert!(start.elapsed() >= Duration::from_millis(30));
+
+    // Unless the sleep was cancelled.
+    let start = time::Instant::now();
+    assert_eq!(execute_process(timeout(sleep(Duration::from_secs(3)), 1)), None);
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    // A clock which does not run stops the runtimes rather than hanging them.
+    let mut scheduler = TestScheduler::new();
+    scheduler.set_clock(clock.clone());
+    let slept = scheduler.spawn_result(sleep(Duration::from_millis(10)));
+    scheduler.run();
+    assert_eq!(slept.get(), None);
+    clock.advance(Duration::from_millis(10));
+    assert!(!scheduler.step());
+    assert_eq!(slept.get(), Some(()));
+    timeout_ms(move|| {
+        let runtime = RuntimeBuilder::new(2).clock(clock).build();
+        runtime.register(sleep(Duration::from_millis(10)));
+        runtime.start().execute();
+    }, 5000);
+}
+
+#[test]
+fn test_overrun_signal() {
+    use super::pacing::*;
+    use std::time::Duration;
+
+    let clock = ManualClock::new();
+    let period = Duration::from_millis(16);
+    let (overruns, misses, monitor) = overrun_signal(period);
+    let mut scheduler = TestScheduler::new();
+    scheduler.set_clock(clock.clone());
+    scheduler.spawn(monitor);
+    let recording = scheduler.record(&overruns);
+    for &ms in [5, 30, 16, 20].iter() {
+        scheduler.step();
+        clock.advance(Duration::from_millis(ms));
+    }
+    scheduler.step_n(2);
+    assert_eq!(recording.emissions(), vec![
+        (2, Some(Overrun { instant: 1, duration: Duration::from_millis(30), period })),
+        (4, Some(Overrun { instant: 3, duration: Duration::from_millis(20), period })),
+    ]);
+    assert_eq!(misses.count(), 2);
+}
+
+#[test]
+fn test_task_with_result() {
+    let tasks = task_signal();
+    let (task, result) = task_with_result(value(()).pause().map(|()| 42));
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(executor(&tasks));
+    scheduler.spawn(tasks.emit(value(task)));
+    let first = scheduler.spawn_result(result.await().map(|v| v + 1));
+    scheduler.step_n(2);
+    assert_eq!(result.get(), None);
+    assert_eq!(first.get(), None);
+    scheduler.step();
+    assert_eq!(result.get(), Some(42));
+    assert_eq!(first.get(), Some(43));
+    let late = scheduler.spawn_result(result.await());
+    scheduler.step();
+    assert_eq!(late.get(), Some(42));
+}
+
+#[test]
+fn test_fixpoint() {
+    let n = Arc::new(Mutex::new(0));
+    let nn = n.clone();
+    let step = move|()| {
+        let mut x = nn.lock().unwrap();
+        *x = std::cmp::min(*x + 1, 5);
+        *x
+    };
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(fixpoint(value(()).map(step)));
+    scheduler.step();
+    assert_eq!(result.get(), Some(5));
+
+    *n.lock().unwrap() = 0;
+    let nn = n.clone();
+    let step = move|()| {
+        let mut x = nn.lock().unwrap();
+        *x = std::cmp::min(*x + 2, 3);
+        *x
+    };
+    let each_instant = |v| if v == 3 { LoopStatus::Exit(v) } else { LoopStatus::Continue };
+    let result = scheduler.spawn_result(fixpoint(value(()).map(step)).map(each_instant).pause().while_loop());
+    scheduler.step_n(2);
+    assert_eq!(result.get(), Some(3));
+
+    // Long fixpoints unwind the stack, and those which never converge give up.
+    *n.lock().unwrap() = 0;
+    let nn = n.clone();
+    let settle = move|()| {
+        let mut x = nn.lock().unwrap();
+        *x = std::cmp::min(*x + 1, 20000);
+        *x
+    };
+    assert_eq!(execute_process(fixpoint(value(()).map(settle))), 20000);
+    let nn = n.clone();
+    let oscillate = move|()| {
+        let mut x = nn.lock().unwrap();
+        *x = 1 - *x;
+        *x
+    };
+    let failed = execute_process(fixpoint(value(()).map(oscillate)).max_iterations(1000).catch_panic()).unwrap_err();
+    assert_eq!(failed.message(), Some(String::from("fixpoint did not converge after 1000 iterations")));
+}
+
+#[test]
+fn test_weak_signal() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y)).hibernate_after(10);
+    let weak = s.downgrade();
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(s.emit(value(1)).map(|_| ()));
+    assert!(weak.upgrade().is_some());
+    drop(s);
+    // Alive until the emission is gathered, the hibernation check does not keep it.
+    scheduler.step();
+    assert!(weak.upgrade().is_none());
+
+    let p = PureSignal::new();
+    let weak = p.downgrade();
+    assert!(weak.upgrade().is_some());
+    drop(p);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn test_select() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let p = select2(s.await(), value("timeout").pause().pause().pause());
+    assert_eq!(execute_process(join(p, s.emit(value(4)).pause())), (Either::Left(4), 4));
+    assert_eq!(execute_process(select2(s.await(), value("timeout").pause())), Either::Right("timeout"));
+
+    let p = select!(value(1).pause(), value('b'), value("c"));
+    assert_eq!(execute_process(p), Either3::Second('b'));
+
+    // The cancelled process ignores the later emissions.
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(select!(s.await(), value(())));
+    scheduler.spawn(s.emit(value(1)).pause());
+    scheduler.run();
+    assert_eq!(result.get(), Some(Either::Right(())));
+}
+
+#[test]
+fn test_race() {
+    let steps = Arc::new(Mutex::new(0));
+    let steps_ref = steps.clone();
+    let slow = value(()).pause().map(move|()| *steps_ref.lock().unwrap() += 1).pause().map(|()| "slow");
+    let p = race(slow, value(2).pause()).map(|v| (v, 3)).pause();
+    assert_eq!(execute_process(p), (Either::Right(2), 3));
+    // The loser is dropped with the steps it scheduled.
+    assert_eq!(*steps.lock().unwrap(), 0);
+
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(value(()).pause().race(s.await()));
+    scheduler.spawn(s.emit(value(1)).pause().pause());
+    scheduler.run();
+    assert_eq!(result.get(), Some(Either::Left(())));
+
+    // The signals emitted by the loser in the instant it loses are still gathered and reset.
+    let p = PureSignal::new();
+    let loser = p.emit().then(s.emit(value(5))).then(value(()).pause());
+    let lost = race(loser, value(())).then(s.await());
+    let later = value(()).pause().pause().then(p.present()).join(s.emit(value(7)).then(s.await()));
+    assert_eq!(execute_process(lost.then(later)), (false, 7));
+}
+
+#[test]
+fn test_multi_race() {
+    let signals: Vec<ValueSignal<i32, i32>> = (0..3).map(|_| ValueSignal::new(0, Box::new(|x, y| x + y))).collect();
+    let any = multi_race(signals.iter().map(|s| s.await()).collect());
+    let emission = signals[2].emit(value(7)).pause().then(signals[0].emit(value(1)).pause());
+    assert_eq!(execute_process(join(any, emission)), ((2, 7), 1));
+    assert_eq!(execute_process(multi_race(vec![value(4), value(5)])), (0, 4));
+}
+
+#[test]
+fn test_timeout() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    assert_eq!(execute_process(s.await().timeout(3)), None);
+    assert_eq!(execute_process(value(1).pause().timeout(3)), Some(1));
+
+    let p = s.await().timeout(3).join(s.emit(value(4)).pause());
+    assert_eq!(execute_process(p), (Some(4), 4));
+    // The emission comes too late for the cancelled await.
+    let p = s.await().timeout(2).join(s.emit(value(4)).pause().pause().pause());
+    assert_eq!(execute_process(p), (None, 4));
+}
+
+#[test]
+fn test_until() {
+    let stop = PureSignal::new();
+    let ticks = Arc::new(Mutex::new(Vec::new()));
+    let ticks_ref = ticks.clone();
+    let mut instant = 0;
+    let tick = move|()| {
+        ticks_ref.lock().unwrap().push(instant);
+        instant += 1;
+        LoopStatus::Continue::<()>
+    };
+    let body = value(()).pause().map(tick).while_loop();
+    let stopping = stop.emit().pause().pause();
+    let p = body.until(stop.await_immediate()).join(stopping);
+    assert_eq!(execute_process(p), (None, ()));
+    // The body executes the instant of the emission, then is dropped.
+    assert_eq!(*ticks.lock().unwrap(), vec![0, 1]);
+
+    let p = value(3).pause().until(stop.await_immediate()).join(stop.emit().pause());
+    assert_eq!(execute_process(p), (Some(3), ()));
+}
+
+#[test]
+fn test_cancel_token() {
+    let token = CancelToken::new();
+    let ticks = Arc::new(Mutex::new(Vec::new()));
+    let ticks_ref = ticks.clone();
+    let mut instant = 0;
+    let tick = move|()| {
+        ticks_ref.lock().unwrap().push(instant);
+        instant += 1;
+        LoopStatus::Continue::<()>
+    };
+    let body = value(()).pause().map(tick).while_loop();
+    let p = body.cancellable(&token).join(value(()).pause().pause().then(token.cancel()));
+    assert_eq!(execute_process(p), (None, ()));
+    assert_eq!(*ticks.lock().unwrap(), vec![0, 1]);
+    assert!(token.is_cancelled());
+    assert_eq!(execute_process(value(3).pause().cancellable(&token)), None);
+
+    let token = CancelToken::new();
+    let never: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let result = Arc::new(Mutex::new(Some(Some(0))));
+    let result_ref = result.clone();
+    let p = never.await().cancellable(&token).map(move|v| *result_ref.lock().unwrap() = Some(v));
+    let mut runtime = SequentialRuntime::new();
+    runtime.on_current_instant(Box::new(|runtime: &mut Runtime, ()| p.call(runtime, |_: &mut Runtime, ()| ())));
+    runtime.instant();
+    token.cancel_external(&runtime.external_inbox());
+    runtime.execute();
+    assert_eq!(*result.lock().unwrap(), Some(None));
+}
+
+#[test]
+fn test_when() {
+    let clock = PureSignal::new();
+    let ticks = Arc::new(Mutex::new(0));
+    let ticks_ref = ticks.clone();
+    let tick = move|()| {
+        let mut ticks = ticks_ref.lock().unwrap();
+        *ticks += 1;
+        if *ticks == 3 { LoopStatus::Exit(*ticks) } else { LoopStatus::Continue }
+    };
+    let body = value(()).pause().map(tick).while_loop();
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(body.when(clock.await_immediate()));
+    // The clock is present during the instants 0, 1, 3 and 5.
+    scheduler.spawn(clock.emit().then(clock.emit().pause()).then(clock.emit().pause().pause()).then(clock.emit().pause().pause()));
+    let mut counts = Vec::new();
+    for _ in 0..6 {
+        scheduler.step();
+        counts.push(*ticks.lock().unwrap());
+    }
+    assert_eq!(counts, vec![0, 1, 1, 2, 2, 3]);
+    assert_eq!(result.get(), Some(3));
+}
+
+#[test]
+fn test_process_panic() {
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let attempts = Arc::new(Mutex::new(0));
+    let names_ref = names.clone();
+    let mut scheduler = TestScheduler::new();
+    scheduler.on_process_panic(move|name, _| {
+        names_ref.lock().unwrap().push(name.to_string());
+        if name == "flaky" { PanicPolicy::Restart } else { PanicPolicy::Skip }
+    });
+    let attempts_ref = attempts.clone();
+    let flaky = value(()).pause().map(move|()| {
+        let attempts = {
+            let mut attempts = attempts_ref.lock().unwrap();
+            *attempts += 1;
+            *attempts
+        };
+        if attempts == 1 {
+            panic!("first attempt");
+        }
+        attempts
+    });
+    let restarted = scheduler.spawn_result(flaky.supervised("flaky"));
+    let skipped = scheduler.spawn_result(value(()).map(|()| -> i32 { panic!("always") }).supervised("broken"));
+    let other = scheduler.spawn_result(value(3).pause());
+    scheduler.spawn(value(()).map(|()| panic!("unsupervised")));
+    scheduler.run();
+    assert_eq!(restarted.get(), Some(2));
+    assert_eq!(skipped.get(), None);
+    assert_eq!(other.get(), Some(3));
+    assert_eq!(*names.lock().unwrap(), vec!["broken", UNSUPERVISED, "flaky"]);
+
+    let mut scheduler = TestScheduler::new();
+    scheduler.on_process_panic(|_, _| PanicPolicy::Abort);
+    scheduler.spawn(value(()).pause().map(|()| panic!("abort")).supervised("aborted"));
+    let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| scheduler.run())).unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"abort"));
+
+    // The signals emitted in the instant of a skipped panic are still reset.
+    let mut scheduler = TestScheduler::new();
+    scheduler.on_process_panic(|_, _| PanicPolicy::Skip);
+    let s = PureSignal::new();
+    scheduler.spawn(s.emit().map(|()| panic!("skipped")).supervised("skipped"));
+    let present = scheduler.spawn_result(value(()).pause().pause().then(s.present()));
+    scheduler.run();
+    assert_eq!(present.get(), Some(false));
+}
+
+#[test]
+fn test_signal_validator() {
+    let errors: ValueSignal<Vec<i32>, i32> = ValueSignal::new(vec![], Box::new(|mut v: Vec<i32>, x| {
+        v.push(x);
+        v
+    }));
+    let power: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y))
+        .with_validator(&errors, |&power| if power <= 10 { Ok(()) } else { Err(power) });
+    let mut scheduler = TestScheduler::new();
+    let first = scheduler.spawn_result(power.await());
+    let absent = scheduler.spawn_result(value(()).pause().then(power.await_or(-1)));
+    let next_valid = scheduler.spawn_result(value(()).pause().then(power.await()));
+    let reported = scheduler.spawn_result(errors.await());
+    scheduler.spawn(power.emit(value(4)).then(power.emit(value(20)).pause()).then(power.emit(value(3)).pause()));
+    scheduler.run();
+    assert_eq!(first.get(), Some(4));
+    assert_eq!(absent.get(), Some(-1));
+    assert_eq!(next_valid.get(), Some(3));
+    assert_eq!(reported.get(), Some(vec![20]));
+}
+
+#[test]
+fn test_assert_process() {
+    let errors: ValueSignal<Vec<InvariantViolation>, InvariantViolation> = ValueSignal::new(vec![], Box::new(|mut v: Vec<InvariantViolation>, x| {
+        v.push(x);
+        v
+    }));
+    let level: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let bounded = |level: i32| if level <= 10 { Ok(()) } else { Err(level) };
+    let mut scheduler = TestScheduler::new();
+    let reported = errors.capture();
+    scheduler.spawn(assert_process("bounded level", level.await().map(bounded), AssertPolicy::emit_on(&errors)));
+    scheduler.spawn(level.emit(value(4)).then(level.emit(value(20)).pause()).then(level.emit(value(3)).pause()));
+    scheduler.run();
+    let violation = InvariantViolation { name: "bounded level".to_string(), instant: 2, values: "20".to_string() };
+    assert_eq!(reported.emissions(), vec![(2, vec![violation.clone()])]);
+    assert_eq!(violation.to_string(), "invariant bounded level broken during instant 2 with 20");
+
+    let mut scheduler = TestScheduler::new();
+    let check = |_| Err("stale");
+    scheduler.spawn(assert_process("fresh", value(()).pause().map(check), AssertPolicy::Abort));
+    scheduler.step();
+    let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| scheduler.step())).unwrap_err();
+    assert_eq!(payload.downcast_ref::<String>().map(|s| &s[..]), Some("invariant fresh broken during instant 1 with \"stale\""));
+}
+
+#[test]
+fn test_emit_audit() {
+    let audit = EmitAudit::new();
+    let power: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y)).audited(&audit, "power");
+    let other: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(power.emit(value(1)).then(power.emit(value(2)).pause()));
+    scheduler.spawn(power.await().then(power.emit_named("feedback", value(1))));
+    scheduler.spawn(power.await_immediate().then(power.emit(value(1))).map(|_| ()));
+    scheduler.spawn(power.await().then(other.emit(value(1))).then(power.emit(value(1)).pause()).map(|_| ()));
+    scheduler.run();
+    let warnings = audit.warnings();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].instant, 0);
+    assert_eq!(warnings[0].signal, "power");
+    assert_eq!(warnings[1].process, "feedback");
+    assert_eq!(warnings[1].to_string(), "instant 1: feedback emitted on power after reading it during the same instant");
+}
+
+#[test]
+fn test_signal_log_divergence() {
+    let run = |mut runtime: SequentialRuntime| {
+        let log = SignalLog::new();
+        let order: ValueSignal<Vec<i32>, i32> = ValueSignal::new(vec![], Box::new(|mut v: Vec<i32>, x| {
+            v.push(x);
+            v
+        }));
+        let sum: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+        let ready = PureSignal::new();
+        // The waiter of `ready` registers before `2` is emitted, but is only woken after the
+        // emission of `2` is queued.
+        let first = ready.await_immediate().then(order.emit(value(1)));
+        let second = order.emit(value(2)).join(value(())).join(ready.emit());
+        let program = log.watch("order", &order).then(log.watch("sum", &sum))
+            .then(sum.emit(value(1)).join(sum.emit(value(2))))
+            .then(first.join(second).pause());
+        runtime.on_current_instant(Box::new(|run: &mut Runtime, ()| program.call(run, |_: &mut Runtime, _| ())));
+        runtime.execute();
+        log
+    };
+    let sequential = run(SequentialRuntime::new());
+    let deterministic = run(SequentialRuntime::new_deterministic());
+    assert_eq!(sequential.emissions().len(), 2);
+    assert_eq!(sequential.first_divergence(&sequential), None);
+    let divergence = sequential.first_divergence(&deterministic).unwrap();
+    assert_eq!(divergence, Divergence {
+        instant: 1,
+        signal: String::from("order"),
+        left: Some(String::from("[2, 1]")),
+        right: Some(String::from("[1, 2]")),
+    });
+    assert_eq!(divergence.to_string(), "signal order diverges at instant 1: [2, 1] != [1, 2]");
+}
+
+#[test]
+fn test_actor() {
+    let (counter, counting) = actor(0, |sum: &mut i32, n: i32| {
+        if n < 0 {
+            return value(LoopStatus::Exit(*sum));
+        }
+        *sum += n;
+        value(LoopStatus::Continue)
+    });
+    let forward = counter.clone();
+    let (relay, relaying) = actor((), move|_: &mut (), n: i32| {
+        forward.send(value(n * 10)).then(value(if n < 0 { LoopStatus::Exit(()) } else { LoopStatus::Continue }))
+    });
+    let mut scheduler = TestScheduler::new();
+    let sum = scheduler.spawn_result(counting);
+    scheduler.spawn(relaying);
+    scheduler.spawn(counter.send(value(1)).join(relay.send(value(2))).then(relay.send(value(-1)).pause()));
+    scheduler.run();
+    // 1 at instant 1, 20 at instant 2, and the stop message at instant 3.
+    assert_eq!(sum.get(), Some(21));
+    assert_eq!(scheduler.instant(), 4);
+
+    // The messages sent while a handler takes two instants are kept for the next ones.
+    let (slow, slowing) = actor(Vec::new(), |received: &mut Vec<i32>, n: i32| {
+        if n < 0 {
+            return value(LoopStatus::Exit(received.clone())).pause();
+        }
+        received.push(n);
+        value(LoopStatus::Continue).pause()
+    });
+    let sending = slow.send(value(1)).pause().then(slow.send(value(2)).pause()).then(slow.send(value(3)).pause())
+        .then(slow.send(value(4)).pause()).then(slow.send(value(-1)).pause());
+    assert_eq!(execute_process(slowing.join(sending)).0, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn test_emit_group() {
+    let position: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let velocity: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let moved = PureSignal::new();
+    let (p, v, m) = (position.clone(), velocity.clone(), moved.clone());
+    let update = value((3, 1)).pause().and_then(move|(x, dx)| {
+        emit_group(vec![p.grouped(x), v.grouped(dx), m.grouped()])
+    });
+    let mut scheduler = TestScheduler::new();
+    let coherent = scheduler.spawn_result(position.await_immediate().then(velocity.present().join(moved.present())));
+    let values = scheduler.spawn_result(position.await().join(velocity.await()));
+    scheduler.spawn(update);
+    scheduler.step();
+    assert_eq!(coherent.get(), None);
+    scheduler.step();
+    assert_eq!(coherent.get(), Some((true, true)));
+    scheduler.run();
+    assert_eq!(values.get(), Some((3, 1)));
+}
+
+#[test]
+fn test_memory_accounting() {
+    let accounting = MemoryAccounting::new(2);
+    let s: ValueSignal<Vec<i32>, i32> = ValueSignal::new(vec![], Box::new(|mut v: Vec<i32>, x| {
+        v.push(x);
+        v
+    })).account_memory(&accounting, "s");
+    let mut scheduler = TestScheduler::new();
+    scheduler.account_memory(&accounting);
+    for _ in 0..3 {
+        scheduler.spawn(s.await());
+    }
+    scheduler.spawn(s.emit(value(1)).pause().pause());
+    scheduler.step();
+    let report = accounting.last_report().unwrap();
+    assert_eq!(report.instant, 0);
+    assert_eq!(report.continuations.count, 1);
+    assert_eq!(report.signals.len(), 1);
+    assert_eq!(report.signals[0].name, "s");
+    assert_eq!(report.signals[0].waiters.count, 3);
+    assert_eq!(report.signals[0].values, MemoryUsage { count: 1, bytes: vec![0; 0].size_hint() });
+    assert!(report.total_bytes() > report.signals[0].values.bytes);
+
+    scheduler.step();
+    scheduler.step();
+    let report = accounting.last_report().unwrap();
+    // The waiters are scheduled for the next instant at the end of the instant of the emission.
+    assert_eq!(report.instant, 2);
+    assert_eq!(report.signals[0].waiters.count, 0);
+    drop(s);
+    scheduler.run();
+    let reports = accounting.reports();
+    assert_eq!(reports.iter().map(|r| r.instant).collect::<Vec<_>>(), vec![2, 3]);
+    assert_eq!(reports[1].signals, vec![]);
+    assert_eq!(reports[1].continuations.count, 0);
+}
+
+#[test]
+fn test_then_with() {
+    let owned = String::from("owned");
+    let p = value(2).pause().then_with(move|n| value(owned.repeat(n)).pause());
+    assert_eq!(execute_process(p), "ownedowned");
+
+    let mut total = 0;
+    let add = move|n| {
+        total += n;
+        value(if total < 3 { LoopStatus::Continue } else { LoopStatus::Exit(total) }).pause()
+    };
+    assert_eq!(execute_process(value(1).then_with(add).while_loop()), 3);
+}
+
+#[test]
+fn test_inbox_capacity() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let recording = s.capture();
+    let mut runtime = SequentialRuntime::new();
+    runtime.inbox_capacity(2);
+    let inbox = runtime.external_inbox();
+    inbox.try_emit(&s, 1).unwrap();
+    inbox.try_emit(&s, 2).unwrap();
+    assert_eq!(inbox.try_emit(&s, 3), Err(3));
+    runtime.instant();
+    inbox.try_emit(&s, 3).unwrap();
+    runtime.instant();
+    runtime.instant();
+    assert_eq!(recording.emissions(), vec![(0, 3), (1, 3)]);
+
+    // A thread emitting more values than the capacity waits for the program to emit them, and
+    // the runtime waits for the thread until its source is dropped.
+    let recording = s.capture();
+    let mut runtime = SequentialRuntime::new();
+    runtime.inbox_capacity(2);
+    let source = runtime.external_inbox().source();
+    let signal = s.clone();
+    let producer = thread::spawn(move|| for i in 1..6 {
+        source.emit(&signal, i);
+    });
+    timeout_ms(move|| runtime.execute(), 1000);
+    producer.join().unwrap();
+    assert_eq!(recording.emissions().iter().map(|&(_, v)| v).sum::<i32>(), 15);
+    assert!(recording.emissions().len() >= 3);
+}
+
+#[test]
+fn test_bounded_subscriber() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let (receiver, subscription) = blocking_io::bounded_subscriber(&s, 1);
+    let consumer = thread::spawn(move|| {
+        thread::sleep(time::Duration::from_millis(50));
+        receiver.iter().take(3).collect::<Vec<_>>()
+    });
+    let mut scheduler = TestScheduler::new();
+    let done = scheduler.spawn_result(subscription);
+    scheduler.spawn(sequence((1..5).map(|i| s.emit(value(i)).pause()).collect()));
+    // Each value waits for the thread to receive the previous one.
+    let start = time::Instant::now();
+    scheduler.step_n(5);
+    assert!(start.elapsed() >= time::Duration::from_millis(40));
+    assert_eq!(consumer.join().unwrap(), vec![1, 2, 3]);
+    scheduler.run();
+    assert_eq!(done.get(), Some(()));
+}
+
+#[test]
+fn test_sequence() {
+    let steps: Vec<_> = (0..50).map(|i| value(i).pause()).collect();
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(sequence(steps));
+    scheduler.run();
+    assert_eq!(result.get(), Some((0..50).collect()));
+    assert_eq!(scheduler.instant(), 51);
+    assert_eq!(execute_process(sequence(Vec::<Value<i32>>::new())), vec![]);
+
+    let mut count = 0;
+    let counter = move|()| {
+        count += 1;
+        count
+    };
+    let counters = sequence(vec![value(()).map(counter.clone()), value(()).map(counter)]);
+    let status = |counts: Vec<i32>| if counts[0] < 3 { LoopStatus::Continue } else { LoopStatus::Exit(counts) };
+    let looped = counters.map(status).while_loop();
+    assert_eq!(execute_process(looped), vec![3, 3]);
+}
+
+#[test]
+fn test_capture() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let captured = s.capture();
+    let emissions = s.emit(value(1)).join(s.emit(value(2))).pause().then(s.emit(value(5)).pause());
+    execute_process(emissions);
+    assert_eq!(captured.emissions(), vec![(1, 3), (2, 5)]);
+    assert_emitted_at(&captured, 2, 5);
+    assert_absent_at(&captured, 0);
+
+    let weak = s.downgrade();
+    drop(s);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn test_graph() {
+    let a: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|_, x| x));
+    let b: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|_, x| x));
+    let mut graph = Graph::new();
+    let sum = graph.node(&[a.clone(), b.clone()], |values| values[0] + values[1]);
+    let doubled = graph.node(&[sum.clone()], |values| values[0] * 2);
+    let sums = sum.capture();
+    let outputs = doubled.capture();
+    let inputs = a.emit(value(1))
+        .then(b.emit(value(2)).pause())
+        .then(a.emit(value(10)).pause().pause());
+    execute_process(graph.build().then(inputs));
+    // The sum waits for both inputs, then follows each change one instant later.
+    assert_eq!(sums.emissions(), vec![(2, 3), (4, 12)]);
+    assert_eq!(outputs.emissions(), vec![(3, 6), (5, 24)]);
+}
+
+#[test]
+fn test_combine_latest() {
+    let left: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|_, x| x));
+    let right: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|_, x| x));
+    let mut scheduler = TestScheduler::new();
+    let combined = scheduler.spawn_result(combine_latest(&[left.clone(), right.clone()]).repeat(3));
+    let readings = left.emit(value(1))
+        .then(left.emit(value(2)).pause())
+        .then(right.emit(value(10)).pause())
+        .then(left.emit(value(3)).join(right.emit(value(20))).pause())
+        .then(right.emit(value(30)).pause());
+    scheduler.spawn(readings.map(|_| ()));
+    scheduler.run();
+    // Nothing is delivered before both sensors were read.
+    assert_eq!(combined.get(), Some(vec![vec![2, 10], vec![3, 20], vec![3, 30]]));
+}
+
+#[test]
+fn test_graph_priority() {
+    let program = || {
+        let urgent: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|_, x| x)).with_priority(Priority(1));
+        let slow: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|_, x| x));
+        let mut graph = Graph::new();
+        let slow_out = graph.node(&[slow.clone()], |values| values[0]);
+        let slow_out = graph.node(&[slow_out], |values| values[0]);
+        let urgent_out = graph.node(&[urgent.clone()], |values| values[0]);
+        let urgent_out = graph.node(&[urgent_out, slow.clone()], |values| values[0]);
+        assert_eq!((urgent_out.priority(), slow_out.priority()), (Priority(1), Priority::NORMAL));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let (slow_order, urgent_order) = (order.clone(), order.clone());
+        let slow_log = slow_out.await_immediate().map(move|()| slow_order.lock().unwrap().push("slow"));
+        let urgent_log = urgent_out.await_immediate().map(move|()| urgent_order.lock().unwrap().push("urgent"));
+        let inputs = slow.emit(value(1)).join(urgent.emit(value(2)));
+        graph.build().then(slow_log.join(urgent_log).join(inputs)).map(move|_| order.lock().unwrap().clone())
+    };
+    // The chain of the urgent input delivers first although the slow one was emitted first.
+    assert_eq!(execute_process(program()), vec!["urgent", "slow"]);
+    assert_eq!(execute_process_par_with(ParallelRuntime::new(1), program()), vec!["urgent", "slow"]);
+}
+
+#[test]
+fn test_configurable() {
+    let rate = LatchSignal::new(1);
+    let total = Arc::new(Mutex::new(0));
+    let total_ref = total.clone();
+    let mut steps = 0;
+    let body = move|rate: i32| {
+        *total_ref.lock().unwrap() += rate;
+        steps += 1;
+        let status = if steps == 4 { LoopStatus::Exit(steps) } else { LoopStatus::Continue };
+        value(status).pause()
+    };
+    // The new rate is read from the iteration following the instant of the emission.
+    let tuning = rate.emit(value(10)).pause();
+    assert_eq!(execute_process(configurable(&rate, body).join(tuning)), (4, ()));
+    assert_eq!(*total.lock().unwrap(), 1 + 1 + 10 + 10);
+    assert_eq!(rate.current(), 10);
+}
+
+#[test]
+fn test_delta_signal() {
+    let grid: DeltaSignal<Vec<i32>, (usize, i32)> = DeltaSignal::new(vec![0; 4], Box::new(|grid, &(i, x)| grid[i] += x));
+    let first = grid.emit(value((1, 5))).join(grid.emit(value((3, 2))));
+    let second = grid.emit(value((1, -1))).pause();
+    let updates = grid.await_deltas().join(grid.await_value());
+    let (_, (deltas, values)) = execute_process(first.join(second).join(updates));
+    assert_eq!(deltas, vec![(1, 5), (3, 2)]);
+    // The value shared with a consumer is not modified by the next deltas.
+    assert_eq!(*values, vec![0, 5, 0, 2]);
+    assert_eq!(*grid.current(), vec![0, 4, 0, 2]);
+}
+
+#[test]
+fn test_signal_reader_writer() {
+    let input: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let output: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let emit = input.writer().emit(value(2)).join(input.writer().emit(value(3)));
+    let result = output.reader().await();
+    // The doubler can only read `input` and emit on `output`.
+    let (input_reader, output_writer) = (input.reader(), output.writer());
+    let doubler = input_reader.await().then_with(move|x| output_writer.emit(value(x * 2)));
+    let program = doubler.join(emit).join(result);
+    assert_eq!(execute_process(program).1, 10);
+}
+
+/// A writer whose output can be read while it is owned by a process.
+#[derive(Clone)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_console() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let mut console = Console::new();
+    console.register("s", &s);
+    let (commands, receiver) = std::sync::mpsc::channel();
+    let output = SharedOutput(Arc::new(Mutex::new(Vec::new())));
+    let mut scheduler = TestScheduler::new();
+    scheduler.spawn(console.commands(receiver, output.clone()));
+    for command in &["signals", "get s", "emit s 5", "emit x 1", "emit s five"] {
+        commands.send(command.to_string()).unwrap();
+    }
+    scheduler.step_n(2);
+    // The value of the emission is recorded at the next instant.
+    for command in &["pause", "get s", "resume", "quit"] {
+        commands.send(command.to_string()).unwrap();
+    }
+    scheduler.run();
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines, vec![
+        "s",
+        "never emitted",
+        "emitted on s at instant 0",
+        "unknown signal \"x\"",
+        "invalid value \"five\": ParseIntError { kind: InvalidDigit }",
+        "paused at instant 2",
+        "5",
+        "resumed",
+        "bye",
+    ]);
+}
+
+#[test]
+fn test_while_loop_spin() {
+    let spinning = value(LoopStatus::Continue::<()>).while_loop().named("spinner").spin_guard(1000);
+    let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| execute_process(spinning))).unwrap_err();
+    assert_eq!(payload.downcast_ref::<String>().map(|s| &s[..]),
+               Some("spinner completed 1000 iterations during instant 0 without pausing"));
+
+    let iterations = Arc::new(Mutex::new(0));
+    let iterations_ref = iterations.clone();
+    let body = move|()| {
+        let mut iterations = iterations_ref.lock().unwrap();
+        *iterations += 1;
+        if *iterations == 7 { LoopStatus::Exit(*iterations) } else { LoopStatus::Continue }
+    };
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(value(()).map(body).while_loop().on_spin(3, SpinPolicy::Pause));
+    scheduler.step();
+    assert_eq!(*iterations.lock().unwrap(), 3);
+    scheduler.step();
+    assert_eq!(*iterations.lock().unwrap(), 6);
+    scheduler.run();
+    assert_eq!(result.get(), Some(7));
+
+    // Loops are not guarded by default, and their iterations do not grow the stack.
+    let mut count = 0;
+    let count_to = move|()| {
+        count += 1;
+        if count == 100_000 { LoopStatus::Exit(count) } else { LoopStatus::Continue }
+    };
+    assert_eq!(execute_process(value(()).map(count_to).while_loop()), 100_000);
+}
+
+#[test]
+fn test_iterations_per_instant() {
+    let iterations = Arc::new(Mutex::new(0));
+    let iterations_ref = iterations.clone();
+    let body = move|()| {
+        let mut iterations = iterations_ref.lock().unwrap();
+        *iterations += 1;
+        if *iterations == 250 { LoopStatus::Exit(*iterations) } else { LoopStatus::Continue }
+    };
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(value(()).map(body).iterations_per_instant(100).while_loop());
+    scheduler.step();
+    assert_eq!(*iterations.lock().unwrap(), 100);
+    scheduler.step();
+    assert_eq!(*iterations.lock().unwrap(), 200);
+    scheduler.run();
+    assert_eq!(result.get(), Some(250));
+
+    // The iterations do not grow the stack.
+    let mut count = 0;
+    let counter = move|()| {
+        count += 1;
+        count
+    };
+    let len = |values: Vec<usize>| values.len();
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(value(()).map(counter).iterations_per_instant(100_000).repeat(200_000).map(len));
+    scheduler.step();
+    assert_eq!(result.get(), None);
+    scheduler.step();
+    assert_eq!(result.get(), Some(200_000));
+}
+
+#[test]
+fn test_repeat() {
+    let mut count = 0;
+    let counter = move|()| {
+        count += 1;
+        count
+    };
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(value(()).pause().map(counter).repeat(3));
+    scheduler.run();
+    assert_eq!(result.get(), Some(vec![1, 2, 3]));
+    assert_eq!(scheduler.instant(), 4);
+    assert_eq!(execute_process(value(1).repeat(0)), vec![]);
+    assert_eq!(execute_process(value(1).repeat(2).repeat(2)), vec![vec![1, 1], vec![1, 1]]);
+}
+
+#[test]
+fn test_waiter_token() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let mut runtime = SequentialRuntime::new();
+    let token = WaiterToken::new();
+    let witness = Arc::new(());
+    let w = witness.clone();
+    s.await().call(&mut runtime, (move|_: &mut Runtime, _: i32| drop(w)).guarded(&token));
+    let p = PureSignal::new();
+    let w = witness.clone();
+    p.present().call(&mut runtime, (move|_: &mut Runtime, _: bool| drop(w)).pause().guarded(&token));
+    assert_eq!(Arc::strong_count(&witness), 3);
+    token.cancel(&mut runtime);
+    runtime.instant();
+    assert_eq!(Arc::strong_count(&witness), 1);
+
+    let called = Arc::new(Mutex::new(false));
+    let c = called.clone();
+    let token = WaiterToken::new();
+    s.await().call(&mut runtime, (move|_: &mut Runtime, _: i32| *c.lock().unwrap() = true).guarded(&token));
+    s.emit(value(1)).call(&mut runtime, |_: &mut Runtime, _| ());
+    token.cancel(&mut runtime);
+    while runtime.instant() {}
+    assert!(!*called.lock().unwrap());
+
+    // Wrapped continuations and the waiters of unique signals are forgotten too.
+    let (u_prod, u_cons) = UniqueProducerSignalProducer::new(0);
+    let (c_prod, c_cons): (UniqueConsumerSignalProducer<i32, i32>, UniqueConsumerSignalConsumer<i32, i32>) =
+        UniqueConsumerSignalProducer::new(Box::new(|| 0), Box::new(|x, y| x + y));
+    let token = WaiterToken::new();
+    let guarded = |w: Arc<()>| (move|_: &mut Runtime, ()| drop(w)).guarded(&token);
+    s.await().map(|_| ()).call(&mut runtime, guarded(witness.clone()));
+    s.await().then(p.await_immediate()).call(&mut runtime, guarded(witness.clone()));
+    s.await().join(p.await_immediate()).map(|_| ()).call(&mut runtime, guarded(witness.clone()));
+    u_cons.await_immediate().map(|_| ()).call(&mut runtime, guarded(witness.clone()));
+    c_cons.await().call_mut(&mut runtime, guarded(witness.clone()).map(|_| ()));
+    s.emit(value(1)).call(&mut runtime, |_: &mut Runtime, _| ());
+    runtime.instant();
+    runtime.instant();
+    assert_eq!(Arc::strong_count(&witness), 5);
+    assert_eq!(token.waited_signals(), 3);
+    token.cancel(&mut runtime);
+    runtime.instant();
+    assert_eq!(Arc::strong_count(&witness), 1);
+    u_prod.emit(value(1)).call(&mut runtime, |_: &mut Runtime, ()| ());
+    c_prod.emit(value(1)).call(&mut runtime, |_: &mut Runtime, ()| ());
+    while runtime.instant() {}
+
+    // The token forgets the signals its continuations stopped waiting for.
+    let token = WaiterToken::new();
+    s.await().call(&mut runtime, (|_: &mut Runtime, _: i32| ()).guarded(&token));
+    p.present().call(&mut runtime, (|_: &mut Runtime, _: bool| ()).guarded(&token));
+    assert_eq!(token.waited_signals(), 2);
+    s.emit(value(1)).call(&mut runtime, |_: &mut Runtime, _| ());
+    while runtime.instant() {}
+    assert_eq!(token.waited_signals(), 0);
+}
+
+#[test]
+fn test_catch_panic() {
+    let failing = value(()).pause().map(|()| -> i32 { panic!("broken") }).catch_panic();
+    let working = value(3).pause().catch_panic();
+    let (failed, worked) = execute_process(failing.join(working));
+    assert_eq!(failed.unwrap_err().message(), Some(String::from("broken")));
+    assert_eq!(worked.unwrap(), 3);
+
+    // The signals emitted in the instant of the panic are still reset.
+    let s = PureSignal::new();
+    let failing = s.emit().map(|()| -> i32 { panic!("broken") }).catch_panic();
+    let later = value(()).pause().pause().then(s.present());
+    assert_eq!(execute_process(failing.then(later)), false);
+}
+
+#[test]
+fn test_from_fn() {
+    // `value` of a vector is not a `ProcessMut`, a new one is built for each iteration.
+    let mut built = 0;
+    let body = move|| {
+        built += 1;
+        let status = if built == 3 { LoopStatus::Exit(vec![built]) } else { LoopStatus::Continue };
+        value(status).pause()
+    };
+    assert_eq!(execute_process(from_fn(body).while_loop()), vec![3]);
+}
+
+#[test]
+fn test_register() {
+    let mut runtime = SequentialRuntime::new();
+    let s: ValueSignal<Vec<i32>, i32> = ValueSignal::new(Vec::new(), Box::new(|mut v, x| { v.push(x); v }));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let received_ref = received.clone();
+    runtime.register(s.await().map(move|v| *received_ref.lock().unwrap() = v));
+    let modules: Vec<_> = (1..4).map(|i| {
+        let (registrar, s) = (runtime.registrar(), s.clone());
+        thread::spawn(move|| registrar.register(s.emit(value(i)).map(|_| ())))
+    }).collect();
+    for module in modules {
+        module.join().unwrap();
+    }
+    runtime.execute();
+    let mut received = received.lock().unwrap().clone();
+    received.sort();
+    assert_eq!(received, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_on_shutdown() {
+    let mut runtime = SequentialRuntime::new();
+    runtime.shutdown_grace(4);
+    let ticks: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let mut instant = 0;
+    let next_instant = move|()| {
+        instant += 1;
+        instant
+    };
+    let tick_forever = |_| LoopStatus::Continue::<()>;
+    runtime.register(ticks.emit(value(()).map(next_instant)).map(tick_forever).pause().while_loop());
+    let shutdown = runtime.shutdown_handle();
+    runtime.register(value(()).pause().repeat(3).map(move|_| shutdown.request()));
+    let flushed = Arc::new(Mutex::new(None));
+    let flushed_ref = flushed.clone();
+    runtime.on_shutdown(ticks.await().map(move|instant| *flushed_ref.lock().unwrap() = Some(instant)));
+    let wait_forever = |_| LoopStatus::Continue::<()>;
+    runtime.on_shutdown(value(()).pause().map(wait_forever).while_loop());
+    runtime.execute();
+    // The shutdown is requested during the instant 3, the finalizers start at the instant 4.
+    assert_eq!(*flushed.lock().unwrap(), Some(4));
+    assert_eq!(runtime.instant_index(), 8);
+
+    // The finalizers also run when the program completes.
+    let mut runtime = SequentialRuntime::new();
+    let (emitted, emitted_ref) = (Arc::new(Mutex::new(false)), ticks.clone());
+    let flushed = emitted.clone();
+    runtime.register(value(()).pause());
+    runtime.on_shutdown(emitted_ref.emit(value(1)).map(move|_| *flushed.lock().unwrap() = true));
+    runtime.execute();
+    assert!(*emitted.lock().unwrap());
+
+    // Without grace, the finalizers still execute the instant starting them, and the request is
+    // cleared when `execute` returns.
+    let mut runtime = SequentialRuntime::new();
+    runtime.shutdown_grace(0);
+    let parallel = RuntimeBuilder::new(2).shutdown_grace(0).build().start();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (shutdown, parallel_shutdown) = (runtime.shutdown_handle(), parallel.shutdown_handle());
+    let record = |log: &Arc<Mutex<Vec<&'static str>>>, entry| {
+        let log = log.clone();
+        value(()).map(move|()| log.lock().unwrap().push(entry))
+    };
+    runtime.register(value(()).map(move|()| shutdown.request()));
+    runtime.on_shutdown(record(&log, "finalized"));
+    runtime.execute();
+    runtime.register(record(&log, "executed again"));
+    runtime.execute();
+    parallel.register(value(()).map(move|()| parallel_shutdown.request()));
+    parallel.on_shutdown(record(&log, "finalized"));
+    parallel.execute();
+    parallel.register(record(&log, "executed again"));
+    parallel.execute();
+    assert_eq!(*log.lock().unwrap(), vec!["finalized", "executed again", "finalized", "executed again"]);
+}
+
+#[test]
+fn test_suspend() {
+    timeout_ms(|| {
+        // Whether each instant was executed by a worker of a parallel runtime.
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let log_ref = log.clone();
+        let record = move|()| log_ref.lock().unwrap().push(current_worker().is_some());
+        let mut runtime = SequentialRuntime::new();
+        runtime.register(value(()).map(record).pause().repeat(10).map(|_| ()));
+        let suspend = runtime.suspend_handle();
+        runtime.register(value(()).pause().pause().map(move|()| suspend.request()));
+        runtime.execute();
+        let suspended = runtime.suspend();
+        assert!(!suspended.is_empty());
+
+        let parallel = RuntimeBuilder::new(2).resume(suspended).start();
+        let suspend = parallel.suspend_handle();
+        parallel.register(value(()).pause().pause().map(move|()| suspend.request()));
+        parallel.execute();
+        let mut runtime = SequentialRuntime::resume(parallel.suspend());
+        runtime.execute();
+        assert_eq!(*log.lock().unwrap(), vec![false, false, true, true, true, false, false, false, false, false]);
+        assert_eq!(runtime.instant_index(), 11);
+        assert!(runtime.suspend().is_empty());
+    }, 5000);
+}
+
+#[test]
+fn test_boxed() {
+    // Processes of different types in the same vector.
+    let processes = vec![value(1).boxed(), value(2).pause().boxed(), value(()).map(|()| 3).boxed()];
+    assert_eq!(execute_process(multi_join(processes)), vec![1, 2, 3]);
+    let mut n = 0;
+    let body = move|()| {
+        n += 1;
+        if n == 3 { LoopStatus::Exit(n) } else { LoopStatus::Continue }
+    };
+    assert_eq!(execute_process(value(()).pause().map(body).boxed_mut().while_loop()), 3);
+}
+
+#[test]
+fn test_swappable() {
+    let registry = SwapRegistry::new();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let pushing = |log: &Arc<Mutex<Vec<i32>>>, n: i32, status: LoopStatus<i32>| {
+        let log = log.clone();
+        move|| {
+            let (log, status) = (log.clone(), status.clone());
+            value(()).pause().map(move|()| { log.lock().unwrap().push(n); status.clone() })
+        }
+    };
+    let mut scheduler = TestScheduler::new();
+    let result = scheduler.spawn_result(registry.swappable("decay", pushing(&log, 1, LoopStatus::Continue)));
+    scheduler.step_n(2);
+    assert_eq!(registry.swap("decay", pushing(&log, 2, LoopStatus::Exit(2))), Ok(1));
+    // The iteration started during instant 1 completes with the previous body.
+    scheduler.run();
+    assert_eq!(*log.lock().unwrap(), vec![1, 1, 2]);
+    assert_eq!(result.get(), Some(2));
+    assert_eq!(registry.version::<i32>("decay"), Ok(1));
+    assert!(registry.version::<i32>("wire").is_err());
+    assert!(registry.swap("decay", || value(LoopStatus::Exit(()))).is_err());
+}
+
+#[test]
+fn test_up_signal_specialization() {
+    let program = |keep_consumer: bool| {
+        let (producer, consumer) = UniqueProducerSignalProducer::new(0);
+        let mut n = 0;
+        let count = move|()| { n += 1; (n + 1) / 2 };
+        // Emits twice per instant, the second emission replacing the first with the same value.
+        let emitter = producer.emit(value(()).map(count)).repeat(2).then(value(()).pause()).repeat(3);
+        let mut received = Vec::new();
+        let collect = move|v: i32| {
+            received.push(v);
+            if received.len() == 3 { LoopStatus::Exit(received.clone()) } else { LoopStatus::Continue }
+        };
+        let status = |(status, ()): (LoopStatus<Vec<i32>>, ())| status;
+        let receiver = consumer.await_immediate().map(collect).join(value(()).pause()).map(status).while_loop();
+        // Without any other consumer, the signal switches to the lock-free runtime while present.
+        let kept = if keep_consumer { Some(consumer) } else { None };
+        emitter.join(receiver).map(move|(_, received)| { drop(kept); received })
+    };
+    for &keep_consumer in &[true, false] {
+        assert_eq!(execute_process(program(keep_consumer)), vec![1, 2, 3]);
+        assert_eq!(execute_process_par_with(ParallelRuntime::new(2), program(keep_consumer)), vec![1, 2, 3]);
+    }
+}
+
+#[test]
+fn test_spsc_signal() {
+    let (producer, consumer) = SPSCSignalProducer::new();
+    let mut n = 0;
+    let count = move|()| { n += 1; n };
+    let emitter = producer.emit(value(()).map(count)).then(value(()).pause()).repeat(3);
+    let mut received = Vec::new();
+    let collect = move|v: i32| {
+        received.push(v);
+        if received.len() == 3 { LoopStatus::Exit(received.clone()) } else { LoopStatus::Continue }
+    };
+    // Waits for the next instant, the signal being present until the end of the current one.
+    let status = |(status, ()): (LoopStatus<Vec<i32>>, ())| status;
+    let receiver = consumer.await_immediate().map(collect).join(value(()).pause()).map(status).while_loop();
+    assert_eq!(execute_process(emitter.join(receiver)).1, vec![1, 2, 3]);
+}
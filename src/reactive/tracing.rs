@@ -0,0 +1,182 @@
+use super::*;
+use std::fs::File;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+//  _____               _
+// |_   _| __ __ _  ___(_)_ __   __ _
+//   | || '__/ _` |/ __| | '_ \ / _` |
+//   | || | | (_| | (__| | | | | (_| |
+//   |_||_|  \__,_|\___|_|_| |_|\__, |
+//                              |___/
+
+thread_local!(static SPAN_NAME: RefCell<Option<String>> = RefCell::new(None));
+
+/// Names the span of the continuation executed by the current thread, unless an outer traced
+/// process already named it.
+fn claim_span_name(name: &str) {
+    SPAN_NAME.with(|span| {
+        let mut span = span.borrow_mut();
+        if span.is_none() {
+            *span = Some(name.to_string());
+        }
+    });
+}
+
+/// The name claimed by the outermost traced process executed since the last call, if any.
+pub(crate) fn take_span_name() -> Option<String> {
+    SPAN_NAME.with(|span| span.borrow_mut().take())
+}
+
+/// A span of a trace, or a marker when it has no duration.
+#[derive(Clone, Debug)]
+pub struct TraceEvent {
+    pub name: String,
+    pub lane: usize,
+    /// Time elapsed between the creation of the tracer and the event.
+    pub start: Duration,
+    pub duration: Option<Duration>,
+}
+
+/// Records the execution of a program, to be visualized with `chrome://tracing`.
+///
+/// Given to a `ParallelRuntime` with `RuntimeBuilder::trace`, it records a span per continuation
+/// on the lane of the worker executing it, and a span and a marker per instant on the lane after
+/// the workers. The spans of the processes wrapped with `Process::traced` are nested in the spans
+/// of their continuations, which are named after the outermost of them.
+#[derive(Clone)]
+pub struct Tracer {
+    origin: Instant,
+    events: Arc<Mutex<Vec<TraceEvent>>>,
+    lanes: Arc<Mutex<HashMap<usize, String>>>,
+}
+
+impl Tracer {
+    pub fn new() -> Self {
+        Tracer {
+            origin: Instant::now(),
+            events: Arc::new(Mutex::new(Vec::new())),
+            lanes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub(crate) fn name_lane(&self, lane: usize, name: &str) {
+        self.lanes.lock().unwrap().insert(lane, name.to_string());
+    }
+
+    pub(crate) fn span(&self, name: &str, lane: usize, start: Instant, end: Instant) {
+        let event = TraceEvent {
+            name: name.to_string(),
+            lane,
+            start: start.duration_since(self.origin),
+            duration: Some(end.duration_since(start)),
+        };
+        self.events.lock().unwrap().push(event);
+    }
+
+    pub(crate) fn marker(&self, name: &str, lane: usize) {
+        let event = TraceEvent { name: name.to_string(), lane, start: self.origin.elapsed(), duration: None };
+        self.events.lock().unwrap().push(event);
+    }
+
+    /// The events recorded so far, in recording order.
+    pub fn events(&self) -> Vec<TraceEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Writes the events in the Chrome trace event format, a lane being a thread of the trace.
+    pub fn write_chrome_trace<W>(&self, out: &mut W) -> io::Result<()> where W: Write {
+        let micros = |d: Duration| d.as_secs() as f64 * 1e6 + d.subsec_nanos() as f64 / 1e3;
+        let mut lines = Vec::new();
+        let mut lanes: Vec<(usize, String)> = self.lanes.lock().unwrap().iter().map(|(&l, n)| (l, n.clone())).collect();
+        lanes.sort();
+        for (lane, name) in lanes {
+            lines.push(format!("{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":0,\"tid\":{},\"args\":{{\"name\":\"{}\"}}}}",
+                               lane, escape_json(&name)));
+        }
+        for event in self.events.lock().unwrap().iter() {
+            let phase = match event.duration {
+                Some(duration) => format!("\"ph\":\"X\",\"dur\":{:.3}", micros(duration)),
+                None => String::from("\"ph\":\"i\",\"s\":\"g\""),
+            };
+            lines.push(format!("{{\"name\":\"{}\",{},\"ts\":{:.3},\"pid\":0,\"tid\":{}}}",
+                               escape_json(&event.name), phase, micros(event.start), event.lane));
+        }
+        write!(out, "[\n{}\n]\n", lines.join(",\n"))
+    }
+
+    /// Writes the trace to the file at `path`, see `write_chrome_trace`.
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        self.write_chrome_trace(&mut File::create(path)?)
+    }
+}
+
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A process whose executions are recorded as spans by a `Tracer`, on the lane of the worker
+/// executing it, or the first lane with a sequential runtime.
+///
+/// Like `Profiled`, a span only covers the time spent in `call` before it returns or reaches its
+/// continuation.
+pub struct Traced<P> {
+    process: P,
+    tracer: Tracer,
+    name: String,
+}
+
+impl<P> Traced<P> {
+    pub fn new(process: P, tracer: &Tracer, name: &str) -> Self {
+        Traced { process, tracer: tracer.clone(), name: name.to_string() }
+    }
+}
+
+impl<P> Process for Traced<P> where P: Process {
+    type Value = P::Value;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        claim_span_name(&self.name);
+        let start = Instant::now();
+        let reached = Arc::new(Mutex::new(None));
+        let reached_ref = reached.clone();
+        self.process.call(runtime, move|runtime: &mut Runtime, v| {
+            *reached_ref.lock().unwrap() = Some(Instant::now());
+            next.call(runtime, v);
+        });
+        let end = reached.lock().unwrap().take().unwrap_or_else(Instant::now);
+        self.tracer.span(&self.name, current_worker().unwrap_or(0), start, end);
+    }
+}
+
+impl<P> ProcessMut for Traced<P> where P: ProcessMut {
+    fn call_mut<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<(Self, Self::Value)> {
+        claim_span_name(&self.name);
+        let start = Instant::now();
+        let reached = Arc::new(Mutex::new(None));
+        let reached_ref = reached.clone();
+        let tracer = self.tracer.clone();
+        let name = self.name.clone();
+        self.process.call_mut(runtime, move|runtime: &mut Runtime, (process, v): (P, P::Value)| {
+            *reached_ref.lock().unwrap() = Some(Instant::now());
+            next.call(runtime, (Traced {process, tracer, name}, v));
+        });
+        let end = reached.lock().unwrap().take().unwrap_or_else(Instant::now);
+        self.tracer.span(&self.name, current_worker().unwrap_or(0), start, end);
+    }
+}
+
+impl<P> CloneProcess for Traced<P> where P: CloneProcess {
+    fn clone_process(&self) -> Self {
+        Traced { process: self.process.clone_process(), tracer: self.tracer.clone(), name: self.name.clone() }
+    }
+}
@@ -0,0 +1,149 @@
+use super::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// __        ___
+// \ \      / / |__   ___ _ __
+//  \ \ /\ / /| '_ \ / _ \ '_ \
+//   \ V  V / | | | |  __/ | | |
+//    \_/\_/  |_| |_|\___|_| |_|
+
+pub struct When<P, Q> {
+    process: P,
+    control: Q,
+}
+
+impl<P, Q> Process for When<P, Q> where P: Process, Q: CloneProcess {
+    type Value = P::Value;
+
+    fn call<C>(self, runtime: &mut Runtime, next: C) where C: Continuation<Self::Value> {
+        let gate = Arc::new(Gate { control: self.control, done: AtomicBool::new(false) });
+        let (process, finished) = (self.process, gate.clone());
+        let gating: Arc<Gating> = gate;
+        gating.run(&gating, runtime, Box::new(move|runtime: &mut Runtime, ()| {
+            process.call(runtime, move|runtime: &mut Runtime, v: P::Value| {
+                finished.done.store(true, Ordering::SeqCst);
+                next.call(runtime, v);
+            });
+        }));
+    }
+}
+
+impl<P, Q> CloneProcess for When<P, Q> where P: CloneProcess, Q: CloneProcess {
+    fn clone_process(&self) -> Self {
+        when(self.process.clone_process(), self.control.clone_process())
+    }
+}
+
+/// Executes `process` only during the instants in which `control` completes immediately, like
+/// `do p when s` in ReactiveML with `s.await_immediate()` as the control: the process starts at
+/// the first instant in which the signal is present, and its steps scheduled for an instant in
+/// which the signal is absent are frozen until the next instant in which it is present.
+///
+/// The steps executed when a signal awaited by the process resumes it are not frozen, as for
+/// `supervised`, nor the steps they schedule.
+pub fn when<P, Q>(process: P, control: Q) -> When<P, Q> where P: Process, Q: CloneProcess {
+    When { process, control }
+}
+
+struct Gate<Q> {
+    control: Q,
+    /// Set once the process completed, after which its steps are not frozen anymore.
+    done: AtomicBool,
+}
+
+/// The type-erased side of a `Gate`, shared by the steps of its process.
+trait Gating: Send + Sync {
+    fn is_done(&self) -> bool;
+
+    /// Executes `c` with a gated runtime once the control completes, from the current instant on.
+    fn run(&self, this: &Arc<Gating>, runtime: &mut Runtime, c: Box<Continuation<()>>);
+}
+
+impl<Q> Gating for Gate<Q> where Q: CloneProcess {
+    fn is_done(&self) -> bool {
+        self.done.load(Ordering::SeqCst)
+    }
+
+    fn run(&self, this: &Arc<Gating>, runtime: &mut Runtime, c: Box<Continuation<()>>) {
+        let gate = this.clone();
+        self.control.clone_process().call(runtime, move|runtime: &mut Runtime, _: Q::Value| {
+            if gate.is_done() {
+                return c.call_box(runtime, ());
+            }
+            c.call_box(&mut GatedRuntime { runtime, gate }, ());
+        });
+    }
+}
+
+/// A step scheduled by a gated process, executed once the control completes.
+struct GatedStep {
+    continuation: Box<Continuation<()>>,
+    gate: Arc<Gating>,
+}
+
+impl Continuation<()> for GatedStep {
+    fn call(self, runtime: &mut Runtime, (): ()) {
+        self.gate.run(&self.gate, runtime, self.continuation);
+    }
+
+    fn call_box(self: Box<Self>, runtime: &mut Runtime, value: ()) {
+        (*self).call(runtime, value);
+    }
+}
+
+/// The runtime given to a gated process, freezing the steps it schedules. The continuations of
+/// the end of the instant are left alone, since the signals rely on them.
+struct GatedRuntime<'a> {
+    runtime: &'a mut Runtime,
+    gate: Arc<Gating>,
+}
+
+impl<'a> GatedRuntime<'a> {
+    fn step(&self, c: Box<Continuation<()>>) -> Box<Continuation<()>> {
+        if self.gate.is_done() {
+            return c;
+        }
+        Box::new(GatedStep { continuation: c, gate: self.gate.clone() })
+    }
+}
+
+impl<'a> Runtime for GatedRuntime<'a> {
+    fn on_current_instant(&mut self, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_current_instant(step);
+    }
+
+    fn on_next_instant(&mut self, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_next_instant(step);
+    }
+
+    fn on_deadline(&mut self, deadline: time::Instant, c: Box<Continuation<()>>) {
+        let step = self.step(c);
+        self.runtime.on_deadline(deadline, step);
+    }
+
+    fn on_end_of_instant(&mut self, c: Box<Continuation<()>>) {
+        self.runtime.on_end_of_instant(c);
+    }
+
+    fn instant_index(&self) -> usize {
+        self.runtime.instant_index()
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.runtime.is_deterministic()
+    }
+
+    fn external_inbox(&self) -> ExternalInbox {
+        self.runtime.external_inbox()
+    }
+
+    fn panic_handler(&self) -> Option<PanicHandler> {
+        self.runtime.panic_handler()
+    }
+
+    fn clock(&self) -> SharedClock {
+        self.runtime.clock()
+    }
+}
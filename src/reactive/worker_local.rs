@@ -0,0 +1,88 @@
+use super::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// __        __         _             _                    _
+// \ \      / /__  _ __| | _____ _ __| |    ___   ___ __ _| |
+//  \ \ /\ / / _ \| '__| |/ / _ \ '__| |   / _ \ / __/ _` | |
+//   \ V  V / (_) | |  |   <  __/ |  | |__| (_) | (_| (_| | |
+//    \_/\_/ \___/|_|  |_|\_\___|_|  |_____\___/ \___\__,_|_|
+
+/// Identifies the `WorkerLocal`s in the storage of each thread.
+static NEXT_LOCAL: AtomicUsize = AtomicUsize::new(0);
+/// Identifies the runtimes in the storage of each thread, 0 standing for threads outside of them.
+static NEXT_RUNTIME: AtomicUsize = AtomicUsize::new(1);
+
+thread_local! {
+    /// The values of the `WorkerLocal`s used by the current thread, by runtime and identifier.
+    static LOCALS: RefCell<HashMap<(usize, usize), Box<Any>>> = RefCell::new(HashMap::new());
+    /// The runtime executed by the current thread.
+    static RUNTIME: Cell<usize> = Cell::new(0);
+}
+
+/// A new identifier for a runtime, under which the workers executing it store their copies.
+pub(crate) fn runtime_id() -> usize {
+    NEXT_RUNTIME.fetch_add(1, Ordering::SeqCst)
+}
+
+/// Makes `runtime` the runtime executed by the current thread until the returned scope is
+/// dropped, and drops the values of its `WorkerLocal`s used by the thread, called when the runtime
+/// starts a new execution. The values of the other runtimes are kept.
+pub(crate) fn start_execution(runtime: usize) -> RuntimeScope {
+    let previous = RUNTIME.with(|current| current.replace(runtime));
+    let locals: Vec<Box<Any>> = LOCALS.with(|locals| {
+        let mut locals = locals.borrow_mut();
+        let keys: Vec<(usize, usize)> = locals.keys().filter(|&&(r, _)| r == runtime).cloned().collect();
+        keys.iter().filter_map(|key| locals.remove(key)).collect()
+    });
+    drop(locals);
+    RuntimeScope { previous }
+}
+
+/// Restores the runtime executed by the thread before `start_execution` when dropped.
+pub(crate) struct RuntimeScope {
+    previous: usize,
+}
+
+impl Drop for RuntimeScope {
+    fn drop(&mut self) {
+        RUNTIME.with(|current| current.set(self.previous));
+    }
+}
+
+/// A value of which each worker of a runtime has its own copy, to let hot closures reuse buffers
+/// from one call to the next without sharing them between threads.
+///
+/// A copy is created with the initializer the first time a worker accesses it, and created anew
+/// each time its runtime starts executing with `execute`, the copies of the other runtimes
+/// executed by the same thread being kept. The copy of a sequential runtime is the one of the
+/// thread executing it. Handles are cheap to clone and can be moved into the closures
+/// of `map` and the like.
+pub struct WorkerLocal<T> {
+    id: usize,
+    init: Arc<Fn() -> T + Send + Sync>,
+}
+
+impl<T> Clone for WorkerLocal<T> {
+    fn clone(&self) -> Self {
+        WorkerLocal { id: self.id, init: self.init.clone() }
+    }
+}
+
+impl<T> WorkerLocal<T> where T: 'static {
+    pub fn new<F>(init: F) -> Self where F: Fn() -> T + Send + Sync + 'static {
+        WorkerLocal { id: NEXT_LOCAL.fetch_add(1, Ordering::SeqCst), init: Arc::new(init) }
+    }
+
+    /// Calls `f` with the copy of the current worker.
+    ///
+    /// The copy is lent to `f` for the duration of the call: were `f` to access the same
+    /// `WorkerLocal` again, it would get a fresh copy, later dropped.
+    pub fn with<F, R>(&self, f: F) -> R where F: FnOnce(&mut T) -> R {
+        let key = (RUNTIME.with(|current| current.get()), self.id);
+        let stored = LOCALS.with(|locals| locals.borrow_mut().remove(&key));
+        let mut local = stored.unwrap_or_else(|| Box::new((self.init)()));
+        let result = f(local.downcast_mut::<T>().expect("a worker local has the wrong type"));
+        LOCALS.with(|locals| locals.borrow_mut().insert(key, local));
+        result
+    }
+}
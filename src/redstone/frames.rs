@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+//  _____
+// |  ___| __ __ _ _ __ ___   ___  ___
+// | |_ | '__/ _` | '_ ` _ \ / _ \/ __|
+// |  _|| | | (_| | | | | | |  __/\__ \
+// |_|  |_|  \__,_|_| |_| |_|\___||___/
+
+/// The duration of an instant in milliseconds above which the next frame is skipped, that of a
+/// 60 Hz display.
+pub const FRAME_BUDGET_MS: u64 = 16;
+
+/// Decides which instants are displayed, to keep the window responsive on maps whose instants
+/// exceed the frame budget.
+///
+/// The frame following an instant over budget is skipped, but never two frames in a row, so that
+/// the display keeps up with at least every other instant. Since a frame shows the whole state of
+/// the map, the next one covers the skipped updates.
+pub struct FrameSkipper {
+    budget: Duration,
+    skipped: bool,
+}
+
+impl FrameSkipper {
+    pub fn new(budget: Duration) -> Self {
+        FrameSkipper { budget, skipped: false }
+    }
+
+    /// Whether to display the current instant, given the duration of the previous one.
+    pub fn should_render(&mut self, previous_instant: Duration) -> bool {
+        self.skipped = !self.skipped && previous_instant > self.budget;
+        !self.skipped
+    }
+}
@@ -11,15 +11,44 @@ use self::piston::input::*;
 use self::glutin_window::GlutinWindow as Window;
 use self::opengl_graphics::{ GlGraphics, OpenGL };
 
+use reactive_rs::reactive::lockstep::*;
+use reactive_rs::reactive::pacing::*;
 use reactive_rs::reactive::process::*;
+use reactive_rs::reactive::runtime::SuspendHandle;
+use reactive_rs::reactive::runtime::parallel_runtime::*;
+use reactive_rs::reactive::runtime::sequential_runtime::*;
 use reactive_rs::reactive::signal::value_signal::*;
 
-use std::ops::{Add, Sub, Mul};
-use std::cmp::max;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
+use std::time::Duration;
 use std::fs::File;
+use std::io;
 use std::io::prelude::*;
+use std::io::LineWriter;
+
+mod frames;
+mod plot;
+mod power;
+mod rules;
+#[cfg(feature = "schematic")]
+mod schematic;
+mod sound;
+mod stimuli;
+#[cfg(test)]
+mod tests;
+mod truth;
+mod view;
+
+use self::frames::*;
+use self::plot::*;
+use self::power::*;
+use self::rules::*;
+use self::sound::*;
+use self::stimuli::*;
+use self::truth::*;
+use self::view::*;
 
 #[derive(PartialEq, Clone, Copy)]
 enum Direction {
@@ -36,15 +65,17 @@ enum Type {
     REDSTONE(Power),
     INVERTER(Direction),
     USER,
+    NOTE,
 }
 
-fn displace((x, y): (usize, usize), dir: Direction) -> (usize, usize){
-    match dir {
-        Direction::SOUTH => return (x  , y+1),
-        Direction::NORTH => return (x  , y-1),
-        Direction::EAST  => return (x+1, y  ),
-        Direction::WEST  => return (x-1, y  ),
-    }
+fn displace(pos: (usize, usize), dir: Direction, rules: SimulationRules, size: (usize, usize)) -> Option<(usize, usize)> {
+    let offset = match dir {
+        Direction::SOUTH => ( 0,  1),
+        Direction::NORTH => ( 0, -1),
+        Direction::EAST  => ( 1,  0),
+        Direction::WEST  => (-1,  0),
+    };
+    rules.offset(pos, offset, size)
 }
 
 fn invert_dir(dir: Direction) -> Direction {
@@ -56,72 +87,56 @@ fn invert_dir(dir: Direction) -> Direction {
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
-struct Power {
-    r: u8,
-    g: u8,
-    b: u8,
-}
-
-impl Add for Power {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
-        return Power{
-            r: self.r + other.r,
-            g: self.g + other.g,
-            b: self.b + other.b}
-    }
+/// Number of instants shown by the plots of the selected cells.
+const PLOT_LENGTH: usize = 200;
+
+const VOID_COLOR:       [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+const BLOCK_COLOR_OUT:  [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const BLOCK_COLOR_IN:   [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+/// Size in pixels below which cells are drawn by tiles, see `TileMap`.
+const MIN_CELL_SIZE: f64 = 3.0;
+/// Size in pixels of the largest side of the minimap, and its distance to the window borders.
+const MINIMAP_SIZE: f64 = 200.0;
+const MINIMAP_MARGIN: f64 = 10.0;
+/// Maximum number of tiles on each side of the minimap.
+const MINIMAP_TILES: usize = 100;
+/// Number of buffers of the window, a frame being drawn over the one shown that many frames ago.
+const WINDOW_BUFFERS: usize = 2;
+
+/// Number of instants a circuit is simulated before its inputs are applied, and then after.
+const TRUTH_TABLE_INSTANTS: usize = 64;
+/// Number of instants the outputs of a circuit must keep their powers to be settled.
+const SETTLED_INSTANTS: usize = 8;
+
+pub type Session = (ValueSignal<Vec<PlayerInput<bool>>, PlayerInput<bool>>, LockstepInputs<bool>, Lockstep<bool>);
+
+/// Options of a run of the simulation, see `redstone_sim_with`.
+#[derive(Default)]
+pub struct SimOptions {
+    /// The map to simulate, `map.txt` by default.
+    pub map_file: Option<String>,
+    /// Shares the user blocks with the other peer of the session: they are powered while a
+    /// player holds space.
+    pub session: Option<Session>,
+    /// A file of powers injected in cells during the run, see `parse_stimuli`.
+    pub stimuli_file: Option<String>,
+    /// A file to which the power of the `probes` is written at every instant, in the format of
+    /// stimuli files.
+    pub record_file: Option<String>,
+    pub probes: Vec<(usize, usize)>,
+    /// Number of workers of the parallel runtime executing the simulation, or 0 to execute it
+    /// with the sequential runtime. The window switches runtimes during the run.
+    pub workers: usize,
 }
 
-impl Sub for Power {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self {
-        return Power{
-            r: self.r - other.r,
-            g: self.g - other.g,
-            b: self.b - other.b}
-    }
-}
-
-impl Mul for Power {
-    type Output = Self;
-
-    fn mul(self, other: Self) -> Self {
-        return Power{
-            r: self.r * other.r,
-            g: self.g * other.g,
-            b: self.b * other.b}
-    }
-}
-//
-//impl Power {
-//    fn less_than(self, other: Power) -> bool {
-//           self.r <= other.r
-//        && self.g <= other.g
-//        && self.b <= other.b
-//        && (
-//               self.r < other.r
-//            || self.g < other.g
-//            || self.b < other.b)
-//    }
-//}
-
-fn max_p(p: Power, q: Power) -> Power {
-    Power{
-        r: max(p.r, q.r),
-        g: max(p.g, q.g),
-        b: max(p.b, q.b)}
-}
-
-const ZERO_POWER: Power = Power{r: 0x0, g: 0x0, b: 0x0};
-const ATOMIC_POWER: Power = Power{r: 0x1, g: 0x1, b: 0x1};
-const MAX_POWER: Power = Power{r: 0xF, g: 0xF, b: 0xF};
-
-pub fn redstone_sim() {
-    let (blocks, w, h) = read_file(String::from("map.txt"));
+/// The display signal gathers the powered cells of an instant with their power.
+type DisplaySignal = ValueSignal<Vec<(usize, usize, Power)>, (usize, usize, Power)>;
 
+/// Builds the processes simulating the tiles of a map, which never terminate, with the signal
+/// gathering the power of each cell and the display signal on which the cells are emitted at
+/// every instant. User blocks are powered while `user_press` is set.
+fn world(blocks: &[Type], w: usize, h: usize, rules: SimulationRules, user_press: &Arc<Mutex<bool>>)
+    -> (Vec<ValueSignal<Power, Power>>, DisplaySignal, impl Process) {
     let mut power_signal = Vec::new();
     for i in 0..(w*h) {
         let filter =
@@ -131,9 +146,10 @@ pub fn redstone_sim() {
                 Type::REDSTONE(filter) => filter,
                 Type::INVERTER(_) => ATOMIC_POWER,
                 Type::USER => ATOMIC_POWER,
+                Type::NOTE => ATOMIC_POWER,
             };
         power_signal.push(ValueSignal::new(ZERO_POWER, Box::new(move |x: Power, y: Power| {
-            max_p(x, y) * filter
+            x.max(y) * filter
         })));
     }
     let display_signal = ValueSignal::new(vec!(), Box::new(|entries: Vec<(usize, usize, Power)>, entry: (usize, usize, Power)| {
@@ -141,69 +157,229 @@ pub fn redstone_sim() {
         entries.push(entry);
         entries
     }));
-    let power_at = |(x, y): (usize, usize)| power_signal[(x % w) + (y % h) * w].clone();
+    let process = {
+        let power_at = |(x, y): (usize, usize)| power_signal[x + y * w].clone();
 
-    let redstone_wire_process = |x: usize, y: usize, filter: Power| {
+        let redstone_wire_process = |x: usize, y: usize, filter: Power, rules: SimulationRules| {
 
-        let decr = move|p: Power| {
-            max_p(p, ATOMIC_POWER) - ATOMIC_POWER
+            let decr = move|p: Power| {
+                p.decay(rules.decay)
+            };
+            let continue_loop: LoopStatus<()> = LoopStatus::Continue;
+            let input = power_at((x, y));
+            let mut outputs = vec!(input.clone());
+            for pos in rules.neighbours((x, y), (w, h)) {
+                outputs.push(power_at(pos));
+            }
+            let emit_outputs = move|power: Power| {
+                multi_join(outputs.iter().map(|output| output.emit(value(power))).collect())
+            };
+            let combine_with_pos = move|power| (x, y, power * filter);
+            let uncombine = move|(_x, _y, power)| power;
+            display_signal.emit(input.await().map(combine_with_pos)).map(uncombine).map(decr).boxed_mut()
+                .and_then(emit_outputs)
+                .then(value(continue_loop)).boxed_mut().while_loop()
         };
-        let continue_loop: LoopStatus<()> = LoopStatus::Continue;
-        let input = power_at((x, y));
-        let combine_with_pos = move|power| (x, y, power * filter);
-        let uncombine = move|(_x, _y, power)| power;
-        input.emit(
-            power_at((x + 1, y    )).emit(
-                power_at((x - 1, y    )).emit(
-                    power_at((x    , y + 1)).emit(
-                        power_at((x    , y - 1)).emit(
-                            display_signal.emit(
-                                input.await().map(combine_with_pos)).map(uncombine).map(decr))))))
-            .then(value(continue_loop)).while_loop()
-    };
 
-    let blocks_copy = blocks.clone();
-    let redstone_torch_process = |x: usize, y: usize, dir: Direction| {
-        let input = power_at(displace((x, y), invert_dir(dir)));
-        let is_powered = |power| {
-            power != ZERO_POWER
+        let redstone_torch_process = |x: usize, y: usize, dir: Direction, rules: SimulationRules| {
+            // Nothing powers a torch whose back is the edge of the map.
+            let input = match displace((x, y), invert_dir(dir), rules, (w, h)) {
+                Some(pos) => power_at(pos),
+                None => ValueSignal::new(ZERO_POWER, Box::new(|x: Power, y: Power| x.max(y))),
+            };
+            let is_powered = |power| {
+                !power.is_zero()
+            };
+            let should_emit = |pos| {
+                let (x, y) = pos;
+                match blocks[x+w*y] {
+                    Type::REDSTONE(_) => true,
+                    Type::BLOCK => true,
+                    Type::NOTE => true,
+                    _ => false
+                }
+            };
+            let mut emit_near = vec!(power_at((x, y)).emit(value(MAX_POWER)));
+            for d in vec!(Direction::NORTH, Direction::SOUTH, Direction::EAST, Direction::WEST) {
+                match displace((x, y), d, rules, (w, h)) {
+                    Some(pos) if d != invert_dir(dir) && should_emit(pos) => emit_near.push(power_at(pos).emit(value(MAX_POWER))),
+                    _ => (),
+                }
+            }
+            let continue_loop: LoopStatus<()> = LoopStatus::Continue;
+            let p = input.emit(value(ZERO_POWER)).then(if_else(input.await().map(is_powered), value(()), multi_join(emit_near).then(display_signal.emit(value((x, y, MAX_POWER)))).then(value(()))));
+            p.then(value(continue_loop)).boxed_mut().while_loop()
         };
-        let should_emit = |pos| {
-            let (x, y) = pos;
-            match blocks_copy[x+w*y] {
-                Type::REDSTONE(_) => true,
-                Type::BLOCK => true,
-                _ => false
+
+        let redstone_user_process = |x: usize, y: usize, rules: SimulationRules| {
+            let mut emit_near = vec!();
+            for d in vec!(Direction::NORTH, Direction::SOUTH, Direction::EAST, Direction::WEST) {
+                if let Some(pos) = displace((x, y), d, rules, (w, h)) {
+                    emit_near.push(power_at(pos).emit(value(MAX_POWER)))
+                }
             }
+            let continue_loop: LoopStatus<()> = LoopStatus::Continue;
+            let user_press = user_press.clone();
+            let is_user_active = move|()| {
+                *user_press.lock().unwrap()
+            };
+            let p = if_else(value(()).map(is_user_active).pause(), value(()), multi_join(emit_near).then(display_signal.emit(value((x, y, MAX_POWER)))).then(value(())));
+            p.then(value(continue_loop)).boxed_mut().while_loop()
         };
-        let mut emit_near = vec!(power_at((x, y)).emit(value(MAX_POWER)));
-        for d in vec!(Direction::NORTH, Direction::SOUTH, Direction::EAST, Direction::WEST) {
-            if d != invert_dir(dir) && should_emit(displace((x, y), d)) {
-                emit_near.push(power_at(displace((x, y), d)).emit(value(MAX_POWER)))
+
+        let redstone_note_process = |x: usize, y: usize| {
+            let input = power_at((x, y));
+            let mut note = NoteBlock::default();
+            let play = move|power: Power| {
+                if let Some(frequency) = note.update(power) {
+                    beep(frequency);
+                }
+                (x, y, power)
+            };
+            let continue_loop: LoopStatus<()> = LoopStatus::Continue;
+            input.emit(value(ZERO_POWER)).then(display_signal.emit(input.await().map(play)))
+                .then(value(continue_loop)).boxed_mut().while_loop()
+        };
+
+        // Boxed so that the type of the whole map does not grow with the kinds of cells.
+        let mut cells = Vec::new();
+        for x in 0..w {
+            for y in 0..h {
+                match blocks[x + y * w] {
+                    Type::VOID => (),
+                    Type::BLOCK => (),
+                    Type::REDSTONE(filter) => cells.push(redstone_wire_process(x, y, filter, rules).boxed()),
+                    Type::INVERTER(dir) => cells.push(redstone_torch_process(x, y, dir, rules).boxed()),
+                    Type::USER => cells.push(redstone_user_process(x, y, rules).boxed()),
+                    Type::NOTE => cells.push(redstone_note_process(x, y).boxed()),
+                }
             }
         }
-        let continue_loop: LoopStatus<()> = LoopStatus::Continue;
-        let p = input.emit(value(ZERO_POWER)).then(if_else(input.await().map(is_powered), value(()), multi_join(emit_near).then(display_signal.emit(value((x, y, MAX_POWER)))).then(value(()))));
-        p.then(value(continue_loop)).while_loop()
+        multi_join(cells)
     };
+    (power_signal, display_signal, process)
+}
 
-    let user_press = Arc::new(Mutex::new(false));
-    let redstone_user_process = |x: usize, y: usize| {
-        let mut emit_near = vec!();
-        for d in vec!(Direction::NORTH, Direction::SOUTH, Direction::EAST, Direction::WEST) {
-            emit_near.push(power_at(displace((x, y), d)).emit(value(MAX_POWER)))
+/// Injects the stimuli of `schedule` in the cells of a map of width `w`, until there are none left.
+fn stimuli_process(power_signal: &[ValueSignal<Power, Power>], w: usize, schedule: StimuliSchedule) -> impl Process<Value = ()> {
+    let schedule = Arc::new(Mutex::new(schedule));
+    let schedule_ref = schedule.clone();
+    let signals = power_signal.to_vec();
+    let due = move|()| schedule_ref.lock().unwrap().next_instant();
+    let inject = move|stimuli: Vec<Stimulus>| {
+        multi_join(stimuli.into_iter().map(|stimulus| {
+            let (x, y) = stimulus.cell;
+            signals[x + y * w].emit(value(stimulus.power))
+        }).collect())
+    };
+    let is_done = move|()| {
+        if schedule.lock().unwrap().is_done() { LoopStatus::Exit(()) } else { LoopStatus::Continue }
+    };
+    value(()).map(due).and_then(inject).then(value(()).pause()).map(is_done).while_loop()
+}
+
+/// Prints the truth table of the circuit between the `inputs` and `outputs` cells of a map.
+///
+/// Each combination of inputs is simulated on a fresh world without window: the circuit runs
+/// unpowered for a while, then the powered inputs are held at full power through stimuli, and the
+/// delay is the number of instants the outputs took to settle.
+pub fn redstone_truth_table(map_file: Option<String>, inputs: Vec<(usize, usize)>, outputs: Vec<(usize, usize)>) {
+    let (blocks, w, h, rules) = read_file(map_file.unwrap_or(String::from("map.txt")));
+    for cell in inputs.iter().chain(outputs.iter()) {
+        assert!(cell.0 < w && cell.1 < h, "Cell outside of the map: {:?}", cell);
+    }
+
+    let mut rows = Vec::new();
+    for combination in input_combinations(inputs.len()) {
+        let mut stimuli = Vec::new();
+        for instant in TRUTH_TABLE_INSTANTS..(2 * TRUTH_TABLE_INSTANTS) {
+            for (&cell, &powered) in inputs.iter().zip(combination.iter()) {
+                if powered {
+                    stimuli.push(Stimulus { instant, cell, power: MAX_POWER });
+                }
+            }
         }
+
+        let (power_signal, display_signal, world_process) = world(&blocks, w, h, rules, &Arc::new(Mutex::new(false)));
+        let trace = Arc::new(Mutex::new(Vec::new()));
+        let trace_ref = trace.clone();
+        let cells = outputs.clone();
+        let record = move|entries: Vec<(usize, usize, Power)>| {
+            let powers: Vec<Power> = cells.iter().map(|&cell| {
+                entries.iter()
+                    .find(|&&(x, y, _)| (x, y) == cell)
+                    .map_or(ZERO_POWER, |&(_, _, power)| power)
+            }).collect();
+            trace_ref.lock().unwrap().push(powers);
+        };
         let continue_loop: LoopStatus<()> = LoopStatus::Continue;
-        let user_press = user_press.clone();
-        let is_user_active = move|()| {
-            *user_press.lock().unwrap()
+        let mut runtime = SequentialRuntime::new();
+        runtime.register(world_process
+            .join(stimuli_process(&power_signal, w, StimuliSchedule::new(stimuli)))
+            .join(display_signal.await_or(Vec::new()).map(record).then(value(continue_loop)).while_loop())
+            .map(|_| ()));
+        // The powers of an instant are recorded at the next one.
+        for _ in 0..(2 * TRUTH_TABLE_INSTANTS + 1) {
+            runtime.instant();
+        }
+
+        let trace = trace.lock().unwrap();
+        rows.push(TruthRow::from_trace(combination, &trace[TRUTH_TABLE_INSTANTS..], SETTLED_INSTANTS));
+    }
+    print!("{}", format_truth_table(&inputs, &outputs, &rows));
+}
+
+pub fn redstone_sim() {
+    redstone_sim_with(SimOptions::default());
+}
+
+pub fn redstone_sim_with(options: SimOptions) {
+    let SimOptions { map_file, session, stimuli_file, record_file, probes, workers } = options;
+    let (blocks, w, h, rules) = read_file(map_file.unwrap_or(String::from("map.txt")));
+
+    let user_press = Arc::new(Mutex::new(false));
+    let (power_signal, display_signal, world_process) = world(&blocks, w, h, rules, &user_press);
+
+    let stimuli = match stimuli_file {
+        Some(filename) => {
+            let mut contents = String::new();
+            File::open(filename).and_then(|mut file| file.read_to_string(&mut contents)).unwrap();
+            parse_stimuli(&contents).unwrap()
+        },
+        None => Vec::new(),
+    };
+    for stimulus in stimuli.iter().map(|stimulus| stimulus.cell).chain(probes.iter().cloned()) {
+        assert!(stimulus.0 < w && stimulus.1 < h, "Cell outside of the map: {:?}", stimulus);
+    }
+    let record_process = |probes: Vec<(usize, usize)>, record_file: Option<String>| {
+        let mut writer = record_file.map(|filename| LineWriter::new(File::create(filename).unwrap()));
+        let mut instant = 0;
+        let write = move|entries: Vec<(usize, usize, Power)>| {
+            let writer = match writer {
+                Some(ref mut writer) => writer,
+                None => return LoopStatus::Exit(()),
+            };
+            for &cell in probes.iter() {
+                let power = entries.iter()
+                    .find(|&&(x, y, _)| (x, y) == cell)
+                    .map_or(ZERO_POWER, |&(_, _, power)| power);
+                writeln!(writer, "{}", Stimulus { instant, cell, power }.to_line()).unwrap();
+            }
+            instant += 1;
+            LoopStatus::Continue
         };
-        let p = if_else(value(()).map(is_user_active).pause(), value(()), multi_join(emit_near).then(display_signal.emit(value((x, y, MAX_POWER)))).then(value(())));
-        p.then(value(continue_loop)).while_loop()
+        display_signal.await().map(write).while_loop()
     };
 
+    // The wall-clock duration of each instant, which paces the display and measures the speed.
+    // The runtime starting the simulation, see `execute_simulation`.
+    let mut runtime = SequentialRuntime::new();
+    let instant_duration = runtime.dt_signal(Pacing::WallClock);
+
     let display_powers: Arc<Mutex<Vec<Power>>> = Arc::new(Mutex::new(vec![ZERO_POWER; w*h]));
     let display_powers_ref = display_powers.clone();
+    let history = Arc::new(Mutex::new(PowerHistory::new(PLOT_LENGTH)));
+    let history_ref = history.clone();
 
     let display_process = || {
         let mut powers = Vec::new();
@@ -223,32 +399,43 @@ pub fn redstone_sim() {
             }
         };
         let powers_ref = powers.clone();
-        let draw = move|_| {
+        let mut frames = FrameSkipper::new(Duration::from_millis(FRAME_BUDGET_MS));
+        let draw = move|(_, previous_instant)| {
 //            use std::thread;
 //            use std::time::Duration;
 //            thread::sleep(Duration::from_millis(150));
-            let mut dpowers = display_powers_ref.lock().unwrap();
             let powers = powers_ref.lock().unwrap();
-            dpowers.clone_from(&powers);
+            history_ref.lock().unwrap().record(&powers);
+            // A skipped frame is covered by the next one, which shows the latest powers.
+            if frames.should_render(previous_instant) {
+                let mut dpowers = display_powers_ref.lock().unwrap();
+                dpowers.clone_from(&powers);
+            }
         };
-        display_signal.await().map(read_entries).map(draw).then(value(continue_loop)).while_loop()
+        display_signal.await().map(read_entries).join(instant_duration.await()).map(draw).then(value(continue_loop)).while_loop()
     };
 
-    let mut p_redstone = Vec::new();
-    let mut p_inverter = Vec::new();
-    let mut p_user = Vec::new();
-    for x in 0..w {
-        for y in 0..h {
-            match blocks[x + y * w] {
-                Type::VOID => (),
-                Type::BLOCK => (),
-                Type::REDSTONE(filter) => p_redstone.push(redstone_wire_process(x, y, filter)),
-                Type::INVERTER(dir) => p_inverter.push(redstone_torch_process(x, y, dir)),
-                Type::USER => p_user.push(redstone_user_process(x, y)),
-            }
-        }
-    }
+    let (session_process, local_inputs) = match session {
+        Some((players_signal, inputs, lockstep)) => {
+            let pressed = Arc::new(Mutex::new([false, false]));
+            let user_press = user_press.clone();
+            let update_press = move|players_inputs: Vec<PlayerInput<bool>>| {
+                let mut pressed = pressed.lock().unwrap();
+                for PlayerInput {player, input} in players_inputs {
+                    pressed[player] = input;
+                }
+                *user_press.lock().unwrap() = pressed[0] || pressed[1];
+            };
+            let continue_loop: LoopStatus<()> = LoopStatus::Continue;
+            let report = |e: io::Error| println!("lockstep session lost: {}", e);
+            let p = players_signal.await().map(update_press).then(value(continue_loop)).while_loop();
+            (Some(lockstep.map(report).join(p)), Some(inputs))
+        },
+        None => (None, None),
+    };
 
+    let switch = RuntimeSwitch::new(workers);
+    let switch_ref = switch.clone();
     let display_powers_ref = display_powers.clone();
     let user_press = user_press.clone();
     thread::spawn(move || {
@@ -268,6 +455,11 @@ pub fn redstone_sim() {
         let zoom_step: f64 = f64::powf(2.0, 1.0/7.0);
         const ZOOM_INIT: f64 = 10.0;
 
+        let occupied: Vec<bool> = blocks.iter().map(|block| match *block {
+            Type::VOID => false,
+            _ => true,
+        }).collect();
+        let minimap_tile = (w.max(h) + MINIMAP_TILES - 1) / MINIMAP_TILES;
         let mut app = App {
             gl: GlGraphics::new(opengl),
             powers: vec![ZERO_POWER; blocks.len()],
@@ -276,10 +468,22 @@ pub fn redstone_sim() {
             height: h,
             zoom: ZOOM_INIT,
             tx: 0.0,
-            ty: 0.0
+            ty: 0.0,
+            cursor: [0.0, 0.0],
+            history,
+            cells: TileMap::new(w, h, 1, occupied.clone()),
+            overview: TileMap::new(w, h, 1, occupied.clone()),
+            minimap: TileMap::new(w, h, minimap_tile.max(1), occupied),
+            show_minimap: true,
+            view: None,
+            full_frames: 0,
+            changed: Vec::new(),
         };
 
 
+        // The number of workers to switch back to from the sequential runtime.
+        let mut parallel_workers = if workers == 0 { DEFAULT_WORKERS } else { workers };
+
         let mut events = Events::new(EventSettings::new());
         while let Some(e) = events.next(&mut window) {
             if let Some(r) = e.render_args() {
@@ -289,6 +493,12 @@ pub fn redstone_sim() {
                 }
                 app.render(&r);
             }
+            if let Some(cursor) = e.mouse_cursor_args() {
+                app.cursor = cursor;
+            }
+            if Some(Button::Mouse(MouseButton::Left)) == e.press_args() {
+                app.toggle_plot();
+            }
             if Some(Button::Keyboard(Key::Backspace)) == e.press_args(){
                 app.zoom *= zoom_step;
                 app.tx *= zoom_step;
@@ -311,30 +521,167 @@ pub fn redstone_sim() {
             if Some(Button::Keyboard(Key::Down)) == e.press_args(){
                 app.ty -= app.zoom;
             }
+            if Some(Button::Keyboard(Key::M)) == e.press_args(){
+                app.show_minimap = !app.show_minimap;
+            }
+            if Some(Button::Keyboard(Key::P)) == e.press_args(){
+                let workers = if switch_ref.workers() == 0 { parallel_workers } else { 0 };
+                switch_ref.switch(workers);
+            }
+            if Some(Button::Keyboard(Key::Equals)) == e.press_args(){
+                parallel_workers = switch_ref.workers() + 1;
+                switch_ref.switch(parallel_workers);
+            }
+            if Some(Button::Keyboard(Key::Minus)) == e.press_args() && switch_ref.workers() > 0 {
+                let workers = switch_ref.workers() - 1;
+                parallel_workers = workers.max(1);
+                switch_ref.switch(workers);
+            }
             if Some(Button::Keyboard(Key::Space)) == e.press_args(){
-                *user_press.lock().unwrap() = true;
+                match local_inputs {
+                    Some(ref inputs) => inputs.push(true),
+                    None => *user_press.lock().unwrap() = true,
+                }
             }
             if Some(Button::Keyboard(Key::Space)) == e.release_args() {
-                *user_press.lock().unwrap() = false;
+                match local_inputs {
+                    Some(ref inputs) => inputs.push(false),
+                    None => *user_press.lock().unwrap() = false,
+                }
             }
         }
     });
 
-    execute_process(multi_join(p_redstone).join(multi_join(p_inverter)).join(multi_join(p_user)).join(display_process()));
+    let simulation = world_process.join(display_process())
+        .join(stimuli_process(&power_signal, w, StimuliSchedule::new(stimuli))).join(record_process(probes, record_file))
+        .join(speed_process(switch.clone(), &instant_duration));
+    match session_process {
+        Some(session_process) => execute_simulation(runtime, simulation.join(session_process), switch),
+        None => execute_simulation(runtime, simulation, switch),
+    }
+
+}
+
+/// The number of workers `P` switches to from the sequential runtime, unless the simulation
+/// started with a parallel runtime.
+const DEFAULT_WORKERS: usize = 4;
+
+/// Chooses the runtime executing the simulation, which the window switches between two instants.
+#[derive(Clone)]
+struct RuntimeSwitch {
+    workers: Arc<AtomicUsize>,
+    /// Suspends the runtime executing the simulation, for the next one to resume it.
+    running: Arc<Mutex<Option<SuspendHandle>>>,
+}
+
+impl RuntimeSwitch {
+    fn new(workers: usize) -> Self {
+        RuntimeSwitch { workers: Arc::new(AtomicUsize::new(workers)), running: Arc::new(Mutex::new(None)) }
+    }
+
+    /// The number of workers of the parallel runtime executing the simulation, 0 for the
+    /// sequential runtime.
+    fn workers(&self) -> usize {
+        self.workers.load(Ordering::SeqCst)
+    }
+
+    /// Executes the simulation with `workers` workers from the end of the running instant on.
+    fn switch(&self, workers: usize) {
+        let running = self.running.lock().unwrap();
+        if self.workers.swap(workers, Ordering::SeqCst) != workers {
+            if let Some(ref suspend) = *running {
+                suspend.request();
+            }
+        }
+    }
+}
+
+/// Executes `simulation` with the runtime chosen by `switch`, suspending it and resuming the
+/// simulation with another runtime at each switch. The simulation starts from `runtime`, whose
+/// dt signal the next runtimes keep emitting.
+fn execute_simulation<P>(runtime: SequentialRuntime, simulation: P, switch: RuntimeSwitch) where P: Process {
+    runtime.register(simulation.map(|_| ()));
+    let mut suspended = runtime.suspend();
+    while !suspended.is_empty() {
+        // Locked until the handle of the new runtime is stored, so that no switch is missed.
+        let mut running = switch.running.lock().unwrap();
+        let workers = switch.workers();
+        if workers == 0 {
+            let mut runtime = SequentialRuntime::resume(suspended);
+            *running = Some(runtime.suspend_handle());
+            drop(running);
+            runtime.execute();
+            suspended = runtime.suspend();
+        } else {
+            let runtime = RuntimeBuilder::new(workers).resume(suspended).start();
+            *running = Some(runtime.suspend_handle());
+            drop(running);
+            runtime.execute();
+            suspended = runtime.suspend();
+        }
+    }
+}
 
+/// Prints the number of instants executed per second, every second, which makes the simulation
+/// a benchmark of the runtimes. Seconds are counted with the wall-clock durations of the instants,
+/// emitted on `dt`, and start again when the runtime is switched.
+fn speed_process(switch: RuntimeSwitch, dt: &ValueSignal<Duration, Duration>) -> impl Process {
+    let mut workers = switch.workers();
+    let mut instants = 0;
+    let mut elapsed = Duration::from_secs(0);
+    let tick = move|dt: Duration| {
+        if switch.workers() != workers {
+            workers = switch.workers();
+            instants = 0;
+            elapsed = Duration::from_secs(0);
+        }
+        instants += 1;
+        elapsed += dt;
+        if elapsed >= Duration::from_secs(1) {
+            let runtime = if workers == 0 { String::from("sequential runtime") } else { format!("parallel runtime, {} workers", workers) };
+            let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+            println!("{:.1} instants per second ({})", instants as f64 / seconds, runtime);
+            instants = 0;
+            elapsed = Duration::from_secs(0);
+        }
+    };
+    let continue_loop: LoopStatus<()> = LoopStatus::Continue;
+    dt.await().map(tick).then(value(continue_loop)).while_loop()
 }
 
-fn read_file(filename: String) -> (Vec<Type>, usize, usize) {
-    let mut file = File::open(filename).unwrap();
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).unwrap();
+#[cfg(feature = "schematic")]
+fn read_schematic_file(filename: &str) -> String {
+    self::schematic::read_schematic(File::open(filename).unwrap()).unwrap().join("\n")
+}
+
+#[cfg(not(feature = "schematic"))]
+fn read_schematic_file(filename: &str) -> String {
+    panic!("Loading {} requires the schematic feature", filename)
+}
+
+/// Reads a map, made of optional metadata lines setting the `SimulationRules`, followed by one
+/// line per row of tiles. Files ending with `.schem` are imported from Minecraft schematics.
+fn read_file(filename: String) -> (Vec<Type>, usize, usize, SimulationRules) {
+    let contents = if filename.ends_with(".schem") {
+        read_schematic_file(&filename)
+    } else {
+        let mut file = File::open(filename).unwrap();
+        let mut contents = String::new();
+        file.read_to_string(&mut contents).unwrap();
+        contents
+    };
 
     let mut blocks: Vec<Type> = Vec::new();
     let mut width = 0;
     let mut height = 0;
+    let mut rules = SimulationRules::default();
 
     let mut lines = contents.lines();
     while let Some(line) = lines.next() {
+        if height == 0 && SimulationRules::is_metadata(line) {
+            rules.set(line).unwrap();
+            continue;
+        }
         if height == 0 {
             width = line.len();
         } else {
@@ -347,13 +694,14 @@ fn read_file(filename: String) -> (Vec<Type>, usize, usize) {
                 '.' => Type::VOID,
                 '#' => Type::BLOCK,
                 '@' => Type::USER,
-                'r' => Type::REDSTONE(Power{r: 0x1, g: 0x0, b: 0x0}),
-                'g' => Type::REDSTONE(Power{r: 0x0, g: 0x1, b: 0x0}),
-                'b' => Type::REDSTONE(Power{r: 0x0, g: 0x0, b: 0x1}),
-                'y' => Type::REDSTONE(Power{r: 0x1, g: 0x1, b: 0x0}),
-                'p' => Type::REDSTONE(Power{r: 0x1, g: 0x0, b: 0x1}),
-                'c' => Type::REDSTONE(Power{r: 0x0, g: 0x1, b: 0x1}),
-                'w' => Type::REDSTONE(Power{r: 0x1, g: 0x1, b: 0x1}),
+                '*' => Type::NOTE,
+                'r' => Type::REDSTONE(Power::new(1, 0, 0)),
+                'g' => Type::REDSTONE(Power::new(0, 1, 0)),
+                'b' => Type::REDSTONE(Power::new(0, 0, 1)),
+                'y' => Type::REDSTONE(Power::new(1, 1, 0)),
+                'p' => Type::REDSTONE(Power::new(1, 0, 1)),
+                'c' => Type::REDSTONE(Power::new(0, 1, 1)),
+                'w' => Type::REDSTONE(Power::new(1, 1, 1)),
                 '^' => Type::INVERTER(Direction::NORTH),
                 'v' => Type::INVERTER(Direction::SOUTH),
                 '<' => Type::INVERTER(Direction::WEST),
@@ -363,7 +711,7 @@ fn read_file(filename: String) -> (Vec<Type>, usize, usize) {
         }
     }
 
-    (blocks, width, height)
+    (blocks, width, height, rules)
 }
 
 pub struct App {
@@ -374,92 +722,251 @@ pub struct App {
     height: usize,
     zoom: f64,
     tx: f64,
-    ty: f64
+    ty: f64,
+    cursor: [f64; 2],
+    /// Powers of the cells selected with the mouse, plotted at the bottom of the window.
+    history: Arc<Mutex<PowerHistory>>,
+    /// The cells, whose changes are drawn again, the map drawn when zoomed out, and the one of
+    /// the minimap, toggled with `M`.
+    cells: TileMap,
+    overview: TileMap,
+    minimap: TileMap,
+    show_minimap: bool,
+    /// The zoom, offsets and size of the window of the last frame, and whether it showed the
+    /// minimap and plots.
+    view: Option<(f64, f64, f64, f64, f64, bool, bool)>,
+    /// Number of frames still drawn whole since the view changed, one per buffer.
+    full_frames: usize,
+    /// The cells or tiles drawn again at the last frame, which the other buffer lacks.
+    changed: Vec<(usize, usize)>,
 }
 
 impl App {
-    fn render(&mut self, args: &RenderArgs) {
+    /// Starts or stops plotting the cell under the cursor.
+    fn toggle_plot(&mut self) {
+        let x = ((self.cursor[0] - self.tx) / self.zoom).floor();
+        let y = ((self.cursor[1] - self.ty) / self.zoom).floor();
+        if 0.0 <= x && x < self.width as f64 && 0.0 <= y && y < self.height as f64 {
+            self.history.lock().unwrap().toggle(x as usize + y as usize * self.width);
+        }
+    }
+
+    /// Draws one strip per plotted cell at the bottom of the window, with a curve per channel.
+    fn render_plots(&mut self, args: &RenderArgs) {
         use self::graphics::*;
 
-        const VOID_COLOR:       [f32; 4] = [0.0, 0.0, 0.0, 1.0];
-        const BLOCK_COLOR_OUT:  [f32; 4] = [0.9, 0.9, 0.9, 1.0];
-        const BLOCK_COLOR_IN:   [f32; 4] = [0.5, 0.5, 0.5, 1.0];
-        const BORDER_SIZE: f64 = 2.0;
-        const POWER_MAX:   u8  = 15;
+        const PLOT_HEIGHT: f64 = 60.0;
+        const PLOT_BACKGROUND: [f32; 4] = [0.1, 0.1, 0.1, 0.9];
+        const CHANNEL_COLORS: [[f32; 4]; 3] = [[1.0, 0.2, 0.2, 1.0], [0.2, 1.0, 0.2, 1.0], [0.3, 0.3, 1.0, 1.0]];
+        const CELL_MARKER: [f32; 4] = [1.0, 1.0, 1.0, 0.5];
+
+        let history = self.history.lock().unwrap();
+        let (width, height) = (args.width as f64, args.height as f64);
+        let step = width / history.length() as f64;
+        for (i, &(cell, ref powers)) in history.cells().iter().enumerate() {
+            let top = height - PLOT_HEIGHT * (i + 1) as f64;
+            let (cx, cy) = ((cell % self.width) as f64, (cell / self.width) as f64);
+            let marker = rectangle::square(cx * self.zoom + self.tx, cy * self.zoom + self.ty, self.zoom);
+            let powers: Vec<Power> = powers.iter().cloned().collect();
+            self.gl.draw(args.viewport(), |c, gl| {
+                rectangle(CELL_MARKER, marker, c.transform, gl);
+                rectangle(PLOT_BACKGROUND, [0.0, top, width, PLOT_HEIGHT - 2.0], c.transform, gl);
+                let level_y = |level: u8| top + (PLOT_HEIGHT - 4.0) * (1.0 - level as f64 / MAX_LEVEL as f64);
+                for t in 1..powers.len() {
+                    let (p, q) = (powers[t - 1], powers[t]);
+                    let (x0, x1) = (step * (t - 1) as f64, step * t as f64);
+                    for (channel, &(a, b)) in [(p.r(), q.r()), (p.g(), q.g()), (p.b(), q.b())].iter().enumerate() {
+                        line(CHANNEL_COLORS[channel], 1.0, [x0, level_y(a), x1, level_y(b)], c.transform, gl);
+                    }
+                }
+            });
+        }
+    }
 
-        self.gl.draw(args.viewport(), |_c, gl| {
-            clear(VOID_COLOR, gl);
-        });
+    /// Draws the cells of the map which are in the window, or its tiles when zoomed out so much
+    /// that cells are smaller than `MIN_CELL_SIZE` pixels, then the minimap and the plots.
+    ///
+    /// Only the cells or tiles whose power changed since the frame of the same buffer are drawn
+    /// again, unless the view changed or cells are plotted, the plots being drawn over the map.
+    fn render(&mut self, args: &RenderArgs) {
+        use self::graphics::*;
 
-        let pixel_size = self.zoom;
+        let plotted = !self.history.lock().unwrap().cells().is_empty();
+        let view = (self.zoom, self.tx, self.ty, args.width as f64, args.height as f64, self.show_minimap, plotted);
+        if self.view != Some(view) || plotted {
+            self.view = Some(view);
+            self.full_frames = WINDOW_BUFFERS;
+        }
+        let full = self.full_frames > 0;
+        if full {
+            self.full_frames -= 1;
+            self.gl.draw(args.viewport(), |_c, gl| {
+                clear(VOID_COLOR, gl);
+            });
+        }
 
-        let square = rectangle::square(0.0, 0.0, pixel_size);
-        let inner_square = rectangle::square(0.0, 0.0, pixel_size-2.0*BORDER_SIZE);
-        let rect = rectangle::rectangle_by_corners(0.0, 0.0, pixel_size, pixel_size/3.0);
+        self.minimap.update(&self.powers);
+        let changed = if self.zoom < MIN_CELL_SIZE {
+            let tile = (MIN_CELL_SIZE / self.zoom).ceil() as usize;
+            if tile != self.overview.tile() {
+                self.overview = self.overview.resized(tile);
+            }
+            let changed = self.overview.update(&self.powers);
+            let redrawn: Vec<(usize, usize)> = changed.iter().chain(self.changed.iter()).cloned().collect();
+            let (zoom, tx, ty) = (self.zoom * tile as f64, self.tx, self.ty);
+            let tiles = cells_to_draw(
+                visible_range(tx, zoom, args.width as f64, self.overview.columns()),
+                visible_range(ty, zoom, args.height as f64, self.overview.rows()),
+                if full { None } else { Some(&redrawn[..]) });
+            render_tiles(&mut self.gl, args, &self.overview, &tiles, !full, zoom, tx, ty);
+            changed
+        } else {
+            let changed = self.cells.update(&self.powers);
+            let redrawn: Vec<(usize, usize)> = changed.iter().chain(self.changed.iter()).cloned().collect();
+            let (blocks, powers, width, size, tx, ty) = (&self.blocks, &self.powers, self.width, self.zoom, self.tx, self.ty);
+            let cells = cells_to_draw(
+                visible_range(tx, size, args.width as f64, self.width),
+                visible_range(ty, size, args.height as f64, self.height),
+                if full { None } else { Some(&redrawn[..]) });
+            // A single draw for every cell, which lets the backend batch them.
+            self.gl.draw(args.viewport(), |c, gl| {
+                for &(ix, iy) in cells.iter() {
+                    let (x, y) = ((ix as f64)*size+tx, (iy as f64)*size+ty);
+                    if !full {
+                        rectangle(VOID_COLOR, rectangle::square(x, y, size), c.transform, gl);
+                    }
+                    render_cell(&c, gl, blocks[ix + iy * width], powers[ix + iy * width], x, y, size);
+                }
+            });
+            changed
+        };
+        self.changed = changed;
+        if self.show_minimap {
+            self.render_minimap(args);
+        }
+        self.render_plots(args);
+    }
 
-        for i in 0..(self.width*self.height) {
-            let (ix, iy) = (i%self.width, i/self.width);
-            let (x, y) = ((ix as f64)*pixel_size+self.tx, (iy as f64)*pixel_size+self.ty);
+    /// Draws the whole map in the top right corner of the window, with the outline of the part
+    /// shown in the window.
+    fn render_minimap(&mut self, args: &RenderArgs) {
+        use self::graphics::*;
 
-            fn color_composant(is_present: bool, power: u8) -> f32 {
-                if is_present { 0.5 + 0.5*((power as f32)/(POWER_MAX as f32)) } else { 0.0 }
-            }
-            fn get_color(r: u8, g: u8, b: u8, power: Power) -> [f32; 4] {
-                [
-                    color_composant(r > 0, power.r),
-                    color_composant(g > 0, power.g),
-                    color_composant(b > 0, power.b),
-                    1.0
-                ]
+        // Opaque, as the map under it is not always drawn again.
+        const MINIMAP_BACKGROUND: [f32; 4] = [0.1, 0.1, 0.1, 1.0];
+        const VIEW_OUTLINE: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+
+        let tiles = self.minimap.columns().max(self.minimap.rows()) as f64;
+        let zoom = MINIMAP_SIZE / tiles;
+        let (width, height) = (zoom * self.minimap.columns() as f64, zoom * self.minimap.rows() as f64);
+        let (left, top) = (args.width as f64 - width - MINIMAP_MARGIN, MINIMAP_MARGIN);
+        self.gl.draw(args.viewport(), |c, gl| {
+            rectangle(MINIMAP_BACKGROUND, [left, top, width, height], c.transform, gl);
+        });
+        let tiles = cells_to_draw(0..self.minimap.columns(), 0..self.minimap.rows(), None);
+        render_tiles(&mut self.gl, args, &self.minimap, &tiles, false, zoom, left, top);
+
+        // The window, in cells of the map then in pixels of the minimap.
+        let scale = zoom / (self.minimap.tile() as f64 * self.zoom);
+        let x0 = left + (-self.tx * scale).max(0.0).min(width);
+        let y0 = top + (-self.ty * scale).max(0.0).min(height);
+        let x1 = left + ((args.width as f64 - self.tx) * scale).max(0.0).min(width);
+        let y1 = top + ((args.height as f64 - self.ty) * scale).max(0.0).min(height);
+        self.gl.draw(args.viewport(), |c, gl| {
+            for &segment in [[x0, y0, x1, y0], [x1, y0, x1, y1], [x1, y1, x0, y1], [x0, y1, x0, y0]].iter() {
+                line(VIEW_OUTLINE, 0.5, segment, c.transform, gl);
             }
+        });
+    }
+}
 
-            match self.blocks[i] {
-                Type::VOID => {
-                    self.gl.draw(args.viewport(), |c, gl| {
-                        let transform = c.transform.trans(x, y);
-                        rectangle(VOID_COLOR, square, transform, gl);
-                    });
-                },
-                Type::BLOCK => {
-                    self.gl.draw(args.viewport(), |c, gl| {
-                        let transform = c.transform.trans(x, y);
-                        rectangle(BLOCK_COLOR_OUT, square, transform, gl);
-                        let transform = c.transform.trans(x+BORDER_SIZE, y+BORDER_SIZE);
-                        rectangle(BLOCK_COLOR_IN, inner_square, transform, gl);
-                    });
-                },
-                Type::REDSTONE(Power{r, g, b}) => {
-                    let color = get_color(r, g, b, self.powers[i]);
-                    self.gl.draw(args.viewport(), |c, gl| {
-                        let transform = c.transform.trans(x, y);
-                        rectangle(color, square, transform, gl);
-                    });
-                },
-                Type::INVERTER(ref dir) => {
-                    let color = get_color(1, 1, 1, self.powers[i]);
-                    self.gl.draw(args.viewport(), |c, gl| {
-                        let pi = std::f64::consts::PI;
-                        let angle = pi/2.0 * match *dir {
-                            Direction::SOUTH => 0.0,
-                            Direction::NORTH => 2.0,
-                            Direction::EAST => 3.0,
-                            Direction::WEST => 1.0
-                        };
-                        let transform = c.transform.trans(x, y).trans(pixel_size/2.0, pixel_size/2.0).rot_rad(angle).trans(-pixel_size/2.0, -pixel_size/2.0);
-                        let transform2 = transform.rot_rad(pi/2.0).trans(0.0, -pixel_size*(0.5+1.0/6.0));
-                        rectangle(color, rect, transform, gl);
-                        rectangle(color, rect, transform2, gl);
-                    });
-                },
-                Type::USER => {
-                    self.gl.draw(args.viewport(), |c, gl| {
-                        let transform = c.transform.trans(x, y);
-                        rectangle(BLOCK_COLOR_IN, square, transform, gl);
-                        let transform = c.transform.trans(x+BORDER_SIZE, y+BORDER_SIZE);
-                        rectangle(BLOCK_COLOR_OUT, inner_square, transform, gl);
-                    });
-                }
+/// Draws the `drawn` tiles of `tiles`, each tile taking `zoom` pixels and the first one being at
+/// `(tx, ty)`: powered tiles take the color of their power, and other tiles are drawn like blocks
+/// unless they are empty. Tiles drawn `over` the previous frame are cleared first.
+fn render_tiles(gl: &mut GlGraphics, args: &RenderArgs, tiles: &TileMap, drawn: &[(usize, usize)], over: bool, zoom: f64, tx: f64, ty: f64) {
+    use self::graphics::*;
+
+    let square = rectangle::square(0.0, 0.0, zoom);
+    gl.draw(args.viewport(), |c, gl| {
+        for &(column, row) in drawn {
+            let transform = c.transform.trans(tx + column as f64 * zoom, ty + row as f64 * zoom);
+            if over {
+                rectangle(VOID_COLOR, square, transform, gl);
             }
+            let power = tiles.power(column, row);
+            let color = if !power.is_zero() {
+                get_color(1, 1, 1, power)
+            } else if tiles.is_occupied(column, row) {
+                BLOCK_COLOR_IN
+            } else {
+                continue;
+            };
+            rectangle(color, square, transform, gl);
+        }
+    });
+}
+
+/// Draws a cell of type `block` with the power `power`, in the square of `size` pixels at `(x, y)`.
+fn render_cell(c: &self::graphics::Context, gl: &mut GlGraphics, block: Type, power: Power, x: f64, y: f64, size: f64) {
+    use self::graphics::*;
+
+    const BORDER_SIZE: f64 = 2.0;
+
+    let square = rectangle::square(0.0, 0.0, size);
+    let inner_square = rectangle::square(0.0, 0.0, size-2.0*BORDER_SIZE);
+    let rect = rectangle::rectangle_by_corners(0.0, 0.0, size, size/3.0);
+    match block {
+        Type::VOID => (),
+        Type::BLOCK => {
+            let transform = c.transform.trans(x, y);
+            rectangle(BLOCK_COLOR_OUT, square, transform, gl);
+            let transform = c.transform.trans(x+BORDER_SIZE, y+BORDER_SIZE);
+            rectangle(BLOCK_COLOR_IN, inner_square, transform, gl);
+        },
+        Type::REDSTONE(channels) => {
+            let color = get_color(channels.r(), channels.g(), channels.b(), power);
+            let transform = c.transform.trans(x, y);
+            rectangle(color, square, transform, gl);
+        },
+        Type::INVERTER(dir) => {
+            let color = get_color(1, 1, 1, power);
+            let pi = std::f64::consts::PI;
+            let angle = pi/2.0 * match dir {
+                Direction::SOUTH => 0.0,
+                Direction::NORTH => 2.0,
+                Direction::EAST => 3.0,
+                Direction::WEST => 1.0
+            };
+            let transform = c.transform.trans(x, y).trans(size/2.0, size/2.0).rot_rad(angle).trans(-size/2.0, -size/2.0);
+            let transform2 = transform.rot_rad(pi/2.0).trans(0.0, -size*(0.5+1.0/6.0));
+            rectangle(color, rect, transform, gl);
+            rectangle(color, rect, transform2, gl);
+        },
+        Type::USER => {
+            let transform = c.transform.trans(x, y);
+            rectangle(BLOCK_COLOR_IN, square, transform, gl);
+            let transform = c.transform.trans(x+BORDER_SIZE, y+BORDER_SIZE);
+            rectangle(BLOCK_COLOR_OUT, inner_square, transform, gl);
+        },
+        Type::NOTE => {
+            let color = get_color(1, 1, 1, power);
+            let transform = c.transform.trans(x, y);
+            rectangle(BLOCK_COLOR_IN, square, transform, gl);
+            let transform = c.transform.trans(x+BORDER_SIZE, y+BORDER_SIZE);
+            ellipse(color, inner_square, transform, gl);
         }
     }
 }
+
+fn color_composant(is_present: bool, power: u8) -> f32 {
+    if is_present { 0.5 + 0.5*((power as f32)/(MAX_LEVEL as f32)) } else { 0.0 }
+}
+
+fn get_color(r: u8, g: u8, b: u8, power: Power) -> [f32; 4] {
+    [
+        color_composant(r > 0, power.r()),
+        color_composant(g > 0, power.g()),
+        color_composant(b > 0, power.b()),
+        1.0
+    ]
+}
@@ -0,0 +1,49 @@
+use std::collections::VecDeque;
+
+use super::power::*;
+
+//  ____  _       _
+// |  _ \| | ___ | |_
+// | |_) | |/ _ \| __|
+// |  __/| | (_) | |_
+// |_|   |_|\___/ \__|
+
+/// The powers of the cells selected for plotting over the last instants.
+pub struct PowerHistory {
+    length: usize,
+    cells: Vec<(usize, VecDeque<Power>)>,
+}
+
+impl PowerHistory {
+    /// Keeps the powers of the last `length` instants.
+    pub fn new(length: usize) -> Self {
+        PowerHistory { length, cells: Vec::new() }
+    }
+
+    /// Starts plotting the cell at index `cell`, or stops if it was plotted.
+    pub fn toggle(&mut self, cell: usize) {
+        match self.cells.iter().position(|&(c, _)| c == cell) {
+            Some(i) => { self.cells.remove(i); },
+            None => self.cells.push((cell, VecDeque::with_capacity(self.length))),
+        }
+    }
+
+    /// Records the powers of an instant, indexed by cell.
+    pub fn record(&mut self, powers: &[Power]) {
+        for &mut (cell, ref mut history) in self.cells.iter_mut() {
+            if history.len() == self.length {
+                history.pop_front();
+            }
+            history.push_back(powers[cell]);
+        }
+    }
+
+    /// The plotted cells in selection order, with their powers from the oldest to the latest.
+    pub fn cells(&self) -> &[(usize, VecDeque<Power>)] {
+        &self.cells
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+}
@@ -0,0 +1,113 @@
+use std::cmp::{max, min};
+use std::ops::{Add, Sub, Mul};
+
+//  ____
+// |  _ \ _____      _____ _ __
+// | |_) / _ \ \ /\ / / _ \ '__|
+// |  __/ (_) \ V  V /  __/ |
+// |_|   \___/ \_/\_/ \___|_|
+
+/// The highest level of a channel.
+pub const MAX_LEVEL: u8 = 0xF;
+
+/// The power of a tile, one level in `0..=MAX_LEVEL` per color channel.
+///
+/// Every operation works channel by channel and saturates to this range instead of overflowing,
+/// so powers can be combined freely by wires, torches and comparators.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Power {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+pub const ZERO_POWER: Power = Power{r: 0x0, g: 0x0, b: 0x0};
+pub const ATOMIC_POWER: Power = Power{r: 0x1, g: 0x1, b: 0x1};
+pub const MAX_POWER: Power = Power{r: MAX_LEVEL, g: MAX_LEVEL, b: MAX_LEVEL};
+
+fn clamp(level: u8) -> u8 {
+    min(level, MAX_LEVEL)
+}
+
+impl Power {
+    /// Levels above `MAX_LEVEL` are clamped.
+    pub fn new(r: u8, g: u8, b: u8) -> Power {
+        Power{r: clamp(r), g: clamp(g), b: clamp(b)}
+    }
+
+    /// The level of the red channel.
+    pub fn r(self) -> u8 {
+        self.r
+    }
+
+    /// The level of the green channel.
+    pub fn g(self) -> u8 {
+        self.g
+    }
+
+    /// The level of the blue channel.
+    pub fn b(self) -> u8 {
+        self.b
+    }
+
+    pub fn max(self, other: Power) -> Power {
+        Power{
+            r: max(self.r, other.r),
+            g: max(self.g, other.g),
+            b: max(self.b, other.b)}
+    }
+
+    pub fn min(self, other: Power) -> Power {
+        Power{
+            r: min(self.r, other.r),
+            g: min(self.g, other.g),
+            b: min(self.b, other.b)}
+    }
+
+    /// Lowers every channel by `amount`, down to zero.
+    pub fn decay(self, amount: u8) -> Power {
+        Power{
+            r: self.r.saturating_sub(amount),
+            g: self.g.saturating_sub(amount),
+            b: self.b.saturating_sub(amount)}
+    }
+
+    pub fn is_zero(self) -> bool {
+        self == ZERO_POWER
+    }
+}
+
+impl Add for Power {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Power{
+            r: clamp(self.r.saturating_add(other.r)),
+            g: clamp(self.g.saturating_add(other.g)),
+            b: clamp(self.b.saturating_add(other.b))}
+    }
+}
+
+impl Sub for Power {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Power{
+            r: self.r.saturating_sub(other.r),
+            g: self.g.saturating_sub(other.g),
+            b: self.b.saturating_sub(other.b)}
+    }
+}
+
+/// Multiplying by a power whose channels are 0 or 1, like the filter of a wire, keeps only the
+/// channels set to 1.
+impl Mul for Power {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Power{
+            r: clamp(self.r.saturating_mul(other.r)),
+            g: clamp(self.g.saturating_mul(other.g)),
+            b: clamp(self.b.saturating_mul(other.b))}
+    }
+}
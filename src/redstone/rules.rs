@@ -0,0 +1,70 @@
+//  ____        _
+// |  _ \ _   _| | ___  ___
+// | |_) | | | | |/ _ \/ __|
+// |  _ <| |_| | |  __/\__ \
+// |_| \_\\__,_|_|\___||___/
+
+/// The physics of a simulation, read from the metadata lines at the top of the map, such as
+/// `decay = 2`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SimulationRules {
+    /// Power lost by each wire a signal goes through.
+    pub decay: u8,
+    /// Whether the edges of the map touch the opposite ones, making it a torus.
+    pub wrap: bool,
+    /// Whether wires also power the wires in their diagonals.
+    pub diagonal: bool,
+}
+
+impl Default for SimulationRules {
+    fn default() -> Self {
+        SimulationRules { decay: 1, wrap: true, diagonal: false }
+    }
+}
+
+impl SimulationRules {
+    /// Whether `line` is a metadata line rather than a row of the map.
+    pub fn is_metadata(line: &str) -> bool {
+        line.contains('=')
+    }
+
+    /// Applies a `key = value` metadata line.
+    pub fn set(&mut self, line: &str) -> Result<(), String> {
+        let mut parts = line.splitn(2, '=').map(|part| part.trim());
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key, value),
+            _ => return Err(format!("Not a metadata line: {}", line)),
+        };
+        let invalid = || format!("Invalid value for {}: {}", key, value);
+        match key {
+            "decay" => self.decay = value.parse().map_err(|_| invalid())?,
+            "wrap" => self.wrap = value.parse().map_err(|_| invalid())?,
+            "diagonal" => self.diagonal = value.parse().map_err(|_| invalid())?,
+            _ => return Err(format!("Unknown rule: {}", key)),
+        }
+        Ok(())
+    }
+
+    /// The position at `(dx, dy)` from `(x, y)` in a map of `(width, height)` tiles, if any.
+    pub fn offset(&self, (x, y): (usize, usize), (dx, dy): (isize, isize), (width, height): (usize, usize))
+        -> Option<(usize, usize)> {
+        let (x, y) = (x as isize + dx, y as isize + dy);
+        let (width, height) = (width as isize, height as isize);
+        if self.wrap {
+            Some((((x % width + width) % width) as usize, ((y % height + height) % height) as usize))
+        } else if 0 <= x && x < width && 0 <= y && y < height {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+
+    /// The positions a wire at `pos` powers, besides its own.
+    pub fn neighbours(&self, pos: (usize, usize), size: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut offsets = vec![(1, 0), (-1, 0), (0, 1), (0, -1)];
+        if self.diagonal {
+            offsets.extend(vec![(1, 1), (1, -1), (-1, 1), (-1, -1)]);
+        }
+        offsets.into_iter().filter_map(|offset| self.offset(pos, offset, size)).collect()
+    }
+}
@@ -0,0 +1,235 @@
+extern crate flate2;
+
+use std::collections::HashMap;
+use std::io::prelude::*;
+
+use self::flate2::read::GzDecoder;
+
+//  ____       _                          _   _
+// / ___|  ___| |__   ___ _ __ ___   __ _| |_(_) ___
+// \___ \ / __| '_ \ / _ \ '_ ` _ \ / _` | __| |/ __|
+//  ___) | (__| | | |  __/ | | | | | (_| | |_| | (__
+// |____/ \___|_| |_|\___|_| |_| |_|\__,_|\__|_|\___|
+
+/// A value of a Named Binary Tag file, the format of Minecraft schematics.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Nbt {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Nbt>),
+    Compound(HashMap<String, Nbt>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Nbt {
+    fn get(&self, name: &str) -> Option<&Nbt> {
+        match *self {
+            Nbt::Compound(ref entries) => entries.get(name),
+            _ => None,
+        }
+    }
+
+    fn as_int(&self) -> Option<i64> {
+        match *self {
+            Nbt::Byte(v) => Some(v as i64),
+            Nbt::Short(v) => Some(v as i64),
+            Nbt::Int(v) => Some(v as i64),
+            Nbt::Long(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+struct NbtReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> NbtReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < n {
+            return Err(String::from("Truncated NBT data"));
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn read_u64(&mut self, n: usize) -> Result<u64, String> {
+        Ok(self.take(n)?.iter().fold(0, |v, &b| (v << 8) | b as u64))
+    }
+
+    fn read_len(&mut self) -> Result<usize, String> {
+        let len = self.read_u64(4)? as i32;
+        if len < 0 { Err(String::from("Negative NBT length")) } else { Ok(len as usize) }
+    }
+
+    fn read_string(&mut self) -> Result<String, String> {
+        let len = self.read_u64(2)? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|e| e.to_string())
+    }
+
+    fn read_payload(&mut self, tag: u8) -> Result<Nbt, String> {
+        Ok(match tag {
+            1 => Nbt::Byte(self.read_u64(1)? as i8),
+            2 => Nbt::Short(self.read_u64(2)? as i16),
+            3 => Nbt::Int(self.read_u64(4)? as i32),
+            4 => Nbt::Long(self.read_u64(8)? as i64),
+            5 => Nbt::Float(f32::from_bits(self.read_u64(4)? as u32)),
+            6 => Nbt::Double(f64::from_bits(self.read_u64(8)?)),
+            7 => {
+                let len = self.read_len()?;
+                Nbt::ByteArray(self.take(len)?.iter().map(|&b| b as i8).collect())
+            },
+            8 => Nbt::String(self.read_string()?),
+            9 => {
+                let tag = self.read_u64(1)? as u8;
+                let len = self.read_len()?;
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(self.read_payload(tag)?);
+                }
+                Nbt::List(items)
+            },
+            10 => {
+                let mut entries = HashMap::new();
+                loop {
+                    let tag = self.read_u64(1)? as u8;
+                    if tag == 0 {
+                        break;
+                    }
+                    let name = self.read_string()?;
+                    entries.insert(name, self.read_payload(tag)?);
+                }
+                Nbt::Compound(entries)
+            },
+            11 => {
+                let len = self.read_len()?;
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(self.read_u64(4)? as i32);
+                }
+                Nbt::IntArray(items)
+            },
+            12 => {
+                let len = self.read_len()?;
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(self.read_u64(8)? as i64);
+                }
+                Nbt::LongArray(items)
+            },
+            _ => return Err(format!("Unknown NBT tag {}", tag)),
+        })
+    }
+}
+
+/// Reads uncompressed NBT data, made of a single named compound.
+pub fn read_nbt(bytes: &[u8]) -> Result<Nbt, String> {
+    let mut reader = NbtReader { bytes };
+    if reader.read_u64(1)? != 10 {
+        return Err(String::from("The root of NBT data must be a compound"));
+    }
+    reader.read_string()?;
+    reader.read_payload(10)
+}
+
+/// The map tile standing for a block of a schematic, given its full state such as
+/// `minecraft:redstone_wall_torch[facing=north,lit=true]`.
+///
+/// Redstone dust becomes a wire carrying every channel, wall torches become inverters, and
+/// levers, buttons and pressure plates become user blocks. Standing torches cannot be powered in
+/// the plane of the map, they become blocks as every other solid block.
+///
+/// Note blocks stay note blocks.
+fn tile(block: &str) -> char {
+    let (name, properties) = match block.find('[') {
+        Some(i) => (&block[..i], &block[i..]),
+        None => (block, ""),
+    };
+    let name = name.trim_left_matches("minecraft:");
+    match name {
+        "air" | "cave_air" | "void_air" => '.',
+        "redstone_wire" => 'w',
+        "redstone_wall_torch" => {
+            if properties.contains("facing=north") { '^' }
+            else if properties.contains("facing=south") { 'v' }
+            else if properties.contains("facing=east") { '>' }
+            else { '<' }
+        },
+        "lever" => '@',
+        "note_block" => '*',
+        _ if name.ends_with("_button") || name.ends_with("_pressure_plate") => '@',
+        _ => '#',
+    }
+}
+
+/// Converts a Sponge schematic (`.schem`, versions 2 and 3) to the rows of a map, seen from above:
+/// each cell of the map is the topmost non-air block of its column.
+pub fn schematic_to_map(schematic: &Nbt) -> Result<Vec<String>, String> {
+    // Version 3 nests the schematic in a `Schematic` compound, and the blocks in `Blocks`.
+    let schematic = schematic.get("Schematic").unwrap_or(schematic);
+    let dimension = |name| schematic.get(name).and_then(Nbt::as_int).map(|v| v as u16 as usize)
+        .ok_or_else(|| format!("Missing {}", name));
+    let (width, height, length) = (dimension("Width")?, dimension("Height")?, dimension("Length")?);
+    let blocks = schematic.get("Blocks").unwrap_or(schematic);
+    let palette = match blocks.get("Palette") {
+        Some(&Nbt::Compound(ref palette)) => palette,
+        _ => return Err(String::from("Missing Palette")),
+    };
+    let mut tiles = HashMap::new();
+    for (block, id) in palette.iter() {
+        tiles.insert(id.as_int().ok_or("Invalid palette id")?, tile(block));
+    }
+    let data = match blocks.get("BlockData").or(blocks.get("Data")) {
+        Some(&Nbt::ByteArray(ref data)) => data,
+        _ => return Err(String::from("Missing BlockData")),
+    };
+
+    // Palette ids are stored as varints, in y, z, x order.
+    let mut ids = Vec::with_capacity(width * height * length);
+    let (mut id, mut shift) = (0i64, 0);
+    for &byte in data.iter() {
+        id |= ((byte as u8 & 0x7F) as i64) << shift;
+        shift += 7;
+        if byte as u8 & 0x80 == 0 {
+            ids.push(id);
+            id = 0;
+            shift = 0;
+        }
+    }
+    if ids.len() != width * height * length {
+        return Err(format!("Expected {} blocks, found {}", width * height * length, ids.len()));
+    }
+
+    let mut rows = Vec::new();
+    for z in 0..length {
+        let mut row = String::new();
+        for x in 0..width {
+            let column = (0..height).rev().map(|y| ids[x + z * width + y * width * length]);
+            let mut tile = '.';
+            for id in column {
+                tile = *tiles.get(&id).ok_or_else(|| format!("Unknown palette id {}", id))?;
+                if tile != '.' {
+                    break;
+                }
+            }
+            row.push(tile);
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Reads a gzipped Sponge schematic, and converts it to the rows of a map.
+pub fn read_schematic<R>(reader: R) -> Result<Vec<String>, String> where R: Read {
+    let mut bytes = Vec::new();
+    GzDecoder::new(reader).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    schematic_to_map(&read_nbt(&bytes)?)
+}
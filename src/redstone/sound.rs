@@ -0,0 +1,50 @@
+#[cfg(feature = "sound")]
+extern crate rodio;
+
+use super::power::*;
+
+//  ____                        _
+// / ___|  ___  _   _ _ __   __| |
+// \___ \ / _ \| | | | '_ \ / _` |
+//  ___) | (_) | |_| | | | | (_| |
+// |____/ \___/ \__,_|_| |_|\__,_|
+
+/// The state of a note block, which plays a note each time it becomes powered.
+#[derive(Default)]
+pub struct NoteBlock {
+    powered: bool,
+}
+
+impl NoteBlock {
+    /// Updates the block with the power of its cell at an instant. Returns the frequency of the
+    /// note to play if the block just became powered, higher levels playing higher notes, one
+    /// semitone apart from 220Hz at level 1.
+    pub fn update(&mut self, power: Power) -> Option<f32> {
+        let level = power.r().max(power.g()).max(power.b());
+        let was_powered = self.powered;
+        self.powered = level > 0;
+        if self.powered && !was_powered {
+            Some(220.0 * 2f32.powf((level - 1) as f32 / 12.0))
+        } else {
+            None
+        }
+    }
+}
+
+/// Plays a short note at `frequency` without blocking.
+#[cfg(feature = "sound")]
+pub fn beep(frequency: f32) {
+    use self::rodio::Source;
+    use std::time::Duration;
+
+    if let Some(device) = rodio::default_output_device() {
+        let note = rodio::source::SineWave::new(frequency as u32)
+            .take_duration(Duration::from_millis(150))
+            .amplify(0.2);
+        rodio::play_raw(&device, note.convert_samples());
+    }
+}
+
+/// Plays nothing: notes are only played with the `sound` feature.
+#[cfg(not(feature = "sound"))]
+pub fn beep(_frequency: f32) {}
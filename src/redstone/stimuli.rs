@@ -0,0 +1,81 @@
+use std::collections::VecDeque;
+
+use super::power::*;
+
+//  ____  _   _                 _ _
+// / ___|| |_(_)_ __ ___  _   _| (_)
+// \___ \| __| | '_ ` _ \| | | | | |
+//  ___) | |_| | | | | | | |_| | | |
+// |____/ \__|_|_| |_| |_|\__,_|_|_|
+
+/// A power injected in a cell at an instant of the simulation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stimulus {
+    pub instant: usize,
+    pub cell: (usize, usize),
+    pub power: Power,
+}
+
+impl Stimulus {
+    /// The line describing the stimulus in a stimuli file, which is also the format of the
+    /// recordings so that they can be injected back.
+    pub fn to_line(&self) -> String {
+        format!("{},{},{},{},{},{}", self.instant, self.cell.0, self.cell.1, self.power.r(), self.power.g(), self.power.b())
+    }
+}
+
+/// Parses a stimuli file, with one `instant,x,y,r,g,b` line per stimulus. Empty lines and lines
+/// starting with `#` are ignored.
+pub fn parse_stimuli(contents: &str) -> Result<Vec<Stimulus>, String> {
+    let mut stimuli = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Result<Vec<usize>, _> = line.split(',').map(|field| field.trim().parse()).collect();
+        match fields {
+            Ok(ref fields) if fields.len() == 6 => {
+                let level = |i: usize| clamp_level(fields[i]);
+                stimuli.push(Stimulus {
+                    instant: fields[0],
+                    cell: (fields[1], fields[2]),
+                    power: Power::new(level(3), level(4), level(5)),
+                });
+            },
+            _ => return Err(format!("Invalid stimulus on line {}: {}", number + 1, line)),
+        }
+    }
+    Ok(stimuli)
+}
+
+fn clamp_level(level: usize) -> u8 {
+    if level > MAX_LEVEL as usize { MAX_LEVEL } else { level as u8 }
+}
+
+/// The stimuli of a run, handed out instant by instant.
+pub struct StimuliSchedule {
+    instant: usize,
+    stimuli: VecDeque<Stimulus>,
+}
+
+impl StimuliSchedule {
+    pub fn new(mut stimuli: Vec<Stimulus>) -> Self {
+        stimuli.sort_by_key(|stimulus| stimulus.instant);
+        StimuliSchedule { instant: 0, stimuli: stimuli.into_iter().collect() }
+    }
+
+    /// The stimuli of the current instant, then moves on to the next one.
+    pub fn next_instant(&mut self) -> Vec<Stimulus> {
+        let mut due = Vec::new();
+        while self.stimuli.front().map_or(false, |stimulus| stimulus.instant <= self.instant) {
+            due.extend(self.stimuli.pop_front());
+        }
+        self.instant += 1;
+        due
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.stimuli.is_empty()
+    }
+}
@@ -0,0 +1,302 @@
+use super::power::*;
+use super::plot::*;
+use super::frames::*;
+use super::rules::*;
+use super::sound::*;
+use super::stimuli::*;
+use super::truth::*;
+use super::view::*;
+
+use std::time::Duration;
+
+//  _____         _
+// |_   _|__  ___| |_ ___
+//   | |/ _ \/ __| __/ __|
+//   | |  __/\__ \ |_\__ \
+//   |_|\___||___/\__|___/
+
+
+#[test]
+fn test_power_new_clamps() {
+    let power = Power::new(3, 15, 200);
+    assert_eq!((power.r(), power.g(), power.b()), (3, MAX_LEVEL, MAX_LEVEL));
+}
+
+#[test]
+fn test_power_add_saturates() {
+    assert_eq!(Power::new(1, 2, 3) + Power::new(4, 5, 6), Power::new(5, 7, 9));
+    assert_eq!(MAX_POWER + ATOMIC_POWER, MAX_POWER);
+    assert_eq!(Power::new(MAX_LEVEL, 0, 0) + Power::new(MAX_LEVEL, 0, 0), Power::new(MAX_LEVEL, 0, 0));
+}
+
+#[test]
+fn test_power_sub_saturates() {
+    assert_eq!(Power::new(5, 7, 9) - Power::new(4, 5, 6), Power::new(1, 2, 3));
+    assert_eq!(ZERO_POWER - ATOMIC_POWER, ZERO_POWER);
+    assert_eq!(Power::new(0, 3, 1) - Power::new(1, 1, 1), Power::new(0, 2, 0));
+}
+
+#[test]
+fn test_power_mul_filters_channels() {
+    let red = Power::new(1, 0, 0);
+    assert_eq!(MAX_POWER * red, Power::new(MAX_LEVEL, 0, 0));
+    assert_eq!(Power::new(4, 4, 4) * Power::new(4, 4, 4), MAX_POWER);
+    assert_eq!(MAX_POWER * ZERO_POWER, ZERO_POWER);
+}
+
+#[test]
+fn test_power_max_min_per_channel() {
+    let p = Power::new(1, 9, 5);
+    let q = Power::new(8, 2, 5);
+    assert_eq!(p.max(q), Power::new(8, 9, 5));
+    assert_eq!(p.min(q), Power::new(1, 2, 5));
+    assert_eq!(p.max(ZERO_POWER), p);
+    assert_eq!(p.min(MAX_POWER), p);
+}
+
+#[test]
+fn test_power_decay() {
+    assert_eq!(Power::new(3, 1, 0).decay(1), Power::new(2, 0, 0));
+    assert_eq!(MAX_POWER.decay(MAX_LEVEL), ZERO_POWER);
+    assert_eq!(ATOMIC_POWER.decay(255), ZERO_POWER);
+    assert!(ZERO_POWER.decay(1).is_zero());
+}
+
+#[test]
+fn test_power_exhaustive_ranges() {
+    for a in 0..MAX_LEVEL + 1 {
+        for b in 0..MAX_LEVEL + 1 {
+            let (p, q) = (Power::new(a, b, a), Power::new(b, a, b));
+            for &c in [(p + q).r(), (p - q).r(), (p * q).r(), p.max(q).r(), p.min(q).r(), p.decay(b).r()].iter() {
+                assert!(c <= MAX_LEVEL);
+            }
+            assert_eq!((p + q).r(), ::std::cmp::min(a + b, MAX_LEVEL));
+            assert_eq!((p - q).r(), a.saturating_sub(b));
+        }
+    }
+}
+
+#[test]
+fn test_rules_metadata() {
+    let mut rules = SimulationRules::default();
+    assert!(SimulationRules::is_metadata("decay = 2"));
+    assert!(!SimulationRules::is_metadata("..r#>"));
+    rules.set("decay = 2").unwrap();
+    rules.set("wrap=false").unwrap();
+    rules.set(" diagonal = true ").unwrap();
+    assert_eq!(rules, SimulationRules { decay: 2, wrap: false, diagonal: true });
+    assert!(rules.set("decay = -1").is_err());
+    assert!(rules.set("gravity = 1").is_err());
+}
+
+#[test]
+fn test_rules_neighbours() {
+    let torus = SimulationRules::default();
+    let mut neighbours = torus.neighbours((0, 0), (3, 2));
+    neighbours.sort();
+    assert_eq!(neighbours, vec![(0, 1), (0, 1), (1, 0), (2, 0)]);
+
+    let flat = SimulationRules { wrap: false, diagonal: true, .. SimulationRules::default() };
+    let mut neighbours = flat.neighbours((0, 0), (3, 2));
+    neighbours.sort();
+    assert_eq!(neighbours, vec![(0, 1), (1, 0), (1, 1)]);
+    assert_eq!(flat.neighbours((1, 1), (3, 3)).len(), 8);
+}
+
+#[test]
+fn test_power_history() {
+    let mut history = PowerHistory::new(2);
+    history.toggle(1);
+    history.record(&[MAX_POWER, ATOMIC_POWER]);
+    history.toggle(0);
+    history.record(&[MAX_POWER, ZERO_POWER]);
+    history.record(&[ZERO_POWER, MAX_POWER]);
+    let cells: Vec<(usize, Vec<Power>)> = history.cells().iter()
+        .map(|&(cell, ref powers)| (cell, powers.iter().cloned().collect()))
+        .collect();
+    assert_eq!(cells, vec![(1, vec![ZERO_POWER, MAX_POWER]), (0, vec![MAX_POWER, ZERO_POWER])]);
+    history.toggle(1);
+    assert_eq!(history.cells().len(), 1);
+}
+
+#[test]
+fn test_parse_stimuli() {
+    let stimuli = parse_stimuli("# instant,x,y,r,g,b\n3, 1, 2, 15, 0, 0\n\n0,0,0,1,1,99\n").unwrap();
+    assert_eq!(stimuli, vec![
+        Stimulus { instant: 3, cell: (1, 2), power: Power::new(MAX_LEVEL, 0, 0) },
+        Stimulus { instant: 0, cell: (0, 0), power: Power::new(1, 1, MAX_LEVEL) },
+    ]);
+    assert_eq!(parse_stimuli(&stimuli[0].to_line()).unwrap(), vec![stimuli[0]]);
+    assert!(parse_stimuli("1,2,3").is_err());
+    assert!(parse_stimuli("1,2,3,4,5,-6").is_err());
+}
+
+#[test]
+fn test_stimuli_schedule() {
+    let at = |instant| Stimulus { instant, cell: (0, 0), power: MAX_POWER };
+    let mut schedule = StimuliSchedule::new(vec![at(2), at(0), at(2)]);
+    assert_eq!(schedule.next_instant(), vec![at(0)]);
+    assert_eq!(schedule.next_instant(), vec![]);
+    assert!(!schedule.is_done());
+    assert_eq!(schedule.next_instant(), vec![at(2), at(2)]);
+    assert!(schedule.is_done());
+}
+
+#[cfg(feature = "schematic")]
+mod schematic_tests {
+    use super::super::schematic::*;
+
+    fn named(tag: u8, name: &str, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![tag, 0, name.len() as u8];
+        bytes.extend(name.bytes());
+        bytes.extend(payload);
+        bytes
+    }
+
+    fn short(v: i16) -> Vec<u8> {
+        vec![(v >> 8) as u8, v as u8]
+    }
+
+    fn int(v: i32) -> Vec<u8> {
+        vec![(v >> 24) as u8, (v >> 16) as u8, (v >> 8) as u8, v as u8]
+    }
+
+    /// A 3x2x1 schematic: dust on a block, a lever, and a wall torch above nothing.
+    fn schematic() -> Vec<u8> {
+        let mut palette = Vec::new();
+        for &(block, id) in [("minecraft:air", 0), ("minecraft:stone", 1), ("minecraft:redstone_wire[power=0]", 2),
+                             ("minecraft:lever[face=floor]", 3), ("minecraft:redstone_wall_torch[facing=east,lit=true]", 4)].iter() {
+            palette.extend(named(3, block, &int(id)));
+        }
+        palette.push(0);
+        // y = 0: stone, air, air; y = 1: dust, lever, torch.
+        let data = [1, 0, 0, 2, 3, 4];
+        let mut block_data = int(data.len() as i32);
+        block_data.extend(data.iter());
+
+        let mut root = Vec::new();
+        root.extend(named(2, "Width", &short(3)));
+        root.extend(named(2, "Height", &short(2)));
+        root.extend(named(2, "Length", &short(1)));
+        root.extend(named(10, "Palette", &palette));
+        root.extend(named(7, "BlockData", &block_data));
+        root.push(0);
+        named(10, "Schematic", &root)
+    }
+
+    #[test]
+    fn test_read_nbt() {
+        match read_nbt(&schematic()).unwrap() {
+            Nbt::Compound(entries) => assert_eq!(entries.get("Width"), Some(&Nbt::Short(3))),
+            nbt => panic!("Unexpected root {:?}", nbt),
+        }
+        assert!(read_nbt(&schematic()[..20]).is_err());
+    }
+
+    #[test]
+    fn test_schematic_to_map() {
+        let map = schematic_to_map(&read_nbt(&schematic()).unwrap()).unwrap();
+        assert_eq!(map, vec![String::from("w@>")]);
+    }
+}
+
+#[test]
+fn test_note_block() {
+    let mut note = NoteBlock::default();
+    assert_eq!(note.update(ZERO_POWER), None);
+    assert_eq!(note.update(ATOMIC_POWER), Some(220.0));
+    assert_eq!(note.update(MAX_POWER), None);
+    assert_eq!(note.update(ZERO_POWER), None);
+    assert_eq!(note.update(Power::new(0, 13, 2)), Some(440.0));
+}
+
+#[test]
+fn test_input_combinations() {
+    assert_eq!(input_combinations(0), vec![Vec::<bool>::new()]);
+    assert_eq!(input_combinations(2), vec![
+        vec![false, false], vec![false, true], vec![true, false], vec![true, true],
+    ]);
+}
+
+#[test]
+fn test_truth_row_from_trace() {
+    let trace = vec![vec![ZERO_POWER], vec![ATOMIC_POWER], vec![MAX_POWER], vec![MAX_POWER], vec![MAX_POWER]];
+    let row = TruthRow::from_trace(vec![true], &trace, 3);
+    assert_eq!(row.outputs, vec![MAX_POWER]);
+    assert_eq!(row.delay, Some(2));
+    assert_eq!(TruthRow::from_trace(vec![true], &trace, 4).delay, None);
+    let blinking = vec![vec![ZERO_POWER], vec![MAX_POWER], vec![ZERO_POWER], vec![MAX_POWER]];
+    assert_eq!(TruthRow::from_trace(vec![false], &blinking, 2).delay, None);
+}
+
+#[test]
+fn test_format_truth_table() {
+    let rows = vec![
+        TruthRow { inputs: vec![false], outputs: vec![MAX_POWER], delay: Some(0) },
+        TruthRow { inputs: vec![true], outputs: vec![ZERO_POWER], delay: None },
+    ];
+    assert_eq!(format_truth_table(&[(1, 0)], &[(12, 3)], &rows),
+               "1,0 | 12,3 | delay\n  0 |    1 | 0\n  1 |    0 | unstable\n");
+}
+
+#[test]
+fn test_visible_range() {
+    assert_eq!(visible_range(0.0, 10.0, 95.0, 20), 0..10);
+    assert_eq!(visible_range(-25.0, 10.0, 50.0, 20), 2..8);
+    assert_eq!(visible_range(-25.0, 10.0, 500.0, 20), 2..20);
+    assert_eq!(visible_range(300.0, 10.0, 200.0, 20), 0..0);
+    assert_eq!(visible_range(-300.0, 10.0, 200.0, 20), 20..20);
+}
+
+#[test]
+fn test_cells_to_draw() {
+    assert_eq!(cells_to_draw(1..3, 0..2, None), vec![(1, 0), (2, 0), (1, 1), (2, 1)]);
+    let changed = [(0, 0), (2, 1), (2, 2)];
+    assert_eq!(cells_to_draw(1..3, 0..2, Some(&changed)), vec![(2, 1)]);
+    assert_eq!(cells_to_draw(0..0, 0..2, None), vec![]);
+}
+
+#[test]
+fn test_tile_map() {
+    // A 5x3 map in tiles of 2x2 cells, with an occupied cell at (4, 2).
+    let mut occupied = vec![false; 15];
+    occupied[14] = true;
+    let mut tiles = TileMap::new(5, 3, 2, occupied);
+    assert_eq!((tiles.columns(), tiles.rows()), (3, 2));
+    assert!(tiles.is_occupied(2, 1));
+    assert!(!tiles.is_occupied(0, 0));
+
+    let mut powers = vec![ZERO_POWER; 15];
+    assert_eq!(tiles.update(&powers), vec![]);
+    powers[1] = ATOMIC_POWER;
+    powers[6] = MAX_POWER;
+    assert_eq!(tiles.update(&powers), vec![(0, 0)]);
+    assert_eq!(tiles.power(0, 0), MAX_POWER);
+    assert_eq!(tiles.power(1, 0), ZERO_POWER);
+    assert_eq!(tiles.update(&powers), vec![]);
+
+    powers[6] = ZERO_POWER;
+    powers[14] = ATOMIC_POWER;
+    assert_eq!(tiles.update(&powers), vec![(0, 0), (2, 1)]);
+    assert_eq!(tiles.power(0, 0), ATOMIC_POWER);
+    assert_eq!(tiles.power(2, 1), ATOMIC_POWER);
+
+    let coarse = tiles.resized(5);
+    assert_eq!((coarse.columns(), coarse.rows()), (1, 1));
+    assert_eq!(coarse.power(0, 0), ATOMIC_POWER);
+    assert!(coarse.is_occupied(0, 0));
+}
+
+#[test]
+fn test_frame_skipper_skips_one_frame_after_slow_instants() {
+    let budget = Duration::from_millis(FRAME_BUDGET_MS);
+    let mut frames = FrameSkipper::new(budget);
+    let fast = Duration::from_millis(2);
+    let slow = Duration::from_millis(40);
+    assert!(frames.should_render(fast));
+    assert!(!frames.should_render(slow));
+    assert!(frames.should_render(slow));
+    assert!(!frames.should_render(slow));
+    assert!(frames.should_render(fast));
+    assert!(frames.should_render(budget));
+}
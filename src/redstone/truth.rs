@@ -0,0 +1,59 @@
+use super::power::*;
+
+//  _____           _   _       _____     _     _
+// |_   _| __ _   _| |_| |__   |_   _|_ _| |__ | | ___
+//   | || '__| | | | __| '_ \    | |/ _` | '_ \| |/ _ \
+//   | || |  | |_| | |_| | | |   | | (_| | |_) | |  __/
+//   |_||_|   \__,_|\__|_| |_|   |_|\__,_|_.__/|_|\___|
+
+/// Every combination of `inputs` booleans, in binary counting order, the first input being the
+/// most significant.
+pub fn input_combinations(inputs: usize) -> Vec<Vec<bool>> {
+    (0..(1usize << inputs)).map(|combination| {
+        (0..inputs).map(|i| combination & (1 << (inputs - 1 - i)) != 0).collect()
+    }).collect()
+}
+
+/// A row of a truth table: the powered inputs, the settled powers of the outputs, and the number
+/// of instants they took to settle once the inputs were applied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TruthRow {
+    pub inputs: Vec<bool>,
+    pub outputs: Vec<Power>,
+    /// `None` when the outputs still changed during the last instants of the run.
+    pub delay: Option<usize>,
+}
+
+impl TruthRow {
+    /// Builds the row from the powers of the outputs at every instant since the inputs were
+    /// applied. The outputs are settled if they did not change during the last `stable` instants.
+    pub fn from_trace(inputs: Vec<bool>, trace: &[Vec<Power>], stable: usize) -> TruthRow {
+        let outputs = trace.last().cloned().unwrap_or(Vec::new());
+        let settled_at = trace.iter().rposition(|powers| *powers != outputs).map_or(0, |i| i + 1);
+        let delay = if trace.len() - settled_at >= stable { Some(settled_at) } else { None };
+        TruthRow { inputs, outputs, delay }
+    }
+}
+
+/// Formats a truth table with a column per input and output cell, an output being `1` when any
+/// of its channels is powered.
+pub fn format_truth_table(inputs: &[(usize, usize)], outputs: &[(usize, usize)], rows: &[TruthRow]) -> String {
+    let label = |&(x, y): &(usize, usize)| format!("{},{}", x, y);
+    let input_labels: Vec<String> = inputs.iter().map(&label).collect();
+    let output_labels: Vec<String> = outputs.iter().map(&label).collect();
+    let mut table = format!("{} | {} | delay\n", input_labels.join(" "), output_labels.join(" "));
+    let cells = |labels: &[String], bits: Vec<bool>| -> String {
+        labels.iter().zip(bits)
+            .map(|(label, bit)| format!("{:>1$}", if bit { 1 } else { 0 }, label.len()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    for row in rows {
+        let delay = row.delay.map_or(String::from("unstable"), |delay| delay.to_string());
+        table.push_str(&format!("{} | {} | {}\n",
+                                cells(&input_labels, row.inputs.clone()),
+                                cells(&output_labels, row.outputs.iter().map(|power| !power.is_zero()).collect()),
+                                delay));
+    }
+    table
+}
@@ -0,0 +1,129 @@
+use std::ops::Range;
+
+use super::power::*;
+
+// __     ___
+// \ \   / (_) _____      __
+//  \ \ / /| |/ _ \ \ /\ / /
+//   \ V / | |  __/\ V  V /
+//    \_/  |_|\___| \_/\_/
+
+/// The cells of a row or a column of the map which are at least partly in the window, given the
+/// `offset` in pixels of the first cell, the size `zoom` of a cell in pixels and the `extent` of
+/// the window in pixels.
+pub fn visible_range(offset: f64, zoom: f64, extent: f64, cells: usize) -> Range<usize> {
+    let first = (-offset / zoom).floor().max(0.0).min(cells as f64) as usize;
+    let last = ((extent - offset) / zoom).ceil().max(0.0).min(cells as f64) as usize;
+    first..last.max(first)
+}
+
+/// The cells to draw in the window, given the `columns` and `rows` in it: all of them, or only
+/// those of `changed` when the rest of the window is still drawn.
+pub fn cells_to_draw(columns: Range<usize>, rows: Range<usize>, changed: Option<&[(usize, usize)]>) -> Vec<(usize, usize)> {
+    match changed {
+        Some(changed) => changed.iter().cloned().filter(|&(x, y)| {
+            columns.start <= x && x < columns.end && rows.start <= y && y < rows.end
+        }).collect(),
+        None => rows.flat_map(|y| columns.clone().map(move|x| (x, y))).collect(),
+    }
+}
+
+/// The map at a lower resolution, made of square tiles of `tile` cells which are summarized by
+/// the strongest power of their cells.
+///
+/// The powers given to `update` are compared to the previous ones, and only the tiles in which
+/// some cell changed are summarized again.
+pub struct TileMap {
+    width: usize,
+    height: usize,
+    tile: usize,
+    /// Whether each cell holds something else than void, which does not change during the run.
+    occupied: Vec<bool>,
+    occupied_tiles: Vec<bool>,
+    powers: Vec<Power>,
+    tiles: Vec<Power>,
+    dirty: Vec<bool>,
+}
+
+impl TileMap {
+    pub fn new(width: usize, height: usize, tile: usize, occupied: Vec<bool>) -> Self {
+        assert!(tile > 0, "Empty tiles");
+        let columns = (width + tile - 1) / tile;
+        let rows = (height + tile - 1) / tile;
+        let mut occupied_tiles = vec![false; columns * rows];
+        for (i, _) in occupied.iter().enumerate().filter(|&(_, &occupied)| occupied) {
+            occupied_tiles[(i / width) / tile * columns + (i % width) / tile] = true;
+        }
+        TileMap {
+            width,
+            height,
+            tile,
+            occupied,
+            occupied_tiles,
+            powers: vec![ZERO_POWER; width * height],
+            tiles: vec![ZERO_POWER; columns * rows],
+            dirty: vec![false; columns * rows],
+        }
+    }
+
+    /// The same map and powers, with tiles of another size.
+    pub fn resized(&self, tile: usize) -> Self {
+        let mut map = TileMap::new(self.width, self.height, tile, self.occupied.clone());
+        map.update(&self.powers);
+        map
+    }
+
+    /// Number of cells on each side of a tile.
+    pub fn tile(&self) -> usize {
+        self.tile
+    }
+
+    pub fn columns(&self) -> usize {
+        (self.width + self.tile - 1) / self.tile
+    }
+
+    pub fn rows(&self) -> usize {
+        (self.height + self.tile - 1) / self.tile
+    }
+
+    /// Records the powers of the cells, indexed like the map, and returns the column and row of
+    /// the tiles summarized again, the only ones to draw again.
+    pub fn update(&mut self, powers: &[Power]) -> Vec<(usize, usize)> {
+        let columns = self.columns();
+        for i in 0..self.powers.len() {
+            if powers[i] != self.powers[i] {
+                self.powers[i] = powers[i];
+                self.dirty[(i / self.width) / self.tile * columns + (i % self.width) / self.tile] = true;
+            }
+        }
+        let mut updated = Vec::new();
+        for t in 0..self.tiles.len() {
+            if self.dirty[t] {
+                self.tiles[t] = self.summarize(t % columns, t / columns);
+                self.dirty[t] = false;
+                updated.push((t % columns, t / columns));
+            }
+        }
+        updated
+    }
+
+    fn summarize(&self, column: usize, row: usize) -> Power {
+        let mut power = ZERO_POWER;
+        for y in (row * self.tile)..((row + 1) * self.tile).min(self.height) {
+            for x in (column * self.tile)..((column + 1) * self.tile).min(self.width) {
+                power = power.max(self.powers[x + y * self.width]);
+            }
+        }
+        power
+    }
+
+    /// The strongest power of the cells of the tile.
+    pub fn power(&self, column: usize, row: usize) -> Power {
+        self.tiles[column + row * self.columns()]
+    }
+
+    /// Whether some cell of the tile holds something else than void.
+    pub fn is_occupied(&self, column: usize, row: usize) -> bool {
+        self.occupied_tiles[column + row * self.columns()]
+    }
+}
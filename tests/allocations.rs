@@ -0,0 +1,69 @@
+//! Checks that steady loops stop allocating, with an allocator counting the allocations of each
+//! thread.
+//!
+//! The allocator lives in its own test binary so that the library does not impose it on the
+//! crates using it. Heaptrack attributes allocations to call stacks but cannot be driven from
+//! `cargo test`, counting them around the instants under test gives the same verdict, zero
+//! allocations per instant, without an external tool. To inspect the allocations left with
+//! heaptrack, run `heaptrack target/debug/deps/allocations-*` and look at the stacks under
+//! `step_n`.
+
+extern crate reactive_rs;
+
+use reactive_rs::reactive::process::*;
+use reactive_rs::reactive::signal::value_signal::*;
+use reactive_rs::reactive::testing::*;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = Cell::new(0);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_steady_loop_without_allocation() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y));
+    let mut scheduler = TestScheduler::new();
+    let emit_forever = |_| LoopStatus::Continue::<()>;
+    let await_forever = |_| LoopStatus::Continue::<()>;
+    scheduler.spawn(s.emit(value(1)).map(emit_forever).pause().while_loop());
+    scheduler.spawn(s.await().map(await_forever).while_loop());
+    scheduler.step_n(10);
+    let before = ALLOCATIONS.with(|n| n.get());
+    scheduler.step_n(100);
+    assert_eq!(ALLOCATIONS.with(|n| n.get()), before);
+}
+
+#[test]
+fn test_hibernating_signal_keeps_its_capacity() {
+    let s: ValueSignal<i32, i32> = ValueSignal::new(0, Box::new(|x, y| x + y)).hibernate_after(1).with_capacity(2);
+    let mut scheduler = TestScheduler::new();
+    let emit_forever = |_| LoopStatus::Continue::<()>;
+    let await_forever = |_| LoopStatus::Continue::<()>;
+    let await_later_forever = |_| LoopStatus::Continue::<()>;
+    scheduler.spawn(s.emit(value(1)).map(emit_forever).pause().pause().pause().pause().while_loop());
+    scheduler.spawn(s.await().map(await_forever).while_loop());
+    // Not waiting when the signal hibernates, and waiting again afterwards.
+    scheduler.spawn(s.await().pause().pause().map(await_later_forever).while_loop());
+    scheduler.step_n(20);
+    let before = ALLOCATIONS.with(|n| n.get());
+    scheduler.step_n(100);
+    assert_eq!(ALLOCATIONS.with(|n| n.get()), before);
+}
@@ -0,0 +1,43 @@
+//! Executes the examples, which double as acceptance tests of the public API.
+
+extern crate reactive_rs;
+
+#[path = "../examples/producer_consumer.rs"]
+mod producer_consumer;
+#[path = "../examples/traffic_light.rs"]
+mod traffic_light;
+#[path = "../examples/ping_pong.rs"]
+mod ping_pong;
+#[path = "../examples/gui_counter.rs"]
+mod gui_counter;
+
+#[test]
+fn test_producer_consumer() {
+    let (sum, largest_batch) = producer_consumer::run(100, 4);
+    assert_eq!(sum, 5050);
+    assert!(largest_batch <= 4);
+}
+
+#[test]
+fn test_traffic_light() {
+    use traffic_light::Light::*;
+
+    let pressed_cycle = vec![Green, Green, Yellow, Yellow, Red, Red, Red];
+    let full_cycle = vec![Green, Green, Green, Green, Green, Yellow, Yellow, Red, Red, Red];
+    assert_eq!(traffic_light::run(2, 2), [pressed_cycle, full_cycle].concat());
+}
+
+#[test]
+fn test_ping_pong() {
+    let hits = ping_pong::run(5);
+    let players: Vec<&str> = hits.iter().map(|&(player, _)| player).collect();
+    assert_eq!(players, vec!["ping", "pong", "ping", "pong", "ping", "pong", "ping"]);
+    assert_eq!(hits.iter().map(|&(_, n)| n).collect::<Vec<_>>(), (0..7).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_gui_counter() {
+    let frames = gui_counter::run(6, &[0, 1, 3]);
+    // A click is emitted during its frame, and counted and drawn during the next one.
+    assert_eq!(frames, vec!["clicks: 0", "clicks: 1", "clicks: 2", "clicks: 2", "clicks: 3", "clicks: 3"]);
+}